@@ -0,0 +1,33 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use eyre::Result;
+use loom_evm_utils::NWETH;
+use loom_strategy_backrun::BackrunConfigSection;
+use loom_types_entities::bidding::{read_bid_history, AdaptiveBidder};
+use loom_types_entities::config::load_from_file;
+
+/// Replays recorded bid outcomes with the bidding settings of the config
+#[derive(Parser, Debug)]
+struct Args {
+    /// Bid history file written by the stuffing tx monitor
+    #[arg(long)]
+    history: PathBuf,
+    #[arg(long, default_value = "config.toml")]
+    config: PathBuf,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let backrun_config: BackrunConfigSection = load_from_file(args.config).await?;
+    let bidding_config = backrun_config.backrun_strategy.bidding().unwrap_or_default();
+    let history = read_bid_history(&args.history).await?;
+
+    let evaluation = AdaptiveBidder::replay(bidding_config, &history);
+    println!("Bids : {} landed : {} recorded landed : {}", evaluation.bids, evaluation.landed, evaluation.recorded_landed);
+    println!("Profit : {} tips : {}", NWETH::to_float(evaluation.profit_eth), NWETH::to_float(evaluation.tips_eth));
+
+    Ok(())
+}
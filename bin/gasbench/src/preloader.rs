@@ -35,20 +35,42 @@ where
 
     drop(market_instance);
 
-    fetch_and_add_pool_by_address(client.clone(), market.clone(), market_state.clone(), CurvePoolAddress::ETH_BTC_USD, PoolClass::Curve)
-        .await?;
+    fetch_and_add_pool_by_address(
+        client.clone(),
+        None,
+        market.clone(),
+        market_state.clone(),
+        CurvePoolAddress::ETH_BTC_USD,
+        PoolClass::Curve,
+    )
+    .await?;
 
-    fetch_and_add_pool_by_address(client.clone(), market.clone(), market_state.clone(), CurvePoolAddress::USDT_BTC_ETH, PoolClass::Curve)
-        .await?;
+    fetch_and_add_pool_by_address(
+        client.clone(),
+        None,
+        market.clone(),
+        market_state.clone(),
+        CurvePoolAddress::USDT_BTC_ETH,
+        PoolClass::Curve,
+    )
+    .await?;
 
-    fetch_and_add_pool_by_address(client.clone(), market.clone(), market_state.clone(), CurvePoolAddress::DAI_USDC_USDT, PoolClass::Curve)
-        .await?;
+    fetch_and_add_pool_by_address(
+        client.clone(),
+        None,
+        market.clone(),
+        market_state.clone(),
+        CurvePoolAddress::DAI_USDC_USDT,
+        PoolClass::Curve,
+    )
+    .await?;
 
-    fetch_and_add_pool_by_address(client.clone(), market.clone(), market_state.clone(), CurveMetapoolAddress::LUSD, PoolClass::Curve)
+    fetch_and_add_pool_by_address(client.clone(), None, market.clone(), market_state.clone(), CurveMetapoolAddress::LUSD, PoolClass::Curve)
         .await?;
 
     fetch_and_add_pool_by_address(
         client.clone(),
+        None,
         market.clone(),
         market_state.clone(),
         UniswapV3PoolAddress::WETH_USDT_3000,
@@ -58,6 +80,7 @@ where
 
     fetch_and_add_pool_by_address(
         client.clone(),
+        None,
         market.clone(),
         market_state.clone(),
         PancakeV2PoolAddress::WETH_USDT,
@@ -66,6 +89,7 @@ where
     .await?;
     fetch_and_add_pool_by_address(
         client.clone(),
+        None,
         market.clone(),
         market_state.clone(),
         UniswapV2PoolAddress::WETH_USDT,
@@ -74,6 +98,7 @@ where
     .await?;
     fetch_and_add_pool_by_address(
         client.clone(),
+        None,
         market.clone(),
         market_state.clone(),
         PancakeV3PoolAddress::USDC_USDT_100,
@@ -83,6 +108,7 @@ where
 
     fetch_and_add_pool_by_address(
         client.clone(),
+        None,
         market.clone(),
         market_state.clone(),
         UniswapV3PoolAddress::USDC_WETH_3000,
@@ -91,6 +117,7 @@ where
     .await?;
     fetch_and_add_pool_by_address(
         client.clone(),
+        None,
         market.clone(),
         market_state.clone(),
         UniswapV3PoolAddress::USDC_WETH_500,
@@ -99,6 +126,7 @@ where
     .await?;
     fetch_and_add_pool_by_address(
         client.clone(),
+        None,
         market.clone(),
         market_state.clone(),
         UniswapV3PoolAddress::WBTC_USDT_3000,
@@ -107,6 +135,7 @@ where
     .await?;
     fetch_and_add_pool_by_address(
         client.clone(),
+        None,
         market.clone(),
         market_state.clone(),
         UniswapV3PoolAddress::USDC_USDT_100,
@@ -116,6 +145,7 @@ where
 
     fetch_and_add_pool_by_address(
         client.clone(),
+        None,
         market.clone(),
         market_state.clone(),
         UniswapV2PoolAddress::LUSD_WETH,
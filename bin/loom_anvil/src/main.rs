@@ -18,7 +18,7 @@ use loom::node::debug_provider::AnvilDebugProviderFactory;
 use eyre::{ErrReport, OptionExt, Result};
 use loom::broadcast::accounts::{InitializeSignersOneShotBlockingActor, NonceAndBalanceMonitorActor, TxSignersActor};
 use loom::broadcast::broadcaster::{AnvilBroadcastActor, FlashbotsBroadcastActor};
-use loom::broadcast::flashbots::client::RelayConfig;
+use loom::broadcast::flashbots::client::{RelayCapabilities, RelayConfig};
 use loom::broadcast::flashbots::Flashbots;
 use loom::core::actors::{Accessor, Actor, Broadcaster, Consumer, Producer, SharedState};
 use loom::core::block_history::BlockHistoryActor;
@@ -40,7 +40,7 @@ use loom::strategy::backrun::{BackrunConfig, StateChangeArbActor};
 use loom::strategy::merger::{ArbSwapPathMergerActor, DiffPathMergerActor, SamePathMergerActor};
 use loom::types::blockchain::{debug_trace_block, ChainParameters, LoomDataTypesEthereum, Mempool};
 use loom::types::entities::{
-    AccountNonceAndBalanceState, BlockHistory, LatestBlock, Market, MarketState, PoolClass, Swap, Token, TxSigners,
+    AccountNonceAndBalanceState, BlockHistory, GasModel, LatestBlock, Market, MarketState, PoolClass, Swap, Token, TxSigners,
 };
 use loom::types::events::{
     MarketEvents, MempoolEvents, MessageBlock, MessageBlockHeader, MessageBlockLogs, MessageBlockStateUpdate, MessageHealthEvent,
@@ -189,6 +189,7 @@ async fn main() -> Result<()> {
     let market_state = SharedState::new(market_state_instance);
     let mempool_instance = SharedState::new(mempool_instance);
     let block_history_state = SharedState::new(BlockHistory::new(10));
+    let gas_model = SharedState::new(GasModel::new());
 
     let tx_signers = TxSigners::new();
     let accounts_state = AccountNonceAndBalanceState::new();
@@ -301,6 +302,7 @@ async fn main() -> Result<()> {
                 debug!(address=%pool_config.address, class=%pool_config.class, "Loading pool");
                 fetch_and_add_pool_by_address(
                     client.clone(),
+                    None,
                     market_instance.clone(),
                     market_state.clone(),
                     pool_config.address,
@@ -313,7 +315,7 @@ async fn main() -> Result<()> {
                 debug!("Loading curve pool");
                 if let Ok(curve_contract) = CurveProtocol::get_contract_from_code(client.clone(), pool_config.address).await {
                     let curve_pool = CurvePool::fetch_pool_data(client.clone(), curve_contract).await?;
-                    fetch_state_and_add_pool(client.clone(), market_instance.clone(), market_state.clone(), curve_pool.into()).await?
+                    fetch_state_and_add_pool(client.clone(), None, market_instance.clone(), market_state.clone(), curve_pool.into()).await?
                 } else {
                     error!("CURVE_POOL_NOT_LOADED");
                 }
@@ -364,7 +366,12 @@ async fn main() -> Result<()> {
 
     // Start estimator actor
     let mut estimator_actor = EvmEstimatorActor::new_with_provider(encoder.clone(), Some(client.clone()));
-    match estimator_actor.consume(swap_compose_channel.clone()).produce(swap_compose_channel.clone()).start() {
+    match estimator_actor
+        .consume(swap_compose_channel.clone())
+        .consume(new_block_headers_channel.clone())
+        .produce(swap_compose_channel.clone())
+        .start()
+    {
         Err(e) => error!("{e}"),
         _ => {
             info!("Estimate actor started successfully")
@@ -422,6 +429,7 @@ async fn main() -> Result<()> {
             .access(market_instance.clone())
             .access(market_state.clone())
             .access(block_history_state.clone())
+            .access(gas_model.clone())
             .consume(market_events_channel.clone())
             .consume(mempool_events_channel.clone())
             .produce(swap_compose_channel.clone())
@@ -477,7 +485,15 @@ async fn main() -> Result<()> {
         }
     }
     if test_config.modules.flashbots {
-        let relays = vec![RelayConfig { id: 1, url: mock_server.as_ref().unwrap().uri(), name: "relay".to_string(), no_sign: Some(false) }];
+        let relays = vec![RelayConfig {
+            id: 1,
+            url: mock_server.as_ref().unwrap().uri(),
+            name: "relay".to_string(),
+            no_sign: Some(false),
+            stats_method: None,
+            cancel_method: None,
+            capabilities: RelayCapabilities::default(),
+        }];
         let flashbots = Flashbots::new(client.clone(), "https://unused", None).with_relays(relays);
         let mut flashbots_broadcast_actor = FlashbotsBroadcastActor::new(flashbots, true);
         match flashbots_broadcast_actor.consume(tx_compose_channel.clone()).start() {
@@ -2,14 +2,15 @@ use alloy_provider::Provider;
 use eyre::Result;
 use tracing::{error, info};
 
-use loom::core::actors::{Accessor, Actor, Consumer, Producer};
+use loom::core::actors::{Accessor, Actor, Consumer, Producer, SharedState};
 use loom::core::router::SwapRouterActor;
 use loom::core::topology::{Topology, TopologyConfig};
 use loom::defi::health_monitor::{StateHealthMonitorActor, StuffingTxMonitorActor};
 use loom::evm::db::LoomDBType;
-use loom::metrics::{BlockLatencyRecorderActor, InfluxDbWriterActor};
-use loom::strategy::backrun::{BackrunConfig, BackrunConfigSection, StateChangeArbActor};
+use loom::metrics::{BlockLatencyRecorderActor, InfluxDbWriterActor, MempoolSourceLatencyRecorderActor};
+use loom::strategy::backrun::{BackrunConfig, BackrunConfigSection, ProfitPolicyReloadActor, StateChangeArbActor};
 use loom::strategy::merger::{ArbSwapPathMergerActor, DiffPathMergerActor, SamePathMergerActor};
+use loom::types::entities::bidding::AdaptiveBidder;
 use loom::types::entities::config::load_from_file;
 use loom::types::events::MarketEvents;
 
@@ -21,9 +22,19 @@ async fn main() -> Result<()> {
     .format_timestamp_micros()
     .init();
 
+    let backrun_config: BackrunConfigSection = load_from_file("./config.toml".to_string().into()).await?;
+    let backrun_config: BackrunConfig = backrun_config.backrun_strategy;
+    let bidder = match backrun_config.bidding() {
+        Some(bidding_config) => Some(SharedState::new(AdaptiveBidder::restore(bidding_config).await?)),
+        None => None,
+    };
+    // shared with the searcher, the encoder and the mergers, reloaded on config change
+    let profit_policy = SharedState::new(backrun_config.profit_policy());
+
     let topology_config = TopologyConfig::load_from_file("config.toml".to_string())?;
     let influxdb_config = topology_config.influxdb.clone();
-    let (topology, mut worker_task_vec) = Topology::<LoomDBType>::from(topology_config).await?;
+    let (mut topology, mut worker_task_vec) =
+        Topology::<LoomDBType>::from_with_shared_state(topology_config, Some(profit_policy.clone()), bidder.clone()).await?;
 
     let client = topology.get_client(Some("local".to_string()).as_ref())?;
     let blockchain = topology.get_blockchain(Some("mainnet".to_string()).as_ref())?;
@@ -32,22 +43,21 @@ async fn main() -> Result<()> {
 
     let tx_signers = topology.get_signers(Some("env_signer".to_string()).as_ref())?;
 
-    let backrun_config: BackrunConfigSection = load_from_file("./config.toml".to_string().into()).await?;
-    let backrun_config: BackrunConfig = backrun_config.backrun_strategy;
-
     let block_nr = client.get_block_number().await?;
     info!("Block : {}", block_nr);
 
     info!("Creating shared state");
 
     info!("Starting state change arb actor");
-    let mut state_change_arb_actor = StateChangeArbActor::new(client.clone(), true, true, backrun_config);
+    let mut state_change_arb_actor =
+        StateChangeArbActor::new(client.clone(), true, true, backrun_config).with_profit_policy(profit_policy.clone());
     match state_change_arb_actor
         .access(blockchain.mempool())
         .access(blockchain.latest_block())
         .access(blockchain.market())
         .access(blockchain_state.market_state())
         .access(blockchain_state.block_history())
+        .access(blockchain.gas_model())
         .consume(blockchain.market_events_channel())
         .consume(blockchain.mempool_events_channel())
         .produce(strategy.swap_compose_channel())
@@ -71,9 +81,12 @@ async fn main() -> Result<()> {
     match swap_path_encoder_actor
         .access(tx_signers.clone())
         .access(blockchain.nonce_and_balance())
+        .access(blockchain.mempool())
+        .access(blockchain_state.market_state())
         .consume(strategy.swap_compose_channel())
         .produce(strategy.swap_compose_channel())
         .produce(blockchain.tx_compose_channel())
+        .produce(blockchain.influxdb_write_channel())
         .start()
     {
         Ok(r) => {
@@ -86,7 +99,7 @@ async fn main() -> Result<()> {
     }
 
     info!("Starting swap path merger actor");
-    let mut swap_path_merger_actor = ArbSwapPathMergerActor::new(multicaller);
+    let mut swap_path_merger_actor = ArbSwapPathMergerActor::new(multicaller).with_profit_policy(profit_policy.clone());
 
     match swap_path_merger_actor
         .access(blockchain.latest_block())
@@ -104,7 +117,7 @@ async fn main() -> Result<()> {
         }
     }
 
-    let mut same_path_merger_actor = SamePathMergerActor::new(client.clone());
+    let mut same_path_merger_actor = SamePathMergerActor::new(client.clone()).with_profit_policy(profit_policy.clone());
 
     match same_path_merger_actor
         .access(blockchain_state.market_state())
@@ -123,6 +136,16 @@ async fn main() -> Result<()> {
         }
     }
 
+    match ProfitPolicyReloadActor::new("./config.toml").with_profit_policy(profit_policy).start() {
+        Ok(r) => {
+            worker_task_vec.extend(r);
+            info!("Profit policy reload actor started successfully")
+        }
+        Err(e) => {
+            panic!("{}", e)
+        }
+    }
+
     // Merger
     let mut diff_path_merger_actor = DiffPathMergerActor::new();
 
@@ -161,10 +184,15 @@ async fn main() -> Result<()> {
 
     // Monitoring transactions we tried to attach to.
     let mut stuffing_txs_monitor_actor = StuffingTxMonitorActor::new(client.clone());
+    if let Some(bidder) = bidder {
+        stuffing_txs_monitor_actor.access(bidder);
+    }
     match stuffing_txs_monitor_actor
         .access(blockchain.latest_block())
         .consume(blockchain.tx_compose_channel())
         .consume(blockchain.market_events_channel())
+        .consume(blockchain.relay_events_channel())
+        .consume(blockchain.mempool_events_channel())
         .start()
     {
         Err(e) => {
@@ -203,6 +231,23 @@ async fn main() -> Result<()> {
                 info!("Block latency recorder actor started successfully")
             }
         }
+
+        let mut mempool_source_latency_recorder_actor = MempoolSourceLatencyRecorderActor::new();
+        match mempool_source_latency_recorder_actor
+            .access(blockchain.mempool_source_stats())
+            .consume(blockchain.new_mempool_tx_channel())
+            .consume(blockchain.new_block_headers_channel())
+            .produce(blockchain.influxdb_write_channel())
+            .start()
+        {
+            Err(e) => {
+                panic!("Mempool source latency recorder actor failed : {}", e)
+            }
+            Ok(r) => {
+                worker_task_vec.extend(r);
+                info!("Mempool source latency recorder actor started successfully")
+            }
+        }
     }
 
     // Checking workers, logging if some close
@@ -226,20 +271,30 @@ async fn main() -> Result<()> {
         }
     });
 
-    // listening to MarketEvents in an infinite loop
+    // listening to MarketEvents until CTRL+C
     let mut s = blockchain.market_events_channel().subscribe().await;
     loop {
-        let msg = s.recv().await;
-        if let Ok(msg) = msg {
-            match msg {
-                MarketEvents::BlockTxUpdate { block_number, block_hash } => {
-                    info!("New block received {} {}", block_number, block_hash);
-                }
-                MarketEvents::BlockStateUpdate { block_hash } => {
-                    info!("New block state received {}", block_hash);
+        tokio::select! {
+            msg = s.recv() => {
+                if let Ok(msg) = msg {
+                    match msg {
+                        MarketEvents::BlockTxUpdate { block_number, block_hash } => {
+                            info!("New block received {} {}", block_number, block_hash);
+                        }
+                        MarketEvents::BlockStateUpdate { block_hash } => {
+                            info!("New block state received {}", block_hash);
+                        }
+                        _ => {}
+                    }
                 }
-                _ => {}
+            }
+            _ = tokio::signal::ctrl_c() => {
+                info!("CTRL+C received... exiting");
+                break;
             }
         }
     }
+
+    topology.shutdown().await;
+    Ok(())
 }
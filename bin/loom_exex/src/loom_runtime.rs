@@ -4,6 +4,7 @@ use alloy::providers::Provider;
 use alloy::transports::Transport;
 use axum::Router;
 use eyre::{ErrReport, OptionExt};
+use loom::core::actors::SharedState;
 use loom::core::blockchain::{Blockchain, BlockchainState, Strategy};
 use loom::core::blockchain_actors::BlockchainActors;
 use loom::core::topology::{BroadcasterConfig, EncoderConfig, TopologyConfig};
@@ -14,6 +15,8 @@ use loom::node::debug_provider::DebugProviderExt;
 use loom::node::exex::loom_exex;
 use loom::storage::db::init_db_pool;
 use loom::strategy::backrun::{BackrunConfig, BackrunConfigSection};
+use loom::strategy::merger::{MergerConfig, MergerConfigSection};
+use loom::types::entities::bidding::AdaptiveBidder;
 use loom::types::entities::config::load_from_file;
 use loom::types::entities::{BlockHistoryState, PoolClass};
 use reth::revm::{Database, DatabaseCommit, DatabaseRef};
@@ -21,6 +24,7 @@ use reth_exex::ExExContext;
 use reth_node_api::FullNodeComponents;
 use std::env;
 use std::future::Future;
+use tokio_util::sync::CancellationToken;
 use tracing::info;
 
 pub async fn init<Node: FullNodeComponents>(
@@ -39,6 +43,7 @@ pub async fn start_loom<P, T, DB>(
     topology_config: TopologyConfig,
     loom_config_filepath: String,
     is_exex: bool,
+    shutdown_token: CancellationToken,
 ) -> eyre::Result<()>
 where
     T: Transport + Clone,
@@ -77,28 +82,46 @@ where
         .broadcaster
         .as_ref()
         .and_then(|b| b.get("mainnet"))
-        .map(|b| match b {
-            BroadcasterConfig::Flashbots(f) => f.relays(),
+        .and_then(|b| match b {
+            BroadcasterConfig::Flashbots(f) => Some(f.relays()),
+            BroadcasterConfig::PrivateTx(_) => None,
         })
         .unwrap_or_default();
 
     let pools_config = PoolsConfig::disable_all().enable(PoolClass::UniswapV2).enable(PoolClass::UniswapV3);
 
-    let backrun_config: BackrunConfigSection = load_from_file::<BackrunConfigSection>(loom_config_filepath.into()).await?;
+    let backrun_config: BackrunConfigSection = load_from_file::<BackrunConfigSection>(loom_config_filepath.clone().into()).await?;
     let backrun_config: BackrunConfig = backrun_config.backrun_strategy;
+    let merger_config: MergerConfig = load_from_file::<MergerConfigSection>(loom_config_filepath.clone().into()).await?.merger;
+    let profit_policy = backrun_config.profit_policy();
 
     let mut bc_actors = BlockchainActors::new(provider.clone(), bc.clone(), bc_state, strategy, relays);
     bc_actors
-        .mempool()?
-        .with_wait_for_node_sync()? // wait for node to sync before
+        .with_shutdown_token(shutdown_token)?
+        .with_db_pool(db_pool.clone())? // store token metadata of loaded pools
+        .with_profit_policy_reload(loom_config_filepath.into(), profit_policy)?; // reload profit policy of the searchers, encoder and mergers on config change
+    if let Some(bidding_config) = backrun_config.bidding() {
+        bc_actors.with_bidder(SharedState::new(AdaptiveBidder::restore(bidding_config).await?))?;
+    }
+    bc_actors.mempool()?.with_wait_for_node_sync()?; // wait for node to sync before
+
+    if let Some(snapshot_config) = topology_config.snapshots.as_ref().and_then(|s| s.get("mainnet")) {
+        bc_actors
+            .with_market_state_snapshot_loader(snapshot_config.path.clone())? // load market state and pools from snapshot
+            .with_market_state_snapshot_writer(snapshot_config.path.clone(), snapshot_config.interval_blocks.unwrap_or(100))?;
+    }
+
+    bc_actors
         .initialize_signers_with_encrypted_key(private_key_encrypted)? // initialize signer with encrypted key
         .with_block_history()? // collect blocks
-        .with_price_station()? // calculate price fo tokens
+        .with_market_price()? // calculate price of tokens from the pool graph
         .with_health_monitor_pools()? // monitor pools health to disable empty
         //.with_health_monitor_state()? // monitor state health
         .with_health_monitor_stuffing_tx()? // collect stuffing tx information
         .with_swap_encoder(Some(multicaller_address))? // convert swaps to opcodes and passes to estimator
         .with_evm_estimator()? // estimate gas, add tips
+        .with_gas_model()? // measure gas of swap steps
+        .with_token_screener()? // detect fee-on-transfer and untradeable tokens
         .with_signers()? // start signer actor that signs transactions before broadcasting
         .with_flashbots_broadcaster( true)? // broadcast signed txes to flashbots
         .with_market_state_preloader()? // preload contracts to market state
@@ -108,7 +131,7 @@ where
         .with_new_pool_loader(pools_config.clone())? // load new pools
         .with_pool_loader()?
         .with_swap_path_merger()? // load merger for multiple swap paths
-        .with_diff_path_merger()? // load merger for different swap paths
+        .with_block_merger(&merger_config)? // load merger packing compatible swap paths of the block or diff path merger
         .with_same_path_merger()? // load merger for same swap paths with different stuffing txes
         .with_backrun_block(backrun_config.clone())? // load backrun searcher for incoming block
         .with_backrun_mempool(backrun_config)? // load backrun searcher for mempool txes
@@ -122,7 +145,9 @@ where
     if let Some(influxdb_config) = topology_config.influxdb {
         bc_actors
             .with_influxdb_writer(influxdb_config.url, influxdb_config.database, influxdb_config.tags)?
-            .with_block_latency_recorder()?;
+            .with_block_latency_recorder()?
+            .with_mempool_source_latency_recorder()?
+            .with_market_state_size_recorder()?;
     }
 
     bc_actors.wait().await;
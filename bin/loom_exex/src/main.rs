@@ -16,8 +16,8 @@ use reth::cli::Cli;
 use reth_node_ethereum::node::EthereumAddOns;
 use reth_node_ethereum::EthereumNode;
 use reth_provider::providers::BlockchainProvider2;
-use std::time::Duration;
-use tokio::{signal, task};
+use tokio::signal;
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
@@ -58,15 +58,21 @@ fn main() -> eyre::Result<()> {
             let ipc_provider = ProviderBuilder::new().on_builtin(handle.node.config.rpc.ipcpath.as_str()).await?;
             let alloy_db = AlloyDB::new(ipc_provider.clone(), BlockId::latest()).unwrap();
 
-            let state_db = LoomDB::new().with_ext_db(alloy_db);
+            let mut state_db = LoomDB::new().with_ext_db(alloy_db);
+            if let Some(eviction) = topology_config.blockchains.get("mainnet").and_then(|b| b.eviction) {
+                info!(?eviction, "Market state eviction enabled");
+                state_db = state_db.with_eviction(eviction.into());
+            }
 
             let bc_state = BlockchainState::<LoomDB>::new_with_market_state(MarketState::new(state_db));
 
             let strategy = Strategy::<LoomDB>::new();
 
             let bc_clone = bc.clone();
-            tokio::task::spawn(async move {
-                if let Err(e) = loom_runtime::start_loom(
+            // reth waits for the graceful shutdown guard, so the market state snapshot is written before the node exits
+            handle.node.task_executor.spawn_critical_with_graceful_shutdown_signal("loom", |shutdown| async move {
+                let shutdown_token = CancellationToken::new();
+                let loom = loom_runtime::start_loom(
                     ipc_provider,
                     bc_clone,
                     bc_state,
@@ -74,9 +80,20 @@ fn main() -> eyre::Result<()> {
                     topology_config,
                     loom_args.loom_config.clone(),
                     true,
-                )
-                .await
-                {
+                    shutdown_token.clone(),
+                );
+                tokio::pin!(loom);
+
+                let result = tokio::select! {
+                    result = &mut loom => result,
+                    guard = shutdown => {
+                        shutdown_token.cancel();
+                        let result = loom.await;
+                        drop(guard);
+                        result
+                    }
+                };
+                if let Err(e) = result {
                     error!("Error starting loom: {:?}", e);
                 }
             });
@@ -102,25 +119,30 @@ fn main() -> eyre::Result<()> {
 
                 let strategy = Strategy::<LoomDB>::new();
 
-                if let Err(e) =
-                    loom_runtime::start_loom(provider, bc_clone, bc_state, strategy, topology_config, loom_args.loom_config.clone(), false)
-                        .await
-                {
-                    error!("Error starting loom: {:#?}", e);
-                    panic!("{}", e)
-                }
+                let shutdown_token = CancellationToken::new();
+                let mut loom = tokio::task::spawn(loom_runtime::start_loom(
+                    provider,
+                    bc_clone,
+                    bc_state,
+                    strategy,
+                    topology_config,
+                    loom_args.loom_config.clone(),
+                    false,
+                    shutdown_token.clone(),
+                ));
 
                 // keep loom running
-                tokio::select! {
+                let result = tokio::select! {
                     _ = signal::ctrl_c() => {
-                    info!("CTRL+C received... exiting");
-                }
-                _ = async {
-                        loop {
-                        tokio::time::sleep(Duration::from_secs(60)).await;
-                        task::yield_now().await;
-                        }
-                    } => {}
+                        info!("CTRL+C received... exiting");
+                        shutdown_token.cancel();
+                        loom.await?
+                    }
+                    result = &mut loom => result?,
+                };
+                if let Err(e) = result {
+                    error!("Error starting loom: {:#?}", e);
+                    panic!("{}", e)
                 }
                 Ok::<(), eyre::Error>(())
             })?;
@@ -0,0 +1,189 @@
+use std::collections::{HashMap, HashSet};
+
+use alloy_primitives::{keccak256, Address, Bytes, TxHash, B256, U256};
+
+/// Bundle sent for a window of blocks. It is replaced by a better bundle for the same stuffing txs and cancelled when
+/// the stuffing txs are mined or the pools it swaps on are changed by a block.
+#[derive(Clone, Debug)]
+pub(crate) struct TrackedBundle {
+    pub replacement_key: B256,
+    pub bundle_hash: B256,
+    pub stuffing_txs_hashes: Vec<TxHash>,
+    pub pools: Vec<Address>,
+    pub profit: U256,
+    pub target_block: u64,
+    pub last_block: u64,
+}
+
+impl TrackedBundle {
+    /// Replacement key of the bundle with stuffing txs. Backruns without stuffing txs are not replaced.
+    pub fn replacement_key(stuffing_txs_hashes: &[TxHash]) -> Option<B256> {
+        if stuffing_txs_hashes.is_empty() {
+            return None;
+        }
+        Some(keccak256(stuffing_txs_hashes.iter().flat_map(|hash| hash.0).collect::<Vec<u8>>()))
+    }
+
+    /// Identity of the bundle txs. Tips are randomized, so the same swap composed twice has different txs.
+    pub fn bundle_hash(rlp_bundle: &[Bytes]) -> B256 {
+        keccak256(rlp_bundle.iter().flat_map(|rlp| rlp.iter().copied()).collect::<Vec<u8>>())
+    }
+
+    /// Replacement key of the backrun only bundle sent together with the stuffing bundle.
+    pub fn backrun_replacement_key(replacement_key: B256) -> B256 {
+        keccak256(replacement_key)
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct BundleTracker {
+    bundles: HashMap<B256, TrackedBundle>,
+}
+
+impl BundleTracker {
+    pub fn len(&self) -> usize {
+        self.bundles.len()
+    }
+
+    /// Returns false if the same bundle is already sent, or the bundle sent for the same stuffing txs is composed for the same state
+    /// and has at least the same profit.
+    pub fn is_better(&self, bundle: &TrackedBundle) -> bool {
+        match self.bundles.get(&bundle.replacement_key) {
+            Some(cur) => cur.bundle_hash != bundle.bundle_hash && (cur.target_block < bundle.target_block || cur.profit < bundle.profit),
+            None => true,
+        }
+    }
+
+    /// Tracks the sent bundle if it is better than the tracked one, see [`BundleTracker::is_better`].
+    pub fn update(&mut self, bundle: TrackedBundle) -> bool {
+        if !self.is_better(&bundle) {
+            return false;
+        }
+        self.bundles.insert(bundle.replacement_key, bundle);
+        true
+    }
+
+    /// Removes bundles with stuffing txs mined in the block.
+    pub fn remove_mined(&mut self, mined_txs: &HashSet<TxHash>) -> Vec<TrackedBundle> {
+        let keys: Vec<B256> = self
+            .bundles
+            .iter()
+            .filter(|(_, bundle)| bundle.stuffing_txs_hashes.iter().any(|hash| mined_txs.contains(hash)))
+            .map(|(key, _)| *key)
+            .collect();
+
+        keys.iter().filter_map(|key| self.bundles.remove(key)).collect()
+    }
+
+    /// Removes bundles after the block is mined. Expired bundles are dropped, bundles composed before the block that swap on pools
+    /// changed by the block are returned as stale.
+    pub fn remove_stale(&mut self, block_number: u64, changed_pools: &HashSet<Address>) -> Vec<TrackedBundle> {
+        self.bundles.retain(|_, bundle| bundle.last_block > block_number);
+
+        let keys: Vec<B256> = self
+            .bundles
+            .iter()
+            .filter(|(_, bundle)| bundle.target_block <= block_number && bundle.pools.iter().any(|pool| changed_pools.contains(pool)))
+            .map(|(key, _)| *key)
+            .collect();
+
+        keys.iter().filter_map(|key| self.bundles.remove(key)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle(stuffing: TxHash, pool: Address, profit: u64, target_block: u64) -> TrackedBundle {
+        TrackedBundle {
+            replacement_key: TrackedBundle::replacement_key(&[stuffing]).unwrap(),
+            bundle_hash: TrackedBundle::bundle_hash(&[Bytes::from(profit.to_be_bytes()), Bytes::from(target_block.to_be_bytes())]),
+            stuffing_txs_hashes: vec![stuffing],
+            pools: vec![pool],
+            profit: U256::from(profit),
+            target_block,
+            last_block: target_block + 2,
+        }
+    }
+
+    #[test]
+    fn test_replacement_key() {
+        assert_eq!(TrackedBundle::replacement_key(&[]), None);
+        assert_eq!(TrackedBundle::replacement_key(&[TxHash::ZERO]), TrackedBundle::replacement_key(&[TxHash::ZERO]));
+        assert_ne!(TrackedBundle::replacement_key(&[TxHash::ZERO]), TrackedBundle::replacement_key(&[TxHash::repeat_byte(1)]));
+    }
+
+    #[test]
+    fn test_update() {
+        let mut tracker = BundleTracker::default();
+        let stuffing = TxHash::repeat_byte(1);
+
+        assert!(tracker.update(bundle(stuffing, Address::ZERO, 100, 10)));
+        assert!(!tracker.update(bundle(stuffing, Address::ZERO, 100, 10)));
+        assert!(!tracker.update(bundle(stuffing, Address::ZERO, 50, 10)));
+        assert!(tracker.update(bundle(stuffing, Address::ZERO, 150, 10)));
+        // composed on a newer state
+        assert!(tracker.update(bundle(stuffing, Address::ZERO, 50, 11)));
+        assert!(tracker.update(bundle(TxHash::repeat_byte(2), Address::ZERO, 50, 11)));
+        assert_eq!(tracker.len(), 2);
+    }
+
+    #[test]
+    fn test_update_same_bundle() {
+        let mut tracker = BundleTracker::default();
+        let stuffing = TxHash::repeat_byte(1);
+
+        let sent = bundle(stuffing, Address::ZERO, 100, 10);
+        assert!(tracker.update(sent.clone()));
+        // resent for the next block
+        assert!(!tracker.update(TrackedBundle { target_block: 11, ..sent.clone() }));
+        // another swap with lower profit composed on a newer state
+        assert!(tracker.update(TrackedBundle { target_block: 11, profit: U256::from(50), bundle_hash: B256::repeat_byte(1), ..sent }));
+    }
+
+    #[test]
+    fn test_is_better() {
+        let mut tracker = BundleTracker::default();
+        let sent = bundle(TxHash::repeat_byte(1), Address::ZERO, 100, 10);
+
+        assert!(tracker.is_better(&sent));
+        assert_eq!(tracker.len(), 0);
+        tracker.update(sent.clone());
+        assert!(!tracker.is_better(&sent));
+        assert!(tracker.is_better(&bundle(TxHash::repeat_byte(1), Address::ZERO, 150, 10)));
+    }
+
+    #[test]
+    fn test_remove_mined() {
+        let mut tracker = BundleTracker::default();
+        tracker.update(bundle(TxHash::repeat_byte(1), Address::ZERO, 100, 10));
+        tracker.update(bundle(TxHash::repeat_byte(2), Address::ZERO, 100, 10));
+
+        let removed = tracker.remove_mined(&HashSet::from([TxHash::repeat_byte(1)]));
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].stuffing_txs_hashes, vec![TxHash::repeat_byte(1)]);
+        assert_eq!(tracker.len(), 1);
+    }
+
+    #[test]
+    fn test_remove_stale() {
+        let mut tracker = BundleTracker::default();
+        let pool = Address::repeat_byte(1);
+        tracker.update(bundle(TxHash::repeat_byte(1), pool, 100, 10));
+        tracker.update(bundle(TxHash::repeat_byte(2), Address::repeat_byte(2), 100, 10));
+        tracker.update(bundle(TxHash::repeat_byte(3), pool, 100, 11));
+
+        // bundle composed after block 10 is not stale
+        let removed = tracker.remove_stale(10, &HashSet::from([pool]));
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].stuffing_txs_hashes, vec![TxHash::repeat_byte(1)]);
+        assert_eq!(tracker.len(), 2);
+
+        // expired bundles are dropped
+        assert!(tracker.remove_stale(12, &HashSet::new()).is_empty());
+        assert_eq!(tracker.len(), 1);
+        assert!(tracker.remove_stale(13, &HashSet::new()).is_empty());
+        assert_eq!(tracker.len(), 0);
+    }
+}
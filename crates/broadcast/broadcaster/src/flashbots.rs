@@ -1,48 +1,269 @@
+use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::Duration;
 
 use alloy_network::Ethereum;
-use alloy_primitives::Bytes;
+use alloy_primitives::{Address, Bytes, TxHash};
 use alloy_provider::Provider;
 use alloy_transport::Transport;
 use eyre::{eyre, Result};
 use tokio::sync::broadcast::error::RecvError;
-use tracing::error;
+use tracing::{debug, error, info};
 
-use loom_broadcast_flashbots::Flashbots;
-use loom_core_actors::{subscribe, Actor, ActorResult, Broadcaster, Consumer, WorkerResult};
-use loom_core_actors_macros::{Accessor, Consumer};
+use crate::bundle_tracker::{BundleTracker, TrackedBundle};
+use crate::private_tx::single_tx;
+use loom_broadcast_flashbots::{Flashbots, RelaySubmissionResult};
+use loom_core_actors::{subscribe, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
+use loom_core_actors_macros::{Accessor, Consumer, Producer};
 use loom_core_blockchain::Blockchain;
-use loom_types_events::{MessageTxCompose, RlpState, TxComposeData, TxComposeMessageType};
+use loom_types_blockchain::{LoomBlock, LoomTx};
+use loom_types_events::{
+    MessageBlock, MessageBlockStateUpdate, MessageRelayEvent, MessageTxCompose, RelayBundleResult, RelayBundleStats, RelayEvents, RlpState,
+    TxComposeData, TxComposeMessageType, MEV_SHARE_ORIGIN,
+};
 
-async fn broadcast_task<P, T>(broadcast_request: TxComposeData, client: Arc<Flashbots<P, T>>) -> Result<()>
+pub(crate) fn relay_bundle_result(result: RelaySubmissionResult) -> RelayBundleResult {
+    RelayBundleResult {
+        relay_id: result.relay_id,
+        relay_name: result.relay_name,
+        target_block: result.target_block,
+        bundle_hash: result.bundle_hash,
+        error: result.error,
+        http_status: result.http_status,
+        latency: result.latency,
+        degraded: result.degraded,
+    }
+}
+
+async fn bundle_stats_task<P, T>(
+    client: Arc<Flashbots<P, T>>,
+    result: RelaySubmissionResult,
+    relay_events_tx: Broadcaster<MessageRelayEvent>,
+    bundle_stats_delay: Duration,
+) -> Result<()>
 where
     T: Transport + Clone,
     P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
 {
-    let block_number = broadcast_request.next_block_number;
+    let Some(bundle_hash) = result.bundle_hash else {
+        return Ok(());
+    };
 
-    if let Some(rlp_bundle) = broadcast_request.rlp_bundle.clone() {
-        let stuffing_rlp_bundle: Vec<Bytes> = rlp_bundle.iter().map(|item| item.unwrap()).collect();
-        let backrun_rlp_bundle: Vec<Bytes> =
-            rlp_bundle.iter().filter(|item| matches!(item, RlpState::Backrun(_))).map(|item| item.unwrap()).collect();
+    tokio::time::sleep(bundle_stats_delay).await;
 
-        if stuffing_rlp_bundle.iter().any(|i| i.is_empty()) || backrun_rlp_bundle.iter().any(|i| i.is_empty()) {
-            Err(eyre!("RLP_BUNDLE_IS_INCORRECT"))
-        } else {
-            client.broadcast_txes(backrun_rlp_bundle.clone(), block_number).await?;
-            client.broadcast_txes(stuffing_rlp_bundle.clone(), block_number).await?;
+    if let Some(stats) = client.bundle_stats(result.relay_name.as_str(), bundle_hash, result.target_block).await? {
+        debug!("Bundle stats {} {} : {:?}", result.relay_name, bundle_hash, stats);
+        let stats = RelayBundleStats {
+            relay_name: result.relay_name,
+            target_block: result.target_block,
+            bundle_hash,
+            is_simulated: stats.is_simulated,
+            is_high_priority: stats.is_high_priority,
+            considered_by_builders: stats.considered_by_builders_at.len(),
+            sealed_by_builders: stats.sealed_by_builders_at.len(),
+            status: stats.status,
+        };
+        if let Err(e) = relay_events_tx.send(MessageRelayEvent::new(RelayEvents::BundleStats(stats))).await {
+            debug!("relay_events_tx.send error : {}", e);
+        }
+    }
+    Ok(())
+}
 
-            Ok(())
+async fn send_relay_results<P, T>(
+    client: Arc<Flashbots<P, T>>,
+    results: Vec<RelaySubmissionResult>,
+    relay_events_tx: Option<Broadcaster<MessageRelayEvent>>,
+    bundle_stats_delay: Option<Duration>,
+) where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    if let Some(relay_events_tx) = relay_events_tx {
+        for result in results {
+            if let Some(bundle_stats_delay) = bundle_stats_delay {
+                tokio::task::spawn(bundle_stats_task(client.clone(), result.clone(), relay_events_tx.clone(), bundle_stats_delay));
+            }
+            if let Err(e) = relay_events_tx.send(MessageRelayEvent::new(RelayEvents::BundleResult(relay_bundle_result(result)))).await {
+                debug!("relay_events_tx.send error : {}", e);
+            }
         }
-    } else {
+    }
+}
+
+async fn broadcast_task<P, T>(
+    broadcast_request: TxComposeData,
+    client: Arc<Flashbots<P, T>>,
+    bundle_tracker: SharedState<BundleTracker>,
+    target_blocks: u64,
+    relay_events_tx: Option<Broadcaster<MessageRelayEvent>>,
+    bundle_stats_delay: Option<Duration>,
+) -> Result<()>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    let block_number = broadcast_request.next_block_number;
+    let replacement_key = TrackedBundle::replacement_key(&broadcast_request.stuffing_txs_hashes);
+
+    let Some(rlp_bundle) = broadcast_request.rlp_bundle.clone() else {
         error!("rlp_bundle is None");
-        Err(eyre!("RLP_BUNDLE_IS_NONE"))
+        return Err(eyre!("RLP_BUNDLE_IS_NONE"));
+    };
+    let stuffing_rlp_bundle: Vec<Bytes> = rlp_bundle.iter().map(|item| item.unwrap()).collect();
+    let backrun_rlp_bundle: Vec<Bytes> =
+        rlp_bundle.iter().filter(|item| matches!(item, RlpState::Backrun(_))).map(|item| item.unwrap()).collect();
+
+    if stuffing_rlp_bundle.iter().any(|i| i.is_empty()) || backrun_rlp_bundle.iter().any(|i| i.is_empty()) {
+        return Err(eyre!("RLP_BUNDLE_IS_INCORRECT"));
+    }
+
+    let tracked_bundle = replacement_key.map(|replacement_key| TrackedBundle {
+        replacement_key,
+        bundle_hash: TrackedBundle::bundle_hash(&backrun_rlp_bundle),
+        stuffing_txs_hashes: broadcast_request.stuffing_txs_hashes.clone(),
+        pools: broadcast_request.swap.as_ref().map(|swap| swap.get_pool_address_vec()).unwrap_or_default(),
+        profit: broadcast_request.swap.as_ref().map(|swap| swap.abs_profit_eth()).unwrap_or_default(),
+        target_block: block_number,
+        last_block: block_number + target_blocks - 1,
+    });
+
+    if let Some(bundle) = tracked_bundle.as_ref() {
+        if !bundle_tracker.read().await.is_better(bundle) {
+            debug!("Bundle {} for block {} is not better than the sent one", bundle.replacement_key, block_number);
+            return Ok(());
+        }
+    }
+
+    let (backrun_results, stuffing_results) = tokio::join!(
+        client.broadcast_txes_window(
+            backrun_rlp_bundle.clone(),
+            block_number,
+            target_blocks,
+            replacement_key.map(TrackedBundle::backrun_replacement_key)
+        ),
+        client.broadcast_txes_window(stuffing_rlp_bundle.clone(), block_number, target_blocks, replacement_key)
+    );
+
+    let results: Vec<RelaySubmissionResult> = backrun_results?.into_iter().chain(stuffing_results?).collect();
+
+    // track the bundle only when it is sent, a failed send must not block the next bundle for the same stuffing txs
+    if let Some(bundle) = tracked_bundle {
+        bundle_tracker.write().await.update(bundle);
+    }
+
+    send_relay_results(client, results, relay_events_tx, bundle_stats_delay).await;
+
+    Ok(())
+}
+
+async fn cancel_bundles_task<P, T>(
+    client: Arc<Flashbots<P, T>>,
+    bundles: Vec<TrackedBundle>,
+    from_block: u64,
+    relay_events_tx: Option<Broadcaster<MessageRelayEvent>>,
+) -> Result<()>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    let mut results = Vec::new();
+
+    for bundle in bundles {
+        let from_block = from_block.max(bundle.target_block);
+        if from_block > bundle.last_block {
+            continue;
+        }
+        info!("Cancelling bundle {} for blocks {}..={}", bundle.replacement_key, from_block, bundle.last_block);
+
+        let (backrun_results, stuffing_results) = tokio::join!(
+            client.cancel_bundles(TrackedBundle::backrun_replacement_key(bundle.replacement_key), from_block, bundle.last_block),
+            client.cancel_bundles(bundle.replacement_key, from_block, bundle.last_block)
+        );
+        results.extend(backrun_results?.into_iter().chain(stuffing_results?));
+    }
+
+    send_relay_results(client, results, relay_events_tx, None).await;
+
+    Ok(())
+}
+
+/// Cancels bundles of the window when their stuffing txs are mined or the pools they swap on are changed by a block.
+async fn bundle_cancel_worker<P, T>(
+    client: Arc<Flashbots<P, T>>,
+    bundle_tracker: SharedState<BundleTracker>,
+    block_with_tx_rx: Broadcaster<MessageBlock>,
+    block_state_update_rx: Broadcaster<MessageBlockStateUpdate>,
+    relay_events_tx: Option<Broadcaster<MessageRelayEvent>>,
+) -> WorkerResult
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    subscribe!(block_with_tx_rx);
+    subscribe!(block_state_update_rx);
+
+    loop {
+        tokio::select! {
+            msg = block_with_tx_rx.recv() => {
+                let block_msg : Result<MessageBlock, RecvError> = msg;
+                match block_msg {
+                    Ok(block_msg) => {
+                        let block = block_msg.inner.block;
+                        let mined_txs : HashSet<TxHash> = block.transactions().iter().map(|tx| tx.tx_hash()).collect();
+                        let bundles = bundle_tracker.write().await.remove_mined(&mined_txs);
+                        if !bundles.is_empty() {
+                            tokio::task::spawn(cancel_bundles_task(client.clone(), bundles, block.number() + 1, relay_events_tx.clone()));
+                        }
+                    }
+                    Err(e) => {
+                        error!("bundle_cancel_worker block_with_tx_rx {}", e)
+                    }
+                }
+            }
+            msg = block_state_update_rx.recv() => {
+                let state_update_msg : Result<MessageBlockStateUpdate, RecvError> = msg;
+                match state_update_msg {
+                    Ok(state_update_msg) => {
+                        let block_number = state_update_msg.inner.block_header.number;
+                        let changed_pools : HashSet<Address> = state_update_msg.inner.state_update.iter().flat_map(|update| update.keys().cloned()).collect();
+                        let mut bundle_tracker_guard = bundle_tracker.write().await;
+                        let bundles = bundle_tracker_guard.remove_stale(block_number, &changed_pools);
+                        debug!("Block {} stale bundles {} tracked bundles {}", block_number, bundles.len(), bundle_tracker_guard.len());
+                        drop(bundle_tracker_guard);
+                        if !bundles.is_empty() {
+                            tokio::task::spawn(cancel_bundles_task(client.clone(), bundles, block_number + 1, relay_events_tx.clone()));
+                        }
+                    }
+                    Err(e) => {
+                        error!("bundle_cancel_worker block_state_update_rx {}", e)
+                    }
+                }
+            }
+        }
     }
 }
 
+/// Returns true if the request is a single tx sent by PrivateTxBroadcastActor for the origins, all origins if None
+fn is_private_tx(broadcast_request: &TxComposeData, private_tx_origins: Option<&Option<Vec<String>>>) -> bool {
+    let origin_allowed = match private_tx_origins {
+        Some(Some(origins)) => broadcast_request.origin.as_ref().is_some_and(|origin| origins.contains(origin)),
+        Some(None) => true,
+        None => false,
+    };
+    origin_allowed && single_tx(broadcast_request).is_some()
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn flashbots_broadcaster_worker<P, T>(
     client: Arc<Flashbots<P, T>>,
+    bundle_tracker: SharedState<BundleTracker>,
+    target_blocks: u64,
     bundle_rx: Broadcaster<MessageTxCompose>,
+    relay_events_tx: Option<Broadcaster<MessageRelayEvent>>,
+    bundle_stats_delay: Option<Duration>,
+    origins: Option<Vec<String>>,
+    private_tx_origins: Option<Option<Vec<String>>>,
     allow_broadcast: bool,
 ) -> WorkerResult
 where
@@ -60,11 +281,23 @@ where
                 match broadcast_msg {
                     Ok(compose_request) => {
                         if let TxComposeMessageType::Broadcast(broadcast_request)  = compose_request.inner {
-                            if allow_broadcast {
+                            let origin_allowed = match &origins {
+                                Some(origins) => broadcast_request.origin.as_ref().is_some_and(|origin| origins.contains(origin)),
+                                None => true,
+                            };
+                            // single tx requests are sent by PrivateTxBroadcastActor
+                            let private_tx = is_private_tx(&broadcast_request, private_tx_origins.as_ref());
+                            // MEV-Share bundles are sent by MevShareBroadcastActor
+                            let mev_share = broadcast_request.origin.as_deref() == Some(MEV_SHARE_ORIGIN);
+                            if allow_broadcast && origin_allowed && !private_tx && !mev_share {
                                       tokio::task::spawn(
                                         broadcast_task(
                                             broadcast_request,
                                             client.clone(),
+                                            bundle_tracker.clone(),
+                                            target_blocks,
+                                            relay_events_tx.clone(),
+                                            bundle_stats_delay,
                                         )
                                     );
                                 }
@@ -112,11 +345,22 @@ where
     }
 }
 
-#[derive(Accessor, Consumer)]
+#[derive(Accessor, Consumer, Producer)]
 pub struct FlashbotsBroadcastActor<P, T> {
     client: Arc<Flashbots<P, T>>,
     #[consumer]
     tx_compose_channel_rx: Option<Broadcaster<MessageTxCompose>>,
+    #[consumer]
+    block_with_tx_rx: Option<Broadcaster<MessageBlock>>,
+    #[consumer]
+    block_state_update_rx: Option<Broadcaster<MessageBlockStateUpdate>>,
+    #[producer]
+    relay_events_channel_tx: Option<Broadcaster<MessageRelayEvent>>,
+    bundle_stats_delay: Option<Duration>,
+    target_blocks: u64,
+    origins: Option<Vec<String>>,
+    /// Origins of single tx requests sent by PrivateTxBroadcastActor, all origins if the inner value is None
+    private_tx_origins: Option<Option<Vec<String>>>,
     allow_broadcast: bool,
 }
 
@@ -126,11 +370,49 @@ where
     P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
 {
     pub fn new(client: Flashbots<P, T>, allow_broadcast: bool) -> FlashbotsBroadcastActor<P, T> {
-        FlashbotsBroadcastActor { client: Arc::new(client), tx_compose_channel_rx: None, allow_broadcast }
+        FlashbotsBroadcastActor {
+            client: Arc::new(client),
+            tx_compose_channel_rx: None,
+            block_with_tx_rx: None,
+            block_state_update_rx: None,
+            relay_events_channel_tx: None,
+            bundle_stats_delay: None,
+            target_blocks: 1,
+            origins: None,
+            private_tx_origins: None,
+            allow_broadcast,
+        }
+    }
+
+    /// Only broadcasts requests of the given origins.
+    pub fn with_origins(self, origins: Vec<String>) -> Self {
+        Self { origins: Some(origins), ..self }
+    }
+
+    /// Skips single tx requests of the origins sent by PrivateTxBroadcastActor, all origins if None.
+    pub fn with_private_tx_origins(self, origins: Option<Vec<String>>) -> Self {
+        Self { private_tx_origins: Some(origins), ..self }
+    }
+
+    /// Sends each bundle for `target_blocks` consecutive blocks. Bundles are cancelled on relays that support it when the stuffing
+    /// txs are mined or the swapped pools are changed, that requires block with txs and block state update channels.
+    pub fn with_target_blocks(self, target_blocks: u64) -> Self {
+        Self { target_blocks: target_blocks.max(1), ..self }
+    }
+
+    /// Polls bundle stats from relays that support it, `delay` after submission.
+    pub fn with_bundle_stats(self, delay: Duration) -> Self {
+        Self { bundle_stats_delay: Some(delay), ..self }
     }
 
     pub fn on_bc(self, bc: &Blockchain) -> Self {
-        Self { tx_compose_channel_rx: Some(bc.tx_compose_channel()), ..self }
+        Self {
+            tx_compose_channel_rx: Some(bc.tx_compose_channel()),
+            block_with_tx_rx: Some(bc.new_block_with_tx_channel()),
+            block_state_update_rx: Some(bc.new_block_state_update_channel()),
+            relay_events_channel_tx: Some(bc.relay_events_channel()),
+            ..self
+        }
     }
 }
 
@@ -140,12 +422,30 @@ where
     P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
 {
     fn start(&self) -> ActorResult {
-        let task = tokio::task::spawn(flashbots_broadcaster_worker(
+        let bundle_tracker = SharedState::new(BundleTracker::default());
+
+        let mut tasks = vec![tokio::task::spawn(flashbots_broadcaster_worker(
             self.client.clone(),
+            bundle_tracker.clone(),
+            self.target_blocks,
             self.tx_compose_channel_rx.clone().unwrap(),
+            self.relay_events_channel_tx.clone(),
+            self.bundle_stats_delay,
+            self.origins.clone(),
+            self.private_tx_origins.clone(),
             self.allow_broadcast,
-        ));
-        Ok(vec![task])
+        ))];
+
+        if let (Some(block_with_tx_rx), Some(block_state_update_rx)) = (self.block_with_tx_rx.clone(), self.block_state_update_rx.clone()) {
+            tasks.push(tokio::task::spawn(bundle_cancel_worker(
+                self.client.clone(),
+                bundle_tracker,
+                block_with_tx_rx,
+                block_state_update_rx,
+                self.relay_events_channel_tx.clone(),
+            )));
+        }
+        Ok(tasks)
     }
 
     fn name(&self) -> &'static str {
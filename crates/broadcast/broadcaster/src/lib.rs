@@ -1,5 +1,10 @@
 pub use anvil::AnvilBroadcastActor;
 pub use flashbots::FlashbotsBroadcastActor;
+pub use mev_share::MevShareBroadcastActor;
+pub use private_tx::PrivateTxBroadcastActor;
 
 mod anvil;
+mod bundle_tracker;
 mod flashbots;
+mod mev_share;
+mod private_tx;
@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use alloy_network::Ethereum;
+use alloy_primitives::Bytes;
+use alloy_provider::Provider;
+use alloy_transport::Transport;
+use eyre::{eyre, Result};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error};
+
+use crate::flashbots::relay_bundle_result;
+use loom_broadcast_flashbots::Flashbots;
+use loom_core_actors::{subscribe, Actor, ActorResult, Broadcaster, Consumer, Producer, WorkerResult};
+use loom_core_actors_macros::{Accessor, Consumer, Producer};
+use loom_core_blockchain::Blockchain;
+use loom_types_events::{
+    MessageRelayEvent, MessageTxCompose, RelayEvents, RlpState, TxComposeData, TxComposeMessageType, MEV_SHARE_ORIGIN,
+};
+
+async fn mev_share_broadcast_task<P, T>(
+    broadcast_request: TxComposeData,
+    client: Arc<Flashbots<P, T>>,
+    relay_events_tx: Option<Broadcaster<MessageRelayEvent>>,
+) -> Result<()>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    let Some(rlp_bundle) = broadcast_request.rlp_bundle else {
+        error!("rlp_bundle is None");
+        return Err(eyre!("RLP_BUNDLE_IS_NONE"));
+    };
+
+    if broadcast_request.stuffing_txs_hashes.is_empty() {
+        return Err(eyre!("NO_HINT_TX_HASH"));
+    }
+
+    let backrun_rlp_bundle: Vec<Bytes> =
+        rlp_bundle.iter().filter(|item| matches!(item, RlpState::Backrun(_))).map(|item| item.unwrap()).collect();
+
+    if backrun_rlp_bundle.is_empty() || backrun_rlp_bundle.iter().any(|i| i.is_empty()) {
+        return Err(eyre!("RLP_BUNDLE_IS_INCORRECT"));
+    }
+
+    let result = client
+        .broadcast_mev_share_bundle(broadcast_request.stuffing_txs_hashes, backrun_rlp_bundle, broadcast_request.next_block_number, None)
+        .await?;
+
+    if let Some(relay_events_tx) = relay_events_tx {
+        if let Err(e) = relay_events_tx.send(MessageRelayEvent::new(RelayEvents::BundleResult(relay_bundle_result(result)))).await {
+            debug!("relay_events_tx.send error : {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+async fn mev_share_broadcaster_worker<P, T>(
+    client: Arc<Flashbots<P, T>>,
+    bundle_rx: Broadcaster<MessageTxCompose>,
+    relay_events_tx: Option<Broadcaster<MessageRelayEvent>>,
+    allow_broadcast: bool,
+) -> WorkerResult
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    subscribe!(bundle_rx);
+
+    loop {
+        tokio::select! {
+            msg = bundle_rx.recv() => {
+                let broadcast_msg : Result<MessageTxCompose, RecvError> = msg;
+                match broadcast_msg {
+                    Ok(compose_request) => {
+                        if let TxComposeMessageType::Broadcast(broadcast_request) = compose_request.inner {
+                            if allow_broadcast && broadcast_request.origin.as_deref() == Some(MEV_SHARE_ORIGIN) {
+                                tokio::task::spawn(
+                                    mev_share_broadcast_task(
+                                        broadcast_request,
+                                        client.clone(),
+                                        relay_events_tx.clone(),
+                                    )
+                                );
+                            }
+                        }
+                    }
+                    Err(e)=>{
+                        error!("mev_share_broadcaster_worker {}", e)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Sends backruns of MEV-Share hints with `mev_sendBundle`
+#[derive(Accessor, Consumer, Producer)]
+pub struct MevShareBroadcastActor<P, T> {
+    client: Arc<Flashbots<P, T>>,
+    #[consumer]
+    tx_compose_channel_rx: Option<Broadcaster<MessageTxCompose>>,
+    #[producer]
+    relay_events_channel_tx: Option<Broadcaster<MessageRelayEvent>>,
+    allow_broadcast: bool,
+}
+
+impl<P, T> MevShareBroadcastActor<P, T>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    pub fn new(client: Flashbots<P, T>, allow_broadcast: bool) -> MevShareBroadcastActor<P, T> {
+        MevShareBroadcastActor { client: Arc::new(client), tx_compose_channel_rx: None, relay_events_channel_tx: None, allow_broadcast }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain) -> Self {
+        Self { tx_compose_channel_rx: Some(bc.tx_compose_channel()), relay_events_channel_tx: Some(bc.relay_events_channel()), ..self }
+    }
+}
+
+impl<P, T> Actor for MevShareBroadcastActor<P, T>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Send + Sync + Clone + 'static,
+{
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(mev_share_broadcaster_worker(
+            self.client.clone(),
+            self.tx_compose_channel_rx.clone().unwrap(),
+            self.relay_events_channel_tx.clone(),
+            self.allow_broadcast,
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "MevShareBroadcastActor"
+    }
+}
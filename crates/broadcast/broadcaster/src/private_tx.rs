@@ -0,0 +1,369 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use alloy_primitives::{keccak256, Address, Bytes, TxHash};
+use eyre::{eyre, Result};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error, info};
+
+use crate::flashbots::relay_bundle_result;
+use loom_broadcast_flashbots::{PrivateTxBroadcaster, RelaySubmissionResult};
+use loom_core_actors::{subscribe, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
+use loom_core_actors_macros::{Accessor, Consumer, Producer};
+use loom_core_blockchain::Blockchain;
+use loom_types_blockchain::{LoomBlock, LoomTx};
+use loom_types_events::{
+    MessageBlock, MessageBlockStateUpdate, MessageRelayEvent, MessageTxCompose, RelayEvents, RlpState, TxComposeData, TxComposeMessageType,
+};
+
+/// Private transaction waiting to be mined.
+struct PendingPrivateTx {
+    pools: Vec<Address>,
+    target_block: u64,
+    max_block: u64,
+}
+
+/// Private transactions sent for a window of blocks that can still be cancelled.
+#[derive(Default)]
+struct PrivateTxTracker {
+    txs: HashMap<TxHash, PendingPrivateTx>,
+    /// Number of the latest block with txs processed
+    last_mined_block: u64,
+    /// Pools changed by blocks that are not processed with txs yet
+    changed_pools: BTreeMap<u64, HashSet<Address>>,
+}
+
+impl PrivateTxTracker {
+    fn add(&mut self, tx_hash: TxHash, pending_tx: PendingPrivateTx) {
+        self.txs.insert(tx_hash, pending_tx);
+    }
+
+    /// Drops txs mined by the block and returns txs made stale by the pools changed in the block if its state update is received
+    fn on_block(&mut self, block_number: u64, mined_txs: &HashSet<TxHash>) -> Vec<TxHash> {
+        self.txs.retain(|tx_hash, _| !mined_txs.contains(tx_hash));
+        self.last_mined_block = self.last_mined_block.max(block_number);
+
+        let changed_pools = self.changed_pools.remove(&block_number);
+        self.changed_pools.retain(|changed_block_number, _| *changed_block_number > block_number);
+        match changed_pools {
+            Some(changed_pools) => self.remove_stale(block_number, &changed_pools),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns txs made stale by the pools changed in the block. Mined txs of the block are dropped first, so stale txs are
+    /// returned only after the block with txs is processed.
+    fn on_state_update(&mut self, block_number: u64, changed_pools: HashSet<Address>) -> Vec<TxHash> {
+        if block_number <= self.last_mined_block {
+            self.remove_stale(block_number, &changed_pools)
+        } else {
+            self.changed_pools.insert(block_number, changed_pools);
+            Vec::new()
+        }
+    }
+
+    /// Expired txs are dropped, txs sent before the block that swap on pools changed by the block are returned as stale
+    fn remove_stale(&mut self, block_number: u64, changed_pools: &HashSet<Address>) -> Vec<TxHash> {
+        self.txs.retain(|_, pending_tx| pending_tx.max_block > block_number);
+
+        let stale_txs: Vec<TxHash> = self
+            .txs
+            .iter()
+            .filter(|(_, pending_tx)| {
+                pending_tx.target_block <= block_number && pending_tx.pools.iter().any(|pool| changed_pools.contains(pool))
+            })
+            .map(|(tx_hash, _)| *tx_hash)
+            .collect();
+
+        for tx_hash in stale_txs.iter() {
+            self.txs.remove(tx_hash);
+        }
+        stale_txs
+    }
+}
+
+/// Returns the transaction of requests without stuffing txs that consist of a single backrun transaction.
+pub(crate) fn single_tx(broadcast_request: &TxComposeData) -> Option<Bytes> {
+    if !broadcast_request.stuffing_txs_hashes.is_empty() {
+        return None;
+    }
+    match broadcast_request.rlp_bundle.as_deref() {
+        Some([RlpState::Backrun(tx)]) if !tx.is_empty() => Some(tx.clone()),
+        _ => None,
+    }
+}
+
+async fn send_relay_results(results: Vec<RelaySubmissionResult>, relay_events_tx: Option<Broadcaster<MessageRelayEvent>>) {
+    if let Some(relay_events_tx) = relay_events_tx {
+        for result in results {
+            if let Err(e) = relay_events_tx.send(MessageRelayEvent::new(RelayEvents::BundleResult(relay_bundle_result(result)))).await {
+                debug!("relay_events_tx.send error : {}", e);
+            }
+        }
+    }
+}
+
+async fn private_tx_broadcast_task(
+    tx: Bytes,
+    target_block: u64,
+    max_block: u64,
+    client: Arc<PrivateTxBroadcaster>,
+    relay_events_tx: Option<Broadcaster<MessageRelayEvent>>,
+) -> Result<()> {
+    let results = client.send_private_tx(tx, target_block, Some(max_block)).await;
+    let sent = results.iter().any(|result| result.is_ok());
+    send_relay_results(results, relay_events_tx).await;
+
+    if sent {
+        Ok(())
+    } else {
+        Err(eyre!("PRIVATE_TX_NOT_SENT"))
+    }
+}
+
+async fn private_tx_cancel_task(
+    tx_hash: TxHash,
+    target_block: u64,
+    client: Arc<PrivateTxBroadcaster>,
+    relay_events_tx: Option<Broadcaster<MessageRelayEvent>>,
+) -> Result<()> {
+    info!("Cancelling private tx {}", tx_hash);
+    let results = client.cancel_private_tx(tx_hash, target_block).await;
+    send_relay_results(results, relay_events_tx).await;
+    Ok(())
+}
+
+async fn private_tx_broadcaster_worker(
+    client: Arc<PrivateTxBroadcaster>,
+    tracker: SharedState<PrivateTxTracker>,
+    tx_compose_rx: Broadcaster<MessageTxCompose>,
+    relay_events_tx: Option<Broadcaster<MessageRelayEvent>>,
+    origins: Option<Vec<String>>,
+    target_blocks: u64,
+    allow_broadcast: bool,
+) -> WorkerResult {
+    subscribe!(tx_compose_rx);
+
+    loop {
+        let compose_request = match tx_compose_rx.recv().await {
+            Ok(compose_request) => compose_request,
+            Err(e) => {
+                error!("private_tx_broadcaster_worker {}", e);
+                continue;
+            }
+        };
+        let TxComposeMessageType::Broadcast(broadcast_request) = compose_request.inner else {
+            continue;
+        };
+        if let Some(origins) = &origins {
+            if !broadcast_request.origin.as_ref().is_some_and(|origin| origins.contains(origin)) {
+                continue;
+            }
+        }
+        let Some(tx) = single_tx(&broadcast_request) else {
+            continue;
+        };
+        if !allow_broadcast {
+            continue;
+        }
+
+        let target_block = broadcast_request.next_block_number;
+        let max_block = target_block + target_blocks - 1;
+
+        tracker.write().await.add(
+            keccak256(&tx),
+            PendingPrivateTx {
+                pools: broadcast_request.swap.as_ref().map(|swap| swap.get_pool_address_vec()).unwrap_or_default(),
+                target_block,
+                max_block,
+            },
+        );
+
+        tokio::task::spawn(private_tx_broadcast_task(tx, target_block, max_block, client.clone(), relay_events_tx.clone()));
+    }
+}
+
+/// Drops mined private txs and cancels txs swapping on pools changed by a block.
+async fn private_tx_cancel_worker(
+    client: Arc<PrivateTxBroadcaster>,
+    tracker: SharedState<PrivateTxTracker>,
+    block_with_tx_rx: Broadcaster<MessageBlock>,
+    block_state_update_rx: Broadcaster<MessageBlockStateUpdate>,
+    relay_events_tx: Option<Broadcaster<MessageRelayEvent>>,
+) -> WorkerResult {
+    subscribe!(block_with_tx_rx);
+    subscribe!(block_state_update_rx);
+
+    loop {
+        let (block_number, stale_txs) = tokio::select! {
+            msg = block_with_tx_rx.recv() => {
+                let block_msg : Result<MessageBlock, RecvError> = msg;
+                match block_msg {
+                    Ok(block_msg) => {
+                        let block = block_msg.inner.block;
+                        let mined_txs : HashSet<TxHash> = block.transactions().iter().map(|tx| tx.tx_hash()).collect();
+                        (block.number(), tracker.write().await.on_block(block.number(), &mined_txs))
+                    }
+                    Err(e) => {
+                        error!("private_tx_cancel_worker block_with_tx_rx {}", e);
+                        continue;
+                    }
+                }
+            }
+            msg = block_state_update_rx.recv() => {
+                let state_update_msg : Result<MessageBlockStateUpdate, RecvError> = msg;
+                match state_update_msg {
+                    Ok(state_update_msg) => {
+                        let block_number = state_update_msg.inner.block_header.number;
+                        let changed_pools : HashSet<Address> =
+                            state_update_msg.inner.state_update.iter().flat_map(|update| update.keys().cloned()).collect();
+                        (block_number, tracker.write().await.on_state_update(block_number, changed_pools))
+                    }
+                    Err(e) => {
+                        error!("private_tx_cancel_worker block_state_update_rx {}", e);
+                        continue;
+                    }
+                }
+            }
+        };
+
+        for tx_hash in stale_txs {
+            tokio::task::spawn(private_tx_cancel_task(tx_hash, block_number + 1, client.clone(), relay_events_tx.clone()));
+        }
+    }
+}
+
+/// Sends broadcast requests consisting of a single transaction without stuffing txs to private transaction endpoints.
+/// Pending transactions are cancelled when the pools they swap on are changed by a block, that requires block with txs and block
+/// state update channels.
+#[derive(Accessor, Consumer, Producer)]
+pub struct PrivateTxBroadcastActor {
+    client: Arc<PrivateTxBroadcaster>,
+    #[consumer]
+    tx_compose_channel_rx: Option<Broadcaster<MessageTxCompose>>,
+    #[consumer]
+    block_with_tx_rx: Option<Broadcaster<MessageBlock>>,
+    #[consumer]
+    block_state_update_rx: Option<Broadcaster<MessageBlockStateUpdate>>,
+    #[producer]
+    relay_events_channel_tx: Option<Broadcaster<MessageRelayEvent>>,
+    origins: Option<Vec<String>>,
+    target_blocks: u64,
+    allow_broadcast: bool,
+}
+
+impl PrivateTxBroadcastActor {
+    pub fn new(client: PrivateTxBroadcaster, allow_broadcast: bool) -> PrivateTxBroadcastActor {
+        PrivateTxBroadcastActor {
+            client: Arc::new(client),
+            tx_compose_channel_rx: None,
+            block_with_tx_rx: None,
+            block_state_update_rx: None,
+            relay_events_channel_tx: None,
+            origins: None,
+            target_blocks: 1,
+            allow_broadcast,
+        }
+    }
+
+    /// Only broadcasts requests of the given origins.
+    pub fn with_origins(self, origins: Vec<String>) -> Self {
+        Self { origins: Some(origins), ..self }
+    }
+
+    /// Transactions are valid for `target_blocks` consecutive blocks.
+    pub fn with_target_blocks(self, target_blocks: u64) -> Self {
+        Self { target_blocks: target_blocks.max(1), ..self }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain) -> Self {
+        Self {
+            tx_compose_channel_rx: Some(bc.tx_compose_channel()),
+            block_with_tx_rx: Some(bc.new_block_with_tx_channel()),
+            block_state_update_rx: Some(bc.new_block_state_update_channel()),
+            relay_events_channel_tx: Some(bc.relay_events_channel()),
+            ..self
+        }
+    }
+}
+
+impl Actor for PrivateTxBroadcastActor {
+    fn start(&self) -> ActorResult {
+        let tracker = SharedState::new(PrivateTxTracker::default());
+
+        let mut tasks = vec![tokio::task::spawn(private_tx_broadcaster_worker(
+            self.client.clone(),
+            tracker.clone(),
+            self.tx_compose_channel_rx.clone().unwrap(),
+            self.relay_events_channel_tx.clone(),
+            self.origins.clone(),
+            self.target_blocks,
+            self.allow_broadcast,
+        ))];
+
+        if let (Some(block_with_tx_rx), Some(block_state_update_rx)) = (self.block_with_tx_rx.clone(), self.block_state_update_rx.clone()) {
+            tasks.push(tokio::task::spawn(private_tx_cancel_worker(
+                self.client.clone(),
+                tracker,
+                block_with_tx_rx,
+                block_state_update_rx,
+                self.relay_events_channel_tx.clone(),
+            )));
+        }
+        Ok(tasks)
+    }
+
+    fn name(&self) -> &'static str {
+        "PrivateTxBroadcastActor"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_tx() {
+        let tx = Bytes::from(vec![1, 2, 3]);
+        let request = TxComposeData { rlp_bundle: Some(vec![RlpState::Backrun(tx.clone())]), ..TxComposeData::default() };
+        assert_eq!(single_tx(&request), Some(tx.clone()));
+
+        let request = TxComposeData {
+            stuffing_txs_hashes: vec![TxHash::ZERO],
+            rlp_bundle: Some(vec![RlpState::Stuffing(Bytes::from(vec![1])), RlpState::Backrun(tx.clone())]),
+            ..TxComposeData::default()
+        };
+        assert_eq!(single_tx(&request), None);
+
+        let request =
+            TxComposeData { rlp_bundle: Some(vec![RlpState::Backrun(tx.clone()), RlpState::Backrun(tx)]), ..TxComposeData::default() };
+        assert_eq!(single_tx(&request), None);
+        assert_eq!(single_tx(&TxComposeData::default()), None);
+    }
+
+    fn pending_tx(pool: Address, target_block: u64) -> PendingPrivateTx {
+        PendingPrivateTx { pools: vec![pool], target_block, max_block: target_block + 1 }
+    }
+
+    #[test]
+    fn test_private_tx_tracker() {
+        let (pool, other_pool) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let mut tracker = PrivateTxTracker::default();
+        tracker.add(TxHash::repeat_byte(1), pending_tx(pool, 10));
+        tracker.add(TxHash::repeat_byte(2), pending_tx(pool, 10));
+        tracker.add(TxHash::repeat_byte(3), pending_tx(pool, 11));
+        tracker.add(TxHash::repeat_byte(4), pending_tx(other_pool, 10));
+
+        // state update received before the block with txs is deferred, the mined tx is not cancelled
+        assert!(tracker.on_state_update(10, [pool].into_iter().collect()).is_empty());
+        let stale_txs = tracker.on_block(10, &[TxHash::repeat_byte(1)].into_iter().collect());
+        assert_eq!(stale_txs, vec![TxHash::repeat_byte(2)]);
+        assert_eq!(tracker.txs.len(), 2);
+
+        // tx sent for the next block is not stale, expired tx is dropped
+        let stale_txs = tracker.on_block(11, &HashSet::new());
+        assert!(stale_txs.is_empty());
+        assert_eq!(tracker.on_state_update(11, [pool].into_iter().collect()), vec![TxHash::repeat_byte(3)]);
+        assert!(tracker.txs.is_empty());
+    }
+}
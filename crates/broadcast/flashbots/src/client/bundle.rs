@@ -3,12 +3,13 @@ use std::fmt::{Display, Formatter};
 use alloy_consensus::TxEnvelope;
 use alloy_network::eip2718::Encodable2718;
 use alloy_network::TransactionResponse;
-use alloy_primitives::{keccak256, Address, Bytes, TxHash, U256, U64};
+use alloy_primitives::{hex, keccak256, Address, Bytes, TxHash, B256, U256, U64};
 use alloy_rpc_types::{AccessList, Log, Transaction};
 use eyre::Result;
 use serde::ser::Error as SerdeError;
 use serde::{Deserialize, Serialize, Serializer};
 
+use crate::client::relay::RelayCapabilities;
 use crate::client::utils::{deserialize_optional_h160, deserialize_u256, deserialize_u64};
 
 /// A bundle hash.
@@ -87,6 +88,19 @@ pub struct BundleRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "baseFee")]
     simulation_basefee: Option<u64>,
+
+    #[serde(rename = "replacementUuid")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replacement_uuid: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    refund_percent: Option<u8>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    refund_recipient: Option<Address>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    builders: Option<Vec<String>>,
 }
 
 pub fn serialize_txs<S>(txs: &[BundleTransaction], s: S) -> Result<S::Ok, S::Error>
@@ -277,6 +291,98 @@ impl BundleRequest {
         self.max_timestamp = Some(timestamp);
         self
     }
+
+    /// Get the replacement uuid of the bundle (if any).
+    pub fn replacement_uuid(&self) -> Option<&String> {
+        self.replacement_uuid.as_ref()
+    }
+
+    /// Set the uuid used to replace or cancel the bundle. A bundle sent with the same uuid replaces this one.
+    ///
+    /// See [`eth_cancelBundle`][fb_cancel_bundle] in the Flashbots documentation.
+    ///
+    /// [fb_cancel_bundle]: https://docs.flashbots.net/flashbots-auction/advanced/rpc-endpoint#eth_cancelbundle
+    pub fn set_replacement_uuid(mut self, uuid: String) -> Self {
+        self.replacement_uuid = Some(uuid);
+        self
+    }
+
+    /// Get the percent of the bundle profit refunded by the builder (if any).
+    pub fn refund_percent(&self) -> Option<u8> {
+        self.refund_percent
+    }
+
+    /// Set the percent of the bundle profit the builder refunds to the refund recipient.
+    pub fn set_refund_percent(mut self, refund_percent: u8) -> Self {
+        self.refund_percent = Some(refund_percent);
+        self
+    }
+
+    /// Get the address receiving the refund (if any).
+    pub fn refund_recipient(&self) -> Option<Address> {
+        self.refund_recipient
+    }
+
+    /// Set the address receiving the refund. Builders refund to the sender of the first transaction if not set.
+    pub fn set_refund_recipient(mut self, refund_recipient: Address) -> Self {
+        self.refund_recipient = Some(refund_recipient);
+        self
+    }
+
+    /// Get the builders the bundle is shared with (if any).
+    pub fn builders(&self) -> Option<&Vec<String>> {
+        self.builders.as_ref()
+    }
+
+    /// Set the builders the relay shares the bundle with.
+    pub fn set_builders(mut self, builders: Vec<String>) -> Self {
+        self.builders = Some(builders);
+        self
+    }
+
+    /// Renders the bundle for a relay, dropping the fields the relay doesn't accept.
+    pub fn render(&self, capabilities: &RelayCapabilities) -> BundleRequest {
+        let mut bundle = self.clone();
+
+        if !capabilities.reverting_tx_hashes {
+            bundle.revertible_transaction_hashes.clear();
+        }
+        if !capabilities.timestamps {
+            bundle.min_timestamp = None;
+            bundle.max_timestamp = None;
+        }
+        if !capabilities.refund {
+            bundle.refund_percent = None;
+            bundle.refund_recipient = None;
+        }
+        if !capabilities.builders {
+            bundle.builders = None;
+        }
+        bundle
+    }
+}
+
+/// Derives the replacement uuid of a bundle from the replacement key and the target block. Bundles for different blocks
+/// have different uuids, so a bundle only replaces the bundle with the same key for the same block.
+pub fn replacement_uuid(key: &B256, block_number: u64) -> String {
+    let mut bytes = key.to_vec();
+    bytes.extend_from_slice(&block_number.to_be_bytes());
+    let hash = keccak256(bytes);
+
+    let mut uuid = [0u8; 16];
+    uuid.copy_from_slice(&hash[..16]);
+    // version 4, variant 1
+    uuid[6] = (uuid[6] & 0x0f) | 0x40;
+    uuid[8] = (uuid[8] & 0x3f) | 0x80;
+
+    format!(
+        "{}-{}-{}-{}-{}",
+        hex::encode(&uuid[0..4]),
+        hex::encode(&uuid[4..6]),
+        hex::encode(&uuid[6..8]),
+        hex::encode(&uuid[8..10]),
+        hex::encode(&uuid[10..16])
+    )
 }
 
 /// Details of a simulated transaction.
@@ -410,6 +516,46 @@ impl SimulatedBundle {
     }
 }
 
+/// Parameters of a bundle stats request.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleStatsRequest {
+    pub bundle_hash: BundleHash,
+    pub block_number: U64,
+}
+
+/// Parameters of a bundle cancellation request.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelBundleRequest {
+    pub replacement_uuid: String,
+}
+
+/// Time at which a builder considered or sealed a bundle.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct BuilderTimestamp {
+    pub pubkey: String,
+    pub timestamp: String,
+}
+
+/// Bundle stats reported by a relay.
+///
+/// See [`flashbots_getBundleStatsV2`][fb_getBundleStatsV2] for more information. Relays implementing their own
+/// stats method usually return a subset of these fields or a plain `status`.
+///
+/// [fb_getBundleStatsV2]: https://docs.flashbots.net/flashbots-auction/advanced/rpc-endpoint#flashbots_getbundlestatsv2
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BundleStats {
+    pub is_high_priority: Option<bool>,
+    pub is_simulated: Option<bool>,
+    pub simulated_at: Option<String>,
+    pub received_at: Option<String>,
+    pub considered_by_builders_at: Vec<BuilderTimestamp>,
+    pub sealed_by_builders_at: Vec<BuilderTimestamp>,
+    pub status: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
@@ -455,6 +601,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn bundle_serialize_replacement_uuid() {
+        let uuid = replacement_uuid(&B256::ZERO, 2);
+        let bundle =
+            BundleRequest::new().push_transaction(Bytes::from(vec![0x1])).set_target_block(U64::from(2)).set_replacement_uuid(uuid.clone());
+
+        assert_eq!(
+            &serde_json::to_string(&bundle).unwrap(),
+            &format!(r#"{{"txs":["0x01"],"blockNumber":"0x2","replacementUuid":"{uuid}"}}"#)
+        );
+
+        let request = CancelBundleRequest { replacement_uuid: uuid.clone() };
+        assert_eq!(&serde_json::to_string(&request).unwrap(), &format!(r#"{{"replacementUuid":"{uuid}"}}"#));
+    }
+
+    #[test]
+    fn bundle_render_capabilities() {
+        let bundle = BundleRequest::new()
+            .push_revertible_transaction(Bytes::from(vec![0x1]))
+            .set_target_block(U64::from(2))
+            .set_min_timestamp(1000)
+            .set_refund_percent(90)
+            .set_refund_recipient(Address::repeat_byte(1))
+            .set_builders(vec!["flashbots".to_string()]);
+
+        assert_eq!(
+            &serde_json::to_string(&bundle.render(&RelayCapabilities::default())).unwrap(),
+            r#"{"txs":["0x01"],"revertingTxHashes":["0x5fe7f977e71dba2ea1a68e21057beebb9be2ac30c6410aa38d4f3fbe41dcffd2"],"blockNumber":"0x2","minTimestamp":1000}"#
+        );
+
+        let capabilities =
+            RelayCapabilities { reverting_tx_hashes: false, timestamps: false, refund: true, ..RelayCapabilities::default() };
+        assert_eq!(
+            &serde_json::to_string(&bundle.render(&capabilities)).unwrap(),
+            r#"{"txs":["0x01"],"blockNumber":"0x2","refundPercent":90,"refundRecipient":"0x0101010101010101010101010101010101010101"}"#
+        );
+
+        let capabilities = RelayCapabilities { builders: true, ..RelayCapabilities::default() };
+        assert_eq!(bundle.render(&capabilities).builders(), Some(&vec!["flashbots".to_string()]));
+        assert_eq!(bundle.render(&capabilities).refund_percent(), None);
+    }
+
+    #[test]
+    fn replacement_uuid_format() {
+        let uuid = replacement_uuid(&B256::ZERO, 2);
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(uuid.chars().nth(14), Some('4'));
+        assert!(matches!(uuid.chars().nth(19), Some('8' | '9' | 'a' | 'b')));
+        assert_eq!(uuid, replacement_uuid(&B256::ZERO, 2));
+        assert_ne!(uuid, replacement_uuid(&B256::ZERO, 3));
+        assert_ne!(uuid, replacement_uuid(&B256::repeat_byte(1), 2));
+    }
+
     #[test]
     fn simulated_bundle_deserialize() {
         let simulated_bundle: SimulatedBundle = serde_json::from_str(
@@ -561,4 +760,46 @@ mod tests {
         assert_eq!(tx.error, Some("execution reverted".into()));
         assert_eq!(tx.revert, Some("transfer failed".into()));
     }
+
+    #[test]
+    fn bundle_stats_deserialize() {
+        let stats: BundleStats = serde_json::from_str(
+            r#"{
+    "isHighPriority": true,
+    "isSimulated": true,
+    "simulatedAt": "2022-10-06T21:36:06.317Z",
+    "receivedAt": "2022-10-06T21:36:06.250Z",
+    "consideredByBuildersAt": [
+      {
+        "pubkey": "0x81babeec8c9f2bb9c329fd8a3b176032fe0ab5f3b92a3f44d4575a231c7bd9c31d10b6328ef68ed1e8c02a3dbc8e80f9",
+        "timestamp": "2022-10-06T21:36:06.343Z"
+      }
+    ],
+    "sealedByBuildersAt": []
+  }"#,
+        )
+        .unwrap();
+
+        assert_eq!(stats.is_high_priority, Some(true));
+        assert_eq!(stats.is_simulated, Some(true));
+        assert_eq!(stats.considered_by_builders_at.len(), 1);
+        assert!(stats.sealed_by_builders_at.is_empty());
+        assert_eq!(stats.status, None);
+
+        let stats: BundleStats = serde_json::from_str(r#"{"status": "Received"}"#).unwrap();
+        assert_eq!(stats.status, Some("Received".into()));
+        assert_eq!(stats.is_simulated, None);
+    }
+
+    #[test]
+    fn bundle_stats_request_serialize() {
+        let request = BundleStatsRequest {
+            bundle_hash: TxHash::from_str("0x73b1e258c7a42fd0230b2fd05529c5d4b6fcb66c227783f8bece8aeacdd1db2e").unwrap(),
+            block_number: U64::from(2),
+        };
+        assert_eq!(
+            &serde_json::to_string(&request).unwrap(),
+            r#"{"bundleHash":"0x73b1e258c7a42fd0230b2fd05529c5d4b6fcb66c227783f8bece8aeacdd1db2e","blockNumber":"0x2"}"#
+        );
+    }
 }
@@ -0,0 +1,74 @@
+use alloy_primitives::{Bytes, TxHash, U64};
+use serde::Serialize;
+
+/// MEV-Share bundle item. Transactions from the hint stream are referenced by hash, own transactions are sent signed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum MevBundleItem {
+    Hash {
+        hash: TxHash,
+    },
+    #[serde(rename_all = "camelCase")]
+    Tx {
+        tx: Bytes,
+        can_revert: bool,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MevBundleInclusion {
+    pub block: U64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_block: Option<U64>,
+}
+
+/// Parameters of `mev_sendBundle`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct MevSendBundle {
+    pub version: String,
+    pub inclusion: MevBundleInclusion,
+    pub body: Vec<MevBundleItem>,
+}
+
+impl MevSendBundle {
+    pub fn new(block: u64, max_block: Option<u64>) -> Self {
+        Self {
+            version: "v0.1".to_string(),
+            inclusion: MevBundleInclusion { block: U64::from(block), max_block: max_block.map(U64::from) },
+            body: Vec::new(),
+        }
+    }
+
+    pub fn push_hash(mut self, hash: TxHash) -> Self {
+        self.body.push(MevBundleItem::Hash { hash });
+        self
+    }
+
+    pub fn push_tx(mut self, tx: Bytes, can_revert: bool) -> Self {
+        self.body.push(MevBundleItem::Tx { tx, can_revert });
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mev_send_bundle_serialize() {
+        let bundle = MevSendBundle::new(100, Some(101)).push_hash(TxHash::repeat_byte(1)).push_tx(Bytes::from(vec![2, 3]), false);
+
+        assert_eq!(
+            serde_json::to_string(&bundle).unwrap(),
+            r#"{"version":"v0.1","inclusion":{"block":"0x64","maxBlock":"0x65"},"body":[{"hash":"0x0101010101010101010101010101010101010101010101010101010101010101"},{"tx":"0x0203","canRevert":false}]}"#
+        );
+    }
+
+    #[test]
+    fn mev_send_bundle_serialize_no_max_block() {
+        let bundle = MevSendBundle::new(100, None);
+
+        assert_eq!(serde_json::to_string(&bundle).unwrap(), r#"{"version":"v0.1","inclusion":{"block":"0x64"},"body":[]}"#);
+    }
+}
@@ -4,15 +4,21 @@
 //! [Flashbots](https://docs.flashbots.net) bundles.
 //!
 pub use body::make_signed_body;
-pub use bundle::{BundleHash, BundleRequest, BundleTransaction, SimulatedBundle, SimulatedTransaction};
+pub use bundle::{
+    replacement_uuid, BuilderTimestamp, BundleHash, BundleRequest, BundleStats, BundleStatsRequest, BundleTransaction, CancelBundleRequest,
+    SimulatedBundle, SimulatedTransaction,
+};
 pub use jsonrpc::SendBundleResponseType;
+pub use mev_share::{MevBundleInclusion, MevBundleItem, MevSendBundle};
 pub use middleware::{FlashbotsMiddleware, FlashbotsMiddlewareError};
-pub use relay::{Relay, RelayConfig, RelayError};
+pub use relay::{Relay, RelayCapabilities, RelayConfig, RelayError, FLASHBOTS_SIGNATURE_HEADER};
 
 mod bundle;
 
 mod middleware;
 
+mod mev_share;
+
 mod jsonrpc;
 mod relay;
 
@@ -11,6 +11,45 @@ use thiserror::Error;
 use tracing::{debug, trace};
 use url::Url;
 
+/// Header carrying the signature of the request body.
+pub const FLASHBOTS_SIGNATURE_HEADER: &str = "X-Flashbots-Signature";
+
+/// Bundle fields and signing accepted by a relay. Bundles are rendered for each relay with [`BundleRequest::render`](crate::client::BundleRequest::render).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RelayCapabilities {
+    /// `refundPercent` and `refundRecipient`
+    pub refund: bool,
+    /// `builders` the relay shares the bundle with
+    pub builders: bool,
+    /// `revertingTxHashes`
+    pub reverting_tx_hashes: bool,
+    /// `minTimestamp` and `maxTimestamp`
+    pub timestamps: bool,
+    /// Header for the body signature, [`FLASHBOTS_SIGNATURE_HEADER`] if not set.
+    pub signature_header: Option<String>,
+}
+
+impl Default for RelayCapabilities {
+    /// Fields of `eth_sendBundle` accepted by all relays.
+    fn default() -> Self {
+        Self { refund: false, builders: false, reverting_tx_hashes: true, timestamps: true, signature_header: None }
+    }
+}
+
+impl RelayCapabilities {
+    pub fn with_refund(self) -> Self {
+        Self { refund: true, ..self }
+    }
+
+    pub fn with_builders(self) -> Self {
+        Self { builders: true, ..self }
+    }
+
+    pub fn signature_header(&self) -> &str {
+        self.signature_header.as_deref().unwrap_or(FLASHBOTS_SIGNATURE_HEADER)
+    }
+}
+
 /// Configuration for a Flashbots relay.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct RelayConfig {
@@ -18,6 +57,11 @@ pub struct RelayConfig {
     pub name: String,
     pub url: String,
     pub no_sign: Option<bool>,
+    /// Method used to poll bundle stats, e.g. `flashbots_getBundleStatsV2`. Polling is disabled if not set.
+    pub stats_method: Option<String>,
+    /// Method used to cancel bundles by `replacementUuid`, e.g. `eth_cancelBundle`. Relays without it don't receive replacement uuids.
+    pub cancel_method: Option<String>,
+    pub capabilities: RelayCapabilities,
 }
 
 /// A Flashbots relay client.
@@ -47,7 +91,7 @@ pub enum RelayError {
     JsonRpcError(#[from] JsonRpcError),
     /// The request parameters were invalid.
     #[error("Client error: {text}")]
-    ClientError { text: String },
+    ClientError { status: u16, text: String },
     /// The request could not be serialized.
     #[error(transparent)]
     RequestSerdeJson(#[from] serde_json::Error),
@@ -59,6 +103,19 @@ pub enum RelayError {
     ResponseSerdeJson { err: serde_json::Error, text: String },
 }
 
+impl RelayError {
+    /// HTTP status code returned by the relay, if the request reached it.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            RelayError::RequestError(err) => err.status().map(|status| status.as_u16()),
+            RelayError::ClientError { status, .. } => Some(*status),
+            // JSON-RPC errors and malformed bodies come with a successful HTTP status
+            RelayError::JsonRpcError(_) | RelayError::ResponseSerdeJson { .. } => Some(200),
+            _ => None,
+        }
+    }
+}
+
 impl Relay {
     /// Initializes a new relay client.
     pub fn new(url: impl Into<Url>, signer: Option<PrivateKeySigner>) -> Self {
@@ -87,7 +144,7 @@ impl Relay {
             trace!("Signer on wallet  : {}", signer.address());
             let signature = signer.sign_message(body_hash.as_bytes()).await.map_err(RelayError::SignerError)?;
 
-            req = req.header("X-Flashbots-Signature", format!("{}:0x{}", signer.address(), hex::encode(signature.as_bytes())));
+            req = req.header(FLASHBOTS_SIGNATURE_HEADER, format!("{}:0x{}", signer.address(), hex::encode(signature.as_bytes())));
         }
 
         let res = req.send().await?;
@@ -99,7 +156,7 @@ impl Relay {
                 let status_code = err.status().unwrap();
                 if status_code.is_client_error() {
                     // Client error (400-499)
-                    Err(RelayError::ClientError { text })
+                    Err(RelayError::ClientError { status: status_code.as_u16(), text })
                 } else {
                     // Internal server error (500-599)
                     Err(RelayError::RequestError(err))
@@ -116,10 +173,20 @@ impl Relay {
     }
 
     pub async fn serialized_request<R: DeserializeOwned>(&self, body: String, signature: Option<String>) -> Result<R, RelayError> {
+        self.serialized_request_with_header(body, signature, FLASHBOTS_SIGNATURE_HEADER).await
+    }
+
+    /// Sends a serialized request with the signature in `signature_header`.
+    pub async fn serialized_request_with_header<R: DeserializeOwned>(
+        &self,
+        body: String,
+        signature: Option<String>,
+        signature_header: &str,
+    ) -> Result<R, RelayError> {
         let mut req = self.client.post(self.url.as_ref()).body(body).header("Content-Type", "application/json");
 
         if let Some(signature) = signature {
-            req = req.header("X-Flashbots-Signature", signature);
+            req = req.header(signature_header, signature);
         }
 
         let res = req.send().await?;
@@ -131,7 +198,7 @@ impl Relay {
                 let status_code = err.status().unwrap();
                 if status_code.is_client_error() {
                     // Client error (400-499)
-                    Err(RelayError::ClientError { text })
+                    Err(RelayError::ClientError { status: status_code.as_u16(), text })
                 } else {
                     // Internal server error (500-599)
                     Err(RelayError::RequestError(err))
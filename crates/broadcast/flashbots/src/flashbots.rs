@@ -1,23 +1,33 @@
 use crate::client::{
-    make_signed_body, BundleRequest, BundleTransaction, FlashbotsMiddleware, FlashbotsMiddlewareError, RelayConfig, SendBundleResponseType,
-    SimulatedBundle,
+    make_signed_body, replacement_uuid, BundleHash, BundleRequest, BundleStats, BundleStatsRequest, BundleTransaction, CancelBundleRequest,
+    FlashbotsMiddleware, FlashbotsMiddlewareError, MevSendBundle, RelayCapabilities, RelayConfig, SendBundleResponseType, SimulatedBundle,
 };
+use crate::submission::{RelayHealth, RelaySubmissionResult};
 use alloy_network::Ethereum;
-use alloy_primitives::{TxHash, U64};
+use alloy_primitives::{Address, Bytes, TxHash, B256, U64};
 use alloy_provider::Provider;
 use alloy_signer_local::PrivateKeySigner;
 use alloy_transport::Transport;
 use eyre::{eyre, Result};
+use futures_util::future::join_all;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tracing::{debug, error, info};
+use std::time::Instant;
+use tracing::{debug, error, info, warn};
 use url::Url;
 
 #[derive(Clone)]
 pub struct FlashbotsClient<P, T> {
     pub flashbots_middleware: FlashbotsMiddleware<P, T>,
     pub name: String,
+    pub id: Option<u16>,
+    pub stats_method: Option<String>,
+    pub cancel_method: Option<String>,
+    pub capabilities: RelayCapabilities,
+    pub health: Arc<RelayHealth>,
 }
 
 impl<P, T> FlashbotsClient<P, T>
@@ -30,7 +40,15 @@ where
 
         let name = url.to_string();
 
-        FlashbotsClient { flashbots_middleware, name }
+        FlashbotsClient {
+            flashbots_middleware,
+            name,
+            id: None,
+            stats_method: None,
+            cancel_method: None,
+            capabilities: RelayCapabilities::default(),
+            health: Arc::new(RelayHealth::default()),
+        }
     }
 
     pub fn new_no_sign(provider: P, url: &str) -> Self {
@@ -38,7 +56,48 @@ where
 
         let name = url.to_string();
 
-        FlashbotsClient { flashbots_middleware: flashbots_client, name }
+        FlashbotsClient {
+            flashbots_middleware: flashbots_client,
+            name,
+            id: None,
+            stats_method: None,
+            cancel_method: None,
+            capabilities: RelayCapabilities::default(),
+            health: Arc::new(RelayHealth::default()),
+        }
+    }
+
+    pub fn new_with_config(provider: P, relay: RelayConfig) -> Self {
+        let client = if relay.no_sign.unwrap_or(false) {
+            FlashbotsClient::new_no_sign(provider, relay.url.as_str())
+        } else {
+            FlashbotsClient::new(provider, relay.url.as_str())
+        };
+
+        FlashbotsClient {
+            name: relay.name,
+            id: Some(relay.id),
+            stats_method: relay.stats_method,
+            cancel_method: relay.cancel_method,
+            capabilities: relay.capabilities,
+            ..client
+        }
+    }
+
+    pub fn with_stats_method(self, stats_method: &str) -> Self {
+        Self { stats_method: Some(stats_method.to_string()), ..self }
+    }
+
+    pub fn with_cancel_method(self, cancel_method: &str) -> Self {
+        Self { cancel_method: Some(cancel_method.to_string()), ..self }
+    }
+
+    pub fn with_capabilities(self, capabilities: RelayCapabilities) -> Self {
+        Self { capabilities, ..self }
+    }
+
+    pub fn with_health(self, health: RelayHealth) -> Self {
+        Self { health: Arc::new(health), ..self }
     }
 
     fn create_flashbots_middleware(provider: P, url: &str) -> FlashbotsMiddleware<P, T> {
@@ -98,6 +157,41 @@ where
             }
         }
     }
+
+    /// Sends a signed bundle and reports the outcome of the submission. Updates relay health.
+    pub async fn submit_signed_body(&self, body: String, signature: String, target_block: u64) -> RelaySubmissionResult {
+        let start_time = Instant::now();
+        let response = self
+            .flashbots_middleware
+            .relay()
+            .serialized_request_with_header::<SendBundleResponseType>(body, Some(signature), self.capabilities.signature_header())
+            .await;
+        let latency = start_time.elapsed();
+
+        let (bundle_hash, error, http_status) = match response {
+            Ok(resp) => (resp.bundle_hash(), None, Some(200)),
+            Err(error) => (None, Some(error.to_string()), error.status()),
+        };
+
+        if error.is_none() {
+            if self.health.record_success() {
+                info!("Relay {} recovered", self.name);
+            }
+        } else if self.health.record_error() {
+            warn!("Relay {} degraded after {} consecutive errors", self.name, self.health.consecutive_errors());
+        }
+
+        RelaySubmissionResult {
+            relay_id: self.id,
+            relay_name: self.name.clone(),
+            target_block,
+            bundle_hash,
+            error,
+            http_status,
+            latency,
+            degraded: self.health.is_degraded(),
+        }
+    }
 }
 
 pub struct Flashbots<P, T> {
@@ -106,6 +200,10 @@ pub struct Flashbots<P, T> {
     provider: P,
     simulation_client: FlashbotsClient<P, T>,
     clients: Vec<Arc<FlashbotsClient<P, T>>>,
+    mev_share_client: Option<Arc<FlashbotsClient<P, T>>>,
+    refund_percent: Option<u8>,
+    refund_recipient: Option<Address>,
+    builders: Option<Vec<String>>,
     _t: PhantomData<T>,
 }
 
@@ -118,15 +216,33 @@ where
         let signer = signer.unwrap_or(PrivateKeySigner::random());
         let simulation_client = FlashbotsClient::new(provider.clone(), simulation_endpoint);
 
-        Flashbots { req_id: AtomicU64::new(0), signer, provider, clients: vec![], simulation_client, _t: PhantomData }
+        Flashbots {
+            req_id: AtomicU64::new(0),
+            signer,
+            provider,
+            clients: vec![],
+            simulation_client,
+            mev_share_client: None,
+            refund_percent: None,
+            refund_recipient: None,
+            builders: None,
+            _t: PhantomData,
+        }
     }
 
     pub fn with_default_relays(self) -> Self {
         let provider = self.provider.clone();
 
-        let flashbots = FlashbotsClient::new(provider.clone(), "https://relay.flashbots.net");
-        let beaverbuild = FlashbotsClient::new(provider.clone(), "https://rpc.beaverbuild.org/");
-        let titan = FlashbotsClient::new(provider.clone(), "https://rpc.titanbuilder.xyz");
+        let flashbots = FlashbotsClient::new(provider.clone(), "https://relay.flashbots.net")
+            .with_stats_method("flashbots_getBundleStatsV2")
+            .with_cancel_method("eth_cancelBundle")
+            .with_capabilities(RelayCapabilities::default().with_builders());
+        let beaverbuild = FlashbotsClient::new(provider.clone(), "https://rpc.beaverbuild.org/")
+            .with_cancel_method("eth_cancelBundle")
+            .with_capabilities(RelayCapabilities::default().with_refund());
+        let titan = FlashbotsClient::new(provider.clone(), "https://rpc.titanbuilder.xyz")
+            .with_cancel_method("eth_cancelBundle")
+            .with_capabilities(RelayCapabilities::default().with_refund());
         let rsync = FlashbotsClient::new(provider.clone(), "https://rsync-builder.xyz");
         //let builder0x69 = FlashbotsClient::new_no_sign(provider.clone(), "https://builder0x69.io");
         let eden = FlashbotsClient::new(provider.clone(), "https://api.edennetwork.io/v1/bundle");
@@ -172,19 +288,31 @@ where
     }
 
     pub fn with_relays(self, relays: Vec<RelayConfig>) -> Self {
-        let clients: Vec<Arc<FlashbotsClient<P, T>>> = relays
-            .into_iter()
-            .map(|relay| {
-                if relay.no_sign.unwrap_or(false) {
-                    Arc::new(FlashbotsClient::new_no_sign(self.provider.clone(), relay.url.as_str()))
-                } else {
-                    Arc::new(FlashbotsClient::new(self.provider.clone(), relay.url.as_str()))
-                }
-            })
-            .collect();
+        let clients: Vec<Arc<FlashbotsClient<P, T>>> =
+            relays.into_iter().map(|relay| Arc::new(FlashbotsClient::new_with_config(self.provider.clone(), relay))).collect();
         Self { clients, ..self }
     }
 
+    /// Sets the relay receiving `mev_sendBundle` requests, usually https://relay.flashbots.net
+    pub fn with_mev_share_relay(self, url: &str) -> Self {
+        Self { mev_share_client: Some(Arc::new(FlashbotsClient::new(self.provider.clone(), url))), ..self }
+    }
+
+    /// Sets the refund requested from relays that support it. Builders refund to the sender of the first transaction if
+    /// `refund_recipient` is not set.
+    pub fn with_refund(self, refund_percent: u8, refund_recipient: Option<Address>) -> Self {
+        Self { refund_percent: Some(refund_percent), refund_recipient, ..self }
+    }
+
+    /// Sets the builders relays that support it share bundles with.
+    pub fn with_builders(self, builders: Vec<String>) -> Self {
+        Self { builders: Some(builders), ..self }
+    }
+
+    pub fn clients(&self) -> &Vec<Arc<FlashbotsClient<P, T>>> {
+        &self.clients
+    }
+
     pub async fn simulate_txes<TX>(
         &self,
         txs: Vec<TX>,
@@ -206,51 +334,210 @@ where
         self.simulation_client.call_bundle(&bundle).await
     }
 
-    pub async fn broadcast_txes<TX>(&self, txs: Vec<TX>, target_block: u64) -> Result<()>
+    fn next_signed_body<R: Serialize + Send + Sync>(&self, method: &str, params: R) -> Result<(String, String)> {
+        let next_req_id = self.req_id.load(Ordering::SeqCst) + 1;
+        self.req_id.store(next_req_id, Ordering::SeqCst);
+
+        make_signed_body(next_req_id, method, params, &self.signer)
+    }
+
+    /// Sends the bundle to all relays. Degraded relays only receive a bundle from time to time.
+    pub async fn broadcast_txes<TX>(&self, txs: Vec<TX>, target_block: u64) -> Result<Vec<RelaySubmissionResult>>
+    where
+        BundleTransaction: From<TX>,
+    {
+        let txs: Vec<BundleTransaction> = txs.into_iter().map(BundleTransaction::from).collect();
+        self.broadcast_bundle(txs, target_block, None).await
+    }
+
+    /// Sends the bundle for each block of `target_block..target_block + blocks`. Relays with a cancel method receive a replacement
+    /// uuid derived from `replacement_key`, so the bundle is replaced by the next one sent with the same key.
+    pub async fn broadcast_txes_window<TX>(
+        &self,
+        txs: Vec<TX>,
+        target_block: u64,
+        blocks: u64,
+        replacement_key: Option<B256>,
+    ) -> Result<Vec<RelaySubmissionResult>>
     where
         BundleTransaction: From<TX>,
     {
+        let txs: Vec<BundleTransaction> = txs.into_iter().map(BundleTransaction::from).collect();
+
+        let block_results =
+            join_all((target_block..target_block + blocks.max(1)).map(|block| self.broadcast_bundle(txs.clone(), block, replacement_key)))
+                .await;
+
+        let mut results = Vec::new();
+        for block_result in block_results {
+            results.extend(block_result?);
+        }
+        Ok(results)
+    }
+
+    async fn broadcast_bundle(
+        &self,
+        txs: Vec<BundleTransaction>,
+        target_block: u64,
+        replacement_key: Option<B256>,
+    ) -> Result<Vec<RelaySubmissionResult>> {
         let mut bundle = BundleRequest::new().set_target_block(U64::from(target_block));
 
         for t in txs.into_iter() {
             bundle = bundle.push_transaction(t);
         }
+        if let Some(refund_percent) = self.refund_percent {
+            bundle = bundle.set_refund_percent(refund_percent);
+        }
+        if let Some(refund_recipient) = self.refund_recipient {
+            bundle = bundle.set_refund_recipient(refund_recipient);
+        }
+        if let Some(builders) = self.builders.clone() {
+            bundle = bundle.set_builders(builders);
+        }
+        let bundle_uuid = replacement_key.map(|key| replacement_uuid(&key, target_block));
 
-        let next_req_id = self.req_id.load(Ordering::SeqCst) + 1;
-        self.req_id.store(next_req_id, Ordering::SeqCst);
-
-        let (body, signature) = make_signed_body(next_req_id, "eth_sendBundle", bundle, &self.signer)?;
+        // relays with the same capabilities receive the same signed body
+        let mut bodies: HashMap<(RelayCapabilities, bool), (String, String)> = HashMap::new();
+        let mut tasks = Vec::new();
 
         for client in self.clients.iter() {
+            if !client.health.should_send() {
+                debug!("Skipping degraded relay {}", client.name);
+                continue;
+            }
+
+            let replaceable = bundle_uuid.is_some() && client.cancel_method.is_some();
+            let body_key = (client.capabilities.clone(), replaceable);
+            let (body_clone, signature_clone) = match bodies.get(&body_key) {
+                Some(body) => body.clone(),
+                None => {
+                    let mut relay_bundle = bundle.render(&client.capabilities);
+                    if let (true, Some(uuid)) = (replaceable, bundle_uuid.clone()) {
+                        relay_bundle = relay_bundle.set_replacement_uuid(uuid);
+                    }
+                    let body = self.next_signed_body("eth_sendBundle", relay_bundle)?;
+                    bodies.insert(body_key, body.clone());
+                    body
+                }
+            };
             let client_clone = client.clone();
-            let body_clone = body.clone();
-            let signature_clone = signature.clone();
 
-            tokio::task::spawn(async move {
+            tasks.push(tokio::task::spawn(async move {
                 debug!("Sending bundle to {}", client_clone.name);
-                let bundle_result = client_clone.send_signed_body(body_clone, signature_clone).await;
-                match bundle_result {
-                    Ok(_) => {
+                let bundle_result = client_clone.submit_signed_body(body_clone, signature_clone, target_block).await;
+                match &bundle_result.error {
+                    None => {
                         debug!("Flashbots bundle broadcast successfully {}", client_clone.name);
                     }
-                    Err(x) => {
-                        error!("Broadcasting error to {} : {}", client_clone.name, x.to_string());
+                    Some(x) => {
+                        error!("Broadcasting error to {} : {}", client_clone.name, x);
                     }
                 }
-            });
+                bundle_result
+            }));
         }
 
-        Ok(())
+        let results = join_all(tasks).await.into_iter().filter_map(|result| result.ok()).collect();
+
+        Ok(results)
+    }
+
+    /// Cancels bundles sent with `replacement_key` for blocks `from_block..=to_block`. Only relays with a cancel method are called.
+    pub async fn cancel_bundles(&self, replacement_key: B256, from_block: u64, to_block: u64) -> Result<Vec<RelaySubmissionResult>> {
+        let mut tasks = Vec::new();
+
+        for block in from_block..=to_block {
+            let request = CancelBundleRequest { replacement_uuid: replacement_uuid(&replacement_key, block) };
+
+            for client in self.clients.iter() {
+                let Some(cancel_method) = client.cancel_method.as_ref() else {
+                    continue;
+                };
+                let (body, signature) = self.next_signed_body(cancel_method, request.clone())?;
+                let client_clone = client.clone();
+
+                tasks.push(tokio::task::spawn(async move {
+                    let cancel_result = client_clone.submit_signed_body(body, signature, block).await;
+                    if let Some(x) = &cancel_result.error {
+                        error!("Bundle cancel error {} : {}", client_clone.name, x);
+                    }
+                    cancel_result
+                }));
+            }
+        }
+
+        let results = join_all(tasks).await.into_iter().filter_map(|result| result.ok()).collect();
+
+        Ok(results)
+    }
+
+    /// Sends a MEV-Share bundle backrunning the hint transactions with own signed transactions.
+    pub async fn broadcast_mev_share_bundle(
+        &self,
+        hint_hashes: Vec<TxHash>,
+        txs: Vec<Bytes>,
+        target_block: u64,
+        max_block: Option<u64>,
+    ) -> Result<RelaySubmissionResult> {
+        let Some(client) = self.mev_share_client.as_ref() else {
+            return Err(eyre!("MEV_SHARE_RELAY_NOT_SET"));
+        };
+
+        let mut bundle = MevSendBundle::new(target_block, max_block);
+        for hash in hint_hashes.into_iter() {
+            bundle = bundle.push_hash(hash);
+        }
+        for tx in txs.into_iter() {
+            bundle = bundle.push_tx(tx, false);
+        }
+
+        let (body, signature) = self.next_signed_body("mev_sendBundle", bundle)?;
+
+        let bundle_result = client.submit_signed_body(body, signature, target_block).await;
+        match &bundle_result.error {
+            None => {
+                debug!("MEV-Share bundle broadcast successfully {}", client.name);
+            }
+            Some(x) => {
+                error!("MEV-Share broadcasting error to {} : {}", client.name, x);
+            }
+        }
+        Ok(bundle_result)
+    }
+
+    /// Requests stats of a submitted bundle from the relay. Returns `None` if the relay has no stats method configured.
+    pub async fn bundle_stats(&self, relay_name: &str, bundle_hash: BundleHash, target_block: u64) -> Result<Option<BundleStats>> {
+        let Some(client) = self.clients.iter().find(|client| client.name == relay_name) else {
+            return Err(eyre!("RELAY_NOT_FOUND"));
+        };
+        let Some(stats_method) = client.stats_method.as_ref() else {
+            return Ok(None);
+        };
+
+        let request = BundleStatsRequest { bundle_hash, block_number: U64::from(target_block) };
+        let (body, signature) = self.next_signed_body(stats_method, request)?;
+
+        match client.flashbots_middleware.relay().serialized_request::<BundleStats>(body, Some(signature)).await {
+            Ok(stats) => Ok(Some(stats)),
+            Err(error) => {
+                error!("Bundle stats error {} : {}", client.name, error);
+                Err(eyre!("FLASHBOTS_RELAY_ERROR"))
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use alloy_primitives::Bytes;
     use alloy_provider::ProviderBuilder;
     use std::env;
+    use std::str::FromStr;
+    use wiremock::matchers::{body_partial_json, header_exists, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     use super::*;
+    use crate::client::FLASHBOTS_SIGNATURE_HEADER;
 
     #[tokio::test]
     async fn test_client_send_bundle() -> Result<()> {
@@ -294,7 +581,9 @@ mod test {
         let tx = Bytes::from(vec![1, 1, 1, 1]);
 
         match flashbots_client.broadcast_txes(vec![tx], block).await {
-            Ok(_resp) => {}
+            Ok(resp) => {
+                assert_eq!(resp.len(), 1);
+            }
             Err(e) => {
                 error!("{}", e);
                 panic!("SHOULD_NOT_FAIL");
@@ -303,4 +592,284 @@ mod test {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_broadcast_relay_results() -> Result<()> {
+        let relay_ok = MockServer::start().await;
+        let relay_err = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_sendBundle"})))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"id":1,"result":{"bundleHash":"0xcc6c61428c6516a252768859d167dc8f5c8c8c682334a184710f898e422530f8"},"jsonrpc":"2.0"}"#,
+            ))
+            .mount(&relay_ok)
+            .await;
+        Mock::given(method("POST")).respond_with(ResponseTemplate::new(403).set_body_string("forbidden")).mount(&relay_err).await;
+
+        let provider = ProviderBuilder::new().on_http(Url::parse(relay_ok.uri().as_str())?).boxed();
+        let relays = vec![
+            RelayConfig {
+                id: 1,
+                name: "ok".to_string(),
+                url: relay_ok.uri(),
+                no_sign: None,
+                stats_method: None,
+                cancel_method: None,
+                capabilities: RelayCapabilities::default(),
+            },
+            RelayConfig {
+                id: 2,
+                name: "err".to_string(),
+                url: relay_err.uri(),
+                no_sign: Some(true),
+                stats_method: None,
+                cancel_method: None,
+                capabilities: RelayCapabilities::default(),
+            },
+        ];
+        let flashbots = Flashbots::new(provider, relay_ok.uri().as_str(), None).with_relays(relays);
+
+        let results = flashbots.broadcast_txes(vec![Bytes::from(vec![1, 1, 1, 1])], 100).await?;
+        assert_eq!(results.len(), 2);
+
+        let ok = results.iter().find(|r| r.relay_name == "ok").unwrap();
+        assert!(ok.is_ok());
+        assert_eq!(ok.relay_id, Some(1));
+        assert_eq!(ok.http_status, Some(200));
+        assert_eq!(ok.target_block, 100);
+        assert_eq!(ok.bundle_hash, Some(TxHash::from_str("0xcc6c61428c6516a252768859d167dc8f5c8c8c682334a184710f898e422530f8")?));
+
+        let err = results.iter().find(|r| r.relay_name == "err").unwrap();
+        assert!(!err.is_ok());
+        assert_eq!(err.http_status, Some(403));
+        assert_eq!(err.bundle_hash, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_degraded_relay_is_skipped() -> Result<()> {
+        let relay_err = MockServer::start().await;
+        Mock::given(method("POST")).respond_with(ResponseTemplate::new(500)).mount(&relay_err).await;
+
+        let provider = ProviderBuilder::new().on_http(Url::parse(relay_err.uri().as_str())?).boxed();
+        let mut flashbots = Flashbots::new(provider.clone(), relay_err.uri().as_str(), None);
+        flashbots.clients = vec![Arc::new(FlashbotsClient::new(provider, relay_err.uri().as_str()).with_health(RelayHealth::new(2, 3)))];
+
+        for _ in 0..2 {
+            let results = flashbots.broadcast_txes(vec![Bytes::from(vec![1])], 100).await?;
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0].http_status, Some(500));
+        }
+        assert!(flashbots.clients()[0].health.is_degraded());
+
+        assert!(flashbots.broadcast_txes(vec![Bytes::from(vec![1])], 100).await?.is_empty());
+        assert!(flashbots.broadcast_txes(vec![Bytes::from(vec![1])], 100).await?.is_empty());
+        assert_eq!(flashbots.broadcast_txes(vec![Bytes::from(vec![1])], 100).await?.len(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bundle_stats() -> Result<()> {
+        let relay = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "flashbots_getBundleStatsV2"})))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"id":1,"result":{"isSimulated":true,"isHighPriority":false},"jsonrpc":"2.0"}"#),
+            )
+            .mount(&relay)
+            .await;
+
+        let provider = ProviderBuilder::new().on_http(Url::parse(relay.uri().as_str())?).boxed();
+        let relays = vec![
+            RelayConfig {
+                id: 1,
+                name: "flashbots".to_string(),
+                url: relay.uri(),
+                no_sign: None,
+                stats_method: Some("flashbots_getBundleStatsV2".to_string()),
+                cancel_method: None,
+                capabilities: RelayCapabilities::default(),
+            },
+            RelayConfig {
+                id: 2,
+                name: "nostats".to_string(),
+                url: relay.uri(),
+                no_sign: None,
+                stats_method: None,
+                cancel_method: None,
+                capabilities: RelayCapabilities::default(),
+            },
+        ];
+        let flashbots = Flashbots::new(provider, relay.uri().as_str(), None).with_relays(relays);
+
+        let stats = flashbots.bundle_stats("flashbots", BundleHash::ZERO, 100).await?.unwrap();
+        assert_eq!(stats.is_simulated, Some(true));
+        assert_eq!(stats.is_high_priority, Some(false));
+
+        assert!(flashbots.bundle_stats("nostats", BundleHash::ZERO, 100).await?.is_none());
+        assert!(flashbots.bundle_stats("unknown", BundleHash::ZERO, 100).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_window_and_cancel() -> Result<()> {
+        let relay_replace = MockServer::start().await;
+        let relay_no_replace = MockServer::start().await;
+        for relay in [&relay_replace, &relay_no_replace] {
+            Mock::given(method("POST"))
+                .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id":1,"result":null,"jsonrpc":"2.0"}"#))
+                .mount(relay)
+                .await;
+        }
+
+        let provider = ProviderBuilder::new().on_http(Url::parse(relay_replace.uri().as_str())?).boxed();
+        let relays = vec![
+            RelayConfig {
+                id: 1,
+                name: "replace".to_string(),
+                url: relay_replace.uri(),
+                no_sign: None,
+                stats_method: None,
+                cancel_method: Some("eth_cancelBundle".to_string()),
+                capabilities: RelayCapabilities::default(),
+            },
+            RelayConfig {
+                id: 2,
+                name: "noreplace".to_string(),
+                url: relay_no_replace.uri(),
+                no_sign: None,
+                stats_method: None,
+                cancel_method: None,
+                capabilities: RelayCapabilities::default(),
+            },
+        ];
+        let flashbots = Flashbots::new(provider, relay_replace.uri().as_str(), None).with_relays(relays);
+        let key = B256::repeat_byte(1);
+
+        let results = flashbots.broadcast_txes_window(vec![Bytes::from(vec![1, 1])], 100, 2, Some(key)).await?;
+        assert_eq!(results.len(), 4);
+        assert_eq!(results.iter().filter(|r| r.target_block == 101).count(), 2);
+
+        let requests: Vec<serde_json::Value> =
+            relay_replace.received_requests().await.unwrap().iter().map(|r| r.body_json().unwrap()).collect();
+        assert_eq!(requests.len(), 2);
+        for request in requests.iter() {
+            let block = u64::from_str_radix(request["params"][0]["blockNumber"].as_str().unwrap().trim_start_matches("0x"), 16)?;
+            assert_eq!(request["params"][0]["replacementUuid"], replacement_uuid(&key, block));
+        }
+
+        let requests: Vec<serde_json::Value> =
+            relay_no_replace.received_requests().await.unwrap().iter().map(|r| r.body_json().unwrap()).collect();
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|r| r["params"][0].get("replacementUuid").is_none()));
+
+        let results = flashbots.cancel_bundles(key, 100, 101).await?;
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.relay_name == "replace" && r.is_ok()));
+
+        let requests: Vec<serde_json::Value> =
+            relay_replace.received_requests().await.unwrap().iter().map(|r| r.body_json().unwrap()).collect();
+        let cancel_requests: Vec<&serde_json::Value> = requests.iter().filter(|r| r["method"] == "eth_cancelBundle").collect();
+        assert_eq!(cancel_requests.len(), 2);
+        assert!(cancel_requests.iter().any(|r| r["params"][0]["replacementUuid"] == replacement_uuid(&key, 101)));
+        assert_eq!(relay_no_replace.received_requests().await.unwrap().len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_relay_capabilities() -> Result<()> {
+        let relay_refund = MockServer::start().await;
+        let relay_default = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header_exists("X-Custom-Signature"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id":1,"result":null,"jsonrpc":"2.0"}"#))
+            .mount(&relay_refund)
+            .await;
+        Mock::given(method("POST"))
+            .and(header_exists(FLASHBOTS_SIGNATURE_HEADER))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id":1,"result":null,"jsonrpc":"2.0"}"#))
+            .mount(&relay_default)
+            .await;
+
+        let provider = ProviderBuilder::new().on_http(Url::parse(relay_refund.uri().as_str())?).boxed();
+        let relays = vec![
+            RelayConfig {
+                id: 1,
+                name: "refund".to_string(),
+                url: relay_refund.uri(),
+                no_sign: None,
+                stats_method: None,
+                cancel_method: None,
+                capabilities: RelayCapabilities {
+                    signature_header: Some("X-Custom-Signature".to_string()),
+                    ..RelayCapabilities::default().with_refund()
+                },
+            },
+            RelayConfig {
+                id: 2,
+                name: "default".to_string(),
+                url: relay_default.uri(),
+                no_sign: None,
+                stats_method: None,
+                cancel_method: None,
+                capabilities: RelayCapabilities::default(),
+            },
+        ];
+        let flashbots = Flashbots::new(provider, relay_refund.uri().as_str(), None)
+            .with_relays(relays)
+            .with_refund(90, Some(Address::repeat_byte(1)))
+            .with_builders(vec!["flashbots".to_string()]);
+
+        let results = flashbots.broadcast_txes(vec![Bytes::from(vec![1, 1])], 100).await?;
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.is_ok()));
+
+        let request: serde_json::Value = relay_refund.received_requests().await.unwrap()[0].body_json()?;
+        assert_eq!(request["params"][0]["refundPercent"], 90);
+        assert_eq!(request["params"][0]["refundRecipient"], "0x0101010101010101010101010101010101010101");
+        assert!(request["params"][0].get("builders").is_none());
+
+        let request: serde_json::Value = relay_default.received_requests().await.unwrap()[0].body_json()?;
+        assert!(request["params"][0].get("refundPercent").is_none());
+        assert!(request["params"][0].get("builders").is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_mev_share_bundle() -> Result<()> {
+        let relay = MockServer::start().await;
+        let hint_hash = TxHash::repeat_byte(1);
+
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({
+                "method": "mev_sendBundle",
+                "params": [{"inclusion": {"block": "0x64", "maxBlock": "0x66"}, "body": [{"hash": hint_hash}, {"tx": "0x0101", "canRevert": false}]}]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"id":1,"result":{"bundleHash":"0xcc6c61428c6516a252768859d167dc8f5c8c8c682334a184710f898e422530f8"},"jsonrpc":"2.0"}"#,
+            ))
+            .mount(&relay)
+            .await;
+
+        let provider = ProviderBuilder::new().on_http(Url::parse(relay.uri().as_str())?).boxed();
+        let flashbots = Flashbots::new(provider, relay.uri().as_str(), None);
+
+        assert!(flashbots.broadcast_mev_share_bundle(vec![hint_hash], vec![Bytes::from(vec![1, 1])], 100, Some(102)).await.is_err());
+
+        let flashbots = flashbots.with_mev_share_relay(relay.uri().as_str());
+        let result = flashbots.broadcast_mev_share_bundle(vec![hint_hash], vec![Bytes::from(vec![1, 1])], 100, Some(102)).await?;
+
+        assert!(result.is_ok());
+        assert_eq!(result.target_block, 100);
+        assert_eq!(result.bundle_hash, Some(TxHash::from_str("0xcc6c61428c6516a252768859d167dc8f5c8c8c682334a184710f898e422530f8")?));
+
+        Ok(())
+    }
 }
@@ -1,4 +1,8 @@
 pub use flashbots::{Flashbots, FlashbotsClient};
+pub use private_tx::{CancelPrivateTxRequest, PrivateTxBroadcaster, PrivateTxEndpointConfig, PrivateTxMethod, PrivateTxRequest};
+pub use submission::{RelayHealth, RelaySubmissionResult, DEFAULT_DEGRADED_ERRORS_THRESHOLD, DEFAULT_DEGRADED_PROBE_INTERVAL};
 
 pub mod client;
 mod flashbots;
+mod private_tx;
+mod submission;
@@ -0,0 +1,267 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use alloy_primitives::{keccak256, Bytes, TxHash, U64};
+use alloy_signer_local::PrivateKeySigner;
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, info, warn};
+use url::Url;
+
+use crate::client::{Relay, RelayError, SendBundleResponseType};
+use crate::submission::{RelayHealth, RelaySubmissionResult};
+
+/// Method used to submit a single transaction to an endpoint.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+pub enum PrivateTxMethod {
+    /// `eth_sendPrivateTransaction`, supports max block number and cancellation.
+    #[default]
+    #[serde(rename = "eth_sendPrivateTransaction")]
+    SendPrivateTransaction,
+    /// `eth_sendRawTransaction`, the transaction stays pending until it is mined or replaced.
+    #[serde(rename = "eth_sendRawTransaction")]
+    SendRawTransaction,
+}
+
+impl PrivateTxMethod {
+    pub fn method(&self) -> &'static str {
+        match self {
+            PrivateTxMethod::SendPrivateTransaction => "eth_sendPrivateTransaction",
+            PrivateTxMethod::SendRawTransaction => "eth_sendRawTransaction",
+        }
+    }
+
+    pub fn can_cancel(&self) -> bool {
+        matches!(self, PrivateTxMethod::SendPrivateTransaction)
+    }
+}
+
+/// Configuration of a private transaction endpoint.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PrivateTxEndpointConfig {
+    pub id: u16,
+    pub name: String,
+    pub url: String,
+    pub method: PrivateTxMethod,
+    pub no_sign: Option<bool>,
+}
+
+/// Parameters of `eth_sendPrivateTransaction`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivateTxRequest {
+    pub tx: Bytes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_block_number: Option<U64>,
+}
+
+/// Parameters of `eth_cancelPrivateTransaction`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelPrivateTxRequest {
+    pub tx_hash: TxHash,
+}
+
+struct PrivateTxEndpoint {
+    id: u16,
+    name: String,
+    method: PrivateTxMethod,
+    relay: Relay,
+    health: RelayHealth,
+}
+
+impl PrivateTxEndpoint {
+    fn new(config: PrivateTxEndpointConfig, signer: &PrivateKeySigner) -> eyre::Result<Self> {
+        let signer = if config.no_sign.unwrap_or(false) { None } else { Some(signer.clone()) };
+        Ok(Self {
+            id: config.id,
+            name: config.name,
+            method: config.method,
+            relay: Relay::new(Url::parse(config.url.as_str())?, signer),
+            health: RelayHealth::default(),
+        })
+    }
+
+    fn submission_result<R>(
+        &self,
+        response: Result<R, RelayError>,
+        tx_hash: Option<TxHash>,
+        target_block: u64,
+        start_time: Instant,
+    ) -> RelaySubmissionResult {
+        let latency = start_time.elapsed();
+        let (tx_hash, error, http_status) = match response {
+            Ok(_) => (tx_hash, None, Some(200)),
+            Err(error) => (None, Some(error.to_string()), error.status()),
+        };
+
+        if error.is_none() {
+            if self.health.record_success() {
+                info!("Private tx endpoint {} recovered", self.name);
+            }
+        } else if self.health.record_error() {
+            warn!("Private tx endpoint {} degraded after {} consecutive errors", self.name, self.health.consecutive_errors());
+        }
+
+        RelaySubmissionResult {
+            relay_id: Some(self.id),
+            relay_name: self.name.clone(),
+            target_block,
+            bundle_hash: tx_hash,
+            error,
+            http_status,
+            latency,
+            degraded: self.health.is_degraded(),
+        }
+    }
+
+    async fn send_tx(&self, tx: Bytes, target_block: u64, max_block_number: Option<u64>) -> RelaySubmissionResult {
+        let start_time = Instant::now();
+        let tx_hash = keccak256(&tx);
+
+        let response = match self.method {
+            PrivateTxMethod::SendPrivateTransaction => {
+                let request = PrivateTxRequest { tx, max_block_number: max_block_number.map(U64::from) };
+                self.relay.request::<_, SendBundleResponseType>(self.method.method(), [request]).await
+            }
+            PrivateTxMethod::SendRawTransaction => self.relay.request::<_, SendBundleResponseType>(self.method.method(), [tx]).await,
+        };
+
+        self.submission_result(response, Some(tx_hash), target_block, start_time)
+    }
+
+    async fn cancel_tx(&self, tx_hash: TxHash, target_block: u64) -> RelaySubmissionResult {
+        let start_time = Instant::now();
+        let response =
+            self.relay.request::<_, serde_json::Value>("eth_cancelPrivateTransaction", [CancelPrivateTxRequest { tx_hash }]).await;
+
+        self.submission_result(response, None, target_block, start_time)
+    }
+}
+
+/// Sends single signed transactions to private transaction endpoints.
+pub struct PrivateTxBroadcaster {
+    endpoints: Vec<Arc<PrivateTxEndpoint>>,
+}
+
+impl PrivateTxBroadcaster {
+    pub fn new(endpoints: Vec<PrivateTxEndpointConfig>, signer: Option<PrivateKeySigner>) -> eyre::Result<Self> {
+        let signer = signer.unwrap_or(PrivateKeySigner::random());
+        let endpoints =
+            endpoints.into_iter().map(|config| PrivateTxEndpoint::new(config, &signer).map(Arc::new)).collect::<eyre::Result<_>>()?;
+        Ok(Self { endpoints })
+    }
+
+    /// Sends the transaction to all endpoints. `max_block_number` is only passed to `eth_sendPrivateTransaction` endpoints.
+    pub async fn send_private_tx(&self, tx: Bytes, target_block: u64, max_block_number: Option<u64>) -> Vec<RelaySubmissionResult> {
+        let mut tasks = Vec::new();
+
+        for endpoint in self.endpoints.iter() {
+            if !endpoint.health.should_send() {
+                debug!("Skipping degraded private tx endpoint {}", endpoint.name);
+                continue;
+            }
+            let endpoint = endpoint.clone();
+            let tx = tx.clone();
+
+            tasks.push(tokio::task::spawn(async move {
+                let result = endpoint.send_tx(tx, target_block, max_block_number).await;
+                match &result.error {
+                    None => debug!("Private tx sent to {}", endpoint.name),
+                    Some(e) => error!("Private tx error {} : {}", endpoint.name, e),
+                }
+                result
+            }));
+        }
+
+        join_all(tasks).await.into_iter().filter_map(|result| result.ok()).collect()
+    }
+
+    /// Cancels the transaction on endpoints using `eth_sendPrivateTransaction`.
+    pub async fn cancel_private_tx(&self, tx_hash: TxHash, target_block: u64) -> Vec<RelaySubmissionResult> {
+        let mut tasks = Vec::new();
+
+        for endpoint in self.endpoints.iter().filter(|endpoint| endpoint.method.can_cancel()) {
+            let endpoint = endpoint.clone();
+
+            tasks.push(tokio::task::spawn(async move {
+                let result = endpoint.cancel_tx(tx_hash, target_block).await;
+                if let Some(e) = &result.error {
+                    error!("Private tx cancel error {} : {}", endpoint.name, e);
+                }
+                result
+            }));
+        }
+
+        join_all(tasks).await.into_iter().filter_map(|result| result.ok()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn endpoint(id: u16, server: &MockServer, method: PrivateTxMethod) -> PrivateTxEndpointConfig {
+        PrivateTxEndpointConfig { id, name: format!("endpoint{id}"), url: server.uri(), method, no_sign: None }
+    }
+
+    #[tokio::test]
+    async fn test_send_and_cancel_private_tx() -> eyre::Result<()> {
+        let private = MockServer::start().await;
+        let raw = MockServer::start().await;
+        let tx = Bytes::from(vec![1, 2, 3]);
+        let tx_hash = keccak256(&tx);
+
+        Mock::given(method("POST"))
+            .and(body_partial_json(
+                serde_json::json!({"method": "eth_sendPrivateTransaction", "params": [{"tx": "0x010203", "maxBlockNumber": "0x66"}]}),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_string(format!(r#"{{"id":1,"result":"{tx_hash}","jsonrpc":"2.0"}}"#)))
+            .mount(&private)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_cancelPrivateTransaction", "params": [{"txHash": tx_hash}]})))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id":1,"result":true,"jsonrpc":"2.0"}"#))
+            .mount(&private)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"method": "eth_sendRawTransaction", "params": ["0x010203"]})))
+            .respond_with(ResponseTemplate::new(200).set_body_string(format!(r#"{{"id":1,"result":"{tx_hash}","jsonrpc":"2.0"}}"#)))
+            .mount(&raw)
+            .await;
+
+        let broadcaster = PrivateTxBroadcaster::new(
+            vec![endpoint(1, &private, PrivateTxMethod::SendPrivateTransaction), endpoint(2, &raw, PrivateTxMethod::SendRawTransaction)],
+            None,
+        )?;
+
+        let results = broadcaster.send_private_tx(tx, 100, Some(102)).await;
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.is_ok() && r.bundle_hash == Some(tx_hash) && r.target_block == 100));
+
+        let results = broadcaster.cancel_private_tx(tx_hash, 101).await;
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_ok());
+        assert_eq!(results[0].relay_id, Some(1));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_private_tx_error() -> eyre::Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST")).respond_with(ResponseTemplate::new(403).set_body_string("forbidden")).mount(&server).await;
+
+        let broadcaster = PrivateTxBroadcaster::new(vec![endpoint(1, &server, PrivateTxMethod::SendPrivateTransaction)], None)?;
+
+        let results = broadcaster.send_private_tx(Bytes::from(vec![1]), 100, None).await;
+        assert_eq!(results.len(), 1);
+        assert!(!results[0].is_ok());
+        assert_eq!(results[0].http_status, Some(403));
+        assert_eq!(results[0].bundle_hash, None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::client::BundleHash;
+
+/// Number of consecutive errors after which a relay is marked as degraded.
+pub const DEFAULT_DEGRADED_ERRORS_THRESHOLD: u32 = 10;
+/// A degraded relay still receives every n-th bundle to detect recovery.
+pub const DEFAULT_DEGRADED_PROBE_INTERVAL: u32 = 10;
+
+/// Outcome of a single bundle submission to a relay.
+#[derive(Clone, Debug)]
+pub struct RelaySubmissionResult {
+    pub relay_id: Option<u16>,
+    pub relay_name: String,
+    pub target_block: u64,
+    /// Bundle hash returned by the relay, if any.
+    pub bundle_hash: Option<BundleHash>,
+    pub error: Option<String>,
+    /// HTTP status code, `None` if the relay was not reached.
+    pub http_status: Option<u16>,
+    pub latency: Duration,
+    /// Relay is degraded after this submission.
+    pub degraded: bool,
+}
+
+impl RelaySubmissionResult {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Error tracking of a relay. Relays that keep erroring are marked as degraded and only probed from time to time.
+#[derive(Debug)]
+pub struct RelayHealth {
+    errors_threshold: u32,
+    probe_interval: u32,
+    consecutive_errors: AtomicU32,
+    skipped: AtomicU32,
+    degraded: AtomicBool,
+    total_success: AtomicU64,
+    total_errors: AtomicU64,
+}
+
+impl Default for RelayHealth {
+    fn default() -> Self {
+        Self::new(DEFAULT_DEGRADED_ERRORS_THRESHOLD, DEFAULT_DEGRADED_PROBE_INTERVAL)
+    }
+}
+
+impl RelayHealth {
+    pub fn new(errors_threshold: u32, probe_interval: u32) -> Self {
+        Self {
+            errors_threshold: errors_threshold.max(1),
+            probe_interval: probe_interval.max(1),
+            consecutive_errors: AtomicU32::new(0),
+            skipped: AtomicU32::new(0),
+            degraded: AtomicBool::new(false),
+            total_success: AtomicU64::new(0),
+            total_errors: AtomicU64::new(0),
+        }
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.degraded.load(Ordering::Relaxed)
+    }
+
+    pub fn consecutive_errors(&self) -> u32 {
+        self.consecutive_errors.load(Ordering::Relaxed)
+    }
+
+    pub fn total_success(&self) -> u64 {
+        self.total_success.load(Ordering::Relaxed)
+    }
+
+    pub fn total_errors(&self) -> u64 {
+        self.total_errors.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if the next bundle should be sent to the relay. Healthy relays receive every bundle.
+    pub fn should_send(&self) -> bool {
+        if !self.is_degraded() {
+            return true;
+        }
+        let skipped = self.skipped.fetch_add(1, Ordering::Relaxed) + 1;
+        skipped % self.probe_interval == 0
+    }
+
+    /// Records a successful submission. Returns true if the relay recovered from degraded state.
+    pub fn record_success(&self) -> bool {
+        self.total_success.fetch_add(1, Ordering::Relaxed);
+        self.consecutive_errors.store(0, Ordering::Relaxed);
+        self.skipped.store(0, Ordering::Relaxed);
+        self.degraded.swap(false, Ordering::Relaxed)
+    }
+
+    /// Records a failed submission. Returns true if the relay became degraded.
+    pub fn record_error(&self) -> bool {
+        self.total_errors.fetch_add(1, Ordering::Relaxed);
+        let consecutive_errors = self.consecutive_errors.fetch_add(1, Ordering::Relaxed) + 1;
+        if consecutive_errors >= self.errors_threshold {
+            !self.degraded.swap(true, Ordering::Relaxed)
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relay_degraded_and_recovered() {
+        let health = RelayHealth::new(3, 2);
+
+        assert!(health.should_send());
+        assert!(!health.record_error());
+        assert!(!health.record_error());
+        assert!(health.record_error());
+        assert!(health.is_degraded());
+        assert!(!health.record_error());
+
+        // only every second bundle is sent to the degraded relay
+        assert!(!health.should_send());
+        assert!(health.should_send());
+        assert!(!health.should_send());
+
+        assert!(health.record_success());
+        assert!(!health.is_degraded());
+        assert_eq!(health.consecutive_errors(), 0);
+        assert!(health.should_send());
+        assert_eq!(health.total_errors(), 4);
+        assert_eq!(health.total_success(), 1);
+    }
+
+    #[test]
+    fn test_relay_errors_reset_on_success() {
+        let health = RelayHealth::new(2, 10);
+
+        assert!(!health.record_error());
+        assert!(!health.record_success());
+        assert!(!health.record_error());
+        assert!(!health.is_degraded());
+    }
+}
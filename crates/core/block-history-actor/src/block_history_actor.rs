@@ -263,14 +263,34 @@ where
                 } else{
                     latest_block_guard.update(msg_block_number, msg_block_hash, None, None, None, Some(msg.state_update.clone()) );
 
-                    let new_market_state_db = if market_state_guard.block_hash.is_zero() || market_state_guard.block_hash == latest_block_parent_hash {
+                    let old_head = market_state_guard.block_hash;
+                    // depth of the orphaned branch the market state was on, reorg is reported after the state is rolled back
+                    let mut reorg_depth : Option<u64> = None;
+
+                    let new_market_state_db = if old_head.is_zero() || old_head == latest_block_parent_hash {
                          market_state_guard.state_db.clone()
                     } else {
-                        match block_history_manager.apply_state_update_on_parent_db(block_history_guard.deref_mut(), &market_state_guard.config, msg_block_hash ).await {
-                            Ok(db) => db,
+                        // revert the market state to the common ancestor and apply the new branch up to the parent block
+                        match block_history_manager.apply_reorg_on_state(block_history_guard.deref_mut(), &market_state_guard.config,
+                            market_state_guard.state_db.clone(), old_head, latest_block_parent_hash).await {
+                            Ok((db, reorg_path)) => {
+                                debug!(%msg_block_number, %msg_block_hash, depth = reorg_path.depth(), applied = reorg_path.applied.len(), "Market state moved to parent block");
+                                if reorg_path.depth() > 0 {
+                                    reorg_depth = Some(reorg_path.depth() as u64);
+                                }
+                                db
+                            }
                             Err(err) => {
-                                error!(%err, %msg_block_number, %msg_block_hash, "Error during apply_state_update_on_parent_db.");
-                                continue
+                                debug!(%err, %msg_block_number, %msg_block_hash, "Market state cannot be moved to parent block, using parent db.");
+                                reorg_depth = block_history_guard.find_common_ancestor(old_head, msg_block_hash)
+                                    .map(|reorg_path| reorg_path.depth() as u64).filter(|depth| *depth > 0);
+                                match block_history_manager.apply_state_update_on_parent_db(block_history_guard.deref_mut(), &market_state_guard.config, msg_block_hash ).await {
+                                    Ok(db) => db,
+                                    Err(err) => {
+                                        error!(%err, %msg_block_number, %msg_block_hash, "Error during apply_state_update_on_parent_db.");
+                                        continue
+                                    }
+                                }
                             }
                         }
                     };
@@ -308,26 +328,48 @@ where
                     market_state_guard.block_number = latest_block_number;
 
 
+                    if let Some(depth) = reorg_depth {
+                        info!(%msg_block_number, depth, %old_head, new_head = %msg_block_hash, "Chain reorg, market state rolled back");
+                        run_async!(market_events_tx.send(MarketEvents::Reorg{ depth, old_head, new_head : msg_block_hash }));
+                    }
+
                     run_async!(market_events_tx.send(MarketEvents::BlockStateUpdate{ block_hash : msg_block_hash} ));
 
+                    // merging and eviction do not touch the block history
+                    drop(latest_block_guard);
+                    drop(block_history_guard);
 
                     #[cfg(not(debug_assertions))]
                     {
                         // Merging DB in background and update market state
                         let market_state_clone = market_state.clone();
+                        let protected_accounts = market_state_guard.config.force_insert_accounts.clone();
+                        let protected_slots = market_state_guard.config.protected_slots.clone();
 
                         tokio::task::spawn( async move{
-                            let merged_db = updated_db.maintain();
+                            let merge_task = tokio::task::spawn_blocking(move || updated_db.maintain().evict(&protected_accounts, &protected_slots));
+                            let merged_db = match merge_task.await {
+                                Ok(merged_db) => merged_db,
+                                Err(err) => {
+                                    error!(%err, "Merging DB failed");
+                                    return
+                                }
+                            };
                             let mut market_state_guard = market_state_clone.write().await;
-                            market_state_guard.state_db = merged_db;
-                            debug!("Merged DB stored in MarketState at block {}", msg_block_number)
+                            // the market state could move to the next block while merging
+                            if market_state_guard.block_hash == msg_block_hash {
+                                market_state_guard.state_db = merged_db;
+                                debug!("Merged DB stored in MarketState at block {}", msg_block_number)
+                            }
                         });
                     }
 
                     #[cfg(debug_assertions)]
                     {
 
-                        market_state_guard.state_db = updated_db.maintain();
+                        let config = &market_state_guard.config;
+                        let merged_db = updated_db.maintain().evict(&config.force_insert_accounts, &config.protected_slots);
+                        market_state_guard.state_db = merged_db;
 
                         let accounts = market_state_guard.state_db.accounts_len();
 
@@ -25,7 +25,7 @@ mod test {
     };
     use loom_types_blockchain::{GethStateUpdate, GethStateUpdateVec};
     use loom_types_entities::MarketState;
-    use loom_types_events::{BlockHeader, Message};
+    use loom_types_events::{BlockHeader, MarketEvents, Message};
     use std::time::Duration;
     use tracing::info;
 
@@ -313,4 +313,95 @@ mod test {
 
         Ok(())
     }
+
+    async fn test_actor_block_history_actor_reorg_state(depth: u64) -> eyre::Result<()> {
+        let _ = env_logger::try_init_from_env(env_logger::Env::default().default_filter_or("info,tokio_tungstenite=off,tungstenite=off"));
+
+        const ADDR_01: Address = Address::repeat_byte(1);
+        let cell_01: B256 = B256::from(U256::from_limbs([1, 0, 0, 0]));
+
+        let anvil = Anvil::new().try_spawn()?;
+        let client_anvil = ClientBuilder::default().http(anvil.endpoint_url()).boxed();
+        let provider = ProviderBuilder::new().on_client(client_anvil);
+
+        let bc = Blockchain::new(1);
+        let state = BlockchainState::<LoomDB>::new_with_market_state(MarketState::new(LoomDB::empty()));
+
+        let mut db = LoomDBType::default();
+        db.apply_geth_update_vec(vec![geth_state_update_add_account(
+            GethStateUpdate::default(),
+            ADDR_01,
+            account_state_add_storage(account_state_with_nonce_and_balance(1, U256::from(2)), cell_01, B256::from(U256::from(2))),
+        )]);
+        state.market_state().write().await.state_db = db;
+
+        BlockHistoryActor::new(provider.clone()).on_bc(&bc, &state).start()?;
+        let mut rx = bc.market_events_channel().subscribe().await;
+
+        let state_0 =
+            geth_state_update_add_account(GethStateUpdate::default(), ADDR_01, account_state_with_nonce_and_balance(2, U256::from(3)));
+        broadcast_latest_block(provider.clone(), &bc, Some(vec![state_0])).await?; // block 0
+
+        let snap = provider.anvil_snapshot().await?;
+        for i in 1..=depth {
+            provider.anvil_mine(Some(U256::from(1)), None).await?; // mine block i#0
+            let account = account_state_add_storage(
+                account_state_with_nonce_and_balance(10 + i, U256::from(20 + i)),
+                cell_01,
+                B256::from(U256::from(30 + i)),
+            );
+            broadcast_latest_block(
+                provider.clone(),
+                &bc,
+                Some(vec![geth_state_update_add_account(GethStateUpdate::default(), ADDR_01, account)]),
+            )
+            .await?; // broadcast i#0
+        }
+        let old_head = bc.latest_block().read().await.block_hash;
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(state.market_state().read().await.state_db.storage_ref(ADDR_01, U256::from(1))?, U256::from(30 + depth));
+
+        provider.anvil_revert(snap).await?;
+        provider.anvil_increase_time(U256::from(60)).await?; // new branch blocks must have other hashes
+        provider.anvil_mine(Some(U256::from(depth + 1)), None).await?; // mine blocks 1#1..=(depth+1)#1
+        let new_head = provider.get_block_by_number(BlockNumberOrTag::Latest, BlockTransactionsKind::Hashes).await?.unwrap().header.hash;
+
+        let state_new_head =
+            geth_state_update_add_account(GethStateUpdate::default(), ADDR_01, account_state_with_nonce_and_balance(3, U256::from(100)));
+        broadcast_latest_block(provider.clone(), &bc, Some(vec![state_new_head])).await?; // broadcast (depth+1)#1
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        let mut reorg_event = None;
+        while let Ok(event) = rx.try_recv() {
+            if let MarketEvents::Reorg { .. } = event {
+                reorg_event = Some(event);
+            }
+        }
+        let Some(MarketEvents::Reorg { depth: reorg_depth, old_head: reorg_old_head, new_head: reorg_new_head }) = reorg_event else {
+            return Err(eyre!("REORG_EVENT_NOT_RECEIVED"));
+        };
+        assert_eq!(reorg_depth, depth);
+        assert_eq!(reorg_old_head, old_head);
+        assert_eq!(reorg_new_head, new_head);
+
+        // slot was changed only by orphaned blocks and must be reverted to the value of the common ancestor
+        assert_eq!(state.market_state().read().await.block_hash, new_head);
+        assert_eq!(state.market_state().read().await.state_db.basic_ref(ADDR_01)?.unwrap().nonce, 3);
+        assert_eq!(state.market_state().read().await.state_db.basic_ref(ADDR_01)?.unwrap().balance, U256::from(100));
+        assert_eq!(state.market_state().read().await.state_db.storage_ref(ADDR_01, U256::from(1))?, U256::from(2));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_actor_block_history_actor_reorg_depth_2() -> eyre::Result<()> {
+        test_actor_block_history_actor_reorg_state(2).await
+    }
+
+    #[tokio::test]
+    async fn test_actor_block_history_actor_reorg_depth_3() -> eyre::Result<()> {
+        test_actor_block_history_actor_reorg_state(3).await
+    }
 }
@@ -1,50 +1,57 @@
 use alloy_network::Ethereum;
 use alloy_primitives::{Address, B256, U256};
 use alloy_provider::{Provider, RootProvider};
+use alloy_signer_local::PrivateKeySigner;
 use alloy_transport::{BoxTransport, Transport};
 use axum::Router;
 use eyre::{eyre, ErrReport, Result};
 use loom_broadcast_accounts::{InitializeSignersOneShotBlockingActor, NonceAndBalanceMonitorActor, TxSignersActor};
-use loom_broadcast_broadcaster::FlashbotsBroadcastActor;
+use loom_broadcast_broadcaster::{FlashbotsBroadcastActor, MevShareBroadcastActor};
 use loom_broadcast_flashbots::client::RelayConfig;
 use loom_broadcast_flashbots::Flashbots;
-use loom_core_actors::{Actor, ActorsManager, SharedState};
+use loom_core_actors::{Accessor, Actor, ActorsManager, SharedState};
 use loom_core_block_history::BlockHistoryActor;
 use loom_core_blockchain::{Blockchain, BlockchainState, Strategy};
 use loom_core_mempool::MempoolActor;
 use loom_core_router::SwapRouterActor;
 use loom_defi_address_book::TokenAddressEth;
-use loom_defi_health_monitor::{PoolHealthMonitorActor, StuffingTxMonitorActor};
+use loom_defi_health_monitor::{PoolHealthMonitorActor, StuffingTxMonitorActor, TokenScreenerActor};
 use loom_defi_market::{
-    CurvePoolLoaderOneShotActor, HistoryPoolLoaderOneShotActor, NewPoolLoaderActor, PoolLoaderActor, RequiredPoolLoaderActor,
+    CurvePoolLoaderOneShotActor, HistoryPoolLoaderOneShotActor, NewPoolLoaderActor, PoolLoaderActor, PoolLogStateUpdateActor,
+    RequiredPoolLoaderActor,
 };
 use loom_defi_pools::PoolsConfig;
-use loom_defi_preloader::MarketStatePreloadedOneShotActor;
-use loom_defi_price::PriceActor;
+use loom_defi_preloader::{MarketStatePreloadedOneShotActor, MarketStateSnapshotActor, MarketStateSnapshotLoaderOneShotActor};
+use loom_defi_price::{MarketPriceActor, PriceActor};
 use loom_evm_db::DatabaseLoomExt;
 use loom_evm_utils::NWETH;
-use loom_execution_estimator::{EvmEstimatorActor, GethEstimatorActor};
+use loom_execution_estimator::{EvmEstimatorActor, GasModelActor, GethEstimatorActor};
 use loom_execution_multicaller::MulticallerSwapEncoder;
-use loom_metrics::{BlockLatencyRecorderActor, InfluxDbWriterActor};
+use loom_metrics::{BlockLatencyRecorderActor, InfluxDbWriterActor, MarketStateSizeRecorderActor, MempoolSourceLatencyRecorderActor};
 use loom_node_actor_config::NodeBlockActorConfig;
 #[cfg(feature = "db-access")]
 use loom_node_db_access::RethDbAccessBlockActor;
 use loom_node_debug_provider::DebugProviderExt;
 use loom_node_grpc::NodeExExGrpcActor;
 use loom_node_json_rpc::{NodeBlockActor, NodeMempoolActor, WaitForNodeSyncOneShotBlockingActor};
+use loom_node_mev_share::MevShareHintActor;
 use loom_rpc_handler::WebServerActor;
 use loom_storage_db::DbPool;
 use loom_strategy_backrun::{
-    BackrunConfig, BlockStateChangeProcessorActor, PendingTxStateChangeProcessorActor, StateChangeArbSearcherActor,
+    BackrunConfig, BlockStateChangeProcessorActor, PendingTxStateChangeProcessorActor, ProfitPolicy, ProfitPolicyReloadActor,
+    StateChangeArbSearcherActor,
 };
-use loom_strategy_merger::{ArbSwapPathMergerActor, DiffPathMergerActor, SamePathMergerActor};
+use loom_strategy_merger::{ArbSwapPathMergerActor, DiffPathMergerActor, MergerConfig, PackingMergerActor, SamePathMergerActor};
+use loom_types_entities::bidding::AdaptiveBidder;
 use loom_types_entities::required_state::RequiredState;
 use loom_types_entities::{BlockHistoryState, PoolClass, TxSigners};
 use revm::{Database, DatabaseCommit, DatabaseRef};
 use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
 
 pub struct BlockchainActors<P, T, DB: Clone + Send + Sync + 'static> {
     provider: P,
@@ -53,12 +60,18 @@ pub struct BlockchainActors<P, T, DB: Clone + Send + Sync + 'static> {
     strategy: Strategy<DB>,
     pub signers: SharedState<TxSigners>,
     actor_manager: ActorsManager,
+    /// Actors finishing their work when the shutdown token is cancelled
+    shutdown_actor_manager: ActorsManager,
+    shutdown_token: CancellationToken,
     encoder: Option<MulticallerSwapEncoder>,
     has_mempool: bool,
     has_state_update: bool,
     has_signers: bool,
     mutlicaller_address: Option<Address>,
     relays: Vec<RelayConfig>,
+    bidder: Option<SharedState<AdaptiveBidder>>,
+    profit_policy: Option<SharedState<ProfitPolicy>>,
+    db_pool: Option<DbPool>,
     _t: PhantomData<T>,
 }
 
@@ -85,18 +98,62 @@ where
             strategy,
             signers: SharedState::new(TxSigners::new()),
             actor_manager: ActorsManager::new(),
+            shutdown_actor_manager: ActorsManager::new(),
+            shutdown_token: CancellationToken::new(),
             encoder: None,
             has_mempool: false,
             has_state_update: false,
             has_signers: false,
             mutlicaller_address: None,
             relays,
+            bidder: None,
+            profit_policy: None,
+            db_pool: None,
             _t: PhantomData,
         }
     }
 
+    /// Waits until all actors finish or the shutdown token is cancelled, then waits for actors finishing on shutdown
     pub async fn wait(self) {
-        self.actor_manager.wait().await
+        tokio::select! {
+            _ = self.actor_manager.wait() => {}
+            _ = self.shutdown_token.cancelled() => {
+                info!("Shutdown requested");
+            }
+        }
+        self.shutdown_token.cancel();
+        self.shutdown_actor_manager.wait().await
+    }
+
+    /// Replaces the token cancelled to shut the actors down
+    pub fn with_shutdown_token(&mut self, shutdown_token: CancellationToken) -> Result<&mut Self> {
+        self.shutdown_token = shutdown_token;
+        Ok(self)
+    }
+
+    /// Stores token metadata of pools loaded by the pool loader started after the call in the database
+    pub fn with_db_pool(&mut self, db_pool: DbPool) -> Result<&mut Self> {
+        self.db_pool = Some(db_pool);
+        Ok(self)
+    }
+
+    /// Shares the bidder with the swap encoder and the stuffing tx monitor started after the call
+    pub fn with_bidder(&mut self, bidder: SharedState<AdaptiveBidder>) -> Result<&mut Self> {
+        self.bidder = Some(bidder);
+        Ok(self)
+    }
+
+    /// Shares the profit policy with the searchers, the encoder and the mergers started after the call and reloads it when the config
+    /// file is modified
+    pub fn with_profit_policy_reload(&mut self, config_path: PathBuf, profit_policy: ProfitPolicy) -> Result<&mut Self> {
+        let profit_policy = SharedState::new(profit_policy);
+        self.actor_manager.start(ProfitPolicyReloadActor::new(config_path).with_profit_policy(profit_policy.clone()))?;
+        self.profit_policy = Some(profit_policy);
+        Ok(self)
+    }
+
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown_token.clone()
     }
 
     /// Start a custom actor
@@ -177,8 +234,20 @@ where
             },
         };
 
-        self.encoder = Some(MulticallerSwapEncoder::new(multicaller_address));
-        self.actor_manager.start(SwapRouterActor::<DB>::new().with_signers(self.signers.clone()).on_bc(&self.bc, &self.strategy))?;
+        let mut encoder = MulticallerSwapEncoder::new(multicaller_address);
+        if let Some(profit_policy) = self.profit_policy.clone() {
+            encoder = encoder.with_profit_policy(profit_policy);
+        }
+        if let Some(bidder) = self.bidder.clone() {
+            encoder = encoder.with_bidder(bidder);
+        }
+        self.encoder = Some(encoder);
+        self.actor_manager.start(
+            SwapRouterActor::<DB>::new()
+                .with_signers(self.signers.clone())
+                .with_stuffing_validation(&self.bc, &self.state)
+                .on_bc(&self.bc, &self.strategy),
+        )?;
         Ok(self)
     }
 
@@ -196,6 +265,29 @@ where
         Ok(self)
     }
 
+    /// Loads market state and pools from the snapshot and replays blocks mined after it. Market state is not changed if the
+    /// snapshot is missing or cannot be loaded
+    pub fn with_market_state_snapshot_loader(&mut self, path: impl Into<PathBuf>) -> Result<&mut Self> {
+        if let Err(e) = self
+            .actor_manager
+            .start_and_wait(MarketStateSnapshotLoaderOneShotActor::new(self.provider.clone(), path).on_bc(&self.bc, &self.state))
+        {
+            warn!("Market state snapshot not loaded : {}", e)
+        }
+        Ok(self)
+    }
+
+    /// Starts market state snapshot writer. Snapshots are written every `interval_blocks` blocks and on shutdown
+    pub fn with_market_state_snapshot_writer(&mut self, path: impl Into<PathBuf>, interval_blocks: u64) -> Result<&mut Self> {
+        self.shutdown_actor_manager.start(
+            MarketStateSnapshotActor::new(path)
+                .with_interval_blocks(interval_blocks)
+                .with_shutdown_token(self.shutdown_token.clone())
+                .on_bc(&self.bc, &self.state),
+        )?;
+        Ok(self)
+    }
+
     /// Starts preloaded virtual artefacts
     pub fn with_market_state_preloader_virtual(&mut self, address_to_copy: Vec<Address>) -> Result<&mut Self> {
         let address_vec = self.signers.inner().try_read()?.get_address_vec();
@@ -255,12 +347,25 @@ where
         Ok(self)
     }
 
+    /// Starts pricing of all market tokens from the pool graph on every block state update
+    pub fn with_market_price(&mut self) -> Result<&mut Self> {
+        self.actor_manager.start(MarketPriceActor::new().on_bc(&self.bc, &self.state))?;
+        Ok(self)
+    }
+
     /// Starts receiving blocks events through RPC
     pub fn with_block_events(&mut self, config: NodeBlockActorConfig) -> Result<&mut Self> {
         self.actor_manager.start(NodeBlockActor::new(self.provider.clone(), config).on_bc(&self.bc))?;
         Ok(self)
     }
 
+    /// Starts producing block state updates of the market pools from block logs. Use with block events without state update
+    /// for nodes that do not support `debug_traceBlock`
+    pub fn with_block_state_from_logs(&mut self) -> Result<&mut Self> {
+        self.actor_manager.start(PoolLogStateUpdateActor::new().on_bc(&self.bc, &self.state))?;
+        Ok(self)
+    }
+
     /// Starts receiving blocks events through direct Reth DB access
     #[cfg(feature = "db-access")]
     pub fn reth_node_with_blocks(&mut self, db_path: String, config: NodeBlockActorConfig) -> Result<&mut Self> {
@@ -314,6 +419,15 @@ where
         Ok(self)
     }
 
+    /// Starts MEV-Share broadcaster sending backruns of MEV-Share hints signed by `signer`
+    pub fn with_mev_share_broadcaster(&mut self, signer: PrivateKeySigner, allow_broadcast: bool) -> Result<&mut Self> {
+        let flashbots = Flashbots::new(self.provider.clone(), "https://relay.flashbots.net", Some(signer))
+            .with_mev_share_relay("https://relay.flashbots.net");
+
+        self.actor_manager.start(MevShareBroadcastActor::new(flashbots, allow_broadcast).on_bc(&self.bc))?;
+        Ok(self)
+    }
+
     /// Start composer : estimator, signer and broadcaster
     pub fn with_composers(&mut self, allow_broadcast: bool) -> Result<&mut Self> {
         self.with_evm_estimator()?.with_signers()?.with_flashbots_broadcaster(allow_broadcast)
@@ -321,7 +435,7 @@ where
 
     /// Starts pool health monitor
     pub fn with_health_monitor_pools(&mut self) -> Result<&mut Self> {
-        self.actor_manager.start(PoolHealthMonitorActor::new().on_bc(&self.bc))?;
+        self.actor_manager.start(PoolHealthMonitorActor::new().on_bc(&self.bc, &self.state))?;
         Ok(self)
     }
     /// Starts state health monitor
@@ -335,7 +449,11 @@ where
 
     /// Starts stuffing tx monitor
     pub fn with_health_monitor_stuffing_tx(&mut self) -> Result<&mut Self> {
-        self.actor_manager.start(StuffingTxMonitorActor::new(self.provider.clone()).on_bc(&self.bc))?;
+        let mut stuffing_tx_monitor = StuffingTxMonitorActor::new(self.provider.clone()).on_bc(&self.bc);
+        if let Some(bidder) = self.bidder.clone() {
+            stuffing_tx_monitor.access(bidder);
+        }
+        self.actor_manager.start(stuffing_tx_monitor)?;
         Ok(self)
     }
 
@@ -353,7 +471,11 @@ where
 
     /// Start pool loader from new block events
     pub fn with_pool_loader(&mut self) -> Result<&mut Self> {
-        self.actor_manager.start(PoolLoaderActor::new(self.provider.clone()).on_bc(&self.bc, &self.state))?;
+        let mut pool_loader = PoolLoaderActor::new(self.provider.clone()).on_bc(&self.bc, &self.state);
+        if let Some(db_pool) = self.db_pool.clone() {
+            pool_loader = pool_loader.with_db_pool(db_pool);
+        }
+        self.actor_manager.start(pool_loader)?;
         Ok(self)
     }
 
@@ -406,7 +528,7 @@ where
             EvmEstimatorActor::<RootProvider<BoxTransport>, BoxTransport, Ethereum, MulticallerSwapEncoder, DB>::new(
                 self.encoder.clone().unwrap(),
             )
-            .on_bc(&self.strategy),
+            .on_bc(&self.bc, &self.strategy),
         )?;
         Ok(self)
     }
@@ -414,21 +536,44 @@ where
     /// Starts EVM gas estimator and tips filler
     pub fn with_evm_estimator_and_provider(&mut self) -> Result<&mut Self> {
         self.actor_manager.start(
-            EvmEstimatorActor::new_with_provider(self.encoder.clone().unwrap(), Some(self.provider.clone())).on_bc(&self.strategy),
+            EvmEstimatorActor::new_with_provider(self.encoder.clone().unwrap(), Some(self.provider.clone()))
+                .on_bc(&self.bc, &self.strategy),
         )?;
         Ok(self)
     }
 
+    /// Starts calibration of the swap gas model used by backrun searchers
+    pub fn with_gas_model(&mut self) -> Result<&mut Self> {
+        let multicaller_address = self.encoder.clone().ok_or(eyre!("NO_ENCODER"))?.multicaller_address;
+        self.actor_manager.start(GasModelActor::new(multicaller_address).on_bc(&self.bc, &self.state))?;
+        Ok(self)
+    }
+
+    /// Start token screener to detect fee-on-transfer and untradeable tokens
+    pub fn with_token_screener(&mut self) -> Result<&mut Self> {
+        let multicaller_address = self.encoder.clone().ok_or(eyre!("NO_ENCODER"))?.multicaller_address;
+        self.actor_manager.start(TokenScreenerActor::new(multicaller_address).on_bc(&self.bc, &self.state))?;
+        Ok(self)
+    }
+
     /// Start swap path merger
     pub fn with_swap_path_merger(&mut self) -> Result<&mut Self> {
         let mutlicaller_address = self.encoder.clone().ok_or(eyre!("NO_ENCODER"))?.multicaller_address;
-        self.actor_manager.start(ArbSwapPathMergerActor::new(mutlicaller_address).on_bc(&self.bc, &self.strategy))?;
+        let mut merger = ArbSwapPathMergerActor::new(mutlicaller_address).on_bc(&self.bc, &self.strategy);
+        if let Some(profit_policy) = self.profit_policy.clone() {
+            merger = merger.with_profit_policy(profit_policy);
+        }
+        self.actor_manager.start(merger)?;
         Ok(self)
     }
 
     /// Start same path merger
     pub fn with_same_path_merger(&mut self) -> Result<&mut Self> {
-        self.actor_manager.start(SamePathMergerActor::new(self.provider.clone()).on_bc(&self.bc, &self.state, &self.strategy))?;
+        let mut merger = SamePathMergerActor::new(self.provider.clone()).on_bc(&self.bc, &self.state, &self.strategy);
+        if let Some(profit_policy) = self.profit_policy.clone() {
+            merger = merger.with_profit_policy(profit_policy);
+        }
+        self.actor_manager.start(merger)?;
         Ok(self)
     }
 
@@ -438,6 +583,25 @@ where
         Ok(self)
     }
 
+    /// Start packing merger that merges the most profitable compatible set of swaps for the block
+    pub fn with_packing_merger(&mut self, config: &MergerConfig) -> Result<&mut Self> {
+        let mut merger = PackingMergerActor::new().with_config(config).on_bc(&self.bc, &self.state, &self.strategy);
+        if let Some(profit_policy) = self.profit_policy.clone() {
+            merger = merger.with_profit_policy(profit_policy);
+        }
+        self.actor_manager.start(merger)?;
+        Ok(self)
+    }
+
+    /// Start packing merger if it is enabled in the config, diff path merger otherwise
+    pub fn with_block_merger(&mut self, config: &MergerConfig) -> Result<&mut Self> {
+        if config.packing {
+            self.with_packing_merger(config)
+        } else {
+            self.with_diff_path_merger()
+        }
+    }
+
     /// Start all mergers
     pub fn with_mergers(&mut self) -> Result<&mut Self> {
         self.with_swap_path_merger()?.with_same_path_merger()?.with_diff_path_merger()
@@ -446,20 +610,29 @@ where
     /// Start backrun on block
     pub fn with_backrun_block(&mut self, backrun_config: BackrunConfig) -> Result<&mut Self> {
         if !self.has_state_update {
-            self.actor_manager.start(StateChangeArbSearcherActor::new(backrun_config).on_bc(&self.bc, &self.strategy))?;
-            self.has_state_update = true
+            self.start_state_change_arb_searcher(backrun_config)?;
         }
         self.actor_manager.start(BlockStateChangeProcessorActor::new().on_bc(&self.bc, &self.state, &self.strategy))?;
         Ok(self)
     }
 
+    fn start_state_change_arb_searcher(&mut self, backrun_config: BackrunConfig) -> Result<()> {
+        let mut searcher = StateChangeArbSearcherActor::new(backrun_config).on_bc(&self.bc, &self.strategy);
+        if let Some(profit_policy) = self.profit_policy.clone() {
+            searcher = searcher.with_profit_policy(profit_policy);
+        }
+        self.actor_manager.start(searcher)?;
+        self.has_state_update = true;
+        Ok(())
+    }
+
     /// Start backrun for pending txs
     pub fn with_backrun_mempool(&mut self, backrun_config: BackrunConfig) -> Result<&mut Self> {
+        let simulation = backrun_config.pending_tx_simulation();
         if !self.has_state_update {
-            self.actor_manager.start(StateChangeArbSearcherActor::new(backrun_config).on_bc(&self.bc, &self.strategy))?;
-            self.has_state_update = true
+            self.start_state_change_arb_searcher(backrun_config)?;
         }
-        self.actor_manager.start(PendingTxStateChangeProcessorActor::new(self.provider.clone()).on_bc(
+        self.actor_manager.start(PendingTxStateChangeProcessorActor::new(self.provider.clone()).with_simulation(simulation).on_bc(
             &self.bc,
             &self.state,
             &self.strategy,
@@ -467,6 +640,15 @@ where
         Ok(self)
     }
 
+    /// Start backrun for MEV-Share hints received from `url`
+    pub fn with_backrun_mev_share(&mut self, backrun_config: BackrunConfig, url: String) -> Result<&mut Self> {
+        if !self.has_state_update {
+            self.start_state_change_arb_searcher(backrun_config)?;
+        }
+        self.actor_manager.start(MevShareHintActor::new(url).on_bc(&self.bc, &self.state, &self.strategy))?;
+        Ok(self)
+    }
+
     /// Start backrun for blocks and pending txs
     pub async fn with_backrun(&mut self, backrun_config: BackrunConfig) -> Result<&mut Self> {
         self.with_backrun_block(backrun_config.clone())?.with_backrun_mempool(backrun_config)
@@ -484,13 +666,25 @@ where
         Ok(self)
     }
 
+    /// Start recorder of mempool source latencies
+    pub fn with_mempool_source_latency_recorder(&mut self) -> Result<&mut Self> {
+        self.actor_manager.start(MempoolSourceLatencyRecorderActor::new().on_bc(&self.bc))?;
+        Ok(self)
+    }
+
+    /// Start market state size recorder
+    pub fn with_market_state_size_recorder(&mut self) -> Result<&mut Self> {
+        self.actor_manager.start(MarketStateSizeRecorderActor::new().on_bc(&self.bc, &self.state))?;
+        Ok(self)
+    }
+
     /// Start web server
     pub fn with_web_server<S>(&mut self, host: String, router: Router<S>, db_pool: DbPool) -> Result<&mut Self>
     where
         S: Clone + Send + Sync + 'static,
         Router: From<Router<S>>,
     {
-        self.actor_manager.start(WebServerActor::new(host, router, db_pool, CancellationToken::new()).on_bc(&self.bc, &self.state))?;
+        self.actor_manager.start(WebServerActor::new(host, router, db_pool, self.shutdown_token.clone()).on_bc(&self.bc, &self.state))?;
         Ok(self)
     }
 
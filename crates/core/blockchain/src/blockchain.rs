@@ -5,10 +5,10 @@ use loom_core_actors::{Broadcaster, SharedState};
 use loom_defi_address_book::TokenAddressEth;
 use loom_types_blockchain::{ChainParameters, Mempool};
 use loom_types_blockchain::{LoomDataTypes, LoomDataTypesEthereum};
-use loom_types_entities::{AccountNonceAndBalanceState, LatestBlock, Market, Token};
+use loom_types_entities::{AccountNonceAndBalanceState, GasModel, LatestBlock, Market, MempoolSourceStats, PoolQuarantine, Token};
 use loom_types_events::{
     MarketEvents, MempoolEvents, MessageBlock, MessageBlockHeader, MessageBlockLogs, MessageBlockStateUpdate, MessageHealthEvent,
-    MessageMempoolDataUpdate, MessageTxCompose, Task,
+    MessageMempoolDataUpdate, MessageRelayEvent, MessageTxCompose, Task,
 };
 
 #[derive(Clone)]
@@ -18,7 +18,10 @@ pub struct Blockchain<LDT: LoomDataTypes + 'static = LoomDataTypesEthereum> {
     market: SharedState<Market<LDT>>,
     latest_block: SharedState<LatestBlock<LDT>>,
     mempool: SharedState<Mempool<LDT>>,
+    mempool_source_stats: SharedState<MempoolSourceStats<LDT>>,
     account_nonce_and_balance: SharedState<AccountNonceAndBalanceState<LDT>>,
+    gas_model: SharedState<GasModel<LDT>>,
+    pool_quarantine: SharedState<PoolQuarantine<LDT>>,
 
     new_block_headers_channel: Broadcaster<MessageBlockHeader<LDT>>,
     new_block_with_tx_channel: Broadcaster<MessageBlock<LDT>>,
@@ -30,6 +33,7 @@ pub struct Blockchain<LDT: LoomDataTypes + 'static = LoomDataTypesEthereum> {
     tx_compose_channel: Broadcaster<MessageTxCompose<LDT>>,
 
     pool_health_monitor_channel: Broadcaster<MessageHealthEvent<LDT>>,
+    relay_events_channel: Broadcaster<MessageRelayEvent>,
     influxdb_write_channel: Broadcaster<WriteQuery>,
     tasks_channel: Broadcaster<Task>,
 }
@@ -48,6 +52,7 @@ impl Blockchain<LoomDataTypesEthereum> {
         let tx_compose_channel: Broadcaster<MessageTxCompose> = Broadcaster::new(2000);
 
         let pool_health_monitor_channel: Broadcaster<MessageHealthEvent> = Broadcaster::new(1000);
+        let relay_events_channel: Broadcaster<MessageRelayEvent> = Broadcaster::new(1000);
         let influx_write_channel: Broadcaster<WriteQuery> = Broadcaster::new(1000);
         let tasks_channel: Broadcaster<Task> = Broadcaster::new(1000);
 
@@ -72,8 +77,11 @@ impl Blockchain<LoomDataTypesEthereum> {
             chain_parameters: ChainParameters::ethereum(),
             market: SharedState::new(market_instance),
             mempool: SharedState::new(Mempool::<LoomDataTypesEthereum>::new()),
+            mempool_source_stats: SharedState::new(MempoolSourceStats::default()),
             latest_block: SharedState::new(LatestBlock::new(0, BlockHash::ZERO)),
             account_nonce_and_balance: SharedState::new(AccountNonceAndBalanceState::new()),
+            gas_model: SharedState::new(GasModel::new()),
+            pool_quarantine: SharedState::new(PoolQuarantine::default()),
             new_block_headers_channel,
             new_block_with_tx_channel,
             new_block_state_update_channel,
@@ -82,6 +90,7 @@ impl Blockchain<LoomDataTypesEthereum> {
             market_events_channel,
             mempool_events_channel,
             pool_health_monitor_channel,
+            relay_events_channel,
             tx_compose_channel,
             influxdb_write_channel: influx_write_channel,
             tasks_channel,
@@ -110,10 +119,22 @@ impl<LDT: LoomDataTypes> Blockchain<LDT> {
         self.mempool.clone()
     }
 
+    pub fn mempool_source_stats(&self) -> SharedState<MempoolSourceStats<LDT>> {
+        self.mempool_source_stats.clone()
+    }
+
     pub fn nonce_and_balance(&self) -> SharedState<AccountNonceAndBalanceState<LDT>> {
         self.account_nonce_and_balance.clone()
     }
 
+    pub fn gas_model(&self) -> SharedState<GasModel<LDT>> {
+        self.gas_model.clone()
+    }
+
+    pub fn pool_quarantine(&self) -> SharedState<PoolQuarantine<LDT>> {
+        self.pool_quarantine.clone()
+    }
+
     pub fn new_block_headers_channel(&self) -> Broadcaster<MessageBlockHeader<LDT>> {
         self.new_block_headers_channel.clone()
     }
@@ -150,6 +171,10 @@ impl<LDT: LoomDataTypes> Blockchain<LDT> {
         self.pool_health_monitor_channel.clone()
     }
 
+    pub fn relay_events_channel(&self) -> Broadcaster<MessageRelayEvent> {
+        self.relay_events_channel.clone()
+    }
+
     pub fn influxdb_write_channel(&self) -> Broadcaster<WriteQuery> {
         self.influxdb_write_channel.clone()
     }
@@ -1,6 +1,7 @@
 use alloy_primitives::BlockNumber;
 use chrono::{Duration, Utc};
 use eyre::eyre;
+use std::collections::{HashMap, HashSet};
 use tokio::sync::broadcast::error::RecvError;
 use tracing::{debug, error, info, trace};
 
@@ -9,7 +10,40 @@ use loom_core_actors_macros::{Accessor, Consumer, Producer};
 use loom_core_blockchain::Blockchain;
 use loom_types_blockchain::{ChainParameters, Mempool, MempoolTx};
 use loom_types_blockchain::{LoomBlock, LoomDataTypes, LoomDataTypesEthereum, LoomHeader, LoomTx};
-use loom_types_events::{MempoolEvents, MessageBlock, MessageBlockHeader, MessageMempoolDataUpdate};
+use loom_types_events::{MarketEvents, MempoolEvents, MessageBlock, MessageBlockHeader, MessageMempoolDataUpdate};
+
+/// Blocks with mined txs kept to return txs of orphaned blocks to the mempool on reorg
+const RECENT_BLOCKS: u64 = 64;
+
+/// Mined tx hashes and parent hash of the block
+type RecentBlocks<LDT> =
+    HashMap<<LDT as LoomDataTypes>::BlockHash, (BlockNumber, <LDT as LoomDataTypes>::BlockHash, Vec<<LDT as LoomDataTypes>::TxHash>)>;
+
+/// Returns txs mined in the orphaned branch of the reorg that are not mined in the new branch
+fn orphaned_tx_hashes<LDT: LoomDataTypes>(
+    recent_blocks: &RecentBlocks<LDT>,
+    depth: u64,
+    old_head: LDT::BlockHash,
+    new_head: LDT::BlockHash,
+) -> Vec<LDT::TxHash> {
+    let mut orphaned: Vec<LDT::TxHash> = Vec::new();
+    let mut ancestor = old_head;
+    for _ in 0..depth {
+        let Some((_, parent_hash, tx_hashes)) = recent_blocks.get(&ancestor) else { break };
+        orphaned.extend(tx_hashes.iter().cloned());
+        ancestor = *parent_hash;
+    }
+
+    let mut mined: HashSet<LDT::TxHash> = HashSet::new();
+    let mut block_hash = new_head;
+    while block_hash != ancestor {
+        let Some((_, parent_hash, tx_hashes)) = recent_blocks.get(&block_hash) else { break };
+        mined.extend(tx_hashes.iter().cloned());
+        block_hash = *parent_hash;
+    }
+
+    orphaned.into_iter().filter(|tx_hash| !mined.contains(tx_hash)).collect()
+}
 
 pub async fn new_mempool_worker<LDT: LoomDataTypes>(
     chain_parameters: ChainParameters,
@@ -17,14 +51,17 @@ pub async fn new_mempool_worker<LDT: LoomDataTypes>(
     mempool_update_rx: Broadcaster<MessageMempoolDataUpdate<LDT>>,
     block_header_rx: Broadcaster<MessageBlockHeader<LDT>>,
     block_with_txs_rx: Broadcaster<MessageBlock<LDT>>,
+    market_events_rx: Broadcaster<MarketEvents<LDT>>,
     broadcaster: Broadcaster<MempoolEvents<LDT>>,
 ) -> WorkerResult {
     subscribe!(mempool_update_rx);
     subscribe!(block_header_rx);
     subscribe!(block_with_txs_rx);
+    subscribe!(market_events_rx);
 
     let mut current_gas_price: Option<u128> = None;
     let mut last_cleaning_block: Option<BlockNumber> = None;
+    let mut recent_blocks: RecentBlocks<LDT> = HashMap::new();
 
     loop {
         tokio::select! {
@@ -63,6 +100,10 @@ pub async fn new_mempool_worker<LDT: LoomDataTypes>(
                     if let Some(tx) = &mempool_update_msg.mempool_tx.tx {
                         if mempool_entry.tx.is_none() {
                             mempool_entry.tx = Some(tx.clone());
+                            if let Some((replaced_tx_hash, replacement_tx_hash)) = mempool_guard.update_nonce_queue(tx) {
+                                trace!(%replaced_tx_hash, %replacement_tx_hash, "Mempool tx replaced");
+                                run_async!(broadcaster.send(MempoolEvents::MempoolTxReplaced { tx_hash: replaced_tx_hash, replaced_by: replacement_tx_hash }));
+                            }
                             if let Some(cur_gas_price) = current_gas_price {
                                 if tx.gas_limit() > 30000 && tx.gas_price() >= cur_gas_price && mempool_guard.is_valid_tx(tx) {
                                     run_async!(broadcaster.send(MempoolEvents::MempoolActualTxUpdate {tx_hash }));
@@ -151,13 +192,37 @@ pub async fn new_mempool_worker<LDT: LoomDataTypes>(
                         }
                     };
                     let mut mempool_write_guard = mempool.write().await;
+                    // senders with several txs in the block are evicted once
+                    let mut senders: HashSet<LDT::Address> = HashSet::new();
                     for tx in block_with_txs.transactions() {
                         mempool_write_guard
                             .set_mined(tx.tx_hash(), block_with_txs.number())
                             .set_nonce(tx.from(), tx.nonce());
+                        senders.insert(tx.from());
                     }
+                    let evicted_txs: usize = senders.iter().map(|sender| mempool_write_guard.evict_mined_nonces(sender).len()).sum();
+                    trace!(block_number = block_with_txs.number(), evicted_txs, "Mempool txs with mined nonces evicted");
 
                     drop(mempool_write_guard);
+
+                    let block_number = block_with_txs.number();
+                    let tx_hashes = block_with_txs.transactions().iter().map(|tx| tx.tx_hash()).collect();
+                    recent_blocks.insert(block_with_txs.hash(), (block_number, block_with_txs.parent_hash(), tx_hashes));
+                    recent_blocks.retain(|_, (recent_block_number, _, _)| *recent_block_number + RECENT_BLOCKS > block_number);
+                },
+                msg = market_events_rx.recv() => {
+                    let Ok(MarketEvents::Reorg { depth, old_head, new_head }) = msg else {
+                        continue;
+                    };
+
+                    // txs of orphaned blocks are pending again unless they are mined in the new branch
+                    let orphaned_txs = orphaned_tx_hashes::<LDT>(&recent_blocks, depth, old_head, new_head);
+                    let mut mempool_write_guard = mempool.write().await;
+                    for tx_hash in orphaned_txs.iter() {
+                        mempool_write_guard.set_unmined(*tx_hash);
+                    }
+                    drop(mempool_write_guard);
+                    info!(depth, %old_head, %new_head, orphaned_txs = orphaned_txs.len(), "Mempool txs of orphaned blocks returned");
             }
         }
     }
@@ -174,6 +239,8 @@ pub struct MempoolActor<LDT: LoomDataTypes + 'static = LoomDataTypesEthereum> {
     block_header_rx: Option<Broadcaster<MessageBlockHeader<LDT>>>,
     #[consumer]
     block_with_txs_rx: Option<Broadcaster<MessageBlock<LDT>>>,
+    #[consumer]
+    market_events_rx: Option<Broadcaster<MarketEvents<LDT>>>,
     #[producer]
     mempool_events_tx: Option<Broadcaster<MempoolEvents<LDT>>>,
 }
@@ -187,6 +254,7 @@ impl<LDT: LoomDataTypes> Default for MempoolActor<LDT> {
             mempool_events_tx: None,
             block_header_rx: None,
             block_with_txs_rx: None,
+            market_events_rx: None,
         }
     }
 }
@@ -203,6 +271,7 @@ impl<LDT: LoomDataTypes> MempoolActor<LDT> {
             mempool_update_rx: Some(bc.new_mempool_tx_channel()),
             block_header_rx: Some(bc.new_block_headers_channel()),
             block_with_txs_rx: Some(bc.new_block_with_tx_channel()),
+            market_events_rx: Some(bc.market_events_channel()),
             mempool_events_tx: Some(bc.mempool_events_channel()),
         }
     }
@@ -216,6 +285,7 @@ impl<LDT: LoomDataTypes> Actor for MempoolActor<LDT> {
             self.mempool_update_rx.clone().unwrap(),
             self.block_header_rx.clone().unwrap(),
             self.block_with_txs_rx.clone().unwrap(),
+            self.market_events_rx.clone().unwrap(),
             self.mempool_events_tx.clone().unwrap(),
         ));
         Ok(vec![task])
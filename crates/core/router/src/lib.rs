@@ -1,3 +1,5 @@
+mod stuffing_tx_validator;
 mod swap_router_actor;
 
+pub use stuffing_tx_validator::{validate_stuffing_txs, StaleStuffingTx, StuffingTxDropReason};
 pub use swap_router_actor::SwapRouterActor;
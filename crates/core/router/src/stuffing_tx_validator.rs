@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use alloy_consensus::Transaction as TransactionTrait;
+use alloy_network::TransactionResponse;
+use alloy_primitives::{Address, TxHash, U256};
+use alloy_rpc_types::Transaction;
+use loom_types_blockchain::Mempool;
+use revm::DatabaseRef;
+use tracing::trace;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StuffingTxDropReason {
+    /// Nonce of the tx is already used by a mined tx
+    NonceUsed,
+    /// Txs with lower nonces of the sender are not mined yet
+    NonceGap,
+    InsufficientBalance,
+    /// Max fee per gas is below the base fee of the next block
+    GasPriceBelowBaseFee,
+    /// Tx with the same sender and nonce and a higher gas price is in the mempool
+    Replaced,
+}
+
+impl StuffingTxDropReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StuffingTxDropReason::NonceUsed => "nonce_used",
+            StuffingTxDropReason::NonceGap => "nonce_gap",
+            StuffingTxDropReason::InsufficientBalance => "insufficient_balance",
+            StuffingTxDropReason::GasPriceBelowBaseFee => "gas_price_below_base_fee",
+            StuffingTxDropReason::Replaced => "replaced",
+        }
+    }
+}
+
+impl Display for StuffingTxDropReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StaleStuffingTx {
+    pub tx_hash: TxHash,
+    pub reason: StuffingTxDropReason,
+}
+
+/// Checks that stuffing txs of the bundle can still be included in the next block on top of the latest state:
+/// nonce, balance for value and gas, max fee versus the next base fee and replacement in the mempool.
+/// Txs of the same sender are checked in bundle order. Senders that can't be read from the state are not checked.
+pub fn validate_stuffing_txs<DB: DatabaseRef>(
+    db: &DB,
+    mempool: &Mempool,
+    stuffing_txs: &[Transaction],
+    next_block_base_fee: u64,
+) -> Result<(), StaleStuffingTx> {
+    let mut senders: HashMap<Address, (u64, U256)> = HashMap::new();
+
+    for tx in stuffing_txs.iter() {
+        let tx_hash = tx.tx_hash();
+        let stale = |reason| Err(StaleStuffingTx { tx_hash, reason });
+
+        let max_fee_per_gas = TransactionTrait::max_fee_per_gas(tx);
+        if max_fee_per_gas < next_block_base_fee as u128 {
+            return stale(StuffingTxDropReason::GasPriceBelowBaseFee);
+        }
+
+        let (nonce, balance) = match senders.get(&tx.from()) {
+            Some(sender) => *sender,
+            None => match db.basic_ref(tx.from()) {
+                Ok(Some(account)) => (account.nonce, account.balance),
+                Ok(None) | Err(_) => {
+                    trace!(%tx_hash, from = %tx.from(), "Stuffing tx sender is not available");
+                    continue;
+                }
+            },
+        };
+        if tx.nonce() < nonce {
+            return stale(StuffingTxDropReason::NonceUsed);
+        }
+        if tx.nonce() > nonce {
+            return stale(StuffingTxDropReason::NonceGap);
+        }
+
+        let max_cost = U256::from(tx.gas_limit()) * U256::from(max_fee_per_gas) + tx.value();
+        if balance < max_cost {
+            return stale(StuffingTxDropReason::InsufficientBalance);
+        }
+
+        if mempool.get_replacement(tx).is_some() {
+            return stale(StuffingTxDropReason::Replaced);
+        }
+
+        let gas_price = tx
+            .max_priority_fee_per_gas()
+            .map_or(max_fee_per_gas, |priority_fee| max_fee_per_gas.min(next_block_base_fee as u128 + priority_fee));
+        let cost = U256::from(tx.gas_limit()) * U256::from(gas_price) + tx.value();
+        senders.insert(tx.from(), (nonce + 1, balance.saturating_sub(cost)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_consensus::TxLegacy;
+    use alloy_primitives::{TxKind, B256};
+    use loom_types_blockchain::mock_tx;
+    use revm::db::{CacheDB, EmptyDB};
+    use revm::primitives::AccountInfo;
+
+    const SENDER: Address = Address::repeat_byte(0xee);
+
+    fn tx(nonce: u64, gas_price: u128, value: u64, hash: u8) -> Transaction {
+        let tx = TxLegacy {
+            nonce,
+            gas_price,
+            gas_limit: 100_000,
+            to: TxKind::Call(Address::repeat_byte(1)),
+            value: U256::from(value),
+            ..Default::default()
+        };
+        mock_tx(SENDER, tx, B256::repeat_byte(hash))
+    }
+
+    fn state_db(nonce: u64, balance: u64) -> CacheDB<EmptyDB> {
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(SENDER, AccountInfo { nonce, balance: U256::from(balance), ..Default::default() });
+        db
+    }
+
+    fn reason(result: Result<(), StaleStuffingTx>) -> Option<StuffingTxDropReason> {
+        result.err().map(|stale_tx| stale_tx.reason)
+    }
+
+    #[test]
+    fn test_validate_stuffing_txs() {
+        let mempool: Mempool = Mempool::default();
+        let db = state_db(5, 1_000_000);
+
+        assert_eq!(reason(validate_stuffing_txs(&db, &mempool, &[tx(5, 10, 0, 1)], 10)), None);
+        assert_eq!(reason(validate_stuffing_txs(&db, &mempool, &[tx(5, 9, 0, 1)], 10)), Some(StuffingTxDropReason::GasPriceBelowBaseFee));
+        assert_eq!(reason(validate_stuffing_txs(&db, &mempool, &[tx(4, 10, 0, 1)], 10)), Some(StuffingTxDropReason::NonceUsed));
+        assert_eq!(reason(validate_stuffing_txs(&db, &mempool, &[tx(6, 10, 0, 1)], 10)), Some(StuffingTxDropReason::NonceGap));
+        assert_eq!(reason(validate_stuffing_txs(&db, &mempool, &[tx(5, 10, 1, 1)], 10)), Some(StuffingTxDropReason::InsufficientBalance));
+    }
+
+    #[test]
+    fn test_validate_same_sender_txs() {
+        let mempool: Mempool = Mempool::default();
+
+        let db = state_db(5, 2_000_000);
+        assert_eq!(reason(validate_stuffing_txs(&db, &mempool, &[tx(5, 10, 0, 1), tx(6, 10, 0, 2)], 10)), None);
+        assert_eq!(
+            reason(validate_stuffing_txs(&db, &mempool, &[tx(5, 10, 0, 1), tx(7, 10, 0, 2)], 10)),
+            Some(StuffingTxDropReason::NonceGap)
+        );
+
+        let db = state_db(5, 1_500_000);
+        assert_eq!(
+            reason(validate_stuffing_txs(&db, &mempool, &[tx(5, 10, 0, 1), tx(6, 10, 0, 2)], 10)),
+            Some(StuffingTxDropReason::InsufficientBalance)
+        );
+    }
+
+    #[test]
+    fn test_validate_absent_sender() {
+        let mempool: Mempool = Mempool::default();
+        let db = CacheDB::new(EmptyDB::default());
+
+        assert_eq!(reason(validate_stuffing_txs(&db, &mempool, &[tx(5, 10, 1, 1), tx(6, 10, 1, 2)], 10)), None);
+        assert_eq!(reason(validate_stuffing_txs(&db, &mempool, &[tx(5, 9, 0, 1)], 10)), Some(StuffingTxDropReason::GasPriceBelowBaseFee));
+    }
+
+    #[test]
+    fn test_validate_replaced() {
+        let db = state_db(5, 10_000_000);
+        let mut mempool: Mempool = Mempool::default();
+        mempool.add_tx(tx(5, 10, 0, 1));
+        assert_eq!(reason(validate_stuffing_txs(&db, &mempool, &[tx(5, 10, 0, 1)], 10)), None);
+
+        mempool.add_tx(tx(5, 20, 0, 2));
+        let result = validate_stuffing_txs(&db, &mempool, &[tx(5, 10, 0, 1)], 10);
+        assert_eq!(result, Err(StaleStuffingTx { tx_hash: B256::repeat_byte(1), reason: StuffingTxDropReason::Replaced }));
+    }
+}
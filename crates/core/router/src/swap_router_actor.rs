@@ -1,14 +1,19 @@
+use chrono::Utc;
 use eyre::{eyre, Result};
+use influxdb::{Timestamp, WriteQuery};
 use loom_core_actors::{Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
 use loom_core_actors_macros::{Accessor, Consumer, Producer};
-use loom_core_blockchain::{Blockchain, Strategy};
-use loom_types_entities::{AccountNonceAndBalanceState, TxSigners};
+use loom_core_blockchain::{Blockchain, BlockchainState, Strategy};
+use loom_types_blockchain::Mempool;
+use loom_types_entities::{AccountNonceAndBalanceState, MarketState, TxSigners};
 use loom_types_events::{MessageSwapCompose, MessageTxCompose, SwapComposeData, SwapComposeMessage, TxComposeData};
 use revm::DatabaseRef;
 use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::broadcast::Receiver;
 use tracing::{debug, error, info};
 
+use crate::stuffing_tx_validator::validate_stuffing_txs;
+
 /// encoder task performs initial routing for swap request
 async fn router_task_prepare<DB: DatabaseRef + Send + Sync + Clone + 'static>(
     route_request: SwapComposeData<DB>,
@@ -48,13 +53,56 @@ async fn router_task_prepare<DB: DatabaseRef + Send + Sync + Clone + 'static>(
     }
 }
 
+/// Drops the bundle if any of its stuffing txs can't be included in the next block on the latest state
+async fn check_stuffing_txs<DB: DatabaseRef + Send + Sync + Clone + 'static>(
+    route_request: &SwapComposeData<DB>,
+    mempool: SharedState<Mempool>,
+    market_state: SharedState<MarketState<DB>>,
+    influxdb_write_channel_tx: Option<Broadcaster<WriteQuery>>,
+) -> Result<()> {
+    if route_request.tx_compose.stuffing_txs.is_empty() {
+        return Ok(());
+    }
+
+    let db = market_state.read().await.state_db.clone();
+    let result = validate_stuffing_txs(
+        &db,
+        &*mempool.read().await,
+        &route_request.tx_compose.stuffing_txs,
+        route_request.tx_compose.next_block_base_fee,
+    );
+
+    if let Err(stale_tx) = result {
+        debug!(tx_hash = %stale_tx.tx_hash, reason = %stale_tx.reason, swap = %route_request.swap, "Bundle with stale stuffing tx dropped");
+        if let Some(influxdb_write_channel_tx) = influxdb_write_channel_tx {
+            let write_query = WriteQuery::new(Timestamp::from(Utc::now()), "stuffing_tx_dropped")
+                .add_tag("reason", stale_tx.reason.to_string())
+                .add_field("block_number", route_request.tx_compose.next_block_number);
+            if let Err(e) = influxdb_write_channel_tx.send(write_query).await {
+                error!("Failed to send stuffing tx drop to influxdb: {:?}", e);
+            }
+        }
+        return Err(eyre!("STALE_STUFFING_TX"));
+    }
+    Ok(())
+}
+
 async fn router_task_broadcast<DB: DatabaseRef + Send + Sync + Clone + 'static>(
     route_request: SwapComposeData<DB>,
     tx_compose_channel_tx: Broadcaster<MessageTxCompose>,
+    mempool: Option<SharedState<Mempool>>,
+    market_state: Option<SharedState<MarketState<DB>>>,
+    influxdb_write_channel_tx: Option<Broadcaster<WriteQuery>>,
 ) -> Result<()> {
     debug!("router_task_broadcast started {}", route_request.swap);
 
-    match tx_compose_channel_tx.send(MessageTxCompose::sign(route_request.tx_compose)).await {
+    if let (Some(mempool), Some(market_state)) = (mempool, market_state) {
+        check_stuffing_txs(&route_request, mempool, market_state, influxdb_write_channel_tx).await?;
+    }
+
+    let sign_request = TxComposeData { swap: Some(route_request.swap), tips: route_request.tips, ..route_request.tx_compose };
+
+    match tx_compose_channel_tx.send(MessageTxCompose::sign(sign_request)).await {
         Err(_) => {
             error!("compose_channel_tx.send(estimate_request)");
             Err(eyre!("ERROR_SENDING_REQUEST"))
@@ -66,9 +114,12 @@ async fn router_task_broadcast<DB: DatabaseRef + Send + Sync + Clone + 'static>(
 async fn swap_router_worker<DB: DatabaseRef + Clone + Send + Sync + 'static>(
     signers: SharedState<TxSigners>,
     account_monitor: SharedState<AccountNonceAndBalanceState>,
+    mempool: Option<SharedState<Mempool>>,
+    market_state: Option<SharedState<MarketState<DB>>>,
     swap_compose_channel_rx: Broadcaster<MessageSwapCompose<DB>>,
     swap_compose_channel_tx: Broadcaster<MessageSwapCompose<DB>>,
     tx_compose_channel_tx: Broadcaster<MessageTxCompose>,
+    influxdb_write_channel_tx: Option<Broadcaster<WriteQuery>>,
 ) -> WorkerResult {
     let mut compose_channel_rx: Receiver<MessageSwapCompose<DB>> = swap_compose_channel_rx.subscribe().await;
 
@@ -98,6 +149,9 @@ async fn swap_router_worker<DB: DatabaseRef + Clone + Send + Sync + 'static>(
                                     router_task_broadcast(
                                         swap_compose_request,
                                         tx_compose_channel_tx.clone(),
+                                        mempool.clone(),
+                                        market_state.clone(),
+                                        influxdb_write_channel_tx.clone(),
                                     )
                                 );
                             }
@@ -118,12 +172,18 @@ pub struct SwapRouterActor<DB: Send + Sync + Clone + 'static> {
     signers: Option<SharedState<TxSigners>>,
     #[accessor]
     account_nonce_balance: Option<SharedState<AccountNonceAndBalanceState>>,
+    #[accessor]
+    mempool: Option<SharedState<Mempool>>,
+    #[accessor]
+    market_state: Option<SharedState<MarketState<DB>>>,
     #[consumer]
     swap_compose_channel_rx: Option<Broadcaster<MessageSwapCompose<DB>>>,
     #[producer]
     swap_compose_channel_tx: Option<Broadcaster<MessageSwapCompose<DB>>>,
     #[producer]
     tx_compose_channel_tx: Option<Broadcaster<MessageTxCompose>>,
+    #[producer]
+    influxdb_write_channel_tx: Option<Broadcaster<WriteQuery>>,
 }
 
 impl<DB> SwapRouterActor<DB>
@@ -134,9 +194,12 @@ where
         SwapRouterActor {
             signers: None,
             account_nonce_balance: None,
+            mempool: None,
+            market_state: None,
             swap_compose_channel_rx: None,
             swap_compose_channel_tx: None,
             tx_compose_channel_tx: None,
+            influxdb_write_channel_tx: None,
         }
     }
 
@@ -144,6 +207,16 @@ where
         Self { signers: Some(signers), ..self }
     }
 
+    /// Validates stuffing txs of ready bundles against the mempool and the latest market state before broadcasting
+    pub fn with_stuffing_validation(self, bc: &Blockchain, state: &BlockchainState<DB>) -> Self {
+        Self {
+            mempool: Some(bc.mempool()),
+            market_state: Some(state.market_state()),
+            influxdb_write_channel_tx: Some(bc.influxdb_write_channel()),
+            ..self
+        }
+    }
+
     pub fn on_bc(self, bc: &Blockchain, strategy: &Strategy<DB>) -> Self {
         Self {
             swap_compose_channel_rx: Some(strategy.swap_compose_channel()),
@@ -163,9 +236,12 @@ where
         let task = tokio::task::spawn(swap_router_worker(
             self.signers.clone().unwrap(),
             self.account_nonce_balance.clone().unwrap(),
+            self.mempool.clone(),
+            self.market_state.clone(),
             self.swap_compose_channel_rx.clone().unwrap(),
             self.swap_compose_channel_tx.clone().unwrap(),
             self.tx_compose_channel_tx.clone().unwrap(),
+            self.influxdb_write_channel_tx.clone(),
         ));
         Ok(vec![task])
     }
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::topology_config::TransportType;
 use crate::topology_config::{BroadcasterConfig, ClientConfigParams, EncoderConfig, EstimatorConfig, SignersConfig, TopologyConfig};
@@ -11,28 +12,34 @@ use alloy_transport_ipc::IpcConnect;
 use alloy_transport_ws::WsConnect;
 use eyre::{eyre, ErrReport, OptionExt, Result};
 use loom_broadcast_accounts::{InitializeSignersOneShotBlockingActor, NonceAndBalanceMonitorActor, TxSignersActor};
-use loom_broadcast_broadcaster::FlashbotsBroadcastActor;
-use loom_broadcast_flashbots::Flashbots;
+use loom_broadcast_broadcaster::{FlashbotsBroadcastActor, MevShareBroadcastActor, PrivateTxBroadcastActor};
+use loom_broadcast_flashbots::{Flashbots, PrivateTxBroadcaster};
 use loom_core_actors::{Accessor, Actor, Consumer, Producer, SharedState, WorkerResult};
 use loom_core_block_history::BlockHistoryActor;
 use loom_core_blockchain::{Blockchain, BlockchainState, Strategy};
 use loom_core_mempool::MempoolActor;
 use loom_defi_health_monitor::PoolHealthMonitorActor;
-use loom_defi_market::{CurvePoolLoaderOneShotActor, HistoryPoolLoaderOneShotActor, NewPoolLoaderActor, PoolLoaderActor};
+use loom_defi_market::{
+    CurvePoolLoaderOneShotActor, HistoryPoolLoaderOneShotActor, NewPoolLoaderActor, PoolLoaderActor, PoolLogStateUpdateActor,
+};
 use loom_defi_pools::PoolsConfig;
-use loom_defi_preloader::MarketStatePreloadedOneShotActor;
-use loom_defi_price::PriceActor;
+use loom_defi_preloader::{MarketStatePreloadedOneShotActor, MarketStateSnapshotActor, MarketStateSnapshotLoaderOneShotActor};
+use loom_defi_price::MarketPriceActor;
 use loom_evm_db::DatabaseLoomExt;
-use loom_execution_estimator::{EvmEstimatorActor, GethEstimatorActor};
+use loom_execution_estimator::{EvmEstimatorActor, GasModelActor, GethEstimatorActor};
 use loom_execution_multicaller::MulticallerSwapEncoder;
 use loom_node_actor_config::NodeBlockActorConfig;
 #[cfg(feature = "db-access")]
-use loom_node_db_access::RethDbAccessBlockActor;
+use loom_node_db_access::{RethDB, RethDbAccessBlockActor};
 use loom_node_grpc::NodeExExGrpcActor;
 use loom_node_json_rpc::{NodeBlockActor, NodeMempoolActor};
+use loom_node_mev_share::{MevShareHintActor, MEV_SHARE_STREAM_URL};
+use loom_types_entities::bidding::AdaptiveBidder;
+use loom_types_entities::profit_policy::ProfitPolicy;
 use loom_types_entities::{BlockHistoryState, MarketState, TxSigners};
 use revm::{Database, DatabaseCommit, DatabaseRef};
 use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info, warn};
 
 pub struct Topology<DB: Clone + Send + Sync + 'static> {
@@ -45,6 +52,9 @@ pub struct Topology<DB: Clone + Send + Sync + 'static> {
     default_blockchain_name: Option<String>,
     default_multicaller_encoder_name: Option<String>,
     default_signer_name: Option<String>,
+    shutdown_token: CancellationToken,
+    /// Workers finishing their work when the shutdown token is cancelled
+    shutdown_tasks: Vec<JoinHandle<WorkerResult>>,
 }
 
 impl<
@@ -61,6 +71,16 @@ impl<
     > Topology<DB>
 {
     pub async fn from(config: TopologyConfig) -> Result<(Topology<DB>, Vec<JoinHandle<WorkerResult>>)> {
+        Self::from_with_shared_state(config, None, None).await
+    }
+
+    /// Same as [`Topology::from`], encoders of the topology take the tips curve from the shared profit policy and the bidder
+    /// adjusts tips of the encoded swaps
+    pub async fn from_with_shared_state(
+        config: TopologyConfig,
+        profit_policy: Option<SharedState<ProfitPolicy>>,
+        bidder: Option<SharedState<AdaptiveBidder>>,
+    ) -> Result<(Topology<DB>, Vec<JoinHandle<WorkerResult>>)> {
         let mut topology = Topology::<DB> {
             clients: HashMap::new(),
             blockchains: HashMap::new(),
@@ -71,6 +91,8 @@ impl<
             default_blockchain_name: None,
             default_multicaller_encoder_name: None,
             default_signer_name: None,
+            shutdown_token: CancellationToken::new(),
+            shutdown_tasks: Vec::new(),
         };
 
         let mut tasks: Vec<JoinHandle<WorkerResult>> = Vec::new();
@@ -117,7 +139,13 @@ impl<
             match v {
                 EncoderConfig::SwapStep(c) => {
                     let address: Address = c.address.parse()?;
-                    let encoder = MulticallerSwapEncoder::new(address);
+                    let mut encoder = MulticallerSwapEncoder::new(address);
+                    if let Some(profit_policy) = profit_policy.clone() {
+                        encoder = encoder.with_profit_policy(profit_policy);
+                    }
+                    if let Some(bidder) = bidder.clone() {
+                        encoder = encoder.with_bidder(bidder);
+                    }
                     topology.multicaller_encoders.insert(k.clone(), encoder);
                     topology.default_multicaller_encoder_name = Some(k.clone());
                 }
@@ -126,7 +154,12 @@ impl<
 
         for (k, params) in config.blockchains.iter() {
             let blockchain = Blockchain::new(params.chain_id.unwrap_or(1) as u64);
-            let market_state = MarketState::new(DB::default());
+            let mut db = DB::default();
+            if let Some(eviction) = params.eviction {
+                info!(?eviction, "Market state eviction enabled for {k}");
+                db.set_eviction_policy(eviction.into());
+            }
+            let market_state = MarketState::new(db);
             let blockchain_state = BlockchainState::<DB>::new_with_market_state(market_state);
             let strategy = Strategy::<DB>::new();
 
@@ -159,6 +192,7 @@ impl<
                 .consume(blockchain.new_mempool_tx_channel())
                 .consume(blockchain.new_block_headers_channel())
                 .consume(blockchain.new_block_with_tx_channel())
+                .consume(blockchain.market_events_channel())
                 .produce(blockchain.mempool_events_channel())
                 .start()
             {
@@ -173,7 +207,14 @@ impl<
 
             info!("Starting pool monitor monitor actor {k}");
             let mut new_pool_health_monior_actor = PoolHealthMonitorActor::new();
-            match new_pool_health_monior_actor.access(blockchain.market()).consume(blockchain.pool_health_monitor_channel()).start() {
+            match new_pool_health_monior_actor
+                .access(blockchain.market())
+                .access(blockchain_state.market_state())
+                .access(blockchain.pool_quarantine())
+                .consume(blockchain.pool_health_monitor_channel())
+                .consume(blockchain.new_block_headers_channel())
+                .start()
+            {
                 Ok(r) => {
                     tasks.extend(r);
                     info!("Pool monitor monitor actor started")
@@ -223,6 +264,41 @@ impl<
             }
         }
 
+        if let Some(snapshots) = config.snapshots {
+            for (name, params) in snapshots {
+                let blockchain = topology.get_blockchain(params.blockchain.as_ref())?;
+                let blockchain_state = topology.get_blockchain_state(params.blockchain.as_ref())?;
+                let client = topology.get_client(params.client.as_ref())?;
+
+                info!("Loading market state snapshot {name} from {}", params.path);
+                let market_state_snapshot_loader =
+                    MarketStateSnapshotLoaderOneShotActor::new(client, params.path.clone()).on_bc(blockchain, blockchain_state);
+                match market_state_snapshot_loader.start_and_wait() {
+                    Ok(_) => {
+                        info!("Market state snapshot loaded")
+                    }
+                    Err(e) => {
+                        warn!("Market state snapshot not loaded : {}", e)
+                    }
+                }
+
+                info!("Starting market state snapshot actor {name}");
+                let market_state_snapshot_actor = MarketStateSnapshotActor::new(params.path)
+                    .with_interval_blocks(params.interval_blocks.unwrap_or(100))
+                    .with_shutdown_token(topology.shutdown_token.clone())
+                    .on_bc(blockchain, blockchain_state);
+                match market_state_snapshot_actor.start() {
+                    Ok(r) => {
+                        topology.shutdown_tasks.extend(r);
+                        info!("Market state snapshot actor started successfully")
+                    }
+                    Err(e) => {
+                        panic!("{}", e)
+                    }
+                }
+            }
+        }
+
         if let Some(preloader_actors) = config.preloaders {
             for (name, params) in preloader_actors {
                 info!("Starting market state preload actor {name}");
@@ -283,6 +359,18 @@ impl<
 
                 #[cfg(feature = "db-access")]
                 if client_config.db_path.is_some() {
+                    // cache misses of the market state are read from the node database instead of json rpc
+                    match RethDB::open(client_config.db_path.clone().unwrap_or_default()) {
+                        Ok(reth_db) => {
+                            let blockchain_state = topology.get_blockchain_state(params.blockchain.as_ref())?;
+                            blockchain_state.market_state().write().await.state_db.with_ext_db(reth_db);
+                            info!("Reth db is used as market state ext db for : {}", name)
+                        }
+                        Err(e) => {
+                            error!("Cannot open reth db for {} : {}", name, e)
+                        }
+                    }
+
                     let mut node_block_actor = RethDbAccessBlockActor::new(
                         client.clone(),
                         NodeBlockActorConfig::all_enabled(),
@@ -307,13 +395,14 @@ impl<
 
                 if client_config.db_path.is_none() {
                     let mut node_block_actor = NodeBlockActor::new(client, NodeBlockActorConfig::all_enabled());
-                    match node_block_actor
+                    node_block_actor
                         .produce(blockchain.new_block_headers_channel())
                         .produce(blockchain.new_block_with_tx_channel())
-                        .produce(blockchain.new_block_logs_channel())
-                        .produce(blockchain.new_block_state_update_channel())
-                        .start()
-                    {
+                        .produce(blockchain.new_block_logs_channel());
+                    if !client_config.state_from_logs {
+                        node_block_actor.produce(blockchain.new_block_state_update_channel());
+                    }
+                    match node_block_actor.start() {
                         Ok(r) => {
                             tasks.extend(r);
                             info!("Node actor started successfully for : {} @ {}", name, blockchain.chain_id())
@@ -322,6 +411,26 @@ impl<
                             panic!("{}", e)
                         }
                     }
+
+                    if client_config.state_from_logs {
+                        let blockchain_state = topology.get_blockchain_state(params.blockchain.as_ref())?;
+                        let mut log_state_update_actor = PoolLogStateUpdateActor::new();
+                        match log_state_update_actor
+                            .access(blockchain.market())
+                            .access(blockchain_state.market_state())
+                            .consume(blockchain.new_block_logs_channel())
+                            .produce(blockchain.new_block_state_update_channel())
+                            .start()
+                        {
+                            Ok(r) => {
+                                tasks.extend(r);
+                                info!("Pool log state update actor started successfully for : {} @ {}", name, blockchain.chain_id())
+                            }
+                            Err(e) => {
+                                panic!("{}", e)
+                            }
+                        }
+                    }
                 }
             }
         }
@@ -352,11 +461,16 @@ impl<
 
         if let Some(price_actors) = config.actors.price {
             for (name, c) in price_actors {
-                let client = topology.get_client(c.client.as_ref())?;
                 let blockchain = topology.get_blockchain(c.blockchain.as_ref())?;
-                info!("Starting price actor");
-                let mut price_actor = PriceActor::new(client);
-                match price_actor.access(blockchain.market()).start() {
+                let blockchain_state = topology.get_blockchain_state(c.blockchain.as_ref())?;
+                info!("Starting market price actor");
+                let mut price_actor = MarketPriceActor::new();
+                match price_actor
+                    .access(blockchain.market())
+                    .access(blockchain_state.market_state())
+                    .consume(blockchain.market_events_channel())
+                    .start()
+                {
                     Ok(r) => {
                         tasks.extend(r);
                         info!("Price actor has been initialized : {}", name)
@@ -397,15 +511,53 @@ impl<
         }
 
         if let Some(broadcaster_actors) = config.actors.broadcaster {
+            // single tx requests sent as private txs are not sent as bundles of the same blockchain
+            let private_tx_origins: HashMap<Option<String>, Option<Vec<String>>> = broadcaster_actors
+                .values()
+                .filter_map(|params| match params {
+                    BroadcasterConfig::PrivateTx(params) => Some((params.blockchain.clone(), params.origins.clone())),
+                    BroadcasterConfig::Flashbots(_) => None,
+                })
+                .collect();
+
             for (name, params) in broadcaster_actors {
                 match params {
                     BroadcasterConfig::Flashbots(params) => {
                         let client = topology.get_client(params.client.as_ref())?;
                         let blockchain = topology.get_blockchain(params.blockchain.as_ref())?;
 
-                        let flashbots_client = Flashbots::new(client, "https://relay.flashbots.net", None).with_default_relays();
+                        let relays = params.relays();
+                        let mut flashbots_client = match relays.is_empty() {
+                            true => Flashbots::new(client, "https://relay.flashbots.net", None).with_default_relays(),
+                            false => Flashbots::new(client, "https://relay.flashbots.net", None).with_relays(relays),
+                        };
+                        if let Some(refund_percent) = params.refund_percent {
+                            let refund_recipient: Option<Address> = params.refund_recipient.as_ref().map(|r| r.parse()).transpose()?;
+                            flashbots_client = flashbots_client.with_refund(refund_percent, refund_recipient);
+                        }
+                        if let Some(builders) = params.builders.clone() {
+                            flashbots_client = flashbots_client.with_builders(builders);
+                        }
                         let mut flashbots_actor = FlashbotsBroadcastActor::new(flashbots_client, true);
-                        match flashbots_actor.consume(blockchain.tx_compose_channel()).start() {
+                        if let Some(bundle_stats_delay) = params.bundle_stats_delay {
+                            flashbots_actor = flashbots_actor.with_bundle_stats(Duration::from_secs(bundle_stats_delay));
+                        }
+                        if let Some(target_blocks) = params.target_blocks {
+                            flashbots_actor = flashbots_actor.with_target_blocks(target_blocks);
+                        }
+                        if let Some(origins) = params.origins.clone() {
+                            flashbots_actor = flashbots_actor.with_origins(origins);
+                        }
+                        if let Some(origins) = private_tx_origins.get(&params.blockchain) {
+                            flashbots_actor = flashbots_actor.with_private_tx_origins(origins.clone());
+                        }
+                        match flashbots_actor
+                            .consume(blockchain.tx_compose_channel())
+                            .consume(blockchain.new_block_with_tx_channel())
+                            .consume(blockchain.new_block_state_update_channel())
+                            .produce(blockchain.relay_events_channel())
+                            .start()
+                        {
                             Ok(r) => {
                                 tasks.extend(r);
                                 info!("Flashbots broadcaster actor {name} started successfully for {}", blockchain.chain_id())
@@ -415,6 +567,33 @@ impl<
                             }
                         }
                     }
+                    BroadcasterConfig::PrivateTx(params) => {
+                        let blockchain = topology.get_blockchain(params.blockchain.as_ref())?;
+
+                        let private_tx_client = PrivateTxBroadcaster::new(params.endpoints(), None)?;
+                        let mut private_tx_actor = PrivateTxBroadcastActor::new(private_tx_client, true);
+                        if let Some(target_blocks) = params.target_blocks {
+                            private_tx_actor = private_tx_actor.with_target_blocks(target_blocks);
+                        }
+                        if let Some(origins) = params.origins.clone() {
+                            private_tx_actor = private_tx_actor.with_origins(origins);
+                        }
+                        match private_tx_actor
+                            .consume(blockchain.tx_compose_channel())
+                            .consume(blockchain.new_block_with_tx_channel())
+                            .consume(blockchain.new_block_state_update_channel())
+                            .produce(blockchain.relay_events_channel())
+                            .start()
+                        {
+                            Ok(r) => {
+                                tasks.extend(r);
+                                info!("Private tx broadcaster actor {name} started successfully for {}", blockchain.chain_id())
+                            }
+                            Err(e) => {
+                                panic!("Error starting private tx broadcaster actor {name} for {} : {}", blockchain.chain_id(), e)
+                            }
+                        }
+                    }
                 }
             }
         } else {
@@ -500,10 +679,32 @@ impl<
 
                         let blockchain = topology.get_blockchain(params.blockchain.as_ref())?;
                         let strategy = topology.get_strategy(params.blockchain.as_ref())?;
+                        let blockchain_state = topology.get_blockchain_state(params.blockchain.as_ref())?;
                         let encoder = topology.get_multicaller_encoder(params.encoder.as_ref())?;
 
+                        let mut gas_model_actor = GasModelActor::<DB>::new(encoder.get_contract_address());
+                        match gas_model_actor
+                            .access(blockchain.market())
+                            .access(blockchain_state.market_state())
+                            .access(blockchain.gas_model())
+                            .consume(blockchain.new_block_headers_channel())
+                            .start()
+                        {
+                            Ok(r) => {
+                                tasks.extend(r);
+                                info!("Gas model actor started successfully {name} @ {}", blockchain.chain_id())
+                            }
+                            Err(e) => {
+                                panic!("Error starting gas model actor {name} @ {} : {}", blockchain.chain_id(), e)
+                            }
+                        }
+
                         let mut evm_estimator_actor = EvmEstimatorActor::new_with_provider(encoder, client);
-                        match evm_estimator_actor.consume(strategy.swap_compose_channel()).produce(strategy.swap_compose_channel()).start()
+                        match evm_estimator_actor
+                            .consume(strategy.swap_compose_channel())
+                            .consume(blockchain.new_block_headers_channel())
+                            .produce(strategy.swap_compose_channel())
+                            .start()
                         {
                             Ok(r) => {
                                 tasks.extend(r);
@@ -540,9 +741,67 @@ impl<
             warn!("No estimator actors in config")
         }
 
+        if let Some(mev_share_actors) = config.actors.mev_share {
+            for (name, params) in mev_share_actors {
+                let client = topology.get_client(params.client.as_ref())?;
+                let blockchain = topology.get_blockchain(params.blockchain.as_ref())?;
+                let blockchain_state = topology.get_blockchain_state(params.blockchain.as_ref())?;
+                let strategy = topology.get_strategy(params.blockchain.as_ref())?;
+
+                let url = params.url.clone().unwrap_or(MEV_SHARE_STREAM_URL.to_string());
+                let mut mev_share_hint_actor = MevShareHintActor::new(url);
+                match mev_share_hint_actor
+                    .access(blockchain.market())
+                    .access(blockchain_state.market_state())
+                    .consume(blockchain.market_events_channel())
+                    .produce(strategy.state_update_channel())
+                    .start()
+                {
+                    Ok(r) => {
+                        tasks.extend(r);
+                        info!("MEV-Share hint actor {name} started successfully for {}", blockchain.chain_id())
+                    }
+                    Err(e) => {
+                        panic!("Error starting MEV-Share hint actor {name} for {} : {}", blockchain.chain_id(), e)
+                    }
+                }
+
+                let relay_url = params.relay_url.clone().unwrap_or("https://relay.flashbots.net".to_string());
+                let flashbots_client =
+                    Flashbots::new(client, relay_url.as_str(), Some(params.signer()?)).with_mev_share_relay(relay_url.as_str());
+                let mut mev_share_broadcast_actor = MevShareBroadcastActor::new(flashbots_client, true);
+                match mev_share_broadcast_actor.consume(blockchain.tx_compose_channel()).produce(blockchain.relay_events_channel()).start()
+                {
+                    Ok(r) => {
+                        tasks.extend(r);
+                        info!("MEV-Share broadcaster actor {name} started successfully for {}", blockchain.chain_id())
+                    }
+                    Err(e) => {
+                        panic!("Error starting MEV-Share broadcaster actor {name} for {} : {}", blockchain.chain_id(), e)
+                    }
+                }
+            }
+        }
+
         Ok((topology, tasks))
     }
 
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown_token.clone()
+    }
+
+    /// Cancels the shutdown token and waits for workers finishing their work on shutdown
+    pub async fn shutdown(&mut self) {
+        self.shutdown_token.cancel();
+        for task in std::mem::take(&mut self.shutdown_tasks) {
+            match task.await {
+                Ok(Ok(s)) => info!("Shutdown worker finished : {s}"),
+                Ok(Err(e)) => error!("Shutdown worker finished with error : {e}"),
+                Err(e) => error!("Shutdown worker join error : {e}"),
+            }
+        }
+    }
+
     pub fn get_client(&self, name: Option<&String>) -> Result<RootProvider<BoxTransport>> {
         match self.clients.get(name.unwrap_or(&"local".to_string())) {
             Some(a) => Ok(a.client().ok_or_eyre("CLIENT_NOT_SET")?.clone()),
@@ -1,16 +1,38 @@
 use std::collections::HashMap;
 use std::fs;
+use std::str::FromStr;
 
 use alloy_provider::RootProvider;
+use alloy_signer_local::PrivateKeySigner;
 use alloy_transport::BoxTransport;
-use eyre::Result;
-use loom_broadcast_flashbots::client::RelayConfig;
+use eyre::{eyre, Result};
+use loom_broadcast_flashbots::client::{RelayCapabilities, RelayConfig};
+use loom_broadcast_flashbots::{PrivateTxEndpointConfig, PrivateTxMethod};
+use loom_evm_db::EvictionPolicy;
 use serde::Deserialize;
 use strum_macros::Display;
 
 #[derive(Debug, Deserialize)]
 pub struct BlockchainConfig {
     pub chain_id: Option<i64>,
+    /// Eviction of cold records from the market state db. Evicted records are read from the node again.
+    pub eviction: Option<EvictionConfig>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(tag = "policy", rename_all = "lowercase")]
+pub enum EvictionConfig {
+    Lru { max_storage_len: usize },
+    Idle { blocks: u64 },
+}
+
+impl From<EvictionConfig> for EvictionPolicy {
+    fn from(config: EvictionConfig) -> Self {
+        match config {
+            EvictionConfig::Lru { max_storage_len } => EvictionPolicy::Lru { max_storage_len },
+            EvictionConfig::Idle { blocks } => EvictionPolicy::Idle { blocks },
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Display)]
@@ -49,6 +71,9 @@ pub struct ClientConfigParams {
     pub transport: TransportType,
     pub db_path: Option<String>,
     pub exex: Option<String>,
+    /// Build state updates of the pools from logs, for nodes without `debug_traceBlock`
+    #[serde(default)]
+    pub state_from_logs: bool,
     #[serde(skip)]
     pub provider: Option<RootProvider<BoxTransport>>,
 }
@@ -104,6 +129,15 @@ pub struct PreloaderConfig {
     pub signers: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SnapshotConfig {
+    pub client: Option<String>,
+    #[serde(rename = "bc")]
+    pub blockchain: Option<String>,
+    pub path: String,
+    pub interval_blocks: Option<u64>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SwapStepEncoderConfig {
     pub address: String,
@@ -135,11 +169,37 @@ pub struct FlashbotsRelayConfig {
     name: String,
     url: String,
     no_sign: Option<bool>,
+    stats_method: Option<String>,
+    cancel_method: Option<String>,
+    /// Relay accepts `refundPercent` and `refundRecipient`. Defaults to false.
+    supports_refund: Option<bool>,
+    /// Relay accepts `builders`. Defaults to false.
+    supports_builders: Option<bool>,
+    /// Relay accepts `revertingTxHashes`. Defaults to true.
+    supports_reverting_tx_hashes: Option<bool>,
+    /// Relay accepts `minTimestamp` and `maxTimestamp`. Defaults to true.
+    supports_timestamps: Option<bool>,
+    /// Header for the request signature. Defaults to `X-Flashbots-Signature`.
+    signature_header: Option<String>,
 }
 
 impl From<FlashbotsRelayConfig> for RelayConfig {
     fn from(config: FlashbotsRelayConfig) -> Self {
-        RelayConfig { id: config.id, name: config.name, url: config.url, no_sign: config.no_sign }
+        RelayConfig {
+            id: config.id,
+            name: config.name,
+            url: config.url,
+            no_sign: config.no_sign,
+            stats_method: config.stats_method,
+            cancel_method: config.cancel_method,
+            capabilities: RelayCapabilities {
+                refund: config.supports_refund.unwrap_or(false),
+                builders: config.supports_builders.unwrap_or(false),
+                reverting_tx_hashes: config.supports_reverting_tx_hashes.unwrap_or(true),
+                timestamps: config.supports_timestamps.unwrap_or(true),
+                signature_header: config.signature_header,
+            },
+        }
     }
 }
 
@@ -150,6 +210,18 @@ pub struct FlashbotsBroadcasterConfig {
     pub client: Option<String>,
     pub smart: Option<bool>,
     pub relays: Option<Vec<FlashbotsRelayConfig>>,
+    /// Delay in seconds after which bundle stats are polled. Polling is disabled if not set.
+    pub bundle_stats_delay: Option<u64>,
+    /// Number of consecutive blocks each bundle is sent for. Defaults to 1.
+    pub target_blocks: Option<u64>,
+    /// Percent of the bundle profit refunded by relays supporting refunds.
+    pub refund_percent: Option<u8>,
+    /// Address receiving refunds, the sender of the first bundle transaction if not set.
+    pub refund_recipient: Option<String>,
+    /// Builders the bundle is shared with by relays supporting it.
+    pub builders: Option<Vec<String>>,
+    /// Origins of the requests sent by the broadcaster, all if not set.
+    pub origins: Option<Vec<String>>,
 }
 
 impl FlashbotsBroadcasterConfig {
@@ -158,11 +230,51 @@ impl FlashbotsBroadcasterConfig {
     }
 }
 
+#[derive(Clone, Debug, Deserialize)]
+pub struct PrivateTxEndpointConfigParams {
+    id: u16,
+    name: String,
+    url: String,
+    method: Option<PrivateTxMethod>,
+    no_sign: Option<bool>,
+}
+
+impl From<PrivateTxEndpointConfigParams> for PrivateTxEndpointConfig {
+    fn from(config: PrivateTxEndpointConfigParams) -> Self {
+        PrivateTxEndpointConfig {
+            id: config.id,
+            name: config.name,
+            url: config.url,
+            method: config.method.unwrap_or_default(),
+            no_sign: config.no_sign,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PrivateTxBroadcasterConfig {
+    #[serde(rename = "bc")]
+    pub blockchain: Option<String>,
+    pub endpoints: Vec<PrivateTxEndpointConfigParams>,
+    /// Number of consecutive blocks each transaction is valid for. Defaults to 1.
+    pub target_blocks: Option<u64>,
+    /// Origins of the requests sent by the broadcaster, all if not set.
+    pub origins: Option<Vec<String>>,
+}
+
+impl PrivateTxBroadcasterConfig {
+    pub fn endpoints(&self) -> Vec<PrivateTxEndpointConfig> {
+        self.endpoints.iter().map(|e| e.clone().into()).collect()
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(tag = "type")]
 pub enum BroadcasterConfig {
     #[serde(rename = "flashbots")]
     Flashbots(FlashbotsBroadcasterConfig),
+    #[serde(rename = "private_tx")]
+    PrivateTx(PrivateTxBroadcasterConfig),
 }
 
 #[derive(Debug, Deserialize)]
@@ -216,6 +328,28 @@ pub struct DatabaseConfig {
     pub url: String,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+pub struct MevShareConfig {
+    #[serde(rename = "bc")]
+    pub blockchain: Option<String>,
+    pub client: Option<String>,
+    /// Hint stream url, Flashbots MEV-Share stream if not set
+    pub url: Option<String>,
+    /// Relay receiving backrun bundles, Flashbots relay if not set
+    pub relay_url: Option<String>,
+    /// Environment variable holding the hex private key bundles are signed with, `MEV_SHARE_SIGNER` if not set
+    pub signer_env: Option<String>,
+}
+
+impl MevShareConfig {
+    /// Relay reputation is bound to the signing key, so the key must be configured
+    pub fn signer(&self) -> Result<PrivateKeySigner> {
+        let signer_env = self.signer_env.as_deref().unwrap_or("MEV_SHARE_SIGNER");
+        let key = std::env::var(signer_env).map_err(|_| eyre!("MEV_SHARE_SIGNER_NOT_SET"))?;
+        PrivateKeySigner::from_str(key.trim()).map_err(|_| eyre!("MEV_SHARE_SIGNER_INVALID"))
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ActorConfig {
     pub broadcaster: Option<HashMap<String, BroadcasterConfig>>,
@@ -226,6 +360,7 @@ pub struct ActorConfig {
     pub pools: Option<HashMap<String, PoolsConfig>>,
     pub noncebalance: Option<HashMap<String, BlockchainClientConfig>>,
     pub estimator: Option<HashMap<String, EstimatorConfig>>,
+    pub mev_share: Option<HashMap<String, MevShareConfig>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -237,6 +372,7 @@ pub struct TopologyConfig {
     pub signers: HashMap<String, SignersConfig>,
     pub encoders: HashMap<String, EncoderConfig>,
     pub preloaders: Option<HashMap<String, PreloaderConfig>>,
+    pub snapshots: Option<HashMap<String, SnapshotConfig>>,
     pub webserver: Option<WebserverConfig>,
     pub database: Option<DatabaseConfig>,
 }
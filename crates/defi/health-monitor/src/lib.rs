@@ -1,7 +1,11 @@
 mod pool_health_monitor;
 mod state_health_monitor;
 mod stuffing_tx_monitor;
+mod swap_simulator;
+mod token_screener;
 
 pub use pool_health_monitor::PoolHealthMonitorActor;
 pub use state_health_monitor::StateHealthMonitorActor;
 pub use stuffing_tx_monitor::StuffingTxMonitorActor;
+pub use swap_simulator::SwapSimulator;
+pub use token_screener::{TokenScreener, TokenScreenerActor};
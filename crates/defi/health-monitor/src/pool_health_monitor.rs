@@ -1,87 +1,252 @@
 use std::collections::HashMap;
 
-use alloy_primitives::Address;
-use eyre::Result;
+use alloy_primitives::{Address, U256};
+use eyre::{eyre, ErrReport, Result};
+use revm::primitives::Env;
+use revm::DatabaseRef;
 use tokio::sync::broadcast::error::RecvError;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
+use crate::swap_simulator::SwapSimulator;
 use loom_core_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, SharedState, WorkerResult};
 use loom_core_actors_macros::{Accessor, Consumer};
-use loom_core_blockchain::Blockchain;
-use loom_types_entities::Market;
-use loom_types_events::{HealthEvent, MessageHealthEvent};
+use loom_core_blockchain::{Blockchain, BlockchainState};
+use loom_evm_utils::evm_env::env_for_block;
+use loom_evm_utils::NWETH;
+use loom_execution_multicaller::DEFAULT_VIRTUAL_ADDRESS;
+use loom_types_entities::{Market, MarketState, PoolErrorReason, PoolQuarantine, PoolQuarantineStatus};
+use loom_types_events::{HealthEvent, MessageBlockHeader, MessageHealthEvent};
 
-pub async fn pool_health_monitor_worker(
+/// Swaps the pool in all directions. Priced tokens are swapped for the probe ETH value, unpriced tokens are swapped with
+/// the amount received from a previous direction. Pools without priced tokens can't be verified and stay quarantined.
+fn verify_pool<DB: DatabaseRef<Error = ErrReport>>(
+    simulator: &SwapSimulator,
+    probe_eth_amount: U256,
+    market: &Market,
+    db: &DB,
+    env: &Env,
+    pool_address: &Address,
+) -> Result<()> {
+    let pool = market.get_pool(pool_address).ok_or(eyre!("POOL_NOT_FOUND"))?;
+
+    let mut probe_amounts: HashMap<Address, U256> = HashMap::new();
+    for token_address in pool.get_tokens() {
+        if let Some(amount) = market.get_token_or_default(&token_address).calc_token_value_from_eth(probe_eth_amount) {
+            if !amount.is_zero() {
+                probe_amounts.insert(token_address, amount);
+            }
+        }
+    }
+    if probe_amounts.is_empty() {
+        return Err(eyre!("NO_PRICED_TOKENS"));
+    }
+
+    let (priced_directions, unpriced_directions): (Vec<(Address, Address)>, Vec<(Address, Address)>) =
+        pool.get_swap_directions().into_iter().partition(|(token_from, _)| probe_amounts.contains_key(token_from));
+
+    for (token_from, token_to) in priced_directions.into_iter().chain(unpriced_directions) {
+        let Some(amount_in) = probe_amounts.get(&token_from).cloned() else {
+            continue;
+        };
+        let amount_out = simulator.simulate_swap(
+            db,
+            env,
+            pool,
+            market.get_token_or_default(&token_from),
+            market.get_token_or_default(&token_to),
+            amount_in,
+        )?;
+        if amount_out.is_zero() {
+            return Err(eyre!("ZERO_OUT_AMOUNT"));
+        }
+        probe_amounts.entry(token_to).or_insert(amount_out);
+    }
+    Ok(())
+}
+
+pub async fn pool_health_monitor_worker<DB: DatabaseRef<Error = ErrReport> + Send + Sync + Clone + 'static>(
+    simulator: SwapSimulator,
+    probe_eth_amount: U256,
     market: SharedState<Market>,
+    market_state: SharedState<MarketState<DB>>,
+    pool_quarantine: SharedState<PoolQuarantine>,
     pool_health_monitor_rx: Broadcaster<MessageHealthEvent>,
+    block_header_rx: Broadcaster<MessageBlockHeader>,
 ) -> WorkerResult {
     subscribe!(pool_health_monitor_rx);
+    subscribe!(block_header_rx);
 
-    let mut pool_errors_map: HashMap<Address, u32> = HashMap::new();
+    let mut block_number: u64 = 0;
 
     loop {
         tokio::select! {
             msg = pool_health_monitor_rx.recv() => {
+                let pool_health_message = match msg {
+                    Ok(pool_health_message) => pool_health_message,
+                    Err(RecvError::Closed) => {
+                        error!("Pool health monitor channel closed");
+                        return Err(eyre!("POOL_HEALTH_MONITOR_RX_CLOSED"));
+                    }
+                    Err(RecvError::Lagged(lag)) => {
+                        warn!("Pool health monitor channel lagged: {}", lag);
+                        continue;
+                    }
+                };
 
-                let pool_health_update : Result<MessageHealthEvent, RecvError>  = msg;
-                match pool_health_update {
-                    Ok(pool_health_message)=>{
-                        if let HealthEvent::PoolSwapError(swap_error) = pool_health_message.inner {
-                            debug!("Pool health_monitor message update: {:?} {} {} ", swap_error.pool, swap_error.msg, swap_error.amount);
-                            let entry = pool_errors_map.entry(swap_error.pool).or_insert(0);
-                            *entry += 1;
-                            if *entry >= 10 {
-                                let start_time=std::time::Instant::now();
-                                let mut market_guard = market.write().await;
-                                debug!(elapsed = start_time.elapsed().as_micros(), "market_guard market.write acquired");
-
-                                market_guard.set_pool_disabled(swap_error.pool, true);
-                                match market_guard.get_pool(&swap_error.pool) {
-                                    Some(pool)=>{
-                                        info!("Disabling pool: protocol={}, address={:?}, msg={} amount={}", pool.get_protocol(),swap_error.pool, swap_error.msg, swap_error.amount);
-                                    }
-                                    _=>{
-                                        error!("Disabled pool missing in market: address={:?}, msg={} amount={}", swap_error.pool, swap_error.msg, swap_error.amount);
-                                    }
-                                }
-                                drop(market_guard);
-                                debug!(elapsed = start_time.elapsed().as_micros(), "market_guard market.write released");
+                if let HealthEvent::PoolSwapError(swap_error) = pool_health_message.inner {
+                    let reason = PoolErrorReason::from(&swap_error);
+                    debug!(pool = ?swap_error.pool, error = %swap_error.error, amount = %swap_error.amount, ?reason, "Pool swap error");
 
-                            }
+                    let mut pool_quarantine_guard = pool_quarantine.write().await;
+                    if !pool_quarantine_guard.record_error(swap_error.pool, reason, swap_error.error.to_string(), block_number) {
+                        continue;
+                    }
+                    let status = pool_quarantine_guard.quarantine(swap_error.pool, block_number);
+                    drop(pool_quarantine_guard);
+
+                    let start_time = std::time::Instant::now();
+                    let mut market_guard = market.write().await;
+                    debug!(elapsed = start_time.elapsed().as_micros(), "market_guard market.write acquired");
+
+                    market_guard.set_pool_disabled(swap_error.pool, true);
+                    match market_guard.get_pool(&swap_error.pool) {
+                        Some(pool) => {
+                            info!("Quarantining pool: protocol={}, address={:?}, status={:?}, error={}", pool.get_protocol(), swap_error.pool, status, swap_error.error);
                         }
+                        _ => {
+                            error!("Quarantined pool missing in market: address={:?}, error={}", swap_error.pool, swap_error.error);
+                        }
+                    }
+                    drop(market_guard);
+                    debug!(elapsed = start_time.elapsed().as_micros(), "market_guard market.write released");
+                }
+            }
+            msg = block_header_rx.recv() => {
+                let block_header = match msg {
+                    Ok(message_block_header) => message_block_header.inner,
+                    Err(RecvError::Closed) => {
+                        error!("Block header channel closed");
+                        return Err(eyre!("BLOCK_HEADER_RX_CLOSED"));
                     }
-                    Err(e)=>{
-                        error!("pool_health_update error {}", e)
+                    Err(RecvError::Lagged(lag)) => {
+                        info!("Block header channel lagged: {}", lag);
+                        continue;
                     }
+                };
+                block_number = block_header.header.number;
+
+                let expired = pool_quarantine.read().await.expired(block_number);
+                if expired.is_empty() {
+                    continue;
                 }
 
+                let env = env_for_block(block_header.next_block_number, block_header.next_block_timestamp);
+                let verified: Vec<(Address, Result<()>)> = {
+                    let market_guard = market.read().await;
+                    let market_state_guard = market_state.read().await;
+                    expired
+                        .into_iter()
+                        .map(|pool_address| {
+                            let state_db = &market_state_guard.state_db;
+                            (pool_address, verify_pool(&simulator, probe_eth_amount, &market_guard, state_db, &env, &pool_address))
+                        })
+                        .collect()
+                };
+
+                let mut pool_quarantine_guard = pool_quarantine.write().await;
+                let mut market_guard = market.write().await;
+                for (pool_address, result) in verified {
+                    match result {
+                        Ok(()) => {
+                            pool_quarantine_guard.release(&pool_address, block_number);
+                            market_guard.set_pool_disabled(pool_address, false);
+                            info!(%pool_address, "Pool verified and enabled");
+                        }
+                        Err(error) => {
+                            let status = pool_quarantine_guard.quarantine(pool_address, block_number);
+                            if status == PoolQuarantineStatus::Disabled {
+                                info!(%pool_address, %error, "Pool verification failed, pool disabled");
+                            } else {
+                                debug!(%pool_address, %error, ?status, "Pool verification failed");
+                            }
+                        }
+                    }
+                }
             }
         }
     }
 }
 
-#[derive(Accessor, Consumer, Default)]
-pub struct PoolHealthMonitorActor {
+/// Quarantines pools with too many swap errors, re-verifies them with a swap in evm when quarantine expires
+/// and disables pools that keep failing. Quarantine state is kept in [`PoolQuarantine`].
+#[derive(Accessor, Consumer)]
+pub struct PoolHealthMonitorActor<DB> {
+    simulator: SwapSimulator,
+    probe_eth_amount: U256,
     #[accessor]
     market: Option<SharedState<Market>>,
+    #[accessor]
+    market_state: Option<SharedState<MarketState<DB>>>,
+    #[accessor]
+    pool_quarantine: Option<SharedState<PoolQuarantine>>,
     #[consumer]
     pool_health_update_rx: Option<Broadcaster<MessageHealthEvent>>,
+    #[consumer]
+    block_header_rx: Option<Broadcaster<MessageBlockHeader>>,
 }
 
-impl PoolHealthMonitorActor {
+impl<DB> Default for PoolHealthMonitorActor<DB> {
+    fn default() -> Self {
+        Self {
+            simulator: SwapSimulator::new(DEFAULT_VIRTUAL_ADDRESS),
+            probe_eth_amount: NWETH::from_float(0.01),
+            market: None,
+            market_state: None,
+            pool_quarantine: None,
+            pool_health_update_rx: None,
+            block_header_rx: None,
+        }
+    }
+}
+
+impl<DB> PoolHealthMonitorActor<DB>
+where
+    DB: DatabaseRef<Error = ErrReport> + Send + Sync + Clone + 'static,
+{
     pub fn new() -> Self {
         PoolHealthMonitorActor::default()
     }
 
-    pub fn on_bc(self, bc: &Blockchain) -> Self {
-        Self { market: Some(bc.market()), pool_health_update_rx: Some(bc.pool_health_monitor_channel()) }
+    pub fn with_probe_eth_amount(self, probe_eth_amount: U256) -> Self {
+        Self { probe_eth_amount, ..self }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain, state: &BlockchainState<DB>) -> Self {
+        Self {
+            market: Some(bc.market()),
+            market_state: Some(state.market_state()),
+            pool_quarantine: Some(bc.pool_quarantine()),
+            pool_health_update_rx: Some(bc.pool_health_monitor_channel()),
+            block_header_rx: Some(bc.new_block_headers_channel()),
+            ..self
+        }
     }
 }
 
-impl Actor for PoolHealthMonitorActor {
+impl<DB> Actor for PoolHealthMonitorActor<DB>
+where
+    DB: DatabaseRef<Error = ErrReport> + Send + Sync + Clone + 'static,
+{
     fn start(&self) -> ActorResult {
-        let task =
-            tokio::task::spawn(pool_health_monitor_worker(self.market.clone().unwrap(), self.pool_health_update_rx.clone().unwrap()));
+        let task = tokio::task::spawn(pool_health_monitor_worker(
+            self.simulator.clone(),
+            self.probe_eth_amount,
+            self.market.clone().unwrap(),
+            self.market_state.clone().unwrap(),
+            self.pool_quarantine.clone().unwrap(),
+            self.pool_health_update_rx.clone().unwrap(),
+            self.block_header_rx.clone().unwrap(),
+        ));
         Ok(vec![task])
     }
 
@@ -1,23 +1,24 @@
 use alloy_network::{Ethereum, TransactionResponse};
-use alloy_primitives::{Address, TxHash, U256};
+use alloy_primitives::{keccak256, Address, BlockHash, BlockNumber, TxHash, U256};
 use alloy_provider::Provider;
 use alloy_transport::Transport;
 use eyre::{eyre, Result};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
 use std::sync::Arc;
 use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::broadcast::Receiver;
-use tracing::{error, info};
+use tracing::{debug, error, info};
 
 use loom_core_blockchain::Blockchain;
 use loom_evm_utils::NWETH;
+use loom_types_entities::bidding::{append_bid_outcome, AdaptiveBidder, BidOutcome, BidResult, OpportunityType};
 use loom_types_entities::{LatestBlock, Swap, Token};
 
 use loom_core_actors::{Accessor, Actor, ActorResult, Broadcaster, Consumer, SharedState, WorkerResult};
 use loom_core_actors_macros::{Accessor, Consumer};
 use loom_types_blockchain::debug_trace_transaction;
-use loom_types_events::{MarketEvents, MessageTxCompose, TxComposeMessageType};
+use loom_types_events::{MarketEvents, MempoolEvents, MessageRelayEvent, MessageTxCompose, RelayEvents, RlpState, TxComposeMessageType};
 
 #[derive(Clone, Debug)]
 struct TxToCheck {
@@ -26,13 +27,74 @@ struct TxToCheck {
     profit: U256,
     tips: U256,
     swap: Swap,
+    backrun_txs: HashSet<TxHash>,
+}
+
+impl TxToCheck {
+    /// Outcome of the bid for the adaptive bidder, None if the opportunity has no profit
+    fn bid_outcome(&self, block_number: BlockNumber, relays: Vec<String>, result: BidResult) -> Option<BidOutcome> {
+        if self.profit.is_zero() {
+            return None;
+        }
+        Some(BidOutcome {
+            block_number,
+            opportunity: OpportunityType::from_swap(&self.swap)?,
+            profit_eth: self.profit,
+            tips_pct: tips_pct(self.tips, self.profit),
+            relays,
+            result,
+        })
+    }
+}
+
+/// Blocks with found stuffing txs kept to check them again if the blocks are orphaned by a reorg
+const RECENT_BLOCKS: u64 = 64;
+
+struct MinedBlock {
+    block_number: BlockNumber,
+    parent_hash: BlockHash,
+    txs: Vec<(TxHash, TxToCheck)>,
+}
+
+/// Returns stuffing txs found in the orphaned branch of the reorg that are not mined in the new branch
+fn orphaned_txs_to_check(
+    mined_blocks: &HashMap<BlockHash, MinedBlock>,
+    depth: u64,
+    old_head: BlockHash,
+    new_head: BlockHash,
+) -> Vec<(TxHash, TxToCheck)> {
+    let mut orphaned: Vec<(TxHash, TxToCheck)> = Vec::new();
+    let mut ancestor = old_head;
+    for _ in 0..depth {
+        let Some(mined_block) = mined_blocks.get(&ancestor) else { break };
+        orphaned.extend(mined_block.txs.iter().cloned());
+        ancestor = mined_block.parent_hash;
+    }
+
+    let mut mined: HashSet<TxHash> = HashSet::new();
+    let mut block_hash = new_head;
+    while block_hash != ancestor {
+        let Some(mined_block) = mined_blocks.get(&block_hash) else { break };
+        mined.extend(mined_block.txs.iter().map(|(tx_hash, _)| *tx_hash));
+        block_hash = mined_block.parent_hash;
+    }
+
+    orphaned.into_iter().filter(|(tx_hash, _)| !mined.contains(tx_hash)).collect()
+}
+
+/// Payment in basis points of the profit
+fn tips_pct(tips: U256, profit: U256) -> u32 {
+    if profit.is_zero() {
+        return 0;
+    }
+    (tips.saturating_mul(U256::from(10000)) / profit).min(U256::from(10000)).to()
 }
 
 async fn check_mf_tx<P: Provider<T, Ethereum> + 'static, T: Transport + Clone>(
     client: P,
     tx_hash: TxHash,
     coinbase: Address,
-) -> Result<()> {
+) -> Result<U256> {
     let (pre, post) = debug_trace_transaction(client, tx_hash, true).await?;
 
     let coinbase_pre = pre.get(&coinbase).ok_or(eyre!("COINBASE_NOT_FOUND_IN_PRE"))?;
@@ -41,6 +103,34 @@ async fn check_mf_tx<P: Provider<T, Ethereum> + 'static, T: Transport + Clone>(
     let balance_diff = coinbase_post.balance.unwrap_or_default().checked_sub(coinbase_pre.balance.unwrap_or_default()).unwrap_or_default();
     info!("Stuffing tx mined MF tx: {:?} sent to coinbase: {}", tx_hash, NWETH::to_float(balance_diff));
 
+    Ok(balance_diff)
+}
+
+/// Records the outcome to the bidder and appends it to the history file of the bidder
+async fn record_bid(bidder: &SharedState<AdaptiveBidder>, outcome: BidOutcome) {
+    let history_path = {
+        let mut bidder_guard = bidder.write().await;
+        bidder_guard.record(outcome.clone());
+        bidder_guard.config().history_path.clone()
+    };
+    if let Some(history_path) = history_path {
+        if let Err(error) = append_bid_outcome(&history_path, &outcome).await {
+            error!(%error, path = %history_path.display(), "Bid outcome is not written");
+        }
+    }
+}
+
+/// Records the missed bid with the coinbase payment of the MF tx as the competitor tips
+async fn record_missed_bid<P: Provider<T, Ethereum> + 'static, T: Transport + Clone>(
+    client: P,
+    bidder: SharedState<AdaptiveBidder>,
+    mf_tx_hash: TxHash,
+    coinbase: Address,
+    outcome: BidOutcome,
+) -> Result<()> {
+    let competitor_tips_pct = check_mf_tx(client, mf_tx_hash, coinbase).await.ok().map(|paid| tips_pct(paid, outcome.profit_eth));
+    debug!(block_number = outcome.block_number, tips_pct = outcome.tips_pct, ?competitor_tips_pct, "Bid missed");
+    record_bid(&bidder, BidOutcome { result: BidResult::Missed { competitor_tips_pct }, ..outcome }).await;
     Ok(())
 }
 
@@ -49,11 +139,25 @@ pub async fn stuffing_tx_monitor_worker<P: Provider<T, Ethereum> + Clone + 'stat
     latest_block: SharedState<LatestBlock>,
     tx_compose_channel_rx: Broadcaster<MessageTxCompose>,
     market_events_rx: Broadcaster<MarketEvents>,
+    relay_events_rx: Option<Broadcaster<MessageRelayEvent>>,
+    mempool_events_rx: Option<Broadcaster<MempoolEvents>>,
+    bidder: Option<SharedState<AdaptiveBidder>>,
 ) -> WorkerResult {
     let mut tx_compose_channel_rx: Receiver<MessageTxCompose> = tx_compose_channel_rx.subscribe().await;
     let mut market_events_rx: Receiver<MarketEvents> = market_events_rx.subscribe().await;
+    let mut relay_events_rx: Option<Receiver<MessageRelayEvent>> = match relay_events_rx {
+        Some(relay_events_rx) => Some(relay_events_rx.subscribe().await),
+        None => None,
+    };
+    let mut mempool_events_rx: Option<Receiver<MempoolEvents>> = match mempool_events_rx {
+        Some(mempool_events_rx) => Some(mempool_events_rx.subscribe().await),
+        None => None,
+    };
 
     let mut txs_to_check: HashMap<TxHash, TxToCheck> = HashMap::new();
+    // relays that accepted bundles for the block
+    let mut block_relays: HashMap<BlockNumber, HashSet<String>> = HashMap::new();
+    let mut mined_blocks: HashMap<BlockHash, MinedBlock> = HashMap::new();
 
     loop {
         tokio::select! {
@@ -61,24 +165,48 @@ pub async fn stuffing_tx_monitor_worker<P: Provider<T, Ethereum> + Clone + 'stat
                 let market_event_msg : Result<MarketEvents, RecvError> = msg;
                 match market_event_msg {
                     Ok(market_event)=>{
-                        if let MarketEvents::BlockTxUpdate{ block_number,..} = market_event {
+                        if let MarketEvents::Reorg{ depth, old_head, new_head } = market_event {
+                            // stuffing txs of orphaned blocks are checked again, bid outcomes recorded for them stay
+                            let orphaned_txs = orphaned_txs_to_check(&mined_blocks, depth, old_head, new_head);
+                            info!("Stuffing txs of orphaned blocks to check again : {} reorg depth {}", orphaned_txs.len(), depth);
+                            txs_to_check.extend(orphaned_txs);
+                        }
+                        if let MarketEvents::BlockTxUpdate{ block_number, block_hash } = market_event {
                             let coinbase =  latest_block.read().await.coinbase().unwrap_or_default();
+                            let parent_hash = latest_block.read().await.parent_hash().unwrap_or_default();
+                            let mined_block = mined_blocks.entry(block_hash).or_insert(MinedBlock{ block_number, parent_hash, txs : Vec::new() });
                             if let Some(txs) = latest_block.read().await.txs().cloned() {
                                 for (idx, tx) in txs.iter().enumerate() {
                                     let tx_hash = tx.tx_hash();
                                     if let Some(tx_to_check) = txs_to_check.get(&tx_hash).cloned(){
                                         info!("Stuffing tx found mined {:?} block: {} -> {} idx: {} profit: {} tips: {} token: {} to: {:?} {}", tx.tx_hash(), tx_to_check.block, block_number, idx, NWETH::to_float(tx_to_check.profit), NWETH::to_float(tx_to_check.tips), tx_to_check.token_in.get_symbol(), tx.to().unwrap_or_default(), tx_to_check.swap );
-                                        if idx < txs.len() - 1 {
-                                            let mf_tx = &txs[idx+1];
-                                            info!("Stuffing tx mined {:?} MF tx: {:?} to: {:?}", tx.tx_hash(), mf_tx.tx_hash(), mf_tx.to().unwrap_or_default() );
-                                            tokio::task::spawn(
-                                                check_mf_tx(client.clone(), mf_tx.tx_hash(), coinbase)
-                                            );
+                                        let mf_tx = txs.get(idx + 1);
+                                        let landed = mf_tx.is_some_and(|mf_tx| tx_to_check.backrun_txs.contains(&mf_tx.tx_hash()));
+                                        let result = if landed { BidResult::Landed } else { BidResult::Missed { competitor_tips_pct: None } };
+                                        let relays : Vec<String> = block_relays.get(&block_number).map(|r| r.iter().cloned().collect()).unwrap_or_default();
+                                        let outcome = bidder.as_ref().and_then(|_| tx_to_check.bid_outcome(block_number, relays, result));
+                                        if let Some(mf_tx) = mf_tx {
+                                            info!("Stuffing tx mined {:?} MF tx: {:?} to: {:?} landed: {}", tx.tx_hash(), mf_tx.tx_hash(), mf_tx.to().unwrap_or_default(), landed);
+                                            match (bidder.as_ref(), outcome) {
+                                                (Some(bidder), Some(outcome)) if !landed => {
+                                                    let bidder = bidder.clone();
+                                                    tokio::task::spawn(record_missed_bid(client.clone(), bidder, mf_tx.tx_hash(), coinbase, outcome));
+                                                }
+                                                (Some(bidder), Some(outcome)) => record_bid(bidder, outcome).await,
+                                                _ => {
+                                                    tokio::task::spawn(check_mf_tx(client.clone(), mf_tx.tx_hash(), coinbase));
+                                                }
+                                            }
+                                        } else if let (Some(bidder), Some(outcome)) = (bidder.as_ref(), outcome) {
+                                            record_bid(bidder, outcome).await;
                                         }
+                                        mined_block.txs.push((tx.tx_hash(), tx_to_check));
                                         txs_to_check.remove::<TxHash>(&tx.tx_hash());
                                     }
                                 }
                             }
+                            block_relays.retain(|relays_block, _| *relays_block + 10 > block_number);
+                            mined_blocks.retain(|_, mined_block| mined_block.block_number + RECENT_BLOCKS > block_number);
                             info!("Stuffing txs to check : {} at block {}", txs_to_check.len(), block_number)
                         }
                     }
@@ -108,6 +236,7 @@ pub async fn stuffing_tx_monitor_worker<P: Provider<T, Ethereum> + Clone + 'stat
                                                 profit : U256::ZERO,
                                                 tips : U256::ZERO,
                                                 swap : swap.clone(),
+                                                backrun_txs : HashSet::new(),
                                         }
                                 );
                                 let profit = swap.abs_profit();
@@ -120,6 +249,17 @@ pub async fn stuffing_tx_monitor_worker<P: Provider<T, Ethereum> + Clone + 'stat
                                     entry.swap = swap.clone();
                                 }
                             }
+                        } else if let TxComposeMessageType::Broadcast(tx_compose_data) = tx_compose_msg.inner {
+                            // signed backrun txs to find out if the bid landed
+                            let backrun_txs : Vec<TxHash> = tx_compose_data.rlp_bundle.iter().flatten().filter_map(|rlp| match rlp {
+                                RlpState::Backrun(rlp) if !rlp.is_empty() => Some(keccak256(rlp)),
+                                _ => None,
+                            }).collect();
+                            for stuffing_tx_hash in tx_compose_data.stuffing_txs_hashes.iter() {
+                                if let Some(entry) = txs_to_check.get_mut(stuffing_tx_hash) {
+                                    entry.backrun_txs.extend(backrun_txs.iter().cloned());
+                                }
+                            }
                         }
                     }
                     Err(e)=>{
@@ -127,10 +267,45 @@ pub async fn stuffing_tx_monitor_worker<P: Provider<T, Ethereum> + Clone + 'stat
                     }
                 }
             }
+
+            msg = async { relay_events_rx.as_mut().unwrap().recv().await }, if relay_events_rx.is_some() => {
+                let relay_event : Result<MessageRelayEvent, RecvError> = msg;
+                match relay_event {
+                    Ok(relay_event) => {
+                        if let RelayEvents::BundleResult(result) = relay_event.inner {
+                            if result.error.is_none() {
+                                block_relays.entry(result.target_block).or_default().insert(result.relay_name);
+                            }
+                        }
+                    }
+                    Err(e)=>{
+                        error!("relay_events_rx : {e}")
+                    }
+                }
+            }
+
+            msg = async { mempool_events_rx.as_mut().unwrap().recv().await }, if mempool_events_rx.is_some() => {
+                let mempool_event : Result<MempoolEvents, RecvError> = msg;
+                match mempool_event {
+                    Ok(MempoolEvents::MempoolTxReplaced{ tx_hash, replaced_by }) => {
+                        // bundles with the replaced stuffing tx can't land
+                        if let Some(tx_to_check) = txs_to_check.remove(&tx_hash) {
+                            info!("Stuffing tx replaced {:?} by {:?} block: {} profit: {}", tx_hash, replaced_by, tx_to_check.block, NWETH::to_float(tx_to_check.profit));
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e)=>{
+                        error!("mempool_events_rx : {e}")
+                    }
+                }
+            }
         }
     }
 }
 
+/// Monitors stuffing txs of sent bundles. When a stuffing tx is mined, the outcome of the bid is recorded to the
+/// [`AdaptiveBidder`] if it is accessed: landed if the next tx is our backrun, missed with the coinbase payment of the next tx otherwise.
+/// Stuffing txs replaced in the mempool are not checked anymore.
 #[derive(Accessor, Consumer)]
 pub struct StuffingTxMonitorActor<P, T> {
     client: P,
@@ -140,12 +315,27 @@ pub struct StuffingTxMonitorActor<P, T> {
     tx_compose_channel_rx: Option<Broadcaster<MessageTxCompose>>,
     #[consumer]
     market_events_rx: Option<Broadcaster<MarketEvents>>,
+    #[consumer]
+    relay_events_rx: Option<Broadcaster<MessageRelayEvent>>,
+    #[consumer]
+    mempool_events_rx: Option<Broadcaster<MempoolEvents>>,
+    #[accessor]
+    bidder: Option<SharedState<AdaptiveBidder>>,
     _t: PhantomData<T>,
 }
 
 impl<P: Provider<T, Ethereum> + Send + Sync + Clone + 'static, T: Transport + Clone> StuffingTxMonitorActor<P, T> {
     pub fn new(client: P) -> Self {
-        StuffingTxMonitorActor { client, latest_block: None, tx_compose_channel_rx: None, market_events_rx: None, _t: PhantomData }
+        StuffingTxMonitorActor {
+            client,
+            latest_block: None,
+            tx_compose_channel_rx: None,
+            market_events_rx: None,
+            relay_events_rx: None,
+            mempool_events_rx: None,
+            bidder: None,
+            _t: PhantomData,
+        }
     }
 
     pub fn on_bc(self, bc: &Blockchain) -> Self {
@@ -153,6 +343,8 @@ impl<P: Provider<T, Ethereum> + Send + Sync + Clone + 'static, T: Transport + Cl
             latest_block: Some(bc.latest_block()),
             tx_compose_channel_rx: Some(bc.tx_compose_channel()),
             market_events_rx: Some(bc.market_events_channel()),
+            relay_events_rx: Some(bc.relay_events_channel()),
+            mempool_events_rx: Some(bc.mempool_events_channel()),
             ..self
         }
     }
@@ -169,6 +361,9 @@ where
             self.latest_block.clone().unwrap(),
             self.tx_compose_channel_rx.clone().unwrap(),
             self.market_events_rx.clone().unwrap(),
+            self.relay_events_rx.clone(),
+            self.mempool_events_rx.clone(),
+            self.bidder.clone(),
         ));
         Ok(vec![task])
     }
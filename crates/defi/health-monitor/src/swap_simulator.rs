@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use alloy_primitives::{Address, Bytes, U256};
+use eyre::{eyre, ErrReport, Result};
+use revm::db::CacheDB;
+use revm::primitives::{Env, TransactTo, CANCUN};
+use revm::{DatabaseRef, Evm};
+
+use loom_evm_utils::evm::{evm_call, evm_transact};
+use loom_evm_utils::BalanceCheater;
+use loom_execution_multicaller::{EncoderHelper, MulticallerDeployer, SwapStepEncoder};
+use loom_types_entities::{PoolWrapper, SwapAmountType, SwapLine, SwapPath, Token};
+
+/// Executes swaps encoded by the multicaller encoders in evm on top of the state db.
+/// Multicaller code is set at the multicaller address, so any address can be used.
+#[derive(Clone)]
+pub struct SwapSimulator {
+    encoder: SwapStepEncoder,
+}
+
+impl SwapSimulator {
+    pub fn new(multicaller: Address) -> Self {
+        Self { encoder: SwapStepEncoder::new(multicaller) }
+    }
+
+    pub fn multicaller(&self) -> Address {
+        self.encoder.get_contract_address()
+    }
+
+    pub fn multicaller_db<'a, DB: DatabaseRef<Error = ErrReport>>(&self, db: &'a DB) -> CacheDB<&'a DB> {
+        MulticallerDeployer::new().multicaller_db(db, self.multicaller())
+    }
+
+    /// Executes the call committing changes to `db`
+    pub fn transact<DB: DatabaseRef<Error = ErrReport>>(
+        db: &mut CacheDB<&DB>,
+        env: &Env,
+        caller: Address,
+        to: Address,
+        data: Bytes,
+    ) -> Result<()> {
+        let mut env = env.clone();
+        env.tx.caller = caller;
+        env.tx.transact_to = TransactTo::Call(to);
+        env.tx.data = data;
+
+        let mut evm = Evm::builder().with_spec_id(CANCUN).with_db(db).with_env(Box::new(env)).build();
+        evm_transact(&mut evm)?;
+        Ok(())
+    }
+
+    pub fn balance_of<DB: DatabaseRef<Error = ErrReport>>(db: &CacheDB<&DB>, env: &Env, token: Address, owner: Address) -> Result<U256> {
+        let (output, _) = evm_call(db, env.clone(), token, EncoderHelper::encode_erc20_balance_of(owner).to_vec())?;
+        if output.len() < 32 {
+            return Err(eyre!("BAD_BALANCE_OUTPUT"));
+        }
+        Ok(U256::from_be_slice(&output[0..32]))
+    }
+
+    /// Returns calculated amount out and the multicaller call of the swap funded by the multicaller balance
+    pub fn encode_swap<DB: DatabaseRef<Error = ErrReport>>(
+        &self,
+        db: &CacheDB<&DB>,
+        env: &Env,
+        pool: &PoolWrapper,
+        token_from: Arc<Token>,
+        token_to: Arc<Token>,
+        amount_in: U256,
+    ) -> Result<(U256, Address, Bytes)> {
+        let mut swap_line = SwapLine {
+            path: SwapPath::new(vec![token_from, token_to], vec![pool.clone()]),
+            amount_in: SwapAmountType::Set(amount_in),
+            ..SwapLine::default()
+        };
+        let (amount_out, _, _) = swap_line.calculate_with_in_amount(db, env.clone(), amount_in)?;
+        swap_line.amount_out = SwapAmountType::Set(amount_out);
+
+        let multicaller = self.multicaller();
+        let opcodes = self.encoder.swap_line_encoder.encode_swap_line_in_amount(&swap_line, multicaller, multicaller)?;
+        let (to, call_data) = self.encoder.to_call_data(&opcodes)?;
+        Ok((amount_out, to, call_data))
+    }
+
+    /// Swaps `amount_in` of `token_from` set as the multicaller balance and returns the received amount of `token_to`
+    pub fn simulate_swap<DB: DatabaseRef<Error = ErrReport>>(
+        &self,
+        db: &DB,
+        env: &Env,
+        pool: &PoolWrapper,
+        token_from: Arc<Token>,
+        token_to: Arc<Token>,
+        amount_in: U256,
+    ) -> Result<U256> {
+        let multicaller = self.multicaller();
+        let mut cache_db = self.multicaller_db(db);
+        let balance_cell = BalanceCheater::get_balance_cell_db(db, token_from.get_address(), multicaller)?;
+        cache_db.insert_account_storage(token_from.get_address(), balance_cell, amount_in)?;
+
+        let balance_before = Self::balance_of(&cache_db, env, token_to.get_address(), multicaller)?;
+        let (_, to, call_data) = self.encode_swap(&cache_db, env, pool, token_from, token_to.clone(), amount_in)?;
+        Self::transact(&mut cache_db, env, env.tx.caller, to, call_data)?;
+        let balance_after = Self::balance_of(&cache_db, env, token_to.get_address(), multicaller)?;
+
+        Ok(balance_after.saturating_sub(balance_before))
+    }
+}
@@ -0,0 +1,434 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use alloy_primitives::{Address, U256};
+use eyre::{eyre, ErrReport, Result};
+use revm::primitives::Env;
+use revm::DatabaseRef;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error, info};
+
+use crate::swap_simulator::SwapSimulator;
+use loom_core_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, SharedState, WorkerResult};
+use loom_core_actors_macros::{Accessor, Consumer};
+use loom_core_blockchain::{Blockchain, BlockchainState};
+use loom_evm_utils::evm_env::env_for_block;
+use loom_evm_utils::{BalanceCheater, NWETH};
+use loom_execution_multicaller::EncoderHelper;
+use loom_types_entities::{Market, MarketState, PoolWrapper, Token, TokenTransferClass};
+use loom_types_events::MessageBlockHeader;
+
+/// Tokens screened per block
+const DEFAULT_BATCH_SIZE: usize = 10;
+/// Tokens that failed screening are retried after this number of blocks, about 1 hour
+const DEFAULT_RETRY_BLOCKS: u64 = 300;
+/// Tokens with reverting screening are retried after this number of blocks
+const DEFAULT_REVERT_RETRY_BLOCKS: u64 = 10;
+/// Reverting screenings in a row classifying the token as untradeable
+const DEFAULT_UNTRADEABLE_REVERTS: u32 = 3;
+
+const PROBE_HOLDER_A: Address = Address::repeat_byte(0xA1);
+const PROBE_HOLDER_B: Address = Address::repeat_byte(0xB2);
+
+/// Classifies transfer behaviour of tokens in evm. The token is bought from a WETH pool with the multicaller,
+/// the bought amount is transferred between two holders and back, and finally sold to the pool.
+/// Reverts classify the token as untradeable, amounts received below expected as fee-on-transfer.
+/// A revert may be caused by the pool state, so the worker requires several reverts in a row before storing it.
+#[derive(Clone)]
+pub struct TokenScreener {
+    simulator: SwapSimulator,
+    probe_eth_amount: U256,
+}
+
+impl TokenScreener {
+    pub fn new(multicaller: Address, probe_eth_amount: U256) -> Self {
+        Self { simulator: SwapSimulator::new(multicaller), probe_eth_amount }
+    }
+
+    pub fn multicaller(&self) -> Address {
+        self.simulator.multicaller()
+    }
+
+    /// Fee in basis points taken from `expected` amount
+    fn fee_bps(expected: U256, received: U256) -> u32 {
+        if expected.is_zero() || received >= expected {
+            return 0;
+        }
+        let fee: u64 = ((expected - received) * U256::from(10000) / expected).to();
+        fee as u32
+    }
+
+    /// Screens the token swapping it with `pool` against `weth`. Errors are returned if the token can't be screened,
+    /// e.g. the balance slot of the token is not found or the pool can't calculate the swap.
+    pub fn screen<DB: DatabaseRef<Error = ErrReport>>(
+        &self,
+        db: &DB,
+        env: Env,
+        pool: &PoolWrapper,
+        weth: Arc<Token>,
+        token: Arc<Token>,
+    ) -> Result<TokenTransferClass> {
+        let multicaller = self.multicaller();
+        let token_address = token.get_address();
+
+        let mut cache_db = self.simulator.multicaller_db(db);
+        let weth_cell = BalanceCheater::get_balance_cell_db(db, weth.get_address(), multicaller)?;
+        cache_db.insert_account_storage(weth.get_address(), weth_cell, self.probe_eth_amount)?;
+
+        // buy
+        let (expected_amount, to, call_data) =
+            self.simulator.encode_swap(&cache_db, &env, pool, weth.clone(), token.clone(), self.probe_eth_amount)?;
+        if SwapSimulator::transact(&mut cache_db, &env, env.tx.caller, to, call_data).is_err() {
+            return Ok(TokenTransferClass::Untradeable);
+        }
+        let bought_amount = SwapSimulator::balance_of(&cache_db, &env, token_address, multicaller)?;
+        if bought_amount.is_zero() {
+            return Ok(TokenTransferClass::Untradeable);
+        }
+        let mut fee_bps = Self::fee_bps(expected_amount, bought_amount);
+
+        // transfer round trip
+        let holder_cell = BalanceCheater::get_balance_cell_db(db, token_address, PROBE_HOLDER_A)?;
+        cache_db.insert_account_storage(token_address, holder_cell, bought_amount)?;
+        let transfer_call_data = EncoderHelper::encode_erc20_transfer(PROBE_HOLDER_B, bought_amount);
+        if SwapSimulator::transact(&mut cache_db, &env, PROBE_HOLDER_A, token_address, transfer_call_data).is_err() {
+            return Ok(TokenTransferClass::Untradeable);
+        }
+        let received_b = SwapSimulator::balance_of(&cache_db, &env, token_address, PROBE_HOLDER_B)?;
+        fee_bps = fee_bps.max(Self::fee_bps(bought_amount, received_b));
+
+        let balance_a = SwapSimulator::balance_of(&cache_db, &env, token_address, PROBE_HOLDER_A)?;
+        let transfer_call_data = EncoderHelper::encode_erc20_transfer(PROBE_HOLDER_A, received_b);
+        if SwapSimulator::transact(&mut cache_db, &env, PROBE_HOLDER_B, token_address, transfer_call_data).is_err() {
+            return Ok(TokenTransferClass::Untradeable);
+        }
+        let received_a = SwapSimulator::balance_of(&cache_db, &env, token_address, PROBE_HOLDER_A)?.saturating_sub(balance_a);
+        fee_bps = fee_bps.max(Self::fee_bps(received_b, received_a));
+
+        if fee_bps > 0 {
+            return Ok(TokenTransferClass::FeeOnTransfer(fee_bps));
+        }
+
+        // sell
+        let (_, to, call_data) = self.simulator.encode_swap(&cache_db, &env, pool, token, weth, bought_amount)?;
+        if SwapSimulator::transact(&mut cache_db, &env, env.tx.caller, to, call_data).is_err() {
+            return Ok(TokenTransferClass::Untradeable);
+        }
+
+        Ok(TokenTransferClass::Normal)
+    }
+}
+
+/// Delays of the next screening of tokens that could not be classified yet
+#[derive(Clone)]
+pub(crate) struct ScreeningRetries {
+    retry_blocks: u64,
+    revert_retry_blocks: u64,
+    untradeable_reverts: u32,
+    // token_address -> block_number of failed screening
+    failed: HashMap<Address, u64>,
+    // token_address -> (reverts, block_number of the last revert)
+    reverted: HashMap<Address, (u32, u64)>,
+}
+
+impl ScreeningRetries {
+    fn new(retry_blocks: u64, revert_retry_blocks: u64, untradeable_reverts: u32) -> Self {
+        Self { retry_blocks, revert_retry_blocks, untradeable_reverts, failed: HashMap::new(), reverted: HashMap::new() }
+    }
+
+    fn is_delayed(&self, token_address: &Address, block_number: u64) -> bool {
+        self.failed.get(token_address).is_some_and(|failed_block| failed_block + self.retry_blocks > block_number)
+            || self.reverted.get(token_address).is_some_and(|(_, reverted_block)| reverted_block + self.revert_retry_blocks > block_number)
+    }
+
+    fn on_failed(&mut self, token_address: Address, block_number: u64) {
+        self.failed.insert(token_address, block_number);
+    }
+
+    /// Returns true if the screening of the token reverted `untradeable_reverts` times in a row
+    fn on_reverted(&mut self, token_address: Address, block_number: u64) -> bool {
+        let reverts = self.reverted.get(&token_address).map_or(0, |(reverts, _)| *reverts) + 1;
+        if reverts >= self.untradeable_reverts {
+            self.reverted.remove(&token_address);
+            return true;
+        }
+        self.reverted.insert(token_address, (reverts, block_number));
+        false
+    }
+
+    fn on_screened(&mut self, token_address: &Address) {
+        self.failed.remove(token_address);
+        self.reverted.remove(token_address);
+    }
+}
+
+pub async fn token_screener_worker<DB: DatabaseRef<Error = ErrReport> + Send + Sync + Clone + 'static>(
+    screener: TokenScreener,
+    batch_size: usize,
+    mut retries: ScreeningRetries,
+    market: SharedState<Market>,
+    market_state: SharedState<MarketState<DB>>,
+    block_header_rx: Broadcaster<MessageBlockHeader>,
+) -> WorkerResult {
+    subscribe!(block_header_rx);
+
+    loop {
+        let block_header = match block_header_rx.recv().await {
+            Ok(message_block_header) => message_block_header.inner,
+            Err(e) => match e {
+                RecvError::Closed => {
+                    error!("Block header channel closed");
+                    return Err(eyre!("BLOCK_HEADER_RX_CLOSED"));
+                }
+                RecvError::Lagged(lag) => {
+                    info!("Block header channel lagged: {}", lag);
+                    continue;
+                }
+            },
+        };
+        let block_number = block_header.header.number;
+
+        let (weth, tokens): (Arc<Token>, Vec<(Arc<Token>, PoolWrapper)>) = {
+            let market_guard = market.read().await;
+            let mut tokens = Vec::new();
+            for (token_address, token) in market_guard.tokens().iter() {
+                if tokens.len() >= batch_size {
+                    break;
+                }
+                if token.get_transfer_class() != TokenTransferClass::Unknown {
+                    continue;
+                }
+                if market_guard.is_basic_token(token_address) {
+                    token.set_transfer_class(TokenTransferClass::Normal);
+                    continue;
+                }
+                if retries.is_delayed(token_address, block_number) {
+                    continue;
+                }
+                let pool = market_guard
+                    .get_token_token_pools(&NWETH::ADDRESS, token_address)
+                    .unwrap_or_default()
+                    .iter()
+                    .filter(|pool_address| !market_guard.is_pool_disabled(pool_address))
+                    .find_map(|pool_address| market_guard.get_pool(pool_address).cloned());
+                match pool {
+                    Some(pool) => tokens.push((token.clone(), pool)),
+                    None => retries.on_failed(*token_address, block_number),
+                }
+            }
+            (market_guard.get_token_or_default(&NWETH::ADDRESS), tokens)
+        };
+
+        if tokens.is_empty() {
+            continue;
+        }
+
+        let env = env_for_block(block_header.next_block_number, block_header.next_block_timestamp);
+        let state_db = market_state.read().await.state_db.clone();
+
+        let screener = screener.clone();
+        let screened = match tokio::task::spawn_blocking(move || {
+            tokens
+                .into_iter()
+                .map(|(token, pool)| {
+                    let result = screener.screen(&state_db, env.clone(), &pool, weth.clone(), token.clone());
+                    (token, pool, result)
+                })
+                .collect::<Vec<_>>()
+        })
+        .await
+        {
+            Ok(screened) => screened,
+            Err(error) => {
+                error!(%error, "Token screening failed");
+                continue;
+            }
+        };
+
+        for (token, pool, result) in screened {
+            match result {
+                Ok(TokenTransferClass::Untradeable) => {
+                    if retries.on_reverted(token.get_address(), block_number) {
+                        info!(token = %token.get_address(), symbol = %token.get_symbol(), "Token screened as untradeable");
+                        token.set_transfer_class(TokenTransferClass::Untradeable);
+                    } else {
+                        debug!(token = %token.get_address(), pool = %pool.get_address(), "Token screening reverted");
+                    }
+                }
+                Ok(transfer_class) => {
+                    if transfer_class != TokenTransferClass::Normal {
+                        info!(token = %token.get_address(), symbol = %token.get_symbol(), ?transfer_class, "Token screened");
+                    }
+                    retries.on_screened(&token.get_address());
+                    token.set_transfer_class(transfer_class);
+                }
+                Err(error) => {
+                    debug!(%error, token = %token.get_address(), pool = %pool.get_address(), "Token screening failed");
+                    retries.on_failed(token.get_address(), block_number);
+                }
+            }
+        }
+    }
+}
+
+/// Screens new tokens of the market for fee-on-transfer and blocked transfers and stores the result on [`Token`].
+/// Swap paths through tokens that are not transfer safe are skipped.
+#[derive(Accessor, Consumer)]
+pub struct TokenScreenerActor<DB> {
+    screener: TokenScreener,
+    batch_size: usize,
+    retries: ScreeningRetries,
+    #[accessor]
+    market: Option<SharedState<Market>>,
+    #[accessor]
+    market_state: Option<SharedState<MarketState<DB>>>,
+    #[consumer]
+    block_header_rx: Option<Broadcaster<MessageBlockHeader>>,
+}
+
+impl<DB> TokenScreenerActor<DB>
+where
+    DB: DatabaseRef<Error = ErrReport> + Send + Sync + Clone + 'static,
+{
+    pub fn new(multicaller_address: Address) -> Self {
+        Self {
+            screener: TokenScreener::new(multicaller_address, NWETH::from_float(0.01)),
+            batch_size: DEFAULT_BATCH_SIZE,
+            retries: ScreeningRetries::new(DEFAULT_RETRY_BLOCKS, DEFAULT_REVERT_RETRY_BLOCKS, DEFAULT_UNTRADEABLE_REVERTS),
+            market: None,
+            market_state: None,
+            block_header_rx: None,
+        }
+    }
+
+    pub fn with_probe_eth_amount(self, probe_eth_amount: U256) -> Self {
+        Self { screener: TokenScreener::new(self.screener.multicaller(), probe_eth_amount), ..self }
+    }
+
+    pub fn with_batch_size(self, batch_size: usize) -> Self {
+        Self { batch_size, ..self }
+    }
+
+    pub fn with_retry_blocks(self, retry_blocks: u64) -> Self {
+        Self { retries: ScreeningRetries { retry_blocks, ..self.retries }, ..self }
+    }
+
+    /// Number of reverting screenings in a row, `revert_retry_blocks` apart, classifying the token as untradeable
+    pub fn with_untradeable_reverts(self, untradeable_reverts: u32, revert_retry_blocks: u64) -> Self {
+        Self { retries: ScreeningRetries { untradeable_reverts, revert_retry_blocks, ..self.retries }, ..self }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain, state: &BlockchainState<DB>) -> Self {
+        Self {
+            market: Some(bc.market()),
+            market_state: Some(state.market_state()),
+            block_header_rx: Some(bc.new_block_headers_channel()),
+            ..self
+        }
+    }
+}
+
+impl<DB> Actor for TokenScreenerActor<DB>
+where
+    DB: DatabaseRef<Error = ErrReport> + Send + Sync + Clone + 'static,
+{
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(token_screener_worker(
+            self.screener.clone(),
+            self.batch_size,
+            self.retries.clone(),
+            self.market.clone().unwrap(),
+            self.market_state.clone().unwrap(),
+            self.block_header_rx.clone().unwrap(),
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "TokenScreenerActor"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_primitives::Bytes;
+    use loom_defi_pools::UniswapV2Pool;
+    use loom_evm_db::LoomDB;
+    use revm::primitives::{AccountInfo, Bytecode};
+
+    #[test]
+    fn test_fee_bps() {
+        assert_eq!(TokenScreener::fee_bps(U256::from(10000), U256::from(10000)), 0);
+        assert_eq!(TokenScreener::fee_bps(U256::from(10000), U256::from(10001)), 0);
+        assert_eq!(TokenScreener::fee_bps(U256::from(10000), U256::from(9700)), 300);
+        assert_eq!(TokenScreener::fee_bps(U256::from(1_000_000), U256::from(999_999)), 0);
+        assert_eq!(TokenScreener::fee_bps(U256::ZERO, U256::ZERO), 0);
+    }
+
+    #[test]
+    fn test_screening_retries() {
+        let token_address = Address::repeat_byte(1);
+        let mut retries = ScreeningRetries::new(300, 10, 3);
+
+        assert!(!retries.on_reverted(token_address, 100));
+        assert!(retries.is_delayed(&token_address, 109));
+        assert!(!retries.is_delayed(&token_address, 110));
+        assert!(!retries.on_reverted(token_address, 110));
+
+        // successful screening resets reverts
+        retries.on_screened(&token_address);
+        assert!(!retries.is_delayed(&token_address, 110));
+        assert!(!retries.on_reverted(token_address, 120));
+        assert!(!retries.on_reverted(token_address, 130));
+        assert!(retries.on_reverted(token_address, 140));
+
+        retries.on_failed(token_address, 150);
+        assert!(retries.is_delayed(&token_address, 449));
+        assert!(!retries.is_delayed(&token_address, 450));
+    }
+
+    // balanceOf(owner) returning sload(keccak256(owner . 3)) for any call
+    const TOKEN_CODE: [u8; 25] = [
+        0x60, 0x04, 0x35, 0x60, 0x00, 0x52, 0x60, 0x03, 0x60, 0x20, 0x52, 0x60, 0x40, 0x60, 0x00, 0x20, 0x54, 0x60, 0x00, 0x52, 0x60, 0x20,
+        0x60, 0x00, 0xf3,
+    ];
+    // getReserves() returning 1 ether reserves, reverts any other call
+    const POOL_CODE: [u8; 49] = [
+        0x60, 0x00, 0x35, 0x60, 0xe0, 0x1c, 0x63, 0x09, 0x02, 0xf1, 0xac, 0x14, 0x60, 0x13, 0x57, 0x60, 0x00, 0x80, 0xfd, 0x5b, 0x67, 0x0d,
+        0xe0, 0xb6, 0xb3, 0xa7, 0x64, 0x00, 0x00, 0x60, 0x00, 0x52, 0x67, 0x0d, 0xe0, 0xb6, 0xb3, 0xa7, 0x64, 0x00, 0x00, 0x60, 0x20, 0x52,
+        0x60, 0x60, 0x60, 0x00, 0xf3,
+    ];
+
+    fn insert_code(db: &mut LoomDB, address: Address, code: Vec<u8>) {
+        let code = Bytecode::new_raw(Bytes::from(code));
+        db.insert_account_info(address, AccountInfo { code_hash: code.hash_slow(), code: Some(code), ..Default::default() });
+    }
+
+    #[test]
+    fn test_screen_reverting_swap() -> Result<()> {
+        let token_address = Address::repeat_byte(0x11);
+        let pool_address = Address::repeat_byte(0x12);
+
+        let mut db = LoomDB::new();
+        insert_code(&mut db, NWETH::ADDRESS, TOKEN_CODE.to_vec());
+        insert_code(&mut db, token_address, TOKEN_CODE.to_vec());
+        insert_code(&mut db, pool_address, POOL_CODE.to_vec());
+
+        let pool = PoolWrapper::new(Arc::new(UniswapV2Pool::new_with_data(
+            pool_address,
+            NWETH::ADDRESS,
+            token_address,
+            Address::ZERO,
+            U256::ZERO,
+            U256::ZERO,
+        )));
+        let weth = Arc::new(Token::new(NWETH::ADDRESS));
+        let token = Arc::new(Token::new(token_address));
+
+        let screener = TokenScreener::new(Address::repeat_byte(0x13), U256::from(10).pow(U256::from(16)));
+        let transfer_class = screener.screen(&db, Env::default(), &pool, weth, token)?;
+        assert_eq!(transfer_class, TokenTransferClass::Untradeable);
+        Ok(())
+    }
+}
@@ -32,7 +32,7 @@ where
     for curve_contract in curve_contracts.into_iter() {
         if let Ok(curve_pool) = CurvePool::fetch_pool_data(client.clone(), curve_contract).await {
             let pool_wrapped = PoolWrapper::new(Arc::new(curve_pool));
-            match fetch_state_and_add_pool(client.clone(), market.clone(), market_state.clone(), pool_wrapped.clone()).await {
+            match fetch_state_and_add_pool(client.clone(), None, market.clone(), market_state.clone(), pool_wrapped.clone()).await {
                 Err(e) => {
                     error!("Curve pool loading error : {}", e)
                 }
@@ -59,6 +59,7 @@ where
 
                                     match fetch_state_and_add_pool(
                                         client.clone(),
+                                        None,
                                         market.clone(),
                                         market_state.clone(),
                                         pool_wrapped.clone(),
@@ -1,12 +1,18 @@
 pub use curve_protocol_pool_actor::CurvePoolLoaderOneShotActor;
 pub use history_pool_actor::HistoryPoolLoaderOneShotActor;
+pub use log_state_translator::LogStateTranslator;
+pub use log_state_update_actor::PoolLogStateUpdateActor;
 pub use new_pool_actor::NewPoolLoaderActor;
 pub use pool_loader::{fetch_and_add_pool_by_address, fetch_state_and_add_pool, PoolLoaderActor};
 pub use required_pools_actor::RequiredPoolLoaderActor;
+pub use token_loader::load_tokens_metadata;
 
 mod curve_protocol_pool_actor;
 mod history_pool_actor;
+mod log_state_translator;
+mod log_state_update_actor;
 mod logs_parser;
 mod new_pool_actor;
 mod pool_loader;
 mod required_pools_actor;
+mod token_loader;
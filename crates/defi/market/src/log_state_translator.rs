@@ -0,0 +1,397 @@
+use std::collections::HashMap;
+
+use alloy_primitives::{Address, B256, I256, U256};
+use alloy_rpc_types::Log;
+use alloy_sol_types::SolEventInterface;
+use eyre::{eyre, ErrReport, OptionExt, Result};
+use revm::primitives::Env;
+use revm::DatabaseRef;
+use tracing::{trace, warn};
+
+use loom_defi_abi::uniswap2::IUniswapV2Pair::IUniswapV2PairEvents;
+use loom_defi_abi::uniswap3::IUniswapV3Pool::IUniswapV3PoolEvents;
+use loom_defi_pools::state_readers::{UniswapV2StateReader, UniswapV3StateReader};
+use loom_evm_db::DatabaseLoomExt;
+use loom_evm_utils::remv_db_direct_access::calc_hashmap_cell;
+use loom_types_blockchain::GethStateUpdate;
+use loom_types_entities::{Market, PoolClass, PoolProtocol, PoolWrapper};
+
+const UNISWAP_V2_RESERVES_SLOT: U256 = U256::from_limbs([8, 0, 0, 0]);
+const UNISWAP_V3_SLOT0_SLOT: U256 = U256::from_limbs([0, 0, 0, 0]);
+const UNISWAP_V3_LIQUIDITY_SLOT: U256 = U256::from_limbs([4, 0, 0, 0]);
+const UNISWAP_V3_TICKS_SLOT: U256 = U256::from_limbs([5, 0, 0, 0]);
+const UNISWAP_V3_TICK_BITMAP_SLOT: U256 = U256::from_limbs([6, 0, 0, 0]);
+
+/// Storage of the pools changed by the logs of the block on top of the state before the block.
+struct StateOverlay<'a, DB> {
+    db: &'a DB,
+    update: GethStateUpdate,
+}
+
+impl<DB: DatabaseRef<Error = ErrReport> + DatabaseLoomExt> StateOverlay<'_, DB> {
+    fn read(&self, address: Address, slot: U256) -> Result<U256> {
+        match self.update.get(&address).and_then(|account| account.storage.get(&B256::from(slot))) {
+            Some(value) => Ok((*value).into()),
+            None => self.db.storage_ref(address, slot),
+        }
+    }
+
+    /// Reads the slot only if it is changed by the block or loaded in the state before the block. Slots missing in the state
+    /// are fetched from the node later and already include the block, so deltas of the block must not be applied to them.
+    fn read_loaded(&self, address: Address, slot: U256) -> Result<Option<U256>> {
+        match self.update.get(&address).and_then(|account| account.storage.get(&B256::from(slot))) {
+            Some(value) => Ok(Some((*value).into())),
+            None if self.db.is_slot(&address, &slot) => self.db.storage_ref(address, slot).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn write(&mut self, address: Address, slot: U256, value: U256) {
+        self.update.entry(address).or_default().storage.insert(B256::from(slot), B256::from(value));
+    }
+}
+
+/// Translates events of the market pools to storage diffs for nodes that do not support state diff tracing.
+///
+/// Only storage read by swap calculations is maintained: reserves of UniswapV2 pools and slot0, liquidity, liquidity of ticks
+/// and tick bitmap of UniswapV3 pools. Token balances, fee growth and oracle observations are not updated.
+/// Liquidity changes of UniswapV3 positions are applied only to slots loaded in the state before the block.
+#[derive(Default)]
+pub struct LogStateTranslator {
+    // false if reserves of the UniswapV2 pool are not stored in slot 8
+    uniswap_v2_reserves_in_slot: HashMap<Address, bool>,
+    uniswap_v3_tick_spacing: HashMap<Address, i32>,
+}
+
+impl LogStateTranslator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns storage diff of the block in the format of `debug_traceBlock` with the prestate tracer in diff mode.
+    /// `db` is the market state before the block.
+    /// `pools` are the market pools emitting the logs, see [LogStateTranslator::log_pools].
+    pub fn translate<DB: DatabaseRef<Error = ErrReport> + DatabaseLoomExt>(
+        &mut self,
+        db: &DB,
+        pools: &HashMap<Address, PoolWrapper>,
+        block_timestamp: u64,
+        logs: &[Log],
+    ) -> GethStateUpdate {
+        let mut overlay = StateOverlay { db, update: GethStateUpdate::new() };
+
+        for log in logs.iter().filter(|log| !log.removed) {
+            let Some(pool) = pools.get(&log.address()) else { continue };
+
+            let result = match (pool.get_class(), pool.get_protocol()) {
+                (PoolClass::UniswapV2, _) => self.apply_uniswap_v2_log(&mut overlay, log, block_timestamp),
+                // different storage layout and events
+                (PoolClass::UniswapV3, PoolProtocol::PancakeV3 | PoolProtocol::Maverick) => Ok(()),
+                (PoolClass::UniswapV3, _) => self.apply_uniswap_v3_log(&mut overlay, log),
+                _ => Ok(()),
+            };
+            if let Err(error) = result {
+                warn!(address = %log.address(), %error, "Pool log is not translated to state update");
+            }
+        }
+
+        overlay.update
+    }
+
+    /// Market pools emitting the logs
+    pub fn log_pools(market: &Market, logs: &[Log]) -> HashMap<Address, PoolWrapper> {
+        logs.iter().filter_map(|log| market.get_pool(&log.address()).map(|pool| (log.address(), pool.clone()))).collect()
+    }
+
+    fn apply_uniswap_v2_log<DB: DatabaseRef<Error = ErrReport> + DatabaseLoomExt>(
+        &mut self,
+        overlay: &mut StateOverlay<DB>,
+        log: &Log,
+        block_timestamp: u64,
+    ) -> Result<()> {
+        let Ok(event) = IUniswapV2PairEvents::decode_log(&log.inner, false) else { return Ok(()) };
+        let IUniswapV2PairEvents::Sync(sync) = event.data else { return Ok(()) };
+        let address = log.address();
+
+        if !self.is_uniswap_v2_reserves_in_slot(overlay.db, address)? {
+            return Ok(());
+        }
+
+        // reserve0, reserve1 and blockTimestampLast are packed in one slot
+        let value = U256::from(sync.reserve0) | (U256::from(sync.reserve1) << 112) | (U256::from(block_timestamp as u32) << 224);
+        trace!(%address, reserve0 = %sync.reserve0, reserve1 = %sync.reserve1, "UniswapV2 reserves from log");
+        overlay.write(address, UNISWAP_V2_RESERVES_SLOT, value);
+        Ok(())
+    }
+
+    fn is_uniswap_v2_reserves_in_slot<DB: DatabaseRef<Error = ErrReport>>(&mut self, db: &DB, address: Address) -> Result<bool> {
+        if let Some(reserves_in_slot) = self.uniswap_v2_reserves_in_slot.get(&address) {
+            return Ok(*reserves_in_slot);
+        }
+
+        let (reserve0, reserve1) = UniswapV2StateReader::get_reserves(db, Env::default(), address)?;
+        let cell = db.storage_ref(address, UNISWAP_V2_RESERVES_SLOT)?;
+        let mask = (U256::from(1) << 112) - U256::from(1);
+        let reserves_in_slot = cell & mask == reserve0 && (cell >> 112) & mask == reserve1;
+        if !reserves_in_slot {
+            warn!(%address, "UniswapV2 pool reserves are not stored in slot 8, state is not updated from logs");
+        }
+
+        self.uniswap_v2_reserves_in_slot.insert(address, reserves_in_slot);
+        Ok(reserves_in_slot)
+    }
+
+    fn apply_uniswap_v3_log<DB: DatabaseRef<Error = ErrReport> + DatabaseLoomExt>(
+        &mut self,
+        overlay: &mut StateOverlay<DB>,
+        log: &Log,
+    ) -> Result<()> {
+        let Ok(event) = IUniswapV3PoolEvents::decode_log(&log.inner, false) else { return Ok(()) };
+        let address = log.address();
+
+        match event.data {
+            IUniswapV3PoolEvents::Swap(swap) => {
+                // sqrtPriceX96 and tick are replaced, oracle fields of slot0 are kept
+                let price_tick_mask = (U256::from(1) << (160 + 24)) - U256::from(1);
+                let slot0 = overlay.read(address, UNISWAP_V3_SLOT0_SLOT)?;
+                let slot0 = (slot0 & !price_tick_mask) | U256::from(swap.sqrtPriceX96) | (U256::from(swap.tick.into_raw()) << 160);
+                trace!(%address, tick = swap.tick.as_i32(), liquidity = swap.liquidity, "UniswapV3 slot0 from log");
+                overlay.write(address, UNISWAP_V3_SLOT0_SLOT, slot0);
+                overlay.write(address, UNISWAP_V3_LIQUIDITY_SLOT, U256::from(swap.liquidity));
+            }
+            IUniswapV3PoolEvents::Mint(mint) => {
+                self.update_position(overlay, address, mint.tickLower.as_i32(), mint.tickUpper.as_i32(), mint.amount as i128)?;
+            }
+            // zero amount burn only collects fees
+            IUniswapV3PoolEvents::Burn(burn) if burn.amount > 0 => {
+                self.update_position(overlay, address, burn.tickLower.as_i32(), burn.tickUpper.as_i32(), -(burn.amount as i128))?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn update_position<DB: DatabaseRef<Error = ErrReport> + DatabaseLoomExt>(
+        &mut self,
+        overlay: &mut StateOverlay<DB>,
+        address: Address,
+        tick_lower: i32,
+        tick_upper: i32,
+        liquidity_delta: i128,
+    ) -> Result<()> {
+        let tick_spacing = self.uniswap_v3_tick_spacing(overlay.db, address)?;
+
+        Self::update_tick(overlay, address, tick_lower, tick_spacing, liquidity_delta, false)?;
+        Self::update_tick(overlay, address, tick_upper, tick_spacing, liquidity_delta, true)?;
+
+        let Some(liquidity) = overlay.read_loaded(address, UNISWAP_V3_LIQUIDITY_SLOT)? else {
+            return Ok(());
+        };
+        // active liquidity changes only if the position is in range
+        let slot0 = overlay.read(address, UNISWAP_V3_SLOT0_SLOT)?;
+        let tick_raw: u32 = ((slot0 >> 160) & U256::from(0xFFFFFF)).saturating_to();
+        let tick = ((tick_raw << 8) as i32) >> 8;
+        if tick_lower <= tick && tick < tick_upper {
+            let liquidity: u128 = liquidity.saturating_to();
+            let liquidity = liquidity.checked_add_signed(liquidity_delta).ok_or_eyre("LIQUIDITY_OVERFLOW")?;
+            overlay.write(address, UNISWAP_V3_LIQUIDITY_SLOT, U256::from(liquidity));
+        }
+        Ok(())
+    }
+
+    /// Updates liquidity of the tick and flips its bitmap bit when the tick is initialized or cleared. A tick missing in the
+    /// state is known to be empty before the block if its bit in the loaded bitmap word is not set. Otherwise the tick is left
+    /// to be fetched from the node, a bitmap bit left set for a cleared tick does not change swap results.
+    fn update_tick<DB: DatabaseRef<Error = ErrReport> + DatabaseLoomExt>(
+        overlay: &mut StateOverlay<DB>,
+        address: Address,
+        tick: i32,
+        tick_spacing: i32,
+        liquidity_delta: i128,
+        upper: bool,
+    ) -> Result<()> {
+        let compressed = tick.div_euclid(tick_spacing);
+        let word_pos = compressed >> 8;
+        let bit = U256::from(1) << compressed.rem_euclid(256) as usize;
+        let word_cell = calc_hashmap_cell(UNISWAP_V3_TICK_BITMAP_SLOT, I256::try_from(word_pos)?.into_raw());
+        let word = overlay.read_loaded(address, word_cell)?;
+
+        // liquidityGross and liquidityNet are packed in the first slot of Tick.Info
+        let cell = calc_hashmap_cell(UNISWAP_V3_TICKS_SLOT, I256::try_from(tick)?.into_raw());
+        let value = match (overlay.read_loaded(address, cell)?, word) {
+            (Some(value), _) => value,
+            (None, Some(word)) if word & bit == U256::ZERO => U256::ZERO,
+            _ => {
+                trace!(%address, tick, "UniswapV3 tick is not loaded, skipped");
+                return Ok(());
+            }
+        };
+        let gross_before: u128 = (value & U256::from(u128::MAX)).saturating_to();
+        let net_before = (value >> 128).saturating_to::<u128>() as i128;
+
+        let gross_after = gross_before.checked_add_signed(liquidity_delta).ok_or_eyre("TICK_LIQUIDITY_OVERFLOW")?;
+        let net_after = if upper { net_before.wrapping_sub(liquidity_delta) } else { net_before.wrapping_add(liquidity_delta) };
+        let value = if gross_after == 0 { U256::ZERO } else { U256::from(gross_after) | (U256::from(net_after as u128) << 128) };
+        overlay.write(address, cell, value);
+
+        if let Some(word) = word.filter(|_| (gross_before == 0) != (gross_after == 0)) {
+            overlay.write(address, word_cell, word ^ bit);
+        }
+        Ok(())
+    }
+
+    fn uniswap_v3_tick_spacing<DB: DatabaseRef<Error = ErrReport>>(&mut self, db: &DB, address: Address) -> Result<i32> {
+        if let Some(tick_spacing) = self.uniswap_v3_tick_spacing.get(&address) {
+            return Ok(*tick_spacing);
+        }
+        let tick_spacing = UniswapV3StateReader::tick_spacing(db, Env::default(), address)? as i32;
+        if tick_spacing <= 0 {
+            return Err(eyre!("BAD_TICK_SPACING"));
+        }
+        self.uniswap_v3_tick_spacing.insert(address, tick_spacing);
+        Ok(tick_spacing)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_primitives::aliases::{I24, U112};
+    use alloy_primitives::{LogData, U160};
+    use alloy_sol_types::SolEvent;
+    use loom_defi_abi::uniswap2::IUniswapV2Pair;
+    use loom_defi_abi::uniswap3::IUniswapV3Pool;
+    use loom_defi_pools::{UniswapV2Pool, UniswapV3Pool};
+    use loom_evm_db::LoomDB;
+
+    fn pool_log(address: Address, data: LogData) -> Log {
+        Log { inner: alloy_primitives::Log { address, data }, ..Default::default() }
+    }
+
+    fn storage(update: &GethStateUpdate, address: Address, slot: U256) -> U256 {
+        update[&address].storage[&B256::from(slot)].into()
+    }
+
+    #[test]
+    fn test_uniswap_v2_sync() -> Result<()> {
+        let pool = Address::repeat_byte(1);
+        let mut market = Market::default();
+        market.add_pool(UniswapV2Pool::new(pool))?;
+
+        let mut translator = LogStateTranslator::new();
+        translator.uniswap_v2_reserves_in_slot.insert(pool, true);
+
+        let sync = IUniswapV2Pair::Sync { reserve0: U112::from(100), reserve1: U112::from(200) };
+        let logs = vec![pool_log(pool, sync.encode_log_data()), pool_log(Address::repeat_byte(2), sync.encode_log_data())];
+        let update = translator.translate(&LoomDB::new(), &LogStateTranslator::log_pools(&market, &logs), 0x1_0000_0005, &logs);
+
+        assert_eq!(update.len(), 1);
+        assert_eq!(storage(&update, pool, UNISWAP_V2_RESERVES_SLOT), U256::from(100) | (U256::from(200) << 112) | (U256::from(5) << 224));
+        Ok(())
+    }
+
+    #[test]
+    fn test_uniswap_v3_swap_mint_burn() -> Result<()> {
+        let pool = Address::repeat_byte(1);
+        let mut market = Market::default();
+        market.add_pool(UniswapV3Pool::new(pool))?;
+
+        let mut translator = LogStateTranslator::new();
+        translator.uniswap_v3_tick_spacing.insert(pool, 60);
+
+        // observation index 1 and cardinality 2 above price and tick
+        let oracle = (U256::from(1) << 184) | (U256::from(2) << 200);
+        let mut db = LoomDB::new();
+        db.insert_account_storage(pool, UNISWAP_V3_SLOT0_SLOT, oracle | U256::from(1000))?;
+        db.insert_account_storage(pool, UNISWAP_V3_LIQUIDITY_SLOT, U256::from(10))?;
+        // -120 / 60 = -2 is bit 254 of word -1, 60 / 60 = 1 is bit 1 of word 0
+        let negative_word = calc_hashmap_cell(UNISWAP_V3_TICK_BITMAP_SLOT, I256::MINUS_ONE.into_raw());
+        let positive_word = calc_hashmap_cell(UNISWAP_V3_TICK_BITMAP_SLOT, U256::ZERO);
+        db.insert_account_storage(pool, negative_word, U256::ZERO)?;
+        db.insert_account_storage(pool, positive_word, U256::ZERO)?;
+
+        let swap = IUniswapV3Pool::Swap {
+            sender: Address::ZERO,
+            recipient: Address::ZERO,
+            amount0: I256::ZERO,
+            amount1: I256::ZERO,
+            sqrtPriceX96: U160::from(2000),
+            liquidity: 50,
+            tick: I24::try_from(-100)?,
+        };
+        let mint = IUniswapV3Pool::Mint {
+            sender: Address::ZERO,
+            owner: Address::ZERO,
+            tickLower: I24::try_from(-120)?,
+            tickUpper: I24::try_from(60)?,
+            amount: 7,
+            amount0: U256::ZERO,
+            amount1: U256::ZERO,
+        };
+        let burn = IUniswapV3Pool::Burn {
+            owner: Address::ZERO,
+            tickLower: I24::try_from(-120)?,
+            tickUpper: I24::try_from(60)?,
+            amount: 3,
+            amount0: U256::ZERO,
+            amount1: U256::ZERO,
+        };
+
+        let logs =
+            vec![pool_log(pool, swap.encode_log_data()), pool_log(pool, mint.encode_log_data()), pool_log(pool, burn.encode_log_data())];
+        let update = translator.translate(&db, &LogStateTranslator::log_pools(&market, &logs), 0, &logs);
+
+        let tick_bits = U256::from((-100i32 as u32) & 0xFFFFFF) << 160;
+        assert_eq!(storage(&update, pool, UNISWAP_V3_SLOT0_SLOT), oracle | tick_bits | U256::from(2000));
+        assert_eq!(storage(&update, pool, UNISWAP_V3_LIQUIDITY_SLOT), U256::from(54));
+
+        let lower_cell = calc_hashmap_cell(UNISWAP_V3_TICKS_SLOT, I256::try_from(-120)?.into_raw());
+        let upper_cell = calc_hashmap_cell(UNISWAP_V3_TICKS_SLOT, I256::try_from(60)?.into_raw());
+        assert_eq!(storage(&update, pool, lower_cell), U256::from(4) | (U256::from(4) << 128));
+        assert_eq!(storage(&update, pool, upper_cell), U256::from(4) | (U256::from(-4i128 as u128) << 128));
+
+        assert_eq!(storage(&update, pool, negative_word), U256::from(1) << 254);
+        assert_eq!(storage(&update, pool, positive_word), U256::from(1) << 1);
+
+        // removing the rest of the position clears ticks and bitmap
+        let burn = IUniswapV3Pool::Burn { amount: 4, ..burn };
+        let logs = [logs, vec![pool_log(pool, burn.encode_log_data())]].concat();
+        let update = translator.translate(&db, &LogStateTranslator::log_pools(&market, &logs), 0, &logs);
+        assert_eq!(storage(&update, pool, lower_cell), U256::ZERO);
+        assert_eq!(storage(&update, pool, negative_word), U256::ZERO);
+        assert_eq!(storage(&update, pool, UNISWAP_V3_LIQUIDITY_SLOT), U256::from(50));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_uniswap_v3_mint_not_loaded() -> Result<()> {
+        let pool = Address::repeat_byte(1);
+        let mut market = Market::default();
+        market.add_pool(UniswapV3Pool::new(pool))?;
+
+        let mut translator = LogStateTranslator::new();
+        translator.uniswap_v3_tick_spacing.insert(pool, 60);
+
+        // the lower tick is initialized before the block but not loaded, the upper tick word and liquidity are not loaded
+        let negative_word = calc_hashmap_cell(UNISWAP_V3_TICK_BITMAP_SLOT, I256::MINUS_ONE.into_raw());
+        let mut db = LoomDB::new();
+        db.insert_account_storage(pool, UNISWAP_V3_SLOT0_SLOT, U256::from(1000))?;
+        db.insert_account_storage(pool, negative_word, U256::from(1) << 254)?;
+
+        let mint = IUniswapV3Pool::Mint {
+            sender: Address::ZERO,
+            owner: Address::ZERO,
+            tickLower: I24::try_from(-120)?,
+            tickUpper: I24::try_from(60)?,
+            amount: 7,
+            amount0: U256::ZERO,
+            amount1: U256::ZERO,
+        };
+        let logs = vec![pool_log(pool, mint.encode_log_data())];
+        let update = translator.translate(&db, &LogStateTranslator::log_pools(&market, &logs), 0, &logs);
+
+        assert!(update.get(&pool).is_none_or(|account| account.storage.is_empty()));
+        Ok(())
+    }
+}
@@ -0,0 +1,117 @@
+use eyre::{eyre, ErrReport};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error, info};
+
+use loom_core_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
+use loom_core_actors_macros::{Accessor, Consumer, Producer};
+use loom_core_blockchain::{Blockchain, BlockchainState};
+use loom_evm_db::DatabaseLoomExt;
+use loom_types_entities::{Market, MarketState};
+use loom_types_events::{BlockStateUpdate, Message, MessageBlockLogs, MessageBlockStateUpdate};
+use revm::DatabaseRef;
+
+use crate::log_state_translator::LogStateTranslator;
+
+pub async fn log_state_update_worker<DB: DatabaseRef<Error = ErrReport> + DatabaseLoomExt + Send + Sync + Clone + 'static>(
+    market: SharedState<Market>,
+    market_state: SharedState<MarketState<DB>>,
+    block_logs_rx: Broadcaster<MessageBlockLogs>,
+    block_state_update_tx: Broadcaster<MessageBlockStateUpdate>,
+) -> WorkerResult {
+    subscribe!(block_logs_rx);
+
+    let mut translator = LogStateTranslator::new();
+
+    loop {
+        let block_logs = match block_logs_rx.recv().await {
+            Ok(block_logs) => block_logs.inner,
+            Err(e) => match e {
+                RecvError::Closed => {
+                    error!("Block logs channel closed");
+                    return Err(eyre!("BLOCK_LOGS_RX_CLOSED"));
+                }
+                RecvError::Lagged(lag) => {
+                    info!("Block logs channel lagged: {}", lag);
+                    continue;
+                }
+            },
+        };
+
+        // storage missing in the state is fetched from the node, so translation runs on a blocking thread without locks
+        let pools = LogStateTranslator::log_pools(&*market.read().await, &block_logs.logs);
+        let db = market_state.read().await.state_db.clone();
+        let (block_timestamp, logs) = (block_logs.block_header.timestamp, block_logs.logs);
+        let (state_update, returned_translator) = tokio::task::spawn_blocking(move || {
+            let state_update = translator.translate(&db, &pools, block_timestamp, &logs);
+            (state_update, translator)
+        })
+        .await?;
+        translator = returned_translator;
+        debug!(block_number = block_logs.block_header.number, pools = state_update.len(), "Block state update from logs");
+
+        let block_state_update = BlockStateUpdate { block_header: block_logs.block_header, state_update: vec![state_update] };
+        if let Err(e) = block_state_update_tx.send(Message::new_with_time(block_state_update)).await {
+            error!("Broadcaster error {}", e);
+        }
+    }
+}
+
+/// Produces block state updates of the market pools from block logs for nodes without `debug_traceBlock` support.
+/// Must replace the state update producer of the node actor.
+#[derive(Accessor, Consumer, Producer)]
+pub struct PoolLogStateUpdateActor<DB> {
+    #[accessor]
+    market: Option<SharedState<Market>>,
+    #[accessor]
+    market_state: Option<SharedState<MarketState<DB>>>,
+    #[consumer]
+    block_logs_rx: Option<Broadcaster<MessageBlockLogs>>,
+    #[producer]
+    block_state_update_tx: Option<Broadcaster<MessageBlockStateUpdate>>,
+}
+
+impl<DB> PoolLogStateUpdateActor<DB>
+where
+    DB: DatabaseRef<Error = ErrReport> + DatabaseLoomExt + Send + Sync + Clone + 'static,
+{
+    pub fn new() -> Self {
+        Self { market: None, market_state: None, block_logs_rx: None, block_state_update_tx: None }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain, state: &BlockchainState<DB>) -> Self {
+        Self {
+            market: Some(bc.market()),
+            market_state: Some(state.market_state()),
+            block_logs_rx: Some(bc.new_block_logs_channel()),
+            block_state_update_tx: Some(bc.new_block_state_update_channel()),
+        }
+    }
+}
+
+impl<DB> Default for PoolLogStateUpdateActor<DB>
+where
+    DB: DatabaseRef<Error = ErrReport> + DatabaseLoomExt + Send + Sync + Clone + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<DB> Actor for PoolLogStateUpdateActor<DB>
+where
+    DB: DatabaseRef<Error = ErrReport> + DatabaseLoomExt + Send + Sync + Clone + 'static,
+{
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(log_state_update_worker(
+            self.market.clone().unwrap(),
+            self.market_state.clone().unwrap(),
+            self.block_logs_rx.clone().unwrap(),
+            self.block_state_update_tx.clone().unwrap(),
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "PoolLogStateUpdateActor"
+    }
+}
@@ -16,6 +16,7 @@ use loom_core_blockchain::{Blockchain, BlockchainState};
 use loom_defi_pools::protocols::{fetch_uni2_factory, fetch_uni3_factory, CurveProtocol};
 use loom_defi_pools::{CurvePool, MaverickPool, PancakeV3Pool, UniswapV2Pool, UniswapV3Pool};
 use loom_node_debug_provider::DebugProviderExt;
+use loom_storage_db::DbPool;
 use loom_types_entities::required_state::RequiredStateReader;
 use loom_types_entities::{get_protocol_by_factory, Market, MarketState, PoolClass, PoolProtocol, PoolWrapper};
 use loom_types_events::Task;
@@ -23,10 +24,13 @@ use loom_types_events::Task;
 use revm::{Database, DatabaseCommit, DatabaseRef};
 use tokio::sync::Semaphore;
 
+use crate::token_loader::load_tokens_metadata;
+
 const MAX_CONCURRENT_TASKS: usize = 20;
 
 pub async fn pool_loader_worker<P, T, N, DB>(
     client: P,
+    db_pool: Option<DbPool>,
     market: SharedState<Market>,
     market_state: SharedState<MarketState<DB>>,
     tasks_rx: Broadcaster<Task>,
@@ -53,17 +57,29 @@ where
                 if processed_pools.insert(pool_address, true).is_some() {
                     continue;
                 }
+                // Skip pools restored from a snapshot
+                if market.read().await.is_pool(&pool_address) {
+                    continue;
+                }
 
                 let sema_clone = semaphore.clone();
                 let client_clone = client.clone();
+                let db_pool_clone = db_pool.clone();
                 let market_clone = market.clone();
                 let market_state = market_state.clone();
 
                 tokio::task::spawn(async move {
                     match sema_clone.acquire().await {
                         Ok(permit) => {
-                            if let Err(error) =
-                                fetch_and_add_pool_by_address(client_clone, market_clone, market_state, pool_address, pool_class).await
+                            if let Err(error) = fetch_and_add_pool_by_address(
+                                client_clone,
+                                db_pool_clone,
+                                market_clone,
+                                market_state,
+                                pool_address,
+                                pool_class,
+                            )
+                            .await
                             {
                                 error!(%error, "failed fetch_and_add_pool_by_address");
                             } else {
@@ -84,6 +100,7 @@ where
 /// Fetch pool data, add it to the market and fetch the required state
 pub async fn fetch_and_add_pool_by_address<P, T, N, DB>(
     client: P,
+    db_pool: Option<DbPool>,
     market: SharedState<Market>,
     market_state: SharedState<MarketState<DB>>,
     pool_address: Address,
@@ -107,7 +124,14 @@ where
 
                 _ => {
                     let pool = UniswapV2Pool::fetch_pool_data(client.clone(), pool_address).await?;
-                    fetch_state_and_add_pool(client.clone(), market.clone(), market_state.clone(), PoolWrapper::new(Arc::new(pool))).await
+                    fetch_state_and_add_pool(
+                        client.clone(),
+                        db_pool,
+                        market.clone(),
+                        market_state.clone(),
+                        PoolWrapper::new(Arc::new(pool)),
+                    )
+                    .await
                 }
             };
 
@@ -130,7 +154,7 @@ where
                         _ => PoolWrapper::new(Arc::new(UniswapV3Pool::fetch_pool_data(client.clone(), pool_address).await?)),
                     };
 
-                    if let Err(e) = fetch_state_and_add_pool(client, market, market_state, pool_wrapped).await {
+                    if let Err(e) = fetch_state_and_add_pool(client, db_pool, market, market_state, pool_wrapped).await {
                         error!("fetch_and_add_pool uni3 error {:#20x} : {}", pool_address, e);
                         return Err(e);
                     }
@@ -146,7 +170,7 @@ where
                 let curve_pool = CurvePool::fetch_pool_data(client.clone(), curve_contract).await?;
                 let pool_wrapped = PoolWrapper::new(Arc::new(curve_pool));
 
-                match fetch_state_and_add_pool(client.clone(), market.clone(), market_state.clone(), pool_wrapped.clone()).await {
+                match fetch_state_and_add_pool(client.clone(), db_pool, market.clone(), market_state.clone(), pool_wrapped.clone()).await {
                     Err(e) => {
                         error!("Curve pool loading error {:?} : {}", pool_wrapped.get_address(), e);
                         return Err(e);
@@ -171,6 +195,7 @@ where
 
 pub async fn fetch_state_and_add_pool<P, T, N, DB>(
     client: P,
+    db_pool: Option<DbPool>,
     market: SharedState<Market>,
     market_state: SharedState<MarketState<DB>>,
     pool_wrapped: PoolWrapper,
@@ -182,11 +207,12 @@ where
     DB: Database + DatabaseRef + DatabaseCommit + Send + Sync + Clone + 'static,
 {
     match pool_wrapped.get_state_required() {
-        Ok(required_state) => match RequiredStateReader::fetch_calls_and_slots(client, required_state, None).await {
+        Ok(required_state) => match RequiredStateReader::fetch_calls_and_slots(client.clone(), required_state, None).await {
             Ok(state) => {
                 let pool_address = pool_wrapped.get_address();
                 {
                     let mut market_state_write_guard = market_state.write().await;
+                    market_state_write_guard.config.protect_pool_state(pool_address, &state);
                     market_state_write_guard.apply_geth_update(state);
                     market_state_write_guard.config.add_force_insert(pool_address);
                     market_state_write_guard.config.disable_cell_vec(pool_address, pool_wrapped.get_read_only_cell_vec());
@@ -194,6 +220,12 @@ where
                     drop(market_state_write_guard);
                 }
 
+                if let Err(error) =
+                    load_tokens_metadata(client, db_pool, market.clone(), market_state.clone(), pool_wrapped.get_tokens()).await
+                {
+                    error!(%error, %pool_address, "load_tokens_metadata");
+                }
+
                 let directions_vec = pool_wrapped.get_swap_directions();
                 let mut directions_tree: BTreeMap<PoolWrapper, Vec<(Address, Address)>> = BTreeMap::new();
                 directions_tree.insert(pool_wrapped.clone(), directions_vec);
@@ -230,6 +262,7 @@ where
 #[derive(Accessor, Consumer)]
 pub struct PoolLoaderActor<P, T, N, DB> {
     client: P,
+    db_pool: Option<DbPool>,
     #[accessor]
     market: Option<SharedState<Market>>,
     #[accessor]
@@ -248,7 +281,12 @@ where
     DB: Database + DatabaseRef + DatabaseCommit + Send + Sync + Clone + Default + 'static,
 {
     pub fn new(client: P) -> Self {
-        Self { client, market: None, market_state: None, tasks_rx: None, _t: PhantomData, _n: PhantomData }
+        Self { client, db_pool: None, market: None, market_state: None, tasks_rx: None, _t: PhantomData, _n: PhantomData }
+    }
+
+    /// Stores token metadata of the loaded pools in the database and reads it from there before fetching
+    pub fn with_db_pool(self, db_pool: DbPool) -> Self {
+        Self { db_pool: Some(db_pool), ..self }
     }
 
     pub fn on_bc(self, bc: &Blockchain, state: &BlockchainState<DB>) -> Self {
@@ -266,6 +304,7 @@ where
     fn start(&self) -> ActorResult {
         let task = tokio::task::spawn(pool_loader_worker(
             self.client.clone(),
+            self.db_pool.clone(),
             self.market.clone().unwrap(),
             self.market_state.clone().unwrap(),
             self.tasks_rx.clone().unwrap(),
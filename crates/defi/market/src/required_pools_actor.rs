@@ -36,7 +36,8 @@ where
         match pool_class {
             PoolClass::UniswapV2 | PoolClass::UniswapV3 => {
                 if let Err(error) =
-                    fetch_and_add_pool_by_address(client.clone(), market.clone(), market_state.clone(), pool_address, pool_class).await
+                    fetch_and_add_pool_by_address(client.clone(), None, market.clone(), market_state.clone(), pool_address, pool_class)
+                        .await
                 {
                     error!(%error, address = %pool_address, "fetch_and_add_pool_by_address")
                 }
@@ -44,7 +45,7 @@ where
             PoolClass::Curve => {
                 if let Ok(curve_contract) = CurveProtocol::get_contract_from_code(client.clone(), pool_address).await {
                     let curve_pool = CurvePool::fetch_pool_data(client.clone(), curve_contract).await?;
-                    fetch_state_and_add_pool(client.clone(), market.clone(), market_state.clone(), curve_pool.into()).await?
+                    fetch_state_and_add_pool(client.clone(), None, market.clone(), market_state.clone(), curve_pool.into()).await?
                 } else {
                     error!("CURVE_POOL_NOT_LOADED");
                 }
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+
+use alloy_network::Network;
+use alloy_primitives::Address;
+use alloy_provider::Provider;
+use alloy_transport::Transport;
+use eyre::Result;
+use futures::future::join_all;
+use revm::primitives::Env;
+use revm::DatabaseRef;
+use tracing::{debug, warn};
+
+use loom_core_actors::SharedState;
+use loom_defi_abi::IERC20::IERC20Instance;
+use loom_defi_pools::state_readers::ERC20StateReader;
+use loom_storage_db::{read_token_metadata as read_stored_token_metadata, write_token_metadata, DbPool, TokenMetadata};
+use loom_types_entities::{Market, MarketState, Token};
+
+/// Reads token metadata from the local state. Missing fields are None.
+fn read_token_metadata<DB: DatabaseRef>(db: &DB, token_address: Address) -> TokenMetadata {
+    TokenMetadata {
+        symbol: ERC20StateReader::symbol(db, Env::default(), token_address).ok(),
+        name: ERC20StateReader::name(db, Env::default(), token_address).ok(),
+        decimals: ERC20StateReader::decimals(db, Env::default(), token_address).ok(),
+    }
+}
+
+/// Fetches fields of the token metadata that are missing in the local state through RPC
+async fn fetch_missing_token_metadata<P, T, N>(client: P, token_address: Address, metadata: TokenMetadata) -> TokenMetadata
+where
+    N: Network,
+    T: Transport + Clone,
+    P: Provider<T, N> + Send + Sync + Clone + 'static,
+{
+    let token = IERC20Instance::new(token_address, client);
+
+    let symbol = match metadata.symbol {
+        Some(symbol) => Some(symbol),
+        None => token.symbol().call_raw().await.ok().and_then(|output| ERC20StateReader::decode_string(&output).ok()),
+    };
+    let name = match metadata.name {
+        Some(name) => Some(name),
+        None => token.name().call_raw().await.ok().and_then(|output| ERC20StateReader::decode_string(&output).ok()),
+    };
+    let decimals = match metadata.decimals {
+        Some(decimals) => Some(decimals),
+        None => token.decimals().call_raw().await.ok().and_then(|output| ERC20StateReader::decode_decimals(&output).ok()),
+    };
+
+    TokenMetadata { symbol, name, decimals }
+}
+
+/// Adds tokens missing in the market with decimals, symbol and name. Metadata stored in the database is used first,
+/// the rest is read from a clone of the market state on a blocking thread and fetched through RPC if the state has no
+/// token code or the call fails. Newly loaded metadata is written back to the database.
+/// Tokens are loaded once, as tokens already in the market are skipped. Tokens that revert are added with default decimals.
+pub async fn load_tokens_metadata<P, T, N, DB>(
+    client: P,
+    db_pool: Option<DbPool>,
+    market: SharedState<Market>,
+    market_state: SharedState<MarketState<DB>>,
+    token_addresses: Vec<Address>,
+) -> Result<()>
+where
+    N: Network,
+    T: Transport + Clone,
+    P: Provider<T, N> + Send + Sync + Clone + 'static,
+    DB: DatabaseRef + Send + Sync + Clone + 'static,
+{
+    let new_tokens: Vec<Address> = {
+        let market_guard = market.read().await;
+        token_addresses.into_iter().filter(|token_address| market_guard.get_token(token_address).is_none()).collect()
+    };
+    if new_tokens.is_empty() {
+        return Ok(());
+    }
+
+    let stored_metadata = match db_pool.as_ref() {
+        Some(db_pool) => read_stored_token_metadata(db_pool, &new_tokens).await.unwrap_or_else(|error| {
+            warn!(%error, "Failed to read stored token metadata");
+            HashMap::new()
+        }),
+        None => HashMap::new(),
+    };
+    let new_tokens: Vec<Address> = new_tokens.into_iter().filter(|token_address| !stored_metadata.contains_key(token_address)).collect();
+
+    let state_db = market_state.read().await.state_db.clone();
+    let local_metadata: Vec<(Address, TokenMetadata)> = tokio::task::spawn_blocking(move || {
+        new_tokens.into_iter().map(|token_address| (token_address, read_token_metadata(&state_db, token_address))).collect()
+    })
+    .await?;
+
+    let fetched_metadata = join_all(local_metadata.into_iter().map(|(token_address, metadata)| {
+        let client = client.clone();
+        async move { (token_address, fetch_missing_token_metadata(client, token_address, metadata).await) }
+    }))
+    .await;
+
+    if let Some(db_pool) = db_pool.as_ref() {
+        if let Err(error) = write_token_metadata(db_pool, &fetched_metadata).await {
+            warn!(%error, "Failed to store token metadata");
+        }
+    }
+
+    let tokens_metadata = stored_metadata.into_iter().chain(fetched_metadata);
+
+    let mut market_guard = market.write().await;
+    for (token_address, metadata) in tokens_metadata {
+        // token could be added by another loader meanwhile
+        if market_guard.get_token(&token_address).is_some() {
+            continue;
+        }
+        if metadata.decimals.is_none() {
+            warn!(%token_address, "Token decimals not available, using default");
+        }
+        debug!(%token_address, symbol = ?metadata.symbol, decimals = ?metadata.decimals, "Adding token");
+        market_guard.add_token(Token::new_with_data(token_address, metadata.symbol, metadata.name, metadata.decimals, false, false))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_primitives::{Bytes, U256};
+    use alloy_provider::ProviderBuilder;
+    use alloy_sol_types::SolCall;
+    use loom_defi_abi::IERC20;
+    use loom_evm_db::LoomDB;
+    use revm::primitives::{AccountInfo, Bytecode};
+    use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+    // returns the same 32 bytes for every call: b"MKR" padded with zeros
+    const BYTES32_TOKEN_CODE: [u8; 41] = [
+        0x7f, b'M', b'K', b'R', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x60, 0x00, 0x52,
+        0x60, 0x20, 0x60, 0x00, 0xf3,
+    ];
+    // reverts every call
+    const REVERTING_TOKEN_CODE: [u8; 5] = [0x60, 0x00, 0x60, 0x00, 0xfd];
+
+    /// Answers eth_call by the selector of the call data and fails calls to unknown selectors
+    struct EthCallResponder {
+        results: HashMap<[u8; 4], Bytes>,
+    }
+
+    impl Respond for EthCallResponder {
+        fn respond(&self, request: &Request) -> ResponseTemplate {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            let tx = &body["params"][0];
+            let input = tx.get("input").or_else(|| tx.get("data")).and_then(|input| input.as_str()).unwrap_or_default();
+            let input: Bytes = input.parse().unwrap_or_default();
+            let selector = input.get(..4).and_then(|selector| <[u8; 4]>::try_from(selector).ok());
+            let response = match selector.and_then(|selector| self.results.get(&selector)) {
+                Some(output) => serde_json::json!({"jsonrpc": "2.0", "id": body["id"], "result": output}),
+                None => serde_json::json!({"jsonrpc": "2.0", "id": body["id"], "error": {"code": 3, "message": "execution reverted"}}),
+            };
+            ResponseTemplate::new(200).set_body_json(response)
+        }
+    }
+
+    async fn mock_rpc(results: HashMap<[u8; 4], Bytes>) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(wiremock::matchers::method("POST")).respond_with(EthCallResponder { results }).mount(&server).await;
+        server
+    }
+
+    fn insert_code(db: &mut LoomDB, address: Address, code: &[u8]) {
+        let code = Bytecode::new_raw(Bytes::copy_from_slice(code));
+        db.insert_account_info(address, AccountInfo { code_hash: code.hash_slow(), code: Some(code), ..Default::default() });
+    }
+
+    #[test]
+    fn test_read_bytes32_symbol() {
+        let token_address = Address::repeat_byte(1);
+        let mut db = LoomDB::new();
+        insert_code(&mut db, token_address, &BYTES32_TOKEN_CODE);
+
+        let metadata = read_token_metadata(&db, token_address);
+        assert_eq!(metadata.symbol, Some("MKR".to_string()));
+        assert_eq!(metadata.name, Some("MKR".to_string()));
+        // bytes32 "MKR" does not fit into u8
+        assert_eq!(metadata.decimals, None);
+    }
+
+    #[tokio::test]
+    async fn test_reverting_token_default_decimals() -> Result<()> {
+        let token_address = Address::repeat_byte(2);
+        let mut db = LoomDB::new();
+        insert_code(&mut db, token_address, &REVERTING_TOKEN_CODE);
+
+        let server = mock_rpc(HashMap::new()).await;
+        let client = ProviderBuilder::new().on_http(server.uri().parse()?);
+        let market = SharedState::new(Market::default());
+        let market_state = SharedState::new(MarketState::new(db));
+
+        load_tokens_metadata(client, None, market.clone(), market_state, vec![token_address]).await?;
+
+        let token = market.read().await.get_token(&token_address).unwrap();
+        assert_eq!(token.get_decimals(), 18);
+        assert_eq!(token.get_symbol(), token_address.to_string());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_token_metadata_without_code() -> Result<()> {
+        let token_address = Address::repeat_byte(3);
+        let results = HashMap::from([
+            (IERC20::decimalsCall::SELECTOR, Bytes::from(IERC20::decimalsCall::abi_encode_returns(&(U256::from(6),)))),
+            (IERC20::symbolCall::SELECTOR, Bytes::from(IERC20::symbolCall::abi_encode_returns(&("USDC".to_string(),)))),
+            (IERC20::nameCall::SELECTOR, Bytes::from(IERC20::nameCall::abi_encode_returns(&("USD Coin".to_string(),)))),
+        ]);
+
+        let server = mock_rpc(results).await;
+        let client = ProviderBuilder::new().on_http(server.uri().parse()?);
+        let market = SharedState::new(Market::default());
+        let market_state = SharedState::new(MarketState::new(LoomDB::new()));
+
+        load_tokens_metadata(client, None, market.clone(), market_state, vec![token_address]).await?;
+
+        let token = market.read().await.get_token(&token_address).unwrap();
+        assert_eq!(token.get_decimals(), 6);
+        assert_eq!(token.get_symbol(), "USDC");
+        assert_eq!(token.get_name(), "USD Coin");
+        Ok(())
+    }
+}
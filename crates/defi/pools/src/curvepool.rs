@@ -8,6 +8,7 @@ use eyre::{eyre, ErrReport, Result};
 use loom_defi_abi::IERC20;
 use loom_defi_address_book::TokenAddressEth;
 use loom_evm_utils::evm::evm_call;
+use loom_types_blockchain::PoolError;
 use loom_types_entities::required_state::RequiredState;
 use loom_types_entities::{AbiSwapEncoder, Pool, PoolClass, PoolProtocol, PreswapRequirement};
 use revm::primitives::Env;
@@ -65,25 +66,25 @@ where
             Ok(i) => Ok(i),
             Err(_) => match self.get_underlying_coin_idx(address) {
                 Ok(i) => Ok(self.tokens.len() as u32 + i - 1),
-                Err(e) => Err(e),
+                Err(e) => Err(e.into()),
             },
         }
     }
-    pub fn get_coin_idx(&self, address: Address) -> Result<u32> {
+    pub fn get_coin_idx(&self, address: Address) -> Result<u32, PoolError> {
         for i in 0..self.tokens.len() {
             if address == self.tokens[i] {
                 return Ok(i as u32);
             }
         }
-        Err(eyre!("COIN_NOT_FOUND"))
+        Err(PoolError::UnsupportedDirection("COIN_NOT_FOUND"))
     }
-    pub fn get_underlying_coin_idx(&self, address: Address) -> Result<u32> {
+    pub fn get_underlying_coin_idx(&self, address: Address) -> Result<u32, PoolError> {
         for i in 0..self.underlying_tokens.len() {
             if address == self.underlying_tokens[i] {
                 return Ok(i as u32);
             }
         }
-        Err(eyre!("COIN_NOT_FOUND"))
+        Err(PoolError::UnsupportedDirection("COIN_NOT_FOUND"))
     }
 
     pub async fn fetch_out_amount(&self, token_address_from: Address, token_address_to: Address, amount_in: U256) -> Result<U256> {
@@ -202,13 +203,13 @@ where
         token_address_from: &Address,
         token_address_to: &Address,
         in_amount: U256,
-    ) -> Result<(U256, u64)> {
+    ) -> Result<(U256, u64), PoolError> {
         let mut env = env;
         env.tx.gas_limit = 500_000;
 
         let call_data = if self.is_meta {
-            let i: Result<u32> = self.get_coin_idx(*token_address_from);
-            let j: Result<u32> = self.get_coin_idx(*token_address_to);
+            let i = self.get_coin_idx(*token_address_from);
+            let j = self.get_coin_idx(*token_address_to);
             if i.is_ok() && j.is_ok() {
                 self.pool_contract.get_dy_call_data(i.unwrap(), j.unwrap(), in_amount)?
             } else {
@@ -239,7 +240,7 @@ where
         let ret = if value.len() > 32 { U256::from_be_slice(&value[0..32]) } else { U256::from_be_slice(&value[0..]) };
 
         if ret.is_zero() {
-            Err(eyre!("ZERO_OUT_AMOUNT"))
+            Err(PoolError::ZeroAmount("ZERO_OUT_AMOUNT"))
         } else {
             Ok((ret - U256::from(1), gas_used))
         }
@@ -252,7 +253,7 @@ where
         token_address_from: &Address,
         token_address_to: &Address,
         out_amount: U256,
-    ) -> Result<(U256, u64)> {
+    ) -> Result<(U256, u64), PoolError> {
         if self.pool_contract.can_calculate_in_amount() {
             let mut env = env;
             env.tx.gas_limit = 500_000;
@@ -266,12 +267,12 @@ where
             let ret = if value.len() > 32 { U256::from_be_slice(&value[0..32]) } else { U256::from_be_slice(&value[0..]) };
 
             if ret.is_zero() {
-                Err(eyre!("ZERO_IN_AMOUNT"))
+                Err(PoolError::ZeroAmount("ZERO_IN_AMOUNT"))
             } else {
                 Ok((ret + U256::from(1), gas_used))
             }
         } else {
-            Err(eyre!("NOT_SUPPORTED"))
+            Err(PoolError::UnsupportedDirection("NOT_SUPPORTED"))
         }
     }
 
@@ -400,16 +401,16 @@ where
         }
     }
 
-    pub fn get_coin_idx(&self, address: Address) -> Result<u32> {
+    pub fn get_coin_idx(&self, address: Address) -> Result<u32, PoolError> {
         for i in 0..self.tokens.len() {
             if address == self.tokens[i] {
                 return Ok(i as u32);
             }
         }
-        Err(eyre!("COIN_NOT_FOUND"))
+        Err(PoolError::UnsupportedDirection("COIN_NOT_FOUND"))
     }
 
-    pub fn get_underlying_coin_idx(&self, address: Address) -> Result<u32> {
+    pub fn get_underlying_coin_idx(&self, address: Address) -> Result<u32, PoolError> {
         match &self.underlying_tokens {
             Some(underlying_tokens) => {
                 for (i, token_address) in underlying_tokens.iter().enumerate() {
@@ -417,9 +418,9 @@ where
                         return Ok(i as u32);
                     }
                 }
-                Err(eyre!("UNDERLYING_COIN_NOT_FOUND"))
+                Err(PoolError::UnsupportedDirection("UNDERLYING_COIN_NOT_FOUND"))
             }
-            _ => Err(eyre!("UNDERLYING_COIN_NOT_SET")),
+            _ => Err(PoolError::StateMissing("UNDERLYING_COIN_NOT_SET")),
         }
     }
 }
@@ -439,8 +440,8 @@ where
         _payload: Bytes,
     ) -> Result<Bytes> {
         if self.is_meta {
-            let i: Result<u32> = self.get_coin_idx(token_from_address);
-            let j: Result<u32> = self.get_coin_idx(token_to_address);
+            let i = self.get_coin_idx(token_from_address);
+            let j = self.get_coin_idx(token_to_address);
 
             match (i, j) {
                 (Ok(i), Ok(j)) => self.curve_contract.get_exchange_call_data(i, j, amount, U256::ZERO, recipient),
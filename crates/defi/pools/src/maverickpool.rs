@@ -2,13 +2,14 @@ use alloy_primitives::{Address, Bytes, U128, U256};
 use alloy_provider::{Network, Provider};
 use alloy_sol_types::{SolCall, SolInterface};
 use alloy_transport::Transport;
-use eyre::{eyre, ErrReport, OptionExt, Result};
+use eyre::{ErrReport, Result};
 use loom_defi_abi::maverick::IMaverickPool::{getStateCall, IMaverickPoolCalls, IMaverickPoolInstance};
 use loom_defi_abi::maverick::IMaverickQuoter::{calculateSwapCall, IMaverickQuoterCalls};
 use loom_defi_abi::maverick::{IMaverickPool, IMaverickQuoter, State};
 use loom_defi_abi::IERC20;
 use loom_defi_address_book::PeripheryAddress;
 use loom_evm_utils::evm::evm_call;
+use loom_types_blockchain::PoolError;
 use loom_types_entities::required_state::RequiredState;
 use loom_types_entities::{AbiSwapEncoder, Pool, PoolClass, PoolProtocol, PreswapRequirement};
 use revm::primitives::Env;
@@ -169,10 +170,10 @@ impl Pool for MaverickPool {
         token_address_from: &Address,
         token_address_to: &Address,
         in_amount: U256,
-    ) -> Result<(U256, u64), ErrReport> {
+    ) -> Result<(U256, u64), PoolError> {
         if in_amount >= U256::from(U128::MAX) {
             error!("IN_AMOUNT_EXCEEDS_MAX {}", self.get_address().to_checksum(None));
-            return Err(eyre!("IN_AMOUNT_EXCEEDS_MAX"));
+            return Err(PoolError::InsufficientLiquidity("IN_AMOUNT_EXCEEDS_MAX"));
         }
 
         let token_a_in = MaverickPool::get_zero_for_one(token_address_from, token_address_to);
@@ -192,12 +193,12 @@ impl Pool for MaverickPool {
 
         let (value, gas_used) = evm_call(state_db, env, PeripheryAddress::MAVERICK_QUOTER, call_data_vec)?;
 
-        let ret = calculateSwapCall::abi_decode_returns(&value, false)?.returnAmount;
+        let ret = calculateSwapCall::abi_decode_returns(&value, false).map_err(|e| PoolError::DecodeError(e.to_string()))?.returnAmount;
 
         if ret.is_zero() {
-            Err(eyre!("ZERO_OUT_AMOUNT"))
+            Err(PoolError::ZeroAmount("ZERO_OUT_AMOUNT"))
         } else {
-            Ok((ret.checked_sub(U256::from(1)).ok_or_eyre("SUBTRACTION_OVERFLOWN")?, gas_used))
+            Ok((ret.checked_sub(U256::from(1)).ok_or(PoolError::MathOverflow("SUBTRACTION_OVERFLOWN"))?, gas_used))
         }
     }
 
@@ -208,13 +209,13 @@ impl Pool for MaverickPool {
         token_address_from: &Address,
         token_address_to: &Address,
         out_amount: U256,
-    ) -> Result<(U256, u64), ErrReport> {
+    ) -> Result<(U256, u64), PoolError> {
         let mut env = env;
         env.tx.gas_limit = 500_000;
 
         if out_amount >= U256::from(U128::MAX) {
             error!("OUT_AMOUNT_EXCEEDS_MAX {} ", self.get_address().to_checksum(None));
-            return Err(eyre!("OUT_AMOUNT_EXCEEDS_MAX"));
+            return Err(PoolError::InsufficientLiquidity("OUT_AMOUNT_EXCEEDS_MAX"));
         }
 
         let token_a_in = MaverickPool::get_zero_for_one(token_address_from, token_address_to);
@@ -231,10 +232,10 @@ impl Pool for MaverickPool {
 
         let (value, gas_used) = evm_call(state_db, env, PeripheryAddress::MAVERICK_QUOTER, call_data_vec)?;
 
-        let ret = calculateSwapCall::abi_decode_returns(&value, false)?.returnAmount;
+        let ret = calculateSwapCall::abi_decode_returns(&value, false).map_err(|e| PoolError::DecodeError(e.to_string()))?.returnAmount;
 
         if ret.is_zero() {
-            Err(eyre!("ZERO_IN_AMOUNT"))
+            Err(PoolError::ZeroAmount("ZERO_IN_AMOUNT"))
         } else {
             Ok((ret + U256::from(1), gas_used))
         }
@@ -16,6 +16,7 @@ use loom_defi_abi::uniswap_periphery::ITickLens;
 use loom_defi_abi::IERC20;
 use loom_defi_address_book::PeripheryAddress;
 use loom_evm_utils::evm::evm_call;
+use loom_types_blockchain::PoolError;
 use loom_types_entities::required_state::RequiredState;
 use loom_types_entities::{AbiSwapEncoder, Pool, PoolClass, PoolProtocol, PreswapRequirement};
 use revm::primitives::Env;
@@ -213,7 +214,7 @@ impl Pool for PancakeV3Pool {
         token_address_from: &Address,
         token_address_to: &Address,
         in_amount: U256,
-    ) -> Result<(U256, u64), ErrReport> {
+    ) -> Result<(U256, u64), PoolError> {
         let mut env = env;
         env.tx.gas_limit = 1_000_000;
 
@@ -230,10 +231,11 @@ impl Pool for PancakeV3Pool {
 
         let (value, gas_used) = evm_call(state_db, env, PeripheryAddress::PANCAKE_V3_QUOTER, call_data)?;
 
-        let ret = IPancakeQuoterV2::quoteExactInputSingleCall::abi_decode_returns(&value, false)?;
+        let ret = IPancakeQuoterV2::quoteExactInputSingleCall::abi_decode_returns(&value, false)
+            .map_err(|e| PoolError::DecodeError(e.to_string()))?;
 
         if ret.amountOut.is_zero() {
-            Err(eyre!("ZERO_OUT_AMOUNT"))
+            Err(PoolError::ZeroAmount("ZERO_OUT_AMOUNT"))
         } else {
             Ok((ret.amountOut - U256::from(1), gas_used))
         }
@@ -246,7 +248,7 @@ impl Pool for PancakeV3Pool {
         token_address_from: &Address,
         token_address_to: &Address,
         out_amount: U256,
-    ) -> Result<(U256, u64), ErrReport> {
+    ) -> Result<(U256, u64), PoolError> {
         let mut env = env;
         env.tx.gas_limit = 1_000_000;
 
@@ -263,10 +265,11 @@ impl Pool for PancakeV3Pool {
 
         let (value, gas_used) = evm_call(state_db, env, PeripheryAddress::PANCAKE_V3_QUOTER, call_data)?;
 
-        let ret = IPancakeQuoterV2::quoteExactOutputSingleCall::abi_decode_returns(&value, false)?;
+        let ret = IPancakeQuoterV2::quoteExactOutputSingleCall::abi_decode_returns(&value, false)
+            .map_err(|e| PoolError::DecodeError(e.to_string()))?;
 
         if ret.amountIn.is_zero() {
-            Err(eyre!("ZERO_IN_AMOUNT"))
+            Err(PoolError::ZeroAmount("ZERO_IN_AMOUNT"))
         } else {
             Ok((ret.amountIn + U256::from(1), gas_used))
         }
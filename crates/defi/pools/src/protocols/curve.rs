@@ -28,6 +28,7 @@ use loom_defi_abi::curve::{
     ICurveI128_2, ICurveI128_2_To, ICurveI128_2_To_Meta, ICurveI128_3, ICurveI128_4, ICurveU256_2, ICurveU256_2_Eth_To, ICurveU256_2_To,
     ICurveU256_3_Eth, ICurveU256_3_Eth_To, ICurveU256_3_Eth_To2,
 };
+use loom_types_blockchain::PoolError;
 
 #[derive(Clone, Debug)]
 pub enum CurveContract<P, T, N>
@@ -261,47 +262,47 @@ where
         match self {
             CurveContract::I128_2(interface) => match interface.get_dy(i.into(), j.into(), amount).call().await {
                 Ok(x) => Ok(x._0),
-                _ => Err(eyre!("CURVE_GET_DY_CALL_ERROR")),
+                _ => Err(eyre!(PoolError::StateMissing("CURVE_GET_DY_CALL_ERROR"))),
             },
             CurveContract::I128_2ToMeta(interface) => match interface.get_dy(i.into(), j.into(), amount).call().await {
                 Ok(x) => Ok(x._0),
-                _ => Err(eyre!("CURVE_GET_DY_CALL_ERROR")),
+                _ => Err(eyre!(PoolError::StateMissing("CURVE_GET_DY_CALL_ERROR"))),
             },
             CurveContract::I128_2To(interface) => match interface.get_dy(i.into(), j.into(), amount).call().await {
                 Ok(x) => Ok(x._0),
-                _ => Err(eyre!("CURVE_GET_DY_CALL_ERROR")),
+                _ => Err(eyre!(PoolError::StateMissing("CURVE_GET_DY_CALL_ERROR"))),
             },
             CurveContract::I128_3(interface) => match interface.get_dy(i.into(), j.into(), amount).call().await {
                 Ok(x) => Ok(x._0),
-                _ => Err(eyre!("CURVE_GET_DY_CALL_ERROR")),
+                _ => Err(eyre!(PoolError::StateMissing("CURVE_GET_DY_CALL_ERROR"))),
             },
             CurveContract::I128_4(interface) => match interface.get_dy(i.into(), j.into(), amount).call().await {
                 Ok(x) => Ok(x._0),
-                _ => Err(eyre!("CURVE_GET_DY_CALL_ERROR")),
+                _ => Err(eyre!(PoolError::StateMissing("CURVE_GET_DY_CALL_ERROR"))),
             },
             CurveContract::U256_2(interface) => match interface.get_dy(U256::from(i), U256::from(j), amount).call().await {
                 Ok(x) => Ok(x._0),
-                _ => Err(eyre!("CURVE_GET_DY_CALL_ERROR")),
+                _ => Err(eyre!(PoolError::StateMissing("CURVE_GET_DY_CALL_ERROR"))),
             },
             CurveContract::U256_2To(interface) => match interface.get_dy(U256::from(i), U256::from(j), amount).call().await {
                 Ok(x) => Ok(x._0),
-                _ => Err(eyre!("CURVE_GET_DY_CALL_ERROR")),
+                _ => Err(eyre!(PoolError::StateMissing("CURVE_GET_DY_CALL_ERROR"))),
             },
             CurveContract::U256_2EthTo(interface) => match interface.get_dy(U256::from(i), U256::from(j), amount).call().await {
                 Ok(x) => Ok(x._0),
-                _ => Err(eyre!("CURVE_GET_DY_CALL_ERROR")),
+                _ => Err(eyre!(PoolError::StateMissing("CURVE_GET_DY_CALL_ERROR"))),
             },
             CurveContract::U256_3EthTo(interface) => match interface.get_dy(U256::from(i), U256::from(j), amount).call().await {
                 Ok(x) => Ok(x._0),
-                _ => Err(eyre!("CURVE_GET_DY_CALL_ERROR")),
+                _ => Err(eyre!(PoolError::StateMissing("CURVE_GET_DY_CALL_ERROR"))),
             },
             CurveContract::U256_3EthTo2(interface) => match interface.get_dy(U256::from(i), U256::from(j), amount).call().await {
                 Ok(x) => Ok(x._0),
-                _ => Err(eyre!("CURVE_GET_DY_CALL_ERROR")),
+                _ => Err(eyre!(PoolError::StateMissing("CURVE_GET_DY_CALL_ERROR"))),
             },
             CurveContract::U256_3Eth(interface) => match interface.get_dy(U256::from(i), U256::from(j), amount).call().await {
                 Ok(x) => Ok(x._0),
-                _ => Err(eyre!("CURVE_GET_DY_CALL_ERROR")),
+                _ => Err(eyre!(PoolError::StateMissing("CURVE_GET_DY_CALL_ERROR"))),
             },
         }
     }
@@ -311,7 +312,7 @@ where
             CurveContract::I128_2To(interface) => Ok(interface.get_dx(i.into(), j.into(), amount).calldata().clone()),
             CurveContract::U256_3EthTo(interface) => Ok(interface.get_dx(U256::from(i), U256::from(j), amount).calldata().clone()),
             CurveContract::U256_3EthTo2(interface) => Ok(interface.get_dx(U256::from(i), U256::from(j), amount).calldata().clone()),
-            _ => Err(eyre!("CURVE_CANNOT_CALC_DX")),
+            _ => Err(eyre!(PoolError::UnsupportedDirection("CURVE_CANNOT_CALC_DX"))),
         };
         ret
     }
@@ -319,7 +320,7 @@ where
     pub fn get_dy_underlying_call_data(&self, i: u32, j: u32, amount: U256) -> Result<Bytes> {
         match self {
             CurveContract::I128_2ToMeta(interface) => Ok(interface.get_dy_underlying(i.into(), j.into(), amount).calldata().clone()),
-            _ => Err(eyre!("GET_DY_UNDERLYING_NOT_SUPPORTED")),
+            _ => Err(eyre!(PoolError::UnsupportedDirection("GET_DY_UNDERLYING_NOT_SUPPORTED"))),
         }
     }
 
@@ -357,14 +358,14 @@ where
                 amounts[i as usize] = amount;
                 Ok(interface.calc_token_amount(amounts, true).calldata().clone())
             }
-            _ => Err(eyre!("CURVE_TOKEN_AMOUNT_CALL_DATA_NOT_SUPPORTED")),
+            _ => Err(eyre!(PoolError::UnsupportedDirection("CURVE_TOKEN_AMOUNT_CALL_DATA_NOT_SUPPORTED"))),
         }
     }
 
     pub fn calc_withdraw_one_coin_call_data(&self, i: u32, amount: U256) -> Result<Bytes> {
         match self {
             CurveContract::I128_3(interface) => Ok(interface.calc_withdraw_one_coin(amount, i.into()).calldata().clone()),
-            _ => Err(eyre!("CURVE_WITHDRAW_ONE_COIN_NOT_SUPPORTED")),
+            _ => Err(eyre!(PoolError::UnsupportedDirection("CURVE_WITHDRAW_ONE_COIN_NOT_SUPPORTED"))),
         }
     }
 
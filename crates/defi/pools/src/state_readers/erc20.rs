@@ -1,7 +1,7 @@
 use alloy_primitives::{Address, U256};
 use alloy_sol_types::SolCall;
 use alloy_sol_types::SolInterface;
-use eyre::Result;
+use eyre::{eyre, Result};
 use loom_defi_abi::IERC20;
 use loom_evm_utils::evm::evm_call;
 use revm::primitives::Env;
@@ -24,4 +24,69 @@ impl ERC20StateReader {
         let call_return = IERC20::allowanceCall::abi_decode_returns(&call_data_result, false)?;
         Ok(call_return._0)
     }
+
+    pub fn decimals<DB: DatabaseRef>(db: &DB, env: Env, erc20_token: Address) -> Result<u8> {
+        let call_data_result = evm_call(db, env, erc20_token, IERC20::IERC20Calls::decimals(IERC20::decimalsCall {}).abi_encode())?.0;
+        Self::decode_decimals(&call_data_result)
+    }
+
+    pub fn symbol<DB: DatabaseRef>(db: &DB, env: Env, erc20_token: Address) -> Result<String> {
+        let call_data_result = evm_call(db, env, erc20_token, IERC20::IERC20Calls::symbol(IERC20::symbolCall {}).abi_encode())?.0;
+        Self::decode_string(&call_data_result)
+    }
+
+    pub fn name<DB: DatabaseRef>(db: &DB, env: Env, erc20_token: Address) -> Result<String> {
+        let call_data_result = evm_call(db, env, erc20_token, IERC20::IERC20Calls::name(IERC20::nameCall {}).abi_encode())?.0;
+        Self::decode_string(&call_data_result)
+    }
+
+    pub fn decode_decimals(output: &[u8]) -> Result<u8> {
+        let decimals = IERC20::decimalsCall::abi_decode_returns(output, false)?._0;
+        if decimals > U256::from(u8::MAX) {
+            return Err(eyre!("BAD_DECIMALS"));
+        }
+        Ok(decimals.to::<u8>())
+    }
+
+    /// Decodes the result of `symbol` or `name`. Some old tokens like MKR return bytes32 instead of string.
+    pub fn decode_string(output: &[u8]) -> Result<String> {
+        let value = match IERC20::symbolCall::abi_decode_returns(output, false) {
+            Ok(call_return) => call_return._0,
+            Err(_) if output.len() == 32 => String::from_utf8(output.iter().copied().take_while(|b| *b != 0).collect())?,
+            Err(e) => return Err(e.into()),
+        };
+        let value = value.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+        if value.is_empty() {
+            return Err(eyre!("EMPTY_STRING"));
+        }
+        Ok(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_primitives::B256;
+
+    #[test]
+    fn test_decode_string() {
+        let output = IERC20::symbolCall::abi_encode_returns(&("USDC".to_string(),));
+        assert_eq!(ERC20StateReader::decode_string(&output).unwrap(), "USDC");
+
+        let mut bytes32 = [0u8; 32];
+        bytes32[..3].copy_from_slice(b"MKR");
+        assert_eq!(ERC20StateReader::decode_string(B256::from(bytes32).as_slice()).unwrap(), "MKR");
+
+        assert!(ERC20StateReader::decode_string(&[0u8; 32]).is_err());
+        assert!(ERC20StateReader::decode_string(&[]).is_err());
+    }
+
+    #[test]
+    fn test_decode_decimals() {
+        let output = IERC20::decimalsCall::abi_encode_returns(&(U256::from(6),));
+        assert_eq!(ERC20StateReader::decode_decimals(&output).unwrap(), 6);
+
+        let output = IERC20::decimalsCall::abi_encode_returns(&(U256::from(256),));
+        assert!(ERC20StateReader::decode_decimals(&output).is_err());
+    }
 }
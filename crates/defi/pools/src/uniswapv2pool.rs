@@ -3,11 +3,12 @@ use alloy_provider::{Network, Provider};
 use alloy_rpc_types::BlockNumberOrTag;
 use alloy_sol_types::SolInterface;
 use alloy_transport::Transport;
-use eyre::{eyre, ErrReport, Result};
+use eyre::{ErrReport, Result};
 use lazy_static::lazy_static;
 use loom_defi_abi::uniswap2::IUniswapV2Pair;
 use loom_defi_abi::IERC20;
 use loom_defi_address_book::FactoryAddress;
+use loom_types_blockchain::PoolError;
 use loom_types_entities::required_state::RequiredState;
 use loom_types_entities::{AbiSwapEncoder, Pool, PoolClass, PoolProtocol, PreswapRequirement};
 use revm::primitives::Env;
@@ -182,13 +183,13 @@ impl UniswapV2Pool {
         Ok(ret)
     }
 
-    pub fn fetch_reserves(&self, state_db: &dyn DatabaseRef<Error = ErrReport>, env: Env) -> Result<(U256, U256)> {
+    pub fn fetch_reserves(&self, state_db: &dyn DatabaseRef<Error = ErrReport>, env: Env) -> Result<(U256, U256), PoolError> {
         let (reserve_0, reserve_1) = match self.reserves_cell {
             Some(cell) => {
                 if let Ok(storage_value) = state_db.storage_ref(self.get_address(), cell) {
                     Self::storage_to_reserves(storage_value)
                 } else {
-                    return Err(eyre!("ERROR_READING_STATE_DB"));
+                    return Err(PoolError::StateMissing("ERROR_READING_STATE_DB"));
                 }
             }
             None => UniswapV2StateReader::get_reserves(&state_db, env, self.get_address())?,
@@ -229,7 +230,7 @@ impl Pool for UniswapV2Pool {
         token_address_from: &Address,
         token_address_to: &Address,
         in_amount: U256,
-    ) -> Result<(U256, u64), ErrReport> {
+    ) -> Result<(U256, u64), PoolError> {
         let (reserves_0, reserves_1) = self.fetch_reserves(state_db, env)?;
 
         let (reserve_in, reserve_out) = match token_address_from < token_address_to {
@@ -237,16 +238,16 @@ impl Pool for UniswapV2Pool {
             false => (reserves_1, reserves_0),
         };
 
-        let amount_in_with_fee = in_amount.checked_mul(self.fee).ok_or(eyre!("AMOUNT_IN_WITH_FEE_OVERFLOW"))?;
-        let numerator = amount_in_with_fee.checked_mul(reserve_out).ok_or(eyre!("NUMERATOR_OVERFLOW"))?;
-        let denominator = reserve_in.checked_mul(U256::from(10000)).ok_or(eyre!("DENOMINATOR_OVERFLOW"))?;
-        let denominator = denominator.checked_add(amount_in_with_fee).ok_or(eyre!("DENOMINATOR_OVERFLOW_FEE"))?;
+        let amount_in_with_fee = in_amount.checked_mul(self.fee).ok_or(PoolError::MathOverflow("AMOUNT_IN_WITH_FEE_OVERFLOW"))?;
+        let numerator = amount_in_with_fee.checked_mul(reserve_out).ok_or(PoolError::MathOverflow("NUMERATOR_OVERFLOW"))?;
+        let denominator = reserve_in.checked_mul(U256::from(10000)).ok_or(PoolError::MathOverflow("DENOMINATOR_OVERFLOW"))?;
+        let denominator = denominator.checked_add(amount_in_with_fee).ok_or(PoolError::MathOverflow("DENOMINATOR_OVERFLOW_FEE"))?;
 
-        let out_amount = numerator.checked_div(denominator).ok_or(eyre!("CANNOT_CALCULATE_ZERO_RESERVE"))?;
+        let out_amount = numerator.checked_div(denominator).ok_or(PoolError::InsufficientLiquidity("CANNOT_CALCULATE_ZERO_RESERVE"))?;
         if out_amount > reserve_out {
-            Err(eyre!("RESERVE_EXCEEDED"))
+            Err(PoolError::InsufficientLiquidity("RESERVE_EXCEEDED"))
         } else if out_amount.is_zero() {
-            Err(eyre!("OUT_AMOUNT_IS_ZERO"))
+            Err(PoolError::ZeroAmount("OUT_AMOUNT_IS_ZERO"))
         } else {
             Ok((out_amount, 100_000))
         }
@@ -259,7 +260,7 @@ impl Pool for UniswapV2Pool {
         token_address_from: &Address,
         token_address_to: &Address,
         out_amount: U256,
-    ) -> Result<(U256, u64), ErrReport> {
+    ) -> Result<(U256, u64), PoolError> {
         let (reserves_0, reserves_1) = self.fetch_reserves(state_db, env)?;
 
         let (reserve_in, reserve_out) = match token_address_from < token_address_to {
@@ -268,19 +269,19 @@ impl Pool for UniswapV2Pool {
         };
 
         if out_amount > reserve_out {
-            return Err(eyre!("RESERVE_OUT_EXCEEDED"));
+            return Err(PoolError::InsufficientLiquidity("RESERVE_OUT_EXCEEDED"));
         }
-        let numerator = reserve_in.checked_mul(out_amount).ok_or(eyre!("NUMERATOR_OVERFLOW"))?;
-        let numerator = numerator.checked_mul(U256::from(10000)).ok_or(eyre!("NUMERATOR_OVERFLOW_FEE"))?;
-        let denominator = reserve_out.checked_sub(out_amount).ok_or(eyre!("DENOMINATOR_UNDERFLOW"))?;
-        let denominator = denominator.checked_mul(self.fee).ok_or(eyre!("DENOMINATOR_OVERFLOW_FEE"))?;
+        let numerator = reserve_in.checked_mul(out_amount).ok_or(PoolError::MathOverflow("NUMERATOR_OVERFLOW"))?;
+        let numerator = numerator.checked_mul(U256::from(10000)).ok_or(PoolError::MathOverflow("NUMERATOR_OVERFLOW_FEE"))?;
+        let denominator = reserve_out.checked_sub(out_amount).ok_or(PoolError::MathOverflow("DENOMINATOR_UNDERFLOW"))?;
+        let denominator = denominator.checked_mul(self.fee).ok_or(PoolError::MathOverflow("DENOMINATOR_OVERFLOW_FEE"))?;
 
         if denominator.is_zero() {
-            Err(eyre!("CANNOT_CALCULATE_ZERO_RESERVE"))
+            Err(PoolError::InsufficientLiquidity("CANNOT_CALCULATE_ZERO_RESERVE"))
         } else {
             let in_amount = numerator.div(denominator); // We assure before that denominator is not zero
             if in_amount.is_zero() {
-                Err(eyre!("IN_AMOUNT_IS_ZERO"))
+                Err(PoolError::ZeroAmount("IN_AMOUNT_IS_ZERO"))
             } else {
                 Ok((in_amount + U256::from(1), 100_000))
             }
@@ -13,6 +13,7 @@ use loom_defi_abi::uniswap3::IUniswapV3Pool::slot0Return;
 use loom_defi_abi::uniswap_periphery::ITickLens;
 use loom_defi_abi::IERC20;
 use loom_defi_address_book::{FactoryAddress, PeripheryAddress};
+use loom_types_blockchain::PoolError;
 use loom_types_entities::required_state::RequiredState;
 use loom_types_entities::{AbiSwapEncoder, Pool, PoolClass, PoolProtocol, PreswapRequirement};
 use revm::primitives::Env;
@@ -245,7 +246,7 @@ impl Pool for UniswapV3Pool {
         token_address_from: &Address,
         _token_address_to: &Address,
         in_amount: U256,
-    ) -> Result<(U256, u64), ErrReport> {
+    ) -> Result<(U256, u64), PoolError> {
         let (ret, gas_used) = if self.get_protocol() == PoolProtocol::UniswapV3 {
             let ret_virtual = UniswapV3PoolVirtual::simulate_swap_in_amount(&state_db, self, *token_address_from, in_amount)?;
 
@@ -259,7 +260,7 @@ impl Pool for UniswapV3Pool {
                     PeripheryAddress::UNISWAP_V3_QUOTER_V2,
                     *token_address_from,
                     *_token_address_to,
-                    self.fee.try_into()?,
+                    self.fee.try_into().map_err(|_| PoolError::MathOverflow("FEE_OVERFLOW"))?,
                     in_amount,
                 )?;
                 println!("calculate_out_amount ret_evm: {:?} ret: {:?} gas_used: {:?}", ret_evm, ret_virtual, gas_used);
@@ -277,14 +278,14 @@ impl Pool for UniswapV3Pool {
                 PeripheryAddress::UNISWAP_V3_QUOTER_V2,
                 *token_address_from,
                 *_token_address_to,
-                self.fee.try_into()?,
+                self.fee.try_into().map_err(|_| PoolError::MathOverflow("FEE_OVERFLOW"))?,
                 in_amount,
             )?;
             (ret_evm, gas_used)
         };
 
         if ret.is_zero() {
-            Err(eyre!("RETURN_RESULT_IS_ZERO"))
+            Err(PoolError::ZeroAmount("RETURN_RESULT_IS_ZERO"))
         } else {
             Ok((ret, gas_used)) // value, gas_used
         }
@@ -297,7 +298,7 @@ impl Pool for UniswapV3Pool {
         token_address_from: &Address,
         _token_address_to: &Address,
         out_amount: U256,
-    ) -> Result<(U256, u64), ErrReport> {
+    ) -> Result<(U256, u64), PoolError> {
         let (ret, gas_used) = if self.get_protocol() == PoolProtocol::UniswapV3 {
             let ret_virtual = UniswapV3PoolVirtual::simulate_swap_out_amount(&state_db, self, *token_address_from, out_amount)?;
 
@@ -311,7 +312,7 @@ impl Pool for UniswapV3Pool {
                     PeripheryAddress::UNISWAP_V3_QUOTER_V2,
                     *token_address_from,
                     *_token_address_to,
-                    self.fee.try_into()?,
+                    self.fee.try_into().map_err(|_| PoolError::MathOverflow("FEE_OVERFLOW"))?,
                     out_amount,
                 )?;
                 println!("calculate_out_amount ret_evm: {:?} ret: {:?} gas_used: {:?}", ret_evm, ret_virtual, gas_used);
@@ -330,14 +331,14 @@ impl Pool for UniswapV3Pool {
                 PeripheryAddress::UNISWAP_V3_QUOTER_V2,
                 *token_address_from,
                 *_token_address_to,
-                self.fee.try_into()?,
+                self.fee.try_into().map_err(|_| PoolError::MathOverflow("FEE_OVERFLOW"))?,
                 out_amount,
             )?;
             (ret_evm, gas_used)
         };
 
         if ret.is_zero() {
-            Err(eyre!("RETURN_RESULT_IS_ZERO"))
+            Err(PoolError::ZeroAmount("RETURN_RESULT_IS_ZERO"))
         } else {
             Ok((ret, gas_used)) // value, gas_used
         }
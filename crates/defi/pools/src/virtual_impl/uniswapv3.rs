@@ -6,6 +6,7 @@ use revm::DatabaseRef;
 use crate::db_reader::UniswapV3DBReader;
 use crate::virtual_impl::tick_provider::TickProviderEVMDB;
 use crate::UniswapV3Pool;
+use loom_types_blockchain::PoolError;
 use loom_types_entities::Pool;
 
 pub struct UniswapV3PoolVirtual;
@@ -181,7 +182,7 @@ impl UniswapV3PoolVirtual {
 
                     current_state.liquidity = if liquidity_net < 0 {
                         if current_state.liquidity < (-liquidity_net as u128) {
-                            return Err(eyre!("LIQUIDITY_UNDERFLOW"));
+                            return Err(eyre!(PoolError::InsufficientLiquidity("LIQUIDITY_UNDERFLOW")));
                         } else {
                             current_state.liquidity - (-liquidity_net as u128)
                         }
@@ -203,7 +204,7 @@ impl UniswapV3PoolVirtual {
             tracing::trace!("AmountOut : {amount_out}");
             Ok(amount_out)
         } else {
-            Err(eyre!("NOT_ENOUGH_LIQUIDITY"))
+            Err(eyre!(PoolError::InsufficientLiquidity("NOT_ENOUGH_LIQUIDITY")))
         }
     }
 
@@ -306,7 +307,7 @@ impl UniswapV3PoolVirtual {
 
                     current_state.liquidity = if liquidity_net < 0 {
                         if current_state.liquidity < (-liquidity_net as u128) {
-                            return Err(eyre!("LIQUIDITY_UNDERFLOW"));
+                            return Err(eyre!(PoolError::InsufficientLiquidity("LIQUIDITY_UNDERFLOW")));
                         } else {
                             current_state.liquidity - (-liquidity_net as u128)
                         }
@@ -330,7 +331,7 @@ impl UniswapV3PoolVirtual {
 
             Ok(amount_in)
         } else {
-            Err(eyre!("NOT_ENOUGH_LIQUIDITY"))
+            Err(eyre!(PoolError::InsufficientLiquidity("NOT_ENOUGH_LIQUIDITY")))
         }
     }
 }
@@ -1,3 +1,11 @@
 pub use preloader_actor::{preload_market_state, MarketStatePreloadedOneShotActor};
+pub use snapshot::{
+    MarketSnapshot, MarketSnapshotHeader, MarketSnapshotPayload, PoolSnapshot, MARKET_SNAPSHOT_MAGIC, MARKET_SNAPSHOT_VERSION,
+};
+pub use snapshot_actor::{market_snapshot_worker, write_market_snapshot, MarketStateSnapshotActor};
+pub use snapshot_loader::{load_market_snapshot, MarketStateSnapshotLoaderOneShotActor};
 
 mod preloader_actor;
+mod snapshot;
+mod snapshot_actor;
+mod snapshot_loader;
@@ -111,14 +111,15 @@ where
                 }
             }
         } else {
+            let balance_cell = BalanceCheater::fetch_balance_cell(client.clone(), token, owner).await?;
             match state.entry(token) {
                 Entry::Vacant(e) => {
                     let mut acc_state = fetch_account_state(client.clone(), token).await?;
-                    acc_state.storage.insert(BalanceCheater::get_balance_cell(token, owner)?.into(), balance.into());
+                    acc_state.storage.insert(balance_cell.into(), balance.into());
                     e.insert(acc_state);
                 }
                 Entry::Occupied(mut e) => {
-                    e.get_mut().storage.insert(BalanceCheater::get_balance_cell(token, owner)?.into(), balance.into());
+                    e.get_mut().storage.insert(balance_cell.into(), balance.into());
                 }
             }
         }
@@ -0,0 +1,191 @@
+use std::io::Cursor;
+use std::path::Path;
+
+use alloy_primitives::{keccak256, Address, BlockHash, BlockNumber, B256, U256};
+use eyre::{eyre, Result};
+use loom_evm_db::LoomDBSnapshot;
+use loom_types_entities::{PoolClass, PoolProtocol};
+use serde::{Deserialize, Serialize};
+
+/// Bytes every snapshot file starts with.
+pub const MARKET_SNAPSHOT_MAGIC: [u8; 8] = *b"LOOMSNAP";
+/// Version of the snapshot format. Snapshots of other versions are rejected.
+pub const MARKET_SNAPSHOT_VERSION: u32 = 2;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarketSnapshotHeader {
+    pub version: u32,
+    pub chain_id: u64,
+    pub block_number: BlockNumber,
+    pub block_hash: BlockHash,
+    pub payload_len: u64,
+    /// keccak256 of the payload
+    pub checksum: B256,
+}
+
+/// Pool of the market. Pools are rebuilt from the snapshot state on load.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolSnapshot {
+    pub address: Address,
+    pub class: PoolClass,
+    pub protocol: PoolProtocol,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarketSnapshotPayload {
+    pub db: LoomDBSnapshot,
+    pub pools: Vec<PoolSnapshot>,
+    /// Slots protected from eviction, see `MarketStateConfig::protected_slots`
+    pub protected_slots: Vec<(Address, Vec<U256>)>,
+}
+
+/// Market state and pool set at a block.
+///
+/// File layout is `MARKET_SNAPSHOT_MAGIC`, bincode encoded [MarketSnapshotHeader] and bincode encoded [MarketSnapshotPayload].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MarketSnapshot {
+    pub chain_id: u64,
+    pub block_number: BlockNumber,
+    pub block_hash: BlockHash,
+    pub payload: MarketSnapshotPayload,
+}
+
+impl MarketSnapshot {
+    pub fn new(
+        chain_id: u64,
+        block_number: BlockNumber,
+        block_hash: BlockHash,
+        db: LoomDBSnapshot,
+        pools: Vec<PoolSnapshot>,
+        protected_slots: Vec<(Address, Vec<U256>)>,
+    ) -> Self {
+        Self { chain_id, block_number, block_hash, payload: MarketSnapshotPayload { db, pools, protected_slots } }
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let payload = bincode::serialize(&self.payload)?;
+        let header = MarketSnapshotHeader {
+            version: MARKET_SNAPSHOT_VERSION,
+            chain_id: self.chain_id,
+            block_number: self.block_number,
+            block_hash: self.block_hash,
+            payload_len: payload.len() as u64,
+            checksum: keccak256(&payload),
+        };
+
+        let mut data = MARKET_SNAPSHOT_MAGIC.to_vec();
+        data.extend(bincode::serialize(&header)?);
+        data.extend(payload);
+        Ok(data)
+    }
+
+    /// Decodes and verifies the header. Returns the header and the payload offset.
+    pub fn decode_header(data: &[u8]) -> Result<(MarketSnapshotHeader, usize)> {
+        if !data.starts_with(&MARKET_SNAPSHOT_MAGIC) {
+            return Err(eyre!("SNAPSHOT_BAD_MAGIC"));
+        }
+        let mut cursor = Cursor::new(&data[MARKET_SNAPSHOT_MAGIC.len()..]);
+        let header: MarketSnapshotHeader = bincode::deserialize_from(&mut cursor)?;
+        if header.version != MARKET_SNAPSHOT_VERSION {
+            return Err(eyre!("SNAPSHOT_VERSION_NOT_SUPPORTED"));
+        }
+        Ok((header, MARKET_SNAPSHOT_MAGIC.len() + cursor.position() as usize))
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let (header, offset) = Self::decode_header(data)?;
+        let payload = &data[offset..];
+        if payload.len() as u64 != header.payload_len {
+            return Err(eyre!("SNAPSHOT_BAD_PAYLOAD_LEN"));
+        }
+        if keccak256(payload) != header.checksum {
+            return Err(eyre!("SNAPSHOT_BAD_CHECKSUM"));
+        }
+
+        Ok(Self {
+            chain_id: header.chain_id,
+            block_number: header.block_number,
+            block_hash: header.block_hash,
+            payload: bincode::deserialize(payload)?,
+        })
+    }
+
+    /// Writes the snapshot to a temporary file and renames it, so an interrupted write never replaces the previous snapshot.
+    pub async fn write(&self, path: &Path) -> Result<()> {
+        Self::write_encoded(path, self.encode()?).await
+    }
+
+    /// Writes the snapshot encoded by [MarketSnapshot::encode] the same way as [MarketSnapshot::write]
+    pub async fn write_encoded(path: &Path, data: Vec<u8>) -> Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, data).await?;
+        tokio::fs::rename(&tmp_path, path).await?;
+        Ok(())
+    }
+
+    pub async fn read(path: &Path) -> Result<Self> {
+        let data = tokio::fs::read(path).await?;
+        Self::decode(&data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Bytes, U256};
+    use loom_evm_db::AccountSnapshot;
+    use loom_types_entities::temp_file_path;
+
+    fn snapshot() -> MarketSnapshot {
+        let db = LoomDBSnapshot {
+            accounts: vec![AccountSnapshot {
+                address: Address::repeat_byte(1),
+                balance: U256::from(100),
+                nonce: 1,
+                code_hash: B256::repeat_byte(2),
+                storage: [(U256::from(1), U256::from(2))].into_iter().collect(),
+            }],
+            contracts: vec![(B256::repeat_byte(2), Bytes::from(vec![0x60, 0x00]))],
+        };
+        let pools = vec![PoolSnapshot { address: Address::repeat_byte(3), class: PoolClass::UniswapV2, protocol: PoolProtocol::UniswapV2 }];
+        let protected_slots = vec![(Address::repeat_byte(5), vec![U256::from(7)])];
+        MarketSnapshot::new(1, 100, BlockHash::repeat_byte(4), db, pools, protected_slots)
+    }
+
+    #[test]
+    fn test_encode_decode() -> Result<()> {
+        let snapshot = snapshot();
+        let data = snapshot.encode()?;
+
+        let (header, _) = MarketSnapshot::decode_header(&data)?;
+        assert_eq!(header.version, MARKET_SNAPSHOT_VERSION);
+        assert_eq!(header.chain_id, 1);
+        assert_eq!(header.block_number, 100);
+        assert_eq!(header.block_hash, BlockHash::repeat_byte(4));
+
+        assert_eq!(MarketSnapshot::decode(&data)?, snapshot);
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_corrupted() -> Result<()> {
+        let data = snapshot().encode()?;
+
+        let mut corrupted = data.clone();
+        *corrupted.last_mut().unwrap() ^= 0xff;
+        assert_eq!(MarketSnapshot::decode(&corrupted).unwrap_err().to_string(), "SNAPSHOT_BAD_CHECKSUM");
+
+        assert_eq!(MarketSnapshot::decode(&data[..data.len() - 1]).unwrap_err().to_string(), "SNAPSHOT_BAD_PAYLOAD_LEN");
+        assert_eq!(MarketSnapshot::decode(&data[1..]).unwrap_err().to_string(), "SNAPSHOT_BAD_MAGIC");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_read() -> Result<()> {
+        let (_dir, path) = temp_file_path("market_snapshot.bin")?;
+        let snapshot = snapshot();
+        snapshot.write(&path).await?;
+        assert_eq!(MarketSnapshot::read(&path).await?, snapshot);
+        Ok(())
+    }
+}
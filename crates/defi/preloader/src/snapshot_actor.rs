@@ -0,0 +1,192 @@
+use std::path::{Path, PathBuf};
+
+use alloy_primitives::{Address, U256};
+use eyre::{eyre, Result};
+use loom_core_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, SharedState, WorkerResult};
+use loom_core_actors_macros::{Accessor, Consumer};
+use loom_core_blockchain::{Blockchain, BlockchainState};
+use loom_evm_db::DatabaseLoomExt;
+use loom_types_entities::{Market, MarketState};
+use loom_types_events::MarketEvents;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+use crate::snapshot::{MarketSnapshot, PoolSnapshot};
+
+/// Writes the snapshot of the market state and pools. Returns the snapshot block number.
+/// Only the state db handle is cloned under the market state lock, the snapshot is built and encoded on a blocking thread.
+pub async fn write_market_snapshot<DB: DatabaseLoomExt + Clone + Send + 'static>(
+    path: &Path,
+    chain_id: u64,
+    market: SharedState<Market>,
+    market_state: SharedState<MarketState<DB>>,
+) -> Result<u64> {
+    let (block_number, block_hash, state_db, protected_slots) = {
+        let market_state_guard = market_state.read().await;
+        (
+            market_state_guard.block_number,
+            market_state_guard.block_hash,
+            market_state_guard.state_db.clone(),
+            market_state_guard.config.protected_slots.clone(),
+        )
+    };
+    if block_hash.is_zero() {
+        return Err(eyre!("MARKET_STATE_NOT_INITIALIZED"));
+    }
+
+    let mut pools: Vec<PoolSnapshot> = market
+        .read()
+        .await
+        .pools()
+        .values()
+        .map(|pool| PoolSnapshot { address: pool.get_address(), class: pool.get_class(), protocol: pool.get_protocol() })
+        .collect();
+    pools.sort_by_key(|pool| pool.address);
+
+    let pools_len = pools.len();
+    let (accounts, data) = tokio::task::spawn_blocking(move || {
+        let db = state_db.snapshot();
+        let accounts = db.accounts.len();
+        let mut protected_slots: Vec<(Address, Vec<U256>)> = protected_slots
+            .into_iter()
+            .map(|(address, slots)| {
+                let mut slots: Vec<U256> = slots.into_iter().collect();
+                slots.sort();
+                (address, slots)
+            })
+            .collect();
+        protected_slots.sort_by_key(|(address, _)| *address);
+        MarketSnapshot::new(chain_id, block_number, block_hash, db, pools, protected_slots).encode().map(|data| (accounts, data))
+    })
+    .await??;
+    MarketSnapshot::write_encoded(path, data).await?;
+    info!(block_number, %block_hash, accounts, pools = pools_len, path = %path.display(), "Market snapshot written");
+
+    Ok(block_number)
+}
+
+pub async fn market_snapshot_worker<DB: DatabaseLoomExt + Send + Sync + Clone + 'static>(
+    path: PathBuf,
+    chain_id: u64,
+    interval_blocks: u64,
+    market: SharedState<Market>,
+    market_state: SharedState<MarketState<DB>>,
+    market_events_rx: Broadcaster<MarketEvents>,
+    shutdown_token: CancellationToken,
+) -> WorkerResult {
+    subscribe!(market_events_rx);
+
+    // market state is loaded from the snapshot or preloaded before the actor is started
+    let mut last_block_number = market_state.read().await.block_number;
+
+    loop {
+        tokio::select! {
+            msg = market_events_rx.recv() => {
+                let market_event_msg : Result<MarketEvents, RecvError> = msg;
+                match market_event_msg {
+                    Ok(MarketEvents::BlockStateUpdate { .. }) => {
+                        let block_number = market_state.read().await.block_number;
+                        if block_number < last_block_number + interval_blocks {
+                            continue;
+                        }
+                        match write_market_snapshot(&path, chain_id, market.clone(), market_state.clone()).await {
+                            Ok(block_number) => last_block_number = block_number,
+                            Err(e) => error!("write_market_snapshot error : {}", e),
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        error!("market_snapshot_worker {}", e)
+                    }
+                }
+            }
+            _ = shutdown_token.cancelled() => {
+                info!("Writing market snapshot on shutdown");
+                if let Err(e) = write_market_snapshot(&path, chain_id, market.clone(), market_state.clone()).await {
+                    error!("write_market_snapshot error : {}", e)
+                }
+                break;
+            }
+        }
+    }
+
+    Ok("DONE".to_string())
+}
+
+/// Writes the snapshot of the market state and pools every `interval_blocks` blocks and when the shutdown token is cancelled.
+/// The worker finishes after the shutdown snapshot is written.
+#[derive(Accessor, Consumer)]
+pub struct MarketStateSnapshotActor<DB> {
+    path: PathBuf,
+    chain_id: u64,
+    interval_blocks: u64,
+    shutdown_token: CancellationToken,
+    #[accessor]
+    market: Option<SharedState<Market>>,
+    #[accessor]
+    market_state: Option<SharedState<MarketState<DB>>>,
+    #[consumer]
+    market_events_rx: Option<Broadcaster<MarketEvents>>,
+}
+
+impl<DB> MarketStateSnapshotActor<DB>
+where
+    DB: DatabaseLoomExt + Send + Sync + Clone + 'static,
+{
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            chain_id: 1,
+            interval_blocks: 100,
+            shutdown_token: CancellationToken::new(),
+            market: None,
+            market_state: None,
+            market_events_rx: None,
+        }
+    }
+
+    pub fn with_chain_id(self, chain_id: u64) -> Self {
+        Self { chain_id, ..self }
+    }
+
+    pub fn with_interval_blocks(self, interval_blocks: u64) -> Self {
+        Self { interval_blocks: interval_blocks.max(1), ..self }
+    }
+
+    pub fn with_shutdown_token(self, shutdown_token: CancellationToken) -> Self {
+        Self { shutdown_token, ..self }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain, state: &BlockchainState<DB>) -> Self {
+        Self {
+            chain_id: bc.chain_id(),
+            market: Some(bc.market()),
+            market_state: Some(state.market_state()),
+            market_events_rx: Some(bc.market_events_channel()),
+            ..self
+        }
+    }
+}
+
+impl<DB> Actor for MarketStateSnapshotActor<DB>
+where
+    DB: DatabaseLoomExt + Send + Sync + Clone + 'static,
+{
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(market_snapshot_worker(
+            self.path.clone(),
+            self.chain_id,
+            self.interval_blocks,
+            self.market.clone().unwrap(),
+            self.market_state.clone().unwrap(),
+            self.market_events_rx.clone().unwrap(),
+            self.shutdown_token.clone(),
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "MarketStateSnapshotActor"
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use alloy_eips::{BlockId, BlockNumberOrTag};
+use alloy_network::Ethereum;
+use alloy_primitives::Address;
+use alloy_provider::Provider;
+use alloy_rpc_types::BlockTransactionsKind;
+use alloy_transport::Transport;
+use eyre::{eyre, ErrReport, OptionExt, Result};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use loom_core_actors::{Accessor, Actor, ActorResult, SharedState, WorkerResult};
+use loom_core_actors_macros::Accessor;
+use loom_core_blockchain::{Blockchain, BlockchainState};
+use loom_defi_market::fetch_and_add_pool_by_address;
+use loom_defi_pools::{MaverickPool, PancakeV3Pool, UniswapV2Pool, UniswapV3Pool};
+use loom_evm_db::DatabaseLoomExt;
+use loom_node_debug_provider::DebugProviderExt;
+use loom_types_blockchain::debug_trace_block;
+use loom_types_entities::{BlockHistoryEntry, BlockHistoryState, Market, MarketState, PoolClass, PoolProtocol, PoolWrapper};
+use revm::primitives::Env;
+use revm::{Database, DatabaseCommit, DatabaseRef};
+use tracing::{debug, error, info};
+
+use crate::snapshot::{MarketSnapshot, PoolSnapshot};
+
+/// Number of blocks fetched and traced concurrently while catching up to the chain head
+const CATCH_UP_CONCURRENCY: usize = 16;
+
+/// Rebuilds the pool from the snapshot state without requests to the node.
+fn restore_pool<DB: DatabaseRef<Error = ErrReport>>(db: &DB, pool_snapshot: &PoolSnapshot) -> Result<PoolWrapper> {
+    let env = Env::default();
+    let address = pool_snapshot.address;
+    let pool = match (pool_snapshot.class, pool_snapshot.protocol) {
+        (PoolClass::UniswapV2, _) => PoolWrapper::new(Arc::new(UniswapV2Pool::fetch_pool_data_evm(db, env, address)?)),
+        (PoolClass::UniswapV3, PoolProtocol::PancakeV3) => {
+            PoolWrapper::new(Arc::new(PancakeV3Pool::fetch_pool_data_evm(db, env, address)?))
+        }
+        (PoolClass::UniswapV3, PoolProtocol::Maverick) => PoolWrapper::new(Arc::new(MaverickPool::fetch_pool_data_evm(db, env, address)?)),
+        (PoolClass::UniswapV3, _) => PoolWrapper::new(Arc::new(UniswapV3Pool::fetch_pool_data_evm(db, env, address)?)),
+        _ => return Err(eyre!("POOL_CLASS_NOT_SUPPORTED")),
+    };
+    // storage missing in the snapshot is read as zero
+    if pool.get_protocol() != pool_snapshot.protocol || pool.get_tokens().iter().any(|token| token.is_zero()) {
+        return Err(eyre!("POOL_STATE_INCOMPLETE"));
+    }
+    Ok(pool)
+}
+
+/// Loads the snapshot, replays state diffs of blocks mined after the snapshot block and replaces the market state.
+/// Pools that cannot be rebuilt from the snapshot state are fetched from the node.
+pub async fn load_market_snapshot<P, T, DB>(
+    client: P,
+    path: PathBuf,
+    chain_id: u64,
+    market: SharedState<Market>,
+    market_state: SharedState<MarketState<DB>>,
+) -> WorkerResult
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + DebugProviderExt<T, Ethereum> + Send + Sync + Clone + 'static,
+    DB: DatabaseRef<Error = ErrReport>
+        + Database<Error = ErrReport>
+        + DatabaseCommit
+        + DatabaseLoomExt
+        + BlockHistoryState
+        + Send
+        + Sync
+        + Clone
+        + 'static,
+{
+    let snapshot = MarketSnapshot::read(&path).await?;
+    if snapshot.chain_id != chain_id {
+        return Err(eyre!("SNAPSHOT_CHAIN_ID_MISMATCH"));
+    }
+
+    let snapshot_block = client
+        .get_block_by_number(BlockNumberOrTag::Number(snapshot.block_number), BlockTransactionsKind::Hashes)
+        .await?
+        .ok_or_eyre("SNAPSHOT_BLOCK_NOT_FOUND")?;
+    if snapshot_block.header.hash != snapshot.block_hash {
+        return Err(eyre!("SNAPSHOT_BLOCK_NOT_CANONICAL"));
+    }
+
+    info!(
+        block_number = snapshot.block_number,
+        accounts = snapshot.payload.db.accounts.len(),
+        storage = snapshot.payload.db.storage_len(),
+        contracts = snapshot.payload.db.contracts.len(),
+        pools = snapshot.payload.pools.len(),
+        "Loading market snapshot"
+    );
+
+    let (mut db, mut config) = {
+        let market_state_guard = market_state.read().await;
+        (market_state_guard.state_db.clone(), market_state_guard.config.clone())
+    };
+    db.apply_snapshot(snapshot.payload.db);
+    let mut db = db.maintain();
+    for (address, slots) in snapshot.payload.protected_slots {
+        config.protect_slots(address, slots);
+    }
+
+    let mut pools: BTreeMap<PoolWrapper, Vec<(Address, Address)>> = BTreeMap::new();
+    let mut fetched_pools: Vec<(Address, PoolClass)> = Vec::new();
+
+    for pool_snapshot in snapshot.payload.pools.iter() {
+        match restore_pool(&db, pool_snapshot) {
+            Ok(pool) => {
+                config.add_force_insert(pool.get_address());
+                config.disable_cell_vec(pool.get_address(), pool.get_read_only_cell_vec());
+                let swap_directions = pool.get_swap_directions();
+                pools.insert(pool, swap_directions);
+            }
+            Err(error) => {
+                debug!(address = %pool_snapshot.address, %error, "Pool is not restored from snapshot");
+                fetched_pools.push((pool_snapshot.address, pool_snapshot.class));
+            }
+        }
+    }
+
+    let head_block_number = client.get_block_number().await?;
+    let mut block_hash = snapshot.block_hash;
+
+    // blocks are fetched and traced concurrently and applied in order
+    let mut block_history_entries = stream::iter(snapshot.block_number + 1..=head_block_number)
+        .map(|block_number| {
+            let client = client.clone();
+            async move {
+                let block = client
+                    .get_block_by_number(BlockNumberOrTag::Number(block_number), BlockTransactionsKind::Hashes)
+                    .await?
+                    .ok_or_eyre("BLOCK_NOT_FOUND")?;
+                let (_, post) = debug_trace_block(client, BlockId::Number(BlockNumberOrTag::Number(block_number)), true).await?;
+                Ok::<BlockHistoryEntry, ErrReport>(BlockHistoryEntry::new(block.header, None, None, Some(post)))
+            }
+        })
+        .buffered(CATCH_UP_CONCURRENCY);
+
+    while let Some(block_history_entry) = block_history_entries.try_next().await? {
+        if block_history_entry.parent_hash() != block_hash {
+            return Err(eyre!("SNAPSHOT_CATCH_UP_REORG"));
+        }
+
+        db = db.apply_update(&block_history_entry, &config);
+        block_hash = block_history_entry.hash();
+        debug!(block_number = block_history_entry.number(), %block_hash, "Market snapshot state diff applied");
+    }
+
+    {
+        let mut market_state_guard = market_state.write().await;
+        market_state_guard.state_db = db.maintain();
+        market_state_guard.config = config;
+        market_state_guard.block_number = head_block_number.max(snapshot.block_number);
+        market_state_guard.block_hash = block_hash;
+    }
+
+    let restored_pools = pools.len();
+    {
+        let mut market_guard = market.write().await;
+        for pool in pools.keys() {
+            // Ignore error if pool already exists
+            let _ = market_guard.add_pool(pool.clone());
+        }
+        let swap_paths = market_guard.build_swap_path_vec(&pools)?;
+        market_guard.add_paths(swap_paths);
+    }
+
+    for (pool_address, pool_class) in fetched_pools.iter() {
+        if let Err(error) =
+            fetch_and_add_pool_by_address(client.clone(), None, market.clone(), market_state.clone(), *pool_address, *pool_class).await
+        {
+            error!(%pool_address, %error, "Failed to fetch snapshot pool");
+        }
+    }
+
+    info!(
+        snapshot_block = snapshot.block_number,
+        head_block_number,
+        restored_pools,
+        fetched_pools = fetched_pools.len(),
+        "Market snapshot loaded"
+    );
+
+    Ok("DONE".to_string())
+}
+
+/// Replaces the market state and pools with the snapshot written by
+/// [MarketStateSnapshotActor](crate::MarketStateSnapshotActor) and catches up to the chain head.
+#[derive(Accessor)]
+pub struct MarketStateSnapshotLoaderOneShotActor<P, T, DB> {
+    client: P,
+    path: PathBuf,
+    chain_id: u64,
+    #[accessor]
+    market: Option<SharedState<Market>>,
+    #[accessor]
+    market_state: Option<SharedState<MarketState<DB>>>,
+    _t: PhantomData<T>,
+}
+
+impl<P, T, DB> MarketStateSnapshotLoaderOneShotActor<P, T, DB>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + DebugProviderExt<T, Ethereum> + Send + Sync + Clone + 'static,
+    DB: DatabaseRef + DatabaseCommit + Send + Sync + Clone + 'static,
+{
+    pub fn new(client: P, path: impl Into<PathBuf>) -> Self {
+        Self { client, path: path.into(), chain_id: 1, market: None, market_state: None, _t: PhantomData }
+    }
+
+    pub fn with_chain_id(self, chain_id: u64) -> Self {
+        Self { chain_id, ..self }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain, state: &BlockchainState<DB>) -> Self {
+        Self { chain_id: bc.chain_id(), market: Some(bc.market()), market_state: Some(state.market_state_commit()), ..self }
+    }
+}
+
+impl<P, T, DB> Actor for MarketStateSnapshotLoaderOneShotActor<P, T, DB>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + DebugProviderExt<T, Ethereum> + Send + Sync + Clone + 'static,
+    DB: DatabaseRef<Error = ErrReport>
+        + Database<Error = ErrReport>
+        + DatabaseCommit
+        + DatabaseLoomExt
+        + BlockHistoryState
+        + Send
+        + Sync
+        + Clone
+        + 'static,
+{
+    fn start_and_wait(&self) -> Result<()> {
+        let rt = tokio::runtime::Runtime::new()?; // we need a different runtime to wait for the result
+        let handler = rt.spawn(load_market_snapshot(
+            self.client.clone(),
+            self.path.clone(),
+            self.chain_id,
+            self.market.clone().unwrap(),
+            self.market_state.clone().unwrap(),
+        ));
+
+        self.wait(Ok(vec![handler]))?;
+        rt.shutdown_background();
+        Ok(())
+    }
+
+    fn start(&self) -> ActorResult {
+        Err(eyre!("NEED_TO_BE_WAITED"))
+    }
+
+    fn name(&self) -> &'static str {
+        "MarketStateSnapshotLoaderOneShotActor"
+    }
+}
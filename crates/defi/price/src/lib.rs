@@ -1,3 +1,7 @@
+mod market_price_actor;
+mod market_pricer;
 mod price_actor;
 
+pub use market_price_actor::MarketPriceActor;
+pub use market_pricer::{weighted_price, MarketPricer, PriceQuote, TokenPools};
 pub use price_actor::PriceActor;
@@ -0,0 +1,137 @@
+use eyre::{eyre, ErrReport};
+use loom_core_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, SharedState, WorkerResult};
+use loom_core_actors_macros::{Accessor, Consumer};
+use loom_core_blockchain::{Blockchain, BlockchainState};
+use loom_evm_utils::evm_env::env_for_block;
+use loom_types_entities::{Market, MarketState};
+use loom_types_events::MarketEvents;
+use revm::DatabaseRef;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error, info};
+
+use crate::market_pricer::MarketPricer;
+
+pub async fn market_price_worker<DB: DatabaseRef<Error = ErrReport> + Send + Sync + Clone + 'static>(
+    pricer: MarketPricer,
+    market: SharedState<Market>,
+    market_state: SharedState<MarketState<DB>>,
+    market_events_rx: Broadcaster<MarketEvents>,
+) -> WorkerResult {
+    subscribe!(market_events_rx);
+
+    let mut next_block: Option<(u64, u64)> = None;
+
+    loop {
+        let market_event = match market_events_rx.recv().await {
+            Ok(market_event) => market_event,
+            Err(RecvError::Closed) => {
+                error!("Market events channel closed");
+                return Err(eyre!("MARKET_EVENTS_RX_CLOSED"));
+            }
+            Err(RecvError::Lagged(lag)) => {
+                info!("Market events channel lagged: {}", lag);
+                continue;
+            }
+        };
+
+        match market_event {
+            MarketEvents::BlockHeaderUpdate { block_number, timestamp, .. } => {
+                next_block = Some((block_number + 1, timestamp + 12));
+            }
+            MarketEvents::BlockStateUpdate { .. } => {
+                let Some((next_block_number, next_block_timestamp)) = next_block else {
+                    continue;
+                };
+                let env = env_for_block(next_block_number, next_block_timestamp);
+
+                let start_time = std::time::Instant::now();
+                let token_pools = MarketPricer::token_pools(&*market.read().await);
+                let state_db = market_state.read().await.state_db.clone();
+
+                let pricer = pricer.clone();
+                let prices = match tokio::task::spawn_blocking(move || pricer.price_tokens(&token_pools, &state_db, &env)).await {
+                    Ok(prices) => prices,
+                    Err(error) => {
+                        error!(%error, "Token pricing failed");
+                        continue;
+                    }
+                };
+
+                let market_guard = market.read().await;
+                for (token_address, price) in prices.iter() {
+                    if let Some(token) = market_guard.get_token(token_address) {
+                        token.set_eth_price(Some(*price));
+                    }
+                }
+                debug!(
+                    block_number = next_block_number - 1,
+                    priced = prices.len(),
+                    tokens = market_guard.tokens().len(),
+                    elapsed = start_time.elapsed().as_millis(),
+                    "Token prices updated"
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Updates ETH prices of all tokens of the market reachable from WETH after every block state update.
+/// Prices are calculated by [`MarketPricer`] from a clone of the current market state on a blocking thread.
+#[derive(Accessor, Consumer)]
+pub struct MarketPriceActor<DB> {
+    pricer: MarketPricer,
+    #[accessor]
+    market: Option<SharedState<Market>>,
+    #[accessor]
+    market_state: Option<SharedState<MarketState<DB>>>,
+    #[consumer]
+    market_events_rx: Option<Broadcaster<MarketEvents>>,
+}
+
+impl<DB> Default for MarketPriceActor<DB> {
+    fn default() -> Self {
+        Self { pricer: MarketPricer::default(), market: None, market_state: None, market_events_rx: None }
+    }
+}
+
+impl<DB> MarketPriceActor<DB>
+where
+    DB: DatabaseRef<Error = ErrReport> + Send + Sync + Clone + 'static,
+{
+    pub fn new() -> Self {
+        MarketPriceActor::default()
+    }
+
+    pub fn with_pricer(self, pricer: MarketPricer) -> Self {
+        Self { pricer, ..self }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain, state: &BlockchainState<DB>) -> Self {
+        Self {
+            market: Some(bc.market()),
+            market_state: Some(state.market_state()),
+            market_events_rx: Some(bc.market_events_channel()),
+            ..self
+        }
+    }
+}
+
+impl<DB> Actor for MarketPriceActor<DB>
+where
+    DB: DatabaseRef<Error = ErrReport> + Send + Sync + Clone + 'static,
+{
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(market_price_worker(
+            self.pricer.clone(),
+            self.market.clone().unwrap(),
+            self.market_state.clone().unwrap(),
+            self.market_events_rx.clone().unwrap(),
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "MarketPriceActor"
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+
+use alloy_primitives::utils::Unit;
+use alloy_primitives::{Address, U256};
+use alloy_sol_types::SolCall;
+use eyre::{ErrReport, Result};
+use loom_defi_abi::IERC20;
+use loom_evm_utils::evm::evm_call;
+use loom_evm_utils::NWETH;
+use loom_types_entities::{Market, PoolWrapper};
+use revm::primitives::Env;
+use revm::DatabaseRef;
+use tracing::trace;
+
+const ONE_ETHER: U256 = Unit::ETHER.wei_const();
+
+/// Price of the token quoted by one pool, weighted by the ETH value of the pool liquidity
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceQuote {
+    pub pool: Address,
+    /// Token amount for one ETH
+    pub price: U256,
+    /// ETH value of the thinner side of the pool
+    pub liquidity: U256,
+}
+
+/// Liquidity weighted average of the quotes
+pub fn weighted_price(quotes: &[PriceQuote]) -> Option<U256> {
+    let total_liquidity = quotes.iter().fold(U256::ZERO, |acc, quote| acc.saturating_add(quote.liquidity));
+    if total_liquidity.is_zero() {
+        return None;
+    }
+    let price =
+        quotes.iter().fold(U256::ZERO, |acc, quote| acc.saturating_add(quote.price.saturating_mul(quote.liquidity) / total_liquidity));
+    (!price.is_zero()).then_some(price)
+}
+
+/// Enabled pools of the market by the token they swap from
+pub type TokenPools = HashMap<Address, Vec<PoolWrapper>>;
+
+/// Prices tokens of the market walking the pool graph from WETH level by level. Every token of the level is priced
+/// with the liquidity weighted average of the quotes from pools connecting it to tokens priced on the previous levels.
+/// Disabled pools and pools with less than `min_liquidity_eth` on any side are ignored. Liquidity is the token balance
+/// of the pool address, so pools that don't hold their tokens are ignored too.
+#[derive(Clone, Debug)]
+pub struct MarketPricer {
+    probe_eth_amount: U256,
+    min_liquidity_eth: U256,
+    max_depth: usize,
+}
+
+impl Default for MarketPricer {
+    fn default() -> Self {
+        Self { probe_eth_amount: NWETH::from_float(0.1), min_liquidity_eth: NWETH::from_float(10.0), max_depth: 4 }
+    }
+}
+
+impl MarketPricer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_probe_eth_amount(self, probe_eth_amount: U256) -> Self {
+        Self { probe_eth_amount, ..self }
+    }
+
+    pub fn with_min_liquidity_eth(self, min_liquidity_eth: U256) -> Self {
+        Self { min_liquidity_eth, ..self }
+    }
+
+    pub fn with_max_depth(self, max_depth: usize) -> Self {
+        Self { max_depth, ..self }
+    }
+
+    fn balance_of<DB: DatabaseRef<Error = ErrReport>>(db: &DB, env: &Env, token: Address, owner: Address) -> Result<U256> {
+        let (output, _) = evm_call(db, env.clone(), token, IERC20::balanceOfCall { account: owner }.abi_encode())?;
+        Ok(IERC20::balanceOfCall::abi_decode_returns(&output, false)?._0)
+    }
+
+    /// Quotes the price of `token_to` swapping `probe_eth_amount` worth of `token_from`. Returns None for thin pools.
+    pub fn quote<DB: DatabaseRef<Error = ErrReport>>(
+        &self,
+        db: &DB,
+        env: &Env,
+        pool: &PoolWrapper,
+        token_from: Address,
+        price_from: U256,
+        token_to: Address,
+    ) -> Result<Option<PriceQuote>> {
+        let amount_in = self.probe_eth_amount.saturating_mul(price_from) / ONE_ETHER;
+        if amount_in.is_zero() || self.probe_eth_amount.is_zero() {
+            return Ok(None);
+        }
+        let liquidity_from = Self::balance_of(db, env, token_from, pool.get_address())?.saturating_mul(ONE_ETHER) / price_from;
+        if liquidity_from < self.min_liquidity_eth {
+            return Ok(None);
+        }
+
+        let (amount_out, _) = pool.calculate_out_amount(db, env.clone(), &token_from, &token_to, amount_in)?;
+        let price = amount_out.saturating_mul(ONE_ETHER) / self.probe_eth_amount;
+        if price.is_zero() {
+            return Ok(None);
+        }
+        let liquidity_to = Self::balance_of(db, env, token_to, pool.get_address())?.saturating_mul(ONE_ETHER) / price;
+        if liquidity_to < self.min_liquidity_eth {
+            return Ok(None);
+        }
+
+        Ok(Some(PriceQuote { pool: pool.get_address(), price, liquidity: liquidity_from.min(liquidity_to) }))
+    }
+
+    /// Pool graph of the market for [MarketPricer::price_tokens]. Disabled pools are not included. Pools are shared,
+    /// so the graph is cheap to build under the market lock and can be priced after the lock is released.
+    pub fn token_pools(market: &Market) -> TokenPools {
+        let mut token_pools = TokenPools::new();
+        for (pool_address, pool) in market.pools().iter() {
+            if market.is_pool_disabled(pool_address) {
+                continue;
+            }
+            for token_from in pool.get_tokens() {
+                token_pools.entry(token_from).or_default().push(pool.clone());
+            }
+        }
+        token_pools
+    }
+
+    /// Returns prices of all tokens reachable from WETH, as the token amount for one ETH
+    pub fn price_tokens<DB: DatabaseRef<Error = ErrReport>>(&self, token_pools: &TokenPools, db: &DB, env: &Env) -> HashMap<Address, U256> {
+        let mut prices: HashMap<Address, U256> = HashMap::from([(NWETH::ADDRESS, ONE_ETHER)]);
+        let mut level: Vec<Address> = vec![NWETH::ADDRESS];
+
+        for _ in 0..self.max_depth {
+            let mut quotes: HashMap<Address, Vec<PriceQuote>> = HashMap::new();
+
+            for token_from in level.iter() {
+                let price_from = prices[token_from];
+                let Some(pools) = token_pools.get(token_from) else {
+                    continue;
+                };
+                for pool in pools.iter() {
+                    let pool_address = pool.get_address();
+                    for (_, token_to) in pool.get_swap_directions().into_iter().filter(|(from, _)| from == token_from) {
+                        if prices.contains_key(&token_to) {
+                            continue;
+                        }
+                        match self.quote(db, env, pool, *token_from, price_from, token_to) {
+                            Ok(Some(quote)) => quotes.entry(token_to).or_default().push(quote),
+                            Ok(None) => {}
+                            Err(error) => trace!(%pool_address, %token_to, %error, "Price quote failed"),
+                        }
+                    }
+                }
+            }
+
+            level.clear();
+            for (token, token_quotes) in quotes {
+                if let Some(price) = weighted_price(&token_quotes) {
+                    prices.insert(token, price);
+                    level.push(token);
+                }
+            }
+            if level.is_empty() {
+                break;
+            }
+        }
+        prices
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use loom_evm_db::LoomDB;
+    use loom_types_blockchain::PoolError;
+    use loom_types_entities::required_state::RequiredState;
+    use loom_types_entities::{AbiSwapEncoder, Pool};
+    use revm::primitives::{AccountInfo, Bytecode, Bytes};
+
+    /// Swaps token0 to token1 at a fixed rate
+    struct RatePool {
+        address: Address,
+        token0: Address,
+        token1: Address,
+        /// token1 amount for one token0
+        rate: U256,
+    }
+
+    impl Pool for RatePool {
+        fn get_address(&self) -> Address {
+            self.address
+        }
+
+        fn get_tokens(&self) -> Vec<Address> {
+            vec![self.token0, self.token1]
+        }
+
+        fn get_swap_directions(&self) -> Vec<(Address, Address)> {
+            vec![(self.token0, self.token1), (self.token1, self.token0)]
+        }
+
+        fn calculate_out_amount(
+            &self,
+            _state: &dyn DatabaseRef<Error = ErrReport>,
+            _env: Env,
+            token_address_from: &Address,
+            _token_address_to: &Address,
+            in_amount: U256,
+        ) -> Result<(U256, u64), PoolError> {
+            let out_amount = if *token_address_from == self.token0 { in_amount * self.rate } else { in_amount / self.rate };
+            Ok((out_amount, 100_000))
+        }
+
+        fn calculate_in_amount(
+            &self,
+            _state: &dyn DatabaseRef<Error = ErrReport>,
+            _env: Env,
+            token_address_from: &Address,
+            _token_address_to: &Address,
+            out_amount: U256,
+        ) -> Result<(U256, u64), PoolError> {
+            let in_amount = if *token_address_from == self.token0 { out_amount / self.rate } else { out_amount * self.rate };
+            Ok((in_amount, 100_000))
+        }
+
+        fn can_flash_swap(&self) -> bool {
+            false
+        }
+
+        fn get_encoder(&self) -> &dyn AbiSwapEncoder {
+            &RateAbiSwapEncoder
+        }
+
+        fn get_state_required(&self) -> Result<RequiredState> {
+            Ok(RequiredState::new())
+        }
+    }
+
+    struct RateAbiSwapEncoder;
+
+    impl AbiSwapEncoder for RateAbiSwapEncoder {}
+
+    // balanceOf returning the storage slot keyed by the owner address:
+    // PUSH1 0x04 CALLDATALOAD SLOAD PUSH1 0x00 MSTORE PUSH1 0x20 PUSH1 0x00 RETURN
+    const TOKEN_CODE: [u8; 12] = [0x60, 0x04, 0x35, 0x54, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+
+    fn set_balance(db: &mut LoomDB, token: Address, owner: Address, balance: f64) {
+        let code = Bytecode::new_raw(Bytes::from_static(&TOKEN_CODE));
+        db.insert_account_info(token, AccountInfo { code: Some(code), ..Default::default() });
+        db.insert_account_storage(token, U256::from_be_slice(owner.as_slice()), NWETH::from_float(balance)).unwrap();
+    }
+
+    #[test]
+    fn test_price_tokens() {
+        let weth = NWETH::ADDRESS;
+        let token_a = Address::repeat_byte(0xa);
+        let token_b = Address::repeat_byte(0xb);
+        let token_c = Address::repeat_byte(0xc);
+
+        let weth_a = RatePool { address: Address::repeat_byte(1), token0: weth, token1: token_a, rate: U256::from(2000) };
+        let a_b = RatePool { address: Address::repeat_byte(2), token0: token_a, token1: token_b, rate: U256::from(2) };
+        // 1 ETH of liquidity is below the minimum, B is priced via A
+        let thin_weth_b = RatePool { address: Address::repeat_byte(3), token0: weth, token1: token_b, rate: U256::from(10) };
+        // disabled pools are neither used to price A nor to reach C
+        let disabled_weth_a = RatePool { address: Address::repeat_byte(4), token0: weth, token1: token_a, rate: U256::from(1000) };
+        let disabled_weth_c = RatePool { address: Address::repeat_byte(5), token0: weth, token1: token_c, rate: U256::from(1) };
+
+        let mut db = LoomDB::new();
+        set_balance(&mut db, weth, weth_a.address, 100.0);
+        set_balance(&mut db, token_a, weth_a.address, 200000.0);
+        set_balance(&mut db, token_a, a_b.address, 100000.0);
+        set_balance(&mut db, token_b, a_b.address, 200000.0);
+        set_balance(&mut db, weth, thin_weth_b.address, 1.0);
+        set_balance(&mut db, token_b, thin_weth_b.address, 10.0);
+        set_balance(&mut db, weth, disabled_weth_a.address, 1000.0);
+        set_balance(&mut db, token_a, disabled_weth_a.address, 1000000.0);
+        set_balance(&mut db, weth, disabled_weth_c.address, 1000.0);
+        set_balance(&mut db, token_c, disabled_weth_c.address, 1000.0);
+
+        let mut market = Market::default();
+        for pool in [weth_a, a_b, thin_weth_b, disabled_weth_a, disabled_weth_c] {
+            market.add_pool(pool).unwrap();
+        }
+        market.set_pool_disabled(Address::repeat_byte(4), true);
+        market.set_pool_disabled(Address::repeat_byte(5), true);
+
+        let prices = MarketPricer::new().price_tokens(&MarketPricer::token_pools(&market), &db, &Env::default());
+
+        assert_eq!(prices.len(), 3);
+        assert_eq!(prices[&weth], ONE_ETHER);
+        assert_eq!(prices[&token_a], NWETH::from_float(2000.0));
+        assert_eq!(prices[&token_b], NWETH::from_float(4000.0));
+    }
+
+    #[test]
+    fn test_weighted_price() {
+        assert_eq!(weighted_price(&[]), None);
+
+        let quotes = [
+            PriceQuote { pool: Address::repeat_byte(1), price: U256::from(3000), liquidity: U256::from(300) },
+            PriceQuote { pool: Address::repeat_byte(2), price: U256::from(2000), liquidity: U256::from(100) },
+        ];
+        assert_eq!(weighted_price(&quotes), Some(U256::from(2750)));
+
+        let quotes = [PriceQuote { pool: Address::repeat_byte(1), price: U256::from(3000), liquidity: U256::ZERO }];
+        assert_eq!(weighted_price(&quotes), None);
+    }
+}
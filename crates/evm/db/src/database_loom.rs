@@ -1,9 +1,11 @@
 use crate::fast_cache_db::FastDbAccount;
+use crate::{EvictionPolicy, LoomDBSnapshot};
 use alloy::primitives::map::HashMap;
 use alloy::primitives::{Address, U256};
 use eyre::ErrReport;
 use revm::primitives::AccountInfo;
 use revm::DatabaseRef;
+use std::collections::HashSet;
 
 pub trait DatabaseLoomExt {
     fn with_ext_db(&mut self, db: impl DatabaseRef<Error = ErrReport> + Send + Sync + 'static);
@@ -12,6 +14,10 @@ pub trait DatabaseLoomExt {
     fn contracts_len(&self) -> usize;
     fn accounts_len(&self) -> usize;
     fn storage_len(&self) -> usize;
+    fn rw_accounts_len(&self) -> usize;
+    fn ro_accounts_len(&self) -> usize;
+    fn rw_storage_len(&self) -> usize;
+    fn ro_storage_len(&self) -> usize;
 
     fn load_account(&mut self, address: Address) -> eyre::Result<&mut FastDbAccount>;
 
@@ -25,5 +31,15 @@ pub trait DatabaseLoomExt {
 
     fn replace_account_storage(&mut self, address: Address, storage: HashMap<U256, U256>) -> eyre::Result<()>;
 
+    fn snapshot(&self) -> LoomDBSnapshot;
+
+    fn apply_snapshot(&mut self, snapshot: LoomDBSnapshot);
+
     fn maintain(self) -> Self;
+
+    /// Enables tracking of accesses and eviction of cold records with the policy
+    fn set_eviction_policy(&mut self, policy: EvictionPolicy);
+
+    /// Evicts cold records except storage of `protected_accounts` and `protected_slots`. Does nothing if eviction is not configured.
+    fn evict(self, protected_accounts: &HashSet<Address>, protected_slots: &std::collections::HashMap<Address, HashSet<U256>>) -> Self;
 }
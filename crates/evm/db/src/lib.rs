@@ -2,6 +2,8 @@ pub use alloydb::AlloyDB;
 pub use database_helpers::DatabaseHelpers;
 pub use database_loom::DatabaseLoomExt;
 pub use loom_db::LoomDB;
+pub use loom_db_eviction::{AccessTracker, EvictionPolicy, EvictionStats, LoomDBEviction};
+pub use loom_db_snapshot::{AccountSnapshot, LoomDBSnapshot};
 
 pub type LoomDBType = LoomDB;
 
@@ -12,4 +14,6 @@ pub mod fast_cache_db;
 pub mod fast_hasher;
 mod in_memory_db;
 mod loom_db;
+mod loom_db_eviction;
 mod loom_db_helper;
+mod loom_db_snapshot;
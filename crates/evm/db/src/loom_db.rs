@@ -1,8 +1,9 @@
 use crate::alloydb::AlloyDB;
 use crate::fast_cache_db::FastDbAccount;
 use crate::fast_hasher::SimpleBuildHasher;
+use crate::loom_db_eviction::{EvictionPolicy, LoomDBEviction};
 use crate::loom_db_helper::LoomDBHelper;
-use crate::DatabaseLoomExt;
+use crate::{DatabaseLoomExt, LoomDBSnapshot};
 use alloy::consensus::constants::KECCAK_EMPTY;
 use alloy::eips::BlockNumberOrTag;
 use alloy::primitives::map::HashMap;
@@ -16,7 +17,7 @@ use revm::db::{AccountState as DBAccountState, EmptyDBTyped};
 use revm::primitives::{Account, AccountInfo, Bytecode};
 use revm::{Database, DatabaseCommit, DatabaseRef};
 use std::collections::hash_map::Entry;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::{Debug, Formatter};
 use std::sync::Arc;
 use tracing::{error, trace};
@@ -34,6 +35,8 @@ where
     pub read_only_db: Option<Arc<LoomDB>>,
     #[cfg_attr(feature = "serde", serde(skip))]
     pub ext_db: Option<Arc<dyn DatabaseRef<Error = ErrReport> + Send + Sync>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub eviction: Option<Arc<LoomDBEviction>>,
 }
 
 impl Debug for LoomDB {
@@ -61,6 +64,7 @@ impl LoomDB {
         Self {
             read_only_db: None,
             ext_db: None,
+            eviction: None,
             accounts: Default::default(),
             contracts,
             logs: Default::default(),
@@ -218,6 +222,30 @@ impl LoomDB {
         Ok(())
     }
 
+    /// remove storage slots from both layers, removed slots are fetched from ext_db on next read
+    pub fn remove_account_storage(&mut self, slots: &[(Address, U256)]) {
+        fn remove_slots(accounts: &mut HashMap<Address, FastDbAccount>, slots: &[(Address, U256)]) {
+            for (address, slot) in slots {
+                if let Some(account) = accounts.get_mut(address) {
+                    // storage is not complete anymore
+                    if account.storage.remove(slot).is_some() && account.account_state == DBAccountState::StorageCleared {
+                        account.account_state = DBAccountState::Touched;
+                    }
+                }
+            }
+        }
+
+        remove_slots(&mut self.accounts, slots);
+        if let Some(read_only_db) = self.read_only_db.as_mut() {
+            if slots
+                .iter()
+                .any(|(address, slot)| read_only_db.accounts.get(address).is_some_and(|account| account.storage.contains_key(slot)))
+            {
+                remove_slots(&mut Arc::make_mut(read_only_db).accounts, slots);
+            }
+        }
+    }
+
     /// replace account storage without overriding account info
     pub fn replace_account_storage(&mut self, address: Address, storage: HashMap<U256, U256>) -> Result<()> {
         let account = self.load_account(address)?;
@@ -251,7 +279,7 @@ impl LoomDB {
 
         let read_only_db = Some(Arc::new(read_only_db));
 
-        LoomDB { read_only_db, ext_db: self.ext_db, ..Default::default() }
+        LoomDB { read_only_db, ext_db: self.ext_db, eviction: self.eviction, ..Default::default() }
     }
 
     pub fn merge_accounts(self) -> LoomDB {
@@ -281,7 +309,7 @@ impl LoomDB {
             None
         };
 
-        LoomDB { read_only_db, ext_db: self.ext_db, ..Default::default() }
+        LoomDB { read_only_db, ext_db: self.ext_db, eviction: self.eviction, ..Default::default() }
     }
 
     pub fn merge_cells(self) -> LoomDB {
@@ -311,7 +339,7 @@ impl LoomDB {
             None
         };
 
-        LoomDB { read_only_db, ext_db: self.ext_db, ..Default::default() }
+        LoomDB { read_only_db, ext_db: self.ext_db, eviction: self.eviction, ..Default::default() }
     }
 
     pub fn apply_geth_update(&mut self, update: BTreeMap<Address, GethAccountState>) {
@@ -475,13 +503,29 @@ impl DatabaseLoomExt for LoomDB {
     }
 
     fn accounts_len(&self) -> usize {
-        self.rw_accounts_len() + self.ro_contracts_len()
+        self.rw_accounts_len() + self.ro_accounts_len()
     }
 
     fn storage_len(&self) -> usize {
         self.rw_storage_len() + self.ro_storage_len()
     }
 
+    fn rw_accounts_len(&self) -> usize {
+        self.rw_accounts_len()
+    }
+
+    fn ro_accounts_len(&self) -> usize {
+        self.ro_accounts_len()
+    }
+
+    fn rw_storage_len(&self) -> usize {
+        self.rw_storage_len()
+    }
+
+    fn ro_storage_len(&self) -> usize {
+        self.ro_storage_len()
+    }
+
     fn load_account(&mut self, address: Address) -> Result<&mut FastDbAccount> {
         self.load_ro_rw_ext_account(address)
     }
@@ -506,15 +550,32 @@ impl DatabaseLoomExt for LoomDB {
         self.replace_account_storage(address, storage)
     }
 
+    fn snapshot(&self) -> LoomDBSnapshot {
+        self.snapshot()
+    }
+
+    fn apply_snapshot(&mut self, snapshot: LoomDBSnapshot) {
+        self.apply_snapshot(snapshot)
+    }
+
     fn maintain(self) -> Self {
         self.merge_all()
     }
+
+    fn set_eviction_policy(&mut self, policy: EvictionPolicy) {
+        self.eviction = Some(Arc::new(LoomDBEviction::new(policy)));
+    }
+
+    fn evict(self, protected_accounts: &HashSet<Address>, protected_slots: &std::collections::HashMap<Address, HashSet<U256>>) -> Self {
+        self.evict(protected_accounts, protected_slots).0
+    }
 }
 
 impl DatabaseRef for LoomDB {
     type Error = eyre::ErrReport;
     fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
         trace!(%address, "basic_ref");
+        self.touch_account(address);
         let result = match address {
             Address::ZERO => Ok(Some(AccountInfo::default())),
             _ => match self.accounts.get(&address) {
@@ -538,6 +599,7 @@ impl DatabaseRef for LoomDB {
 
     fn storage_ref(&self, address: Address, slot: U256) -> Result<U256, Self::Error> {
         trace!(%address, ?slot, "storage_ref");
+        self.touch_slot(address, slot);
 
         match self.accounts.get(&address) {
             Some(acc_entry) => match acc_entry.storage.get(&slot) {
@@ -571,6 +633,7 @@ impl Database for LoomDB {
 
     fn basic(&mut self, address: Address) -> std::result::Result<Option<AccountInfo>, Self::Error> {
         trace!(%address, "basic");
+        self.touch_account(address);
 
         let basic = match self.accounts.entry(address) {
             Entry::Occupied(entry) => entry.into_mut(),
@@ -599,6 +662,7 @@ impl Database for LoomDB {
     /// It is assumed that account is already loaded.
     fn storage(&mut self, address: Address, slot: U256) -> std::result::Result<U256, Self::Error> {
         trace!(%address, ?slot, "storage");
+        self.touch_slot(address, slot);
 
         match self.accounts.entry(address) {
             Entry::Occupied(mut acc_entry) => {
@@ -771,6 +835,24 @@ mod test {
         assert_eq!(new_state.storage(account, key).unwrap(), value);
     }
 
+    #[test]
+    fn test_remove_account_storage() {
+        let account = Address::with_last_byte(42);
+        let (key0, key1, key2) = (U256::from(1), U256::from(2), U256::from(3));
+        let mut init_state = LoomDB::new();
+        init_state.insert_account_storage(account, key0, U256::from(10)).unwrap();
+        init_state.insert_account_storage(account, key2, U256::from(30)).unwrap();
+
+        let mut new_state = LoomDB::new().with_ro_db(Some(init_state));
+        new_state.insert_account_storage(account, key1, U256::from(20)).unwrap();
+
+        new_state.remove_account_storage(&[(account, key0), (account, key1)]);
+
+        assert!(!new_state.is_rw_ro_slot(&account, &key0));
+        assert!(!new_state.is_rw_ro_slot(&account, &key1));
+        assert_eq!(new_state.storage_ref(account, key2).unwrap(), U256::from(30));
+    }
+
     #[test]
     fn test_replace_account_storage() {
         let account = Address::with_last_byte(42);
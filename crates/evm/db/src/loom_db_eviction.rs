@@ -0,0 +1,398 @@
+use crate::loom_db::LoomDB;
+use alloy::primitives::{Address, U256};
+use revm::db::AccountState as DBAccountState;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock, RwLockWriteGuard};
+use tracing::debug;
+
+/// Policy for evicting cold storage slots from the read only layer of [LoomDB].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Keeps at most `max_storage_len` slots in the read only layer, least recently used slots are evicted first.
+    Lru { max_storage_len: usize },
+    /// Evicts slots and accounts that were not read or written during the last `blocks` blocks.
+    Idle { blocks: u64 },
+}
+
+/// Number of shards of access records. Readers of records in different shards never wait for each other.
+const ACCESS_SHARDS: usize = 64;
+
+/// Block of the last access by key split into shards by the key hash. A shard is write locked only to add a new key.
+#[derive(Debug)]
+struct AccessRecords<K> {
+    shards: Vec<RwLock<HashMap<K, AtomicU64>>>,
+}
+
+impl<K> Default for AccessRecords<K> {
+    fn default() -> Self {
+        Self { shards: (0..ACCESS_SHARDS).map(|_| RwLock::new(HashMap::default())).collect() }
+    }
+}
+
+impl<K: Hash + Eq + Copy> AccessRecords<K> {
+    fn shard_index(&self, key: &K) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        hasher.finish() as usize % self.shards.len()
+    }
+
+    fn shard(&self, key: &K) -> &RwLock<HashMap<K, AtomicU64>> {
+        &self.shards[self.shard_index(key)]
+    }
+
+    #[inline]
+    fn touch(&self, key: K, block: u64) {
+        let shard = self.shard(&key);
+        if let Ok(records) = shard.read() {
+            if let Some(last_access) = records.get(&key) {
+                if last_access.load(Ordering::Relaxed) != block {
+                    last_access.store(block, Ordering::Relaxed);
+                }
+                return;
+            }
+        }
+        if let Ok(mut records) = shard.write() {
+            records.entry(key).or_insert_with(|| AtomicU64::new(block)).store(block, Ordering::Relaxed);
+        }
+    }
+
+    fn last_access(&self, key: &K) -> Option<u64> {
+        self.shard(key).read().ok()?.get(key).map(|last_access| last_access.load(Ordering::Relaxed))
+    }
+
+    /// Write locks all shards
+    fn write(&self) -> Option<AccessRecordsGuard<'_, K>> {
+        let shards = self.shards.iter().map(|shard| shard.write().ok()).collect::<Option<Vec<_>>>()?;
+        Some(AccessRecordsGuard { records: self, shards })
+    }
+}
+
+struct AccessRecordsGuard<'a, K> {
+    records: &'a AccessRecords<K>,
+    shards: Vec<RwLockWriteGuard<'a, HashMap<K, AtomicU64>>>,
+}
+
+impl<K: Hash + Eq + Copy> AccessRecordsGuard<'_, K> {
+    fn shard_mut(&mut self, key: &K) -> &mut HashMap<K, AtomicU64> {
+        let idx = self.records.shard_index(key);
+        &mut self.shards[idx]
+    }
+
+    /// Returns the last access of the key, records `first_access` for a new key
+    fn get_or_insert(&mut self, key: K, first_access: u64) -> u64 {
+        self.shard_mut(&key).entry(key).or_insert_with(|| AtomicU64::new(first_access)).load(Ordering::Relaxed)
+    }
+
+    fn get(&self, key: &K) -> Option<u64> {
+        self.shards[self.records.shard_index(key)].get(key).map(|last_access| last_access.load(Ordering::Relaxed))
+    }
+
+    fn remove(&mut self, key: &K) {
+        self.shard_mut(key).remove(key);
+    }
+
+    fn retain(&mut self, mut f: impl FnMut(&K) -> bool) {
+        for shard in self.shards.iter_mut() {
+            shard.retain(|key, _| f(key));
+        }
+    }
+}
+
+/// Block of the last access to accounts and storage slots. Shared by all clones of [LoomDB].
+#[derive(Debug, Default)]
+pub struct AccessTracker {
+    block: AtomicU64,
+    accounts: AccessRecords<Address>,
+    slots: AccessRecords<(Address, U256)>,
+}
+
+impl AccessTracker {
+    pub fn block(&self) -> u64 {
+        self.block.load(Ordering::Relaxed)
+    }
+
+    fn next_block(&self) -> u64 {
+        self.block.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    #[inline]
+    pub fn touch_account(&self, address: Address) {
+        self.accounts.touch(address, self.block())
+    }
+
+    #[inline]
+    pub fn touch_slot(&self, address: Address, slot: U256) {
+        let block = self.block();
+        self.accounts.touch(address, block);
+        self.slots.touch((address, slot), block)
+    }
+
+    pub fn account_last_access(&self, address: &Address) -> Option<u64> {
+        self.accounts.last_access(address)
+    }
+
+    pub fn slot_last_access(&self, address: &Address, slot: &U256) -> Option<u64> {
+        self.slots.last_access(&(*address, *slot))
+    }
+}
+
+/// Eviction policy and access tracker of [LoomDB].
+#[derive(Debug)]
+pub struct LoomDBEviction {
+    pub policy: EvictionPolicy,
+    pub tracker: AccessTracker,
+}
+
+impl LoomDBEviction {
+    pub fn new(policy: EvictionPolicy) -> Self {
+        Self { policy, tracker: AccessTracker::default() }
+    }
+}
+
+/// Number of records removed by [LoomDB::evict].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EvictionStats {
+    pub accounts: usize,
+    pub slots: usize,
+}
+
+impl LoomDB {
+    pub fn with_eviction(self, policy: EvictionPolicy) -> Self {
+        Self { eviction: Some(Arc::new(LoomDBEviction::new(policy))), ..self }
+    }
+
+    pub fn eviction(&self) -> Option<&LoomDBEviction> {
+        self.eviction.as_deref()
+    }
+
+    #[inline]
+    pub(crate) fn touch_account(&self, address: Address) {
+        if let Some(eviction) = &self.eviction {
+            eviction.tracker.touch_account(address)
+        }
+    }
+
+    #[inline]
+    pub(crate) fn touch_slot(&self, address: Address, slot: U256) {
+        if let Some(eviction) = &self.eviction {
+            eviction.tracker.touch_slot(address, slot)
+        }
+    }
+
+    /// Advances the access tracker by one block and evicts cold records of the read only layer according to the policy.
+    /// Storage of `protected_accounts` and `protected_slots` is never evicted. Evicted records are fetched from `ext_db` when
+    /// they are read next time.
+    pub fn evict(
+        mut self,
+        protected_accounts: &HashSet<Address>,
+        protected_slots: &HashMap<Address, HashSet<U256>>,
+    ) -> (Self, EvictionStats) {
+        let Some(eviction) = self.eviction.clone() else { return (self, EvictionStats::default()) };
+        let block = eviction.tracker.next_block();
+
+        let (Some(mut account_records), Some(mut slot_records)) = (eviction.tracker.accounts.write(), eviction.tracker.slots.write())
+        else {
+            return (self, EvictionStats::default());
+        };
+        let Some(read_only_db) = self.read_only_db.take() else { return (self, EvictionStats::default()) };
+        let mut read_only_db = Arc::unwrap_or_clone(read_only_db);
+        // reads of evicted records must fall through to ext_db of this layer
+        read_only_db.ext_db = None;
+
+        // records that were never accessed are evicted first by LRU and aged from the first eviction by idle policy
+        let first_access = match eviction.policy {
+            EvictionPolicy::Lru { .. } => 0,
+            EvictionPolicy::Idle { .. } => block,
+        };
+
+        let mut slots: Vec<(u64, Address, U256)> = Vec::new();
+        for (address, account) in read_only_db.accounts.iter() {
+            account_records.get_or_insert(*address, first_access);
+            if protected_accounts.contains(address) {
+                continue;
+            }
+            let account_protected_slots = protected_slots.get(address);
+            for slot in account.storage.keys() {
+                if account_protected_slots.is_some_and(|account_protected_slots| account_protected_slots.contains(slot)) {
+                    continue;
+                }
+                let last_access = slot_records.get_or_insert((*address, *slot), first_access);
+                slots.push((last_access, *address, *slot));
+            }
+        }
+
+        match eviction.policy {
+            EvictionPolicy::Lru { max_storage_len } => {
+                let storage_len: usize = read_only_db.accounts.values().map(|account| account.storage.len()).sum();
+                let excess = storage_len.saturating_sub(max_storage_len).min(slots.len());
+                if excess > 0 {
+                    slots.select_nth_unstable_by_key(excess - 1, |(last_access, _, _)| *last_access);
+                }
+                slots.truncate(excess);
+            }
+            EvictionPolicy::Idle { blocks } => {
+                slots.retain(|(last_access, _, _)| last_access + blocks < block);
+            }
+        }
+
+        let mut stats = EvictionStats { slots: slots.len(), ..Default::default() };
+        let mut emptied_accounts: HashSet<Address> = HashSet::new();
+
+        for (_, address, slot) in slots {
+            if let Some(account) = read_only_db.accounts.get_mut(&address) {
+                account.storage.remove(&slot);
+                // storage is not complete anymore
+                if account.account_state == DBAccountState::StorageCleared {
+                    account.account_state = DBAccountState::Touched;
+                }
+                if account.storage.is_empty() {
+                    emptied_accounts.insert(address);
+                }
+            }
+            slot_records.remove(&(address, slot));
+        }
+
+        let is_cold_account = |address: &Address| match eviction.policy {
+            EvictionPolicy::Lru { .. } => emptied_accounts.contains(address),
+            EvictionPolicy::Idle { blocks } => account_records.get(address).is_some_and(|last_access| last_access + blocks < block),
+        };
+
+        let accounts: Vec<Address> = read_only_db
+            .accounts
+            .iter()
+            .filter(|(address, account)| account.storage.is_empty() && !protected_accounts.contains(*address) && is_cold_account(address))
+            .map(|(address, _)| *address)
+            .collect();
+
+        stats.accounts = accounts.len();
+        for address in accounts {
+            read_only_db.accounts.remove(&address);
+        }
+
+        // forget records that are not stored anymore
+        account_records.retain(|address| read_only_db.accounts.contains_key(address) || self.accounts.contains_key(address));
+        slot_records.retain(|(address, slot)| {
+            read_only_db.accounts.get(address).is_some_and(|account| account.storage.contains_key(slot))
+                || self.accounts.get(address).is_some_and(|account| account.storage.contains_key(slot))
+        });
+        drop(account_records);
+        drop(slot_records);
+
+        debug!(block, accounts = stats.accounts, slots = stats.slots, "LoomDB records evicted");
+
+        self.read_only_db = Some(Arc::new(read_only_db));
+        (self, stats)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use revm::primitives::AccountInfo;
+    use revm::DatabaseRef;
+
+    fn db_with_slots(policy: EvictionPolicy, accounts: &[Address]) -> LoomDB {
+        let mut db = LoomDB::new().with_eviction(policy);
+        for address in accounts {
+            db.insert_account_info(*address, AccountInfo { nonce: 1, ..Default::default() });
+            for slot in 0..4u64 {
+                db.insert_account_storage(*address, U256::from(slot), U256::from(slot + 1)).unwrap();
+            }
+        }
+        db.merge_all()
+    }
+
+    #[test]
+    fn test_evict_idle() {
+        let pool = Address::repeat_byte(1);
+        let token = Address::repeat_byte(2);
+        let cold = Address::repeat_byte(3);
+        let mut db = db_with_slots(EvictionPolicy::Idle { blocks: 2 }, &[pool, token, cold]);
+        let protected: HashSet<Address> = [pool].into();
+
+        for _ in 0..3 {
+            db.storage_ref(token, U256::from(1)).unwrap();
+            (db, _) = db.evict(&protected, &HashMap::default());
+        }
+        let (db, stats) = db.evict(&protected, &HashMap::default());
+
+        assert_eq!(stats, EvictionStats { accounts: 1, slots: 7 });
+        assert_eq!(db.ro_storage_len(), 5);
+        assert!(db.is_rw_ro_slot(&pool, &U256::from(3)));
+        assert!(db.is_rw_ro_slot(&token, &U256::from(1)));
+        assert!(!db.is_rw_ro_slot(&token, &U256::from(2)));
+        assert!(!db.is_rw_ro_account(&cold));
+    }
+
+    #[test]
+    fn test_evict_lru() {
+        let pool = Address::repeat_byte(1);
+        let token = Address::repeat_byte(2);
+        let db = db_with_slots(EvictionPolicy::Lru { max_storage_len: 8 }, &[pool, token]);
+        let protected: HashSet<Address> = [pool].into();
+
+        let (mut db, stats) = db.evict(&protected, &HashMap::default());
+        assert_eq!(stats, EvictionStats::default());
+
+        db.storage_ref(token, U256::from(0)).unwrap();
+        db.storage_ref(token, U256::from(1)).unwrap();
+        db.insert_account_storage(token, U256::from(10), U256::from(11)).unwrap();
+        let (db, stats) = db.merge_all().evict(&protected, &HashMap::default());
+
+        assert_eq!(stats, EvictionStats { accounts: 0, slots: 1 });
+        assert_eq!(db.ro_storage_len(), 8);
+        assert!(db.is_rw_ro_slot(&token, &U256::from(0)));
+        assert!(db.is_rw_ro_slot(&token, &U256::from(1)));
+    }
+
+    #[test]
+    fn test_evict_protected_slots() {
+        let pool = Address::repeat_byte(1);
+        let token = Address::repeat_byte(2);
+        let db = db_with_slots(EvictionPolicy::Idle { blocks: 0 }, &[token]);
+        // balance slot of the pool in the token contract
+        let protected_slots: HashMap<Address, HashSet<U256>> = [(token, HashSet::from([U256::from(1)]))].into_iter().collect();
+
+        let (db, _) = db.evict(&HashSet::from([pool]), &protected_slots);
+        let (db, stats) = db.evict(&HashSet::from([pool]), &protected_slots);
+
+        assert_eq!(stats, EvictionStats { accounts: 0, slots: 3 });
+        assert!(db.is_rw_ro_slot(&token, &U256::from(1)));
+        assert!(!db.is_rw_ro_slot(&token, &U256::from(2)));
+    }
+
+    #[test]
+    fn test_access_records_shards() {
+        let tracker = AccessTracker::default();
+        tracker.next_block();
+        for slot in 0..1000u64 {
+            tracker.touch_slot(Address::repeat_byte(1), U256::from(slot));
+        }
+        tracker.next_block();
+        tracker.touch_slot(Address::repeat_byte(1), U256::from(7));
+
+        assert_eq!(tracker.slot_last_access(&Address::repeat_byte(1), &U256::from(7)), Some(2));
+        assert_eq!(tracker.slot_last_access(&Address::repeat_byte(1), &U256::from(8)), Some(1));
+        assert_eq!(tracker.account_last_access(&Address::repeat_byte(1)), Some(2));
+        assert!(tracker.slots.shards.iter().filter(|shard| !shard.read().unwrap().is_empty()).count() > 1);
+    }
+
+    #[test]
+    fn test_evicted_slot_is_fetched_from_ext_db() {
+        let token = Address::repeat_byte(2);
+        let mut ext_db = LoomDB::new();
+        ext_db.insert_account_info(token, AccountInfo { nonce: 1, ..Default::default() });
+        ext_db.insert_account_storage(token, U256::from(1), U256::from(100)).unwrap();
+
+        let db = db_with_slots(EvictionPolicy::Idle { blocks: 0 }, &[token]).with_ext_db(ext_db);
+        let (db, _) = db.evict(&HashSet::new(), &HashMap::default());
+        let (db, stats) = db.evict(&HashSet::new(), &HashMap::default());
+
+        assert_eq!(stats.slots, 4);
+        assert_eq!(db.storage_ref(token, U256::from(1)).unwrap(), U256::from(100));
+        assert_eq!(db.basic_ref(token).unwrap().unwrap().nonce, 1);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::loom_db::LoomDB;
+use alloy::consensus::constants::KECCAK_EMPTY;
+use alloy::primitives::{Address, Bytes, B256, U256};
+use revm::db::AccountState as DBAccountState;
+use revm::primitives::{AccountInfo, Bytecode};
+use std::collections::BTreeMap;
+
+/// Account with its storage stored in [LoomDBSnapshot]. Code is referenced by `code_hash`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountSnapshot {
+    pub address: Address,
+    pub balance: U256,
+    pub nonce: u64,
+    pub code_hash: B256,
+    pub storage: BTreeMap<U256, U256>,
+}
+
+/// Accounts, storage and contracts of [LoomDB] including its read only layer. Records are sorted, so the same state always
+/// produces the same snapshot.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LoomDBSnapshot {
+    pub accounts: Vec<AccountSnapshot>,
+    pub contracts: Vec<(B256, Bytes)>,
+}
+
+impl LoomDBSnapshot {
+    pub fn storage_len(&self) -> usize {
+        self.accounts.iter().map(|account| account.storage.len()).sum()
+    }
+}
+
+impl LoomDB {
+    /// Creates snapshot of existing accounts. Read write records override read only ones.
+    pub fn snapshot(&self) -> LoomDBSnapshot {
+        let mut accounts: BTreeMap<Address, AccountSnapshot> = BTreeMap::new();
+        let mut contracts: BTreeMap<B256, Bytes> = BTreeMap::new();
+
+        for db in self.read_only_db.iter().map(|db| db.as_ref()).chain(std::iter::once(self)) {
+            for (code_hash, bytecode) in db.contracts.iter() {
+                if *code_hash != KECCAK_EMPTY && !code_hash.is_zero() && !bytecode.is_empty() {
+                    contracts.insert(*code_hash, bytecode.original_bytes());
+                }
+            }
+
+            for (address, account) in db.accounts.iter() {
+                if account.account_state == DBAccountState::NotExisting {
+                    accounts.remove(address);
+                    continue;
+                }
+                if let Some(code) = &account.info.code {
+                    if !code.is_empty() && account.info.code_hash != KECCAK_EMPTY {
+                        contracts.entry(account.info.code_hash).or_insert_with(|| code.original_bytes());
+                    }
+                }
+
+                let entry = accounts.entry(*address).or_insert_with(|| AccountSnapshot { address: *address, ..Default::default() });
+                entry.balance = account.info.balance;
+                entry.nonce = account.info.nonce;
+                entry.code_hash = account.info.code_hash;
+                if account.account_state == DBAccountState::StorageCleared {
+                    entry.storage.clear();
+                }
+                entry.storage.extend(account.storage.iter().map(|(slot, value)| (*slot, *value)));
+            }
+        }
+
+        LoomDBSnapshot { accounts: accounts.into_values().collect(), contracts: contracts.into_iter().collect() }
+    }
+
+    /// Inserts accounts, storage and contracts from the snapshot.
+    pub fn apply_snapshot(&mut self, snapshot: LoomDBSnapshot) {
+        for (code_hash, code) in snapshot.contracts {
+            self.contracts.insert(code_hash, Bytecode::new_raw(code));
+        }
+
+        for account in snapshot.accounts {
+            let code = if account.code_hash == KECCAK_EMPTY { None } else { self.contracts.get(&account.code_hash).cloned() };
+
+            let entry = self.accounts.entry(account.address).or_default();
+            entry.info = AccountInfo { balance: account.balance, nonce: account.nonce, code_hash: account.code_hash, code };
+            entry.account_state = DBAccountState::Touched;
+            entry.storage.extend(account.storage);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use revm::DatabaseRef;
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let contract = Address::repeat_byte(1);
+        let eoa = Address::repeat_byte(2);
+        let code = Bytecode::new_raw(Bytes::from(vec![0x60, 0x00, 0x60, 0x00, 0xf3]));
+        let code_hash = code.hash_slow();
+
+        let mut ro_db = LoomDB::new();
+        ro_db.insert_account_info(contract, AccountInfo { balance: U256::from(1), nonce: 1, code_hash, code: Some(code.clone()) });
+        ro_db.insert_account_storage(contract, U256::from(1), U256::from(100)).unwrap();
+        ro_db.insert_account_storage(contract, U256::from(2), U256::from(200)).unwrap();
+
+        let mut db = LoomDB::new().with_ro_db(Some(ro_db));
+        db.insert_account_info(eoa, AccountInfo { balance: U256::from(10), nonce: 5, ..Default::default() });
+        db.insert_account_info(contract, AccountInfo { balance: U256::from(2), nonce: 1, code_hash, code: Some(code.clone()) });
+        db.insert_account_storage(contract, U256::from(2), U256::from(300)).unwrap();
+
+        let snapshot = db.snapshot();
+        assert_eq!(snapshot.accounts.len(), 2);
+        assert_eq!(snapshot.contracts, vec![(code_hash, code.original_bytes())]);
+        assert_eq!(snapshot.storage_len(), 2);
+        assert_eq!(snapshot.accounts[0].address, contract);
+        assert_eq!(snapshot.accounts[0].balance, U256::from(2));
+
+        let mut restored = LoomDB::new();
+        restored.apply_snapshot(snapshot.clone());
+
+        let info = restored.basic_ref(contract).unwrap().unwrap();
+        assert_eq!(info.balance, U256::from(2));
+        assert_eq!(info.code_hash, code_hash);
+        assert_eq!(restored.code_by_hash_ref(code_hash).unwrap().original_bytes(), code.original_bytes());
+        assert_eq!(restored.storage_ref(contract, U256::from(1)).unwrap(), U256::from(100));
+        assert_eq!(restored.storage_ref(contract, U256::from(2)).unwrap(), U256::from(300));
+        assert_eq!(restored.basic_ref(eoa).unwrap().unwrap().nonce, 5);
+
+        assert_eq!(restored.snapshot(), snapshot);
+    }
+}
@@ -3,36 +3,25 @@ use alloy::eips::BlockNumHash;
 use alloy::primitives::TxHash;
 use alloy::rpc::types::trace::geth::AccountState;
 use alloy::rpc::types::Log;
+use alloy::sol_types::decode_revert_reason;
 use alloy::{
     primitives::{Address, Bytes, B256, U256},
     rpc::types::{AccessList, AccessListItem, Header, Transaction, TransactionRequest},
 };
 use eyre::eyre;
 use lazy_static::lazy_static;
+pub use loom_types_blockchain::EvmError;
 use loom_types_blockchain::GethStateUpdate;
-use revm::primitives::{Account, Env, ExecutionResult, HaltReason, Output, ResultAndState, TransactTo, CANCUN};
+use revm::primitives::{Account, Env, ExecutionResult, Output, ResultAndState, TransactTo, CANCUN};
 use revm::{Database, DatabaseCommit, DatabaseRef, Evm};
 use std::collections::BTreeMap;
 use std::fmt::Debug;
-use thiserror::Error;
 use tracing::{debug, error};
 
 lazy_static! {
     static ref COINBASE: Address = "0x1f9090aaE28b8a3dCeaDf281B0F12828e676c326".parse().unwrap();
 }
 
-#[derive(Debug, Error)]
-pub enum EvmError {
-    #[error("Evm transact error")]
-    TransactError,
-    #[error("Evm transact commit error with err={0}")]
-    TransactCommitError(String),
-    #[error("Reverted with reason={0}, gas_used={1}")]
-    Reverted(String, u64),
-    #[error("Halted with halt_reason={0:?}, gas_used={1}")]
-    Halted(HaltReason, u64),
-}
-
 fn parse_execution_result(execution_result: ExecutionResult, gas_used: u64) -> eyre::Result<(Vec<u8>, u64)> {
     match execution_result {
         ExecutionResult::Success { output: Output::Call(value), .. } => Ok((value.to_vec(), gas_used)),
@@ -170,6 +159,9 @@ pub fn convert_evm_result_to_rpc(
 }
 
 pub fn revert_bytes_to_string(bytes: &Bytes) -> String {
+    if let Some(reason) = decode_revert_reason(bytes) {
+        return reason;
+    }
     if bytes.len() < 4 {
         return format!("{:?}", bytes);
     }
@@ -1,5 +1,5 @@
 pub use nweth::NWETH;
-pub use revm_balances::BalanceCheater;
+pub use revm_balances::{cached_balance_slot, discover_balance_slot, BalanceCheater, BalanceSlot, BalanceSlotLayout};
 
 pub mod evm;
 pub mod evm_env;
@@ -1,21 +1,190 @@
+use crate::evm::evm_call;
 use crate::remv_db_direct_access::calc_hashmap_cell;
 use crate::{nweth, NWETH};
+use alloy::eips::BlockNumberOrTag;
+use alloy::primitives::{keccak256, Bytes};
+use alloy::sol_types::SolCall;
 use alloy::{network::Network, primitives::Address, providers::Provider, sol_types::private::U256, transports::Transport};
-use eyre::{eyre, Result};
+use eyre::{eyre, OptionExt, Result};
+use lazy_static::lazy_static;
+use loom_defi_abi::IERC20;
 use loom_defi_abi::IERC20::IERC20Instance;
-use loom_evm_db::LoomDBType;
+use loom_evm_db::{AlloyDB, LoomDBType};
 use loom_node_debug_provider::{AnvilProviderExt, DebugProviderExt};
-use tracing::error;
+use revm::db::CacheDB;
+use revm::interpreter::{opcode, Interpreter};
+use revm::primitives::{Env, TransactTo, CANCUN};
+use revm::{inspector_handle_register, Database, DatabaseRef, Evm, EvmContext, Inspector};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use tracing::{debug, error};
+
+/// Layout of the balances mapping of an ERC20 token.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BalanceSlotLayout {
+    /// keccak256(owner . slot)
+    Solidity,
+    /// keccak256(slot . owner)
+    Vyper,
+}
+
+/// Storage slot of the balances mapping of an ERC20 token.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BalanceSlot {
+    pub slot: U256,
+    pub layout: BalanceSlotLayout,
+}
+
+impl BalanceSlot {
+    pub fn new(slot: U256, layout: BalanceSlotLayout) -> Self {
+        Self { slot, layout }
+    }
+
+    /// Storage cell of the `owner` balance.
+    pub fn cell(&self, owner: Address) -> U256 {
+        let owner = U256::from_be_slice(owner.as_slice());
+        match self.layout {
+            BalanceSlotLayout::Solidity => calc_hashmap_cell(self.slot, owner),
+            BalanceSlotLayout::Vyper => calc_hashmap_cell(owner, self.slot),
+        }
+    }
+}
+
+lazy_static! {
+    static ref BALANCE_SLOTS: RwLock<HashMap<Address, BalanceSlot>> =
+        RwLock::new(HashMap::from([(NWETH::ADDRESS, BalanceSlot::new(U256::from(3), BalanceSlotLayout::Solidity))]));
+    static ref PROBE_OWNER: Address = "0x5ca1ab1e00000000000000000000000000b0b0b0".parse().unwrap();
+    static ref PROBE_BALANCE: U256 = U256::from(0x1234_5678_9abc_def0u64) << 64;
+}
+
+/// Records storage reads and 64 bytes keccak256 preimages of the call.
+#[derive(Default)]
+struct BalanceSlotInspector {
+    sloads: Vec<(Address, U256)>,
+    preimages: HashMap<U256, [u8; 64]>,
+}
+
+impl<DB: Database> Inspector<DB> for BalanceSlotInspector {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        match interp.current_opcode() {
+            opcode::SLOAD => {
+                if let Ok(slot) = interp.stack().peek(0) {
+                    // storage of the proxy for delegate calls
+                    self.sloads.push((interp.contract.target_address, slot));
+                }
+            }
+            opcode::KECCAK256 => {
+                if let (Ok(offset), Ok(size)) = (interp.stack().peek(0), interp.stack().peek(1)) {
+                    let offset: usize = offset.saturating_to();
+                    if size == U256::from(64) && offset.saturating_add(64) <= interp.shared_memory.len() {
+                        if let Ok(preimage) = <[u8; 64]>::try_from(interp.shared_memory.slice(offset, 64)) {
+                            self.preimages.insert(keccak256(preimage).into(), preimage);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn balance_of<DB: DatabaseRef>(db: DB, token: Address, owner: Address) -> Result<U256> {
+    let call_data = IERC20::balanceOfCall { account: owner }.abi_encode();
+    let (output, _) = evm_call(db, Env::default(), token, call_data)?;
+    Ok(IERC20::balanceOfCall::abi_decode_returns(&output, false)?._0)
+}
+
+/// Returns cached balance slot of the token.
+pub fn cached_balance_slot(token: Address) -> Option<BalanceSlot> {
+    BALANCE_SLOTS.read().ok()?.get(&token).copied()
+}
+
+/// Finds the balances mapping of the token by tracing `balanceOf` and caches it.
+///
+/// Candidates are storage reads of the token whose key is a keccak256 of the owner and a slot. A candidate is accepted if
+/// `balanceOf` returns the value written to it.
+pub fn discover_balance_slot<DB: DatabaseRef>(db: &DB, token: Address) -> Result<BalanceSlot> {
+    if let Some(balance_slot) = cached_balance_slot(token) {
+        return Ok(balance_slot);
+    }
+
+    let mut env = Env::default();
+    env.tx.transact_to = TransactTo::Call(token);
+    env.tx.data = Bytes::from(IERC20::balanceOfCall { account: *PROBE_OWNER }.abi_encode());
+
+    let mut evm = Evm::builder()
+        .with_ref_db(db)
+        .with_spec_id(CANCUN)
+        .with_env(Box::new(env))
+        .with_external_context(BalanceSlotInspector::default())
+        .append_handler_register(inspector_handle_register)
+        .build();
+
+    let result = evm.transact().map_err(|_| eyre!("TRANSACT_ERROR"))?;
+    if !result.result.is_success() {
+        return Err(eyre!("BALANCE_OF_FAILED"));
+    }
+    let inspector = evm.context.external;
+
+    let owner = U256::from_be_slice(PROBE_OWNER.as_slice());
+    for (address, cell) in inspector.sloads.into_iter().filter(|(address, _)| *address == token) {
+        let Some(preimage) = inspector.preimages.get(&cell) else { continue };
+        let (first, second) = (U256::from_be_slice(&preimage[..32]), U256::from_be_slice(&preimage[32..]));
+
+        let balance_slot = if first == owner {
+            BalanceSlot::new(second, BalanceSlotLayout::Solidity)
+        } else if second == owner {
+            BalanceSlot::new(first, BalanceSlotLayout::Vyper)
+        } else {
+            continue;
+        };
+
+        let mut probe_db = CacheDB::new(db);
+        probe_db.insert_account_storage(address, cell, *PROBE_BALANCE).map_err(|_| eyre!("ERROR_INSERTING_ACCOUNT_STORAGE"))?;
+        if balance_of(&probe_db, token, *PROBE_OWNER).ok() != Some(*PROBE_BALANCE) {
+            continue;
+        }
+
+        debug!(%token, slot = %balance_slot.slot, layout = ?balance_slot.layout, "Balance slot discovered");
+        if let Ok(mut balance_slots) = BALANCE_SLOTS.write() {
+            balance_slots.insert(token, balance_slot);
+        }
+        return Ok(balance_slot);
+    }
+
+    Err(eyre!("BALANCE_SLOT_NOT_FOUND"))
+}
 
 pub struct BalanceCheater {}
 
 #[allow(dead_code)]
 impl BalanceCheater {
+    /// Returns balance cell of tokens with known or already discovered balance slot.
     pub fn get_balance_cell(token: Address, owner: Address) -> Result<U256> {
-        match token {
-            NWETH::ADDRESS => Ok(calc_hashmap_cell(U256::from(3u32), U256::from_be_slice(owner.as_slice()))),
-            _ => Err(eyre!("ADDRESS_CELL_UNKNOWN")),
+        match cached_balance_slot(token) {
+            Some(balance_slot) => Ok(balance_slot.cell(owner)),
+            None => Err(eyre!("ADDRESS_CELL_UNKNOWN")),
+        }
+    }
+
+    /// Returns balance cell of the token, the balance slot is discovered on `db` if it is unknown.
+    pub fn get_balance_cell_db<DB: DatabaseRef>(db: &DB, token: Address, owner: Address) -> Result<U256> {
+        Ok(discover_balance_slot(db, token)?.cell(owner))
+    }
+
+    /// Returns balance cell of the token, the balance slot is discovered on the latest state of the node if it is unknown.
+    /// Requires multi thread tokio runtime.
+    pub async fn fetch_balance_cell<P, T, N>(client: P, token: Address, owner: Address) -> Result<U256>
+    where
+        N: Network,
+        T: Transport + Clone,
+        P: Provider<T, N> + Send + Sync + Clone + 'static,
+    {
+        if let Some(balance_slot) = cached_balance_slot(token) {
+            return Ok(balance_slot.cell(owner));
         }
+        let db = AlloyDB::new(client, BlockNumberOrTag::Latest.into()).ok_or_eyre("ALLOY_DB_NOT_CREATED")?;
+        Self::get_balance_cell_db(&db, token, owner)
     }
 
     pub async fn get_anvil_token_balance<P, T, N>(client: P, token: Address, owner: Address) -> eyre::Result<U256>
@@ -24,7 +193,8 @@ impl BalanceCheater {
         T: Transport + Clone,
         P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
     {
-        let value = client.get_storage_at(token, Self::get_balance_cell(token, owner)?).await?;
+        let balance_cell = Self::fetch_balance_cell(client.clone(), token, owner).await?;
+        let value = client.get_storage_at(token, balance_cell).await?;
 
         Ok(value)
     }
@@ -35,7 +205,7 @@ impl BalanceCheater {
         N: Network,
         P: Provider<T, N> + AnvilProviderExt<T, N> + Send + Sync + Clone + 'static,
     {
-        let balance_cell = Self::get_balance_cell(token, owner)?;
+        let balance_cell = Self::fetch_balance_cell(client.clone(), token, owner).await?;
 
         if let Err(e) = client.set_storage(token, balance_cell.into(), balance.into()).await {
             error!("{e}");
@@ -49,10 +219,10 @@ impl BalanceCheater {
             return Err(eyre!("STORAGE_NOT_SET"));
         }
 
-        let weth_instance = IERC20Instance::new(token, client.clone());
+        let token_instance = IERC20Instance::new(token, client.clone());
 
-        let balance = weth_instance.balanceOf(owner).call().await?;
-        if balance._0 != nweth::NWETH::from_float(1.0) {
+        let token_balance = token_instance.balanceOf(owner).call().await?;
+        if token_balance._0 != balance {
             return Err(eyre!("BALANCE_NOT_SET"));
         }
         Ok(())
@@ -68,7 +238,7 @@ impl BalanceCheater {
     }
 
     pub fn set_evm_token_balance(db: &mut LoomDBType, token: Address, owner: Address, balance: U256) -> eyre::Result<()> {
-        let balance_cell = calc_hashmap_cell(U256::from(3), U256::from_be_slice(owner.as_slice()));
+        let balance_cell = Self::get_balance_cell_db(&*db, token, owner)?;
 
         db.insert_account_storage(token, balance_cell, balance).map_err(|_| eyre!("ERROR_INSERTING_ACCOUNT_STORAGE"))
     }
@@ -77,3 +247,61 @@ impl BalanceCheater {
         Self::set_evm_token_balance(db, token, owner, NWETH::from_float(balance))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use loom_evm_db::LoomDB;
+    use revm::primitives::{AccountInfo, Bytecode};
+
+    // balanceOf(owner) returning sload(keccak256(owner . 3))
+    const SOLIDITY_TOKEN_CODE: [u8; 25] = [
+        0x60, 0x04, 0x35, 0x60, 0x00, 0x52, 0x60, 0x03, 0x60, 0x20, 0x52, 0x60, 0x40, 0x60, 0x00, 0x20, 0x54, 0x60, 0x00, 0x52, 0x60, 0x20,
+        0x60, 0x00, 0xf3,
+    ];
+    // balanceOf(owner) returning sload(keccak256(7 . owner))
+    const VYPER_TOKEN_CODE: [u8; 25] = [
+        0x60, 0x07, 0x60, 0x00, 0x52, 0x60, 0x04, 0x35, 0x60, 0x20, 0x52, 0x60, 0x40, 0x60, 0x00, 0x20, 0x54, 0x60, 0x00, 0x52, 0x60, 0x20,
+        0x60, 0x00, 0xf3,
+    ];
+
+    // delegatecall to the implementation with the same calldata, returns 32 bytes
+    fn proxy_code(implementation: Address) -> Vec<u8> {
+        let mut code = vec![0x36, 0x60, 0x00, 0x60, 0x00, 0x37, 0x60, 0x20, 0x60, 0x00, 0x36, 0x60, 0x00, 0x73];
+        code.extend_from_slice(implementation.as_slice());
+        code.extend_from_slice(&[0x5a, 0xf4, 0x50, 0x60, 0x20, 0x60, 0x00, 0xf3]);
+        code
+    }
+
+    fn insert_code(db: &mut LoomDB, address: Address, code: Vec<u8>) {
+        let code = Bytecode::new_raw(Bytes::from(code));
+        db.insert_account_info(address, AccountInfo { code_hash: code.hash_slow(), code: Some(code), ..Default::default() });
+    }
+
+    #[test]
+    fn test_discover_balance_slot() -> Result<()> {
+        let solidity_token = Address::repeat_byte(0x11);
+        let vyper_token = Address::repeat_byte(0x12);
+        let implementation = Address::repeat_byte(0x13);
+        let proxy_token = Address::repeat_byte(0x14);
+
+        let mut db = LoomDB::new();
+        insert_code(&mut db, solidity_token, SOLIDITY_TOKEN_CODE.to_vec());
+        insert_code(&mut db, vyper_token, VYPER_TOKEN_CODE.to_vec());
+        insert_code(&mut db, implementation, SOLIDITY_TOKEN_CODE.to_vec());
+        insert_code(&mut db, proxy_token, proxy_code(implementation));
+
+        assert!(BalanceCheater::get_balance_cell(solidity_token, Address::ZERO).is_err());
+        assert_eq!(discover_balance_slot(&db, solidity_token)?, BalanceSlot::new(U256::from(3), BalanceSlotLayout::Solidity));
+        assert_eq!(discover_balance_slot(&db, vyper_token)?, BalanceSlot::new(U256::from(7), BalanceSlotLayout::Vyper));
+        assert_eq!(discover_balance_slot(&db, proxy_token)?, BalanceSlot::new(U256::from(3), BalanceSlotLayout::Solidity));
+        assert_eq!(cached_balance_slot(vyper_token), Some(BalanceSlot::new(U256::from(7), BalanceSlotLayout::Vyper)));
+
+        let owner = Address::repeat_byte(0x22);
+        for token in [solidity_token, vyper_token, proxy_token] {
+            BalanceCheater::set_evm_token_balance(&mut db, token, owner, U256::from(1000))?;
+            assert_eq!(balance_of(&db, token, owner)?, U256::from(1000));
+        }
+        Ok(())
+    }
+}
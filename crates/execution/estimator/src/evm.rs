@@ -2,16 +2,22 @@ use alloy_consensus::TxEnvelope;
 use alloy_eips::eip2718::Encodable2718;
 use alloy_eips::BlockNumberOrTag;
 use alloy_network::{Ethereum, Network};
-use alloy_primitives::{Bytes, TxKind, U256};
+use alloy_primitives::{Bytes, TxHash, TxKind, U256};
 use alloy_provider::Provider;
 use alloy_rpc_types::{TransactionInput, TransactionRequest};
 use alloy_transport::Transport;
 use eyre::{eyre, Result};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc::UnboundedSender;
 use tracing::{debug, error, info, trace};
 
-use loom_core_blockchain::Strategy;
+use loom_core_blockchain::{Blockchain, Strategy};
 use loom_evm_utils::NWETH;
 use loom_types_entities::SwapEncoder;
 
@@ -20,18 +26,17 @@ use loom_core_actors_macros::{Consumer, Producer};
 use loom_evm_db::{AlloyDB, DatabaseLoomExt};
 use loom_evm_utils::evm::evm_access_list;
 use loom_evm_utils::evm_env::env_for_block;
-use loom_types_events::{MessageSwapCompose, SwapComposeData, SwapComposeMessage, TxComposeData, TxState};
+use loom_types_events::{MessageBlockHeader, MessageSwapCompose, SwapComposeData, SwapComposeMessage, TxComposeData, TxState};
 use revm::DatabaseRef;
 
-async fn estimator_task<T, N, DB>(
-    client: Option<impl Provider<T, N> + 'static>,
-    swap_encoder: impl SwapEncoder,
+/// Simulates the swap on the shared poststate snapshot and returns the request ready for signing.
+/// Returns None if the simulation has failed.
+fn estimate_swap<DB>(
+    swap_encoder: &impl SwapEncoder,
     estimate_request: SwapComposeData<DB>,
-    compose_channel_tx: Broadcaster<MessageSwapCompose<DB>>,
-) -> Result<()>
+    db: &DB,
+) -> Result<Option<MessageSwapCompose<DB>>>
 where
-    T: Transport + Clone,
-    N: Network,
     DB: DatabaseRef + DatabaseLoomExt + Send + Sync + Clone + 'static,
 {
     debug!(
@@ -73,23 +78,9 @@ where
         ..TransactionRequest::default()
     };
 
-    let Some(mut db) = estimate_request.poststate else {
-        error!("StateDB is None");
-        return Err(eyre!("STATE_DB_IS_NONE"));
-    };
-
-    if let Some(client) = client {
-        let ext_db = AlloyDB::new(client, BlockNumberOrTag::Latest.into());
-        if let Some(ext_db) = ext_db {
-            db.with_ext_db(ext_db)
-        } else {
-            error!("AlloyDB is None");
-        }
-    }
-
     let evm_env = env_for_block(estimate_request.tx_compose.next_block_number, estimate_request.tx_compose.next_block_timestamp);
 
-    let (gas_used, access_list) = match evm_access_list(&db, &evm_env, &tx_request) {
+    let (gas_used, access_list) = match evm_access_list(db, &evm_env, &tx_request) {
         Ok((gas_used, access_list)) => (gas_used, access_list),
         Err(e) => {
             trace!(
@@ -99,7 +90,7 @@ where
                 estimate_request.swap
             );
             // simulation has failed but this could be caused by a token / pool with unsupported fee issue
-            return Ok(());
+            return Ok(None);
         }
     };
     let swap = estimate_request.swap.clone();
@@ -174,19 +165,11 @@ where
 
     let sign_request = MessageSwapCompose::ready(SwapComposeData {
         tx_compose: TxComposeData { tx_bundle: Some(tx_with_state), ..estimate_request.tx_compose },
-        poststate: Some(db),
+        poststate: Some(db.clone()),
         tips: Some(total_tips + gas_cost),
         ..estimate_request
     });
 
-    let result = match compose_channel_tx.send(sign_request).await {
-        Err(error) => {
-            error!(%error, "compose_channel_tx.send");
-            Err(eyre!("COMPOSE_CHANNEL_SEND_ERROR"))
-        }
-        _ => Ok(()),
-    };
-
     let sim_duration = chrono::Local::now() - start_time;
 
     info!(
@@ -199,12 +182,82 @@ where
         " +++ Simulation successful",
     );
 
-    result
+    Ok(Some(sign_request))
+}
+
+/// Queues estimates on the thread pool. Estimates of the same block and stuffing txs share one poststate snapshot.
+fn estimate_batch<T, N, DB>(
+    thread_pool: &ThreadPool,
+    client: Option<impl Provider<T, N> + Clone + 'static>,
+    encoder: &(impl SwapEncoder + Send + Sync + Clone + 'static),
+    batch: Vec<SwapComposeData<DB>>,
+    latest_block_number: Arc<AtomicU64>,
+    ready_tx: UnboundedSender<(u64, MessageSwapCompose<DB>)>,
+) where
+    T: Transport + Clone,
+    N: Network,
+    DB: DatabaseRef + DatabaseLoomExt + Send + Sync + Clone + 'static,
+{
+    // estimates for the next block mean that the previous block has been received
+    if let Some(next_block_number) = batch.iter().map(|estimate_request| estimate_request.tx_compose.next_block_number).max() {
+        latest_block_number.fetch_max(next_block_number.saturating_sub(1), Ordering::Relaxed);
+    }
+
+    let mut snapshots: HashMap<(u64, Vec<TxHash>), Arc<DB>> = HashMap::new();
+
+    for estimate_request in batch {
+        let next_block_number = estimate_request.tx_compose.next_block_number;
+        if next_block_number <= latest_block_number.load(Ordering::Relaxed) {
+            trace!(next_block_number, swap = %estimate_request.swap, "Estimate dropped, block has passed");
+            continue;
+        }
+
+        let snapshot = match snapshots.entry((next_block_number, estimate_request.tx_compose.stuffing_txs_hashes.clone())) {
+            Entry::Occupied(entry) => entry.get().clone(),
+            Entry::Vacant(entry) => {
+                let Some(mut db) = estimate_request.poststate.clone() else {
+                    error!("StateDB is None");
+                    continue;
+                };
+                if let Some(client) = client.clone() {
+                    let ext_db = AlloyDB::new(client, BlockNumberOrTag::Latest.into());
+                    if let Some(ext_db) = ext_db {
+                        db.with_ext_db(ext_db)
+                    } else {
+                        error!("AlloyDB is None");
+                    }
+                }
+                entry.insert(Arc::new(db)).clone()
+            }
+        };
+
+        let encoder = encoder.clone();
+        let latest_block_number = latest_block_number.clone();
+        let ready_tx = ready_tx.clone();
+
+        thread_pool.spawn(move || {
+            if next_block_number <= latest_block_number.load(Ordering::Relaxed) {
+                trace!(next_block_number, swap = %estimate_request.swap, "Estimate dropped, block has passed");
+                return;
+            }
+            match estimate_swap(&encoder, estimate_request, snapshot.as_ref()) {
+                Ok(Some(sign_request)) => {
+                    if let Err(error) = ready_tx.send((next_block_number, sign_request)) {
+                        error!(%error, "ready_tx.send");
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => error!("Error in EVM estimate_swap: {:?}", e),
+            }
+        });
+    }
 }
 
 async fn estimator_worker<T, N, DB>(
     client: Option<impl Provider<T, N> + Clone + 'static>,
     encoder: impl SwapEncoder + Send + Sync + Clone + 'static,
+    threads: usize,
+    latest_block_number: Arc<AtomicU64>,
     compose_channel_rx: Broadcaster<MessageSwapCompose<DB>>,
     compose_channel_tx: Broadcaster<MessageSwapCompose<DB>>,
 ) -> WorkerResult
@@ -215,6 +268,10 @@ where
 {
     subscribe!(compose_channel_rx);
 
+    info!("Starting EVM estimator threads={threads}");
+    let thread_pool = ThreadPoolBuilder::new().num_threads(threads).build()?;
+    let (ready_tx, mut ready_rx) = tokio::sync::mpsc::unbounded_channel::<(u64, MessageSwapCompose<DB>)>();
+
     loop {
         tokio::select! {
             msg = compose_channel_rx.recv() => {
@@ -222,36 +279,67 @@ where
                 match compose_request_msg {
                     Ok(compose_request) =>{
                         if let SwapComposeMessage::Estimate(estimate_request) = compose_request.inner {
-                            let compose_channel_tx_cloned = compose_channel_tx.clone();
-                            let encoder_cloned = encoder.clone();
-                            let client_cloned = client.clone();
-                            tokio::task::spawn(
-                                async move {
-                                if let Err(e) = estimator_task(
-                                        client_cloned,
-                                        encoder_cloned,
-                                        estimate_request.clone(),
-                                        compose_channel_tx_cloned,
-                                ).await {
-                                        error!("Error in EVM estimator_task: {:?}", e);
-                                    }
+                            // take all queued estimates to share snapshots between them
+                            let mut batch = vec![estimate_request];
+                            while let Ok(compose_request) = compose_channel_rx.try_recv() {
+                                if let SwapComposeMessage::Estimate(estimate_request) = compose_request.inner {
+                                    batch.push(estimate_request);
                                 }
-                            );
+                            }
+                            debug!(batch_len = batch.len(), "EVM estimation batch");
+                            estimate_batch(&thread_pool, client.clone(), &encoder, batch, latest_block_number.clone(), ready_tx.clone());
                         }
                     }
                     Err(e)=>{error!("{e}")}
                 }
             }
+            msg = ready_rx.recv() => {
+                if let Some((next_block_number, sign_request)) = msg {
+                    if next_block_number <= latest_block_number.load(Ordering::Relaxed) {
+                        trace!(next_block_number, "Estimate dropped, block has passed");
+                        continue;
+                    }
+                    if let Err(error) = compose_channel_tx.send(sign_request).await {
+                        error!(%error, "compose_channel_tx.send");
+                    }
+                }
+            }
         }
     }
 }
 
+async fn latest_block_worker(block_header_rx: Broadcaster<MessageBlockHeader>, latest_block_number: Arc<AtomicU64>) -> WorkerResult {
+    subscribe!(block_header_rx);
+
+    loop {
+        match block_header_rx.recv().await {
+            Ok(block_header) => {
+                latest_block_number.fetch_max(block_header.inner.header.number, Ordering::Relaxed);
+            }
+            Err(e) => match e {
+                RecvError::Closed => {
+                    error!("Block header channel closed");
+                    return Err(eyre!("BLOCK_HEADER_RX_CLOSED"));
+                }
+                RecvError::Lagged(lag) => {
+                    info!("Block header channel lagged: {}", lag);
+                }
+            },
+        }
+    }
+}
+
+/// Estimates gas and fills tips of swaps simulating them in evm on a thread pool.
+/// Estimates for blocks that have already passed are dropped.
 #[derive(Consumer, Producer)]
 pub struct EvmEstimatorActor<P, T, N, E, DB: Clone + Send + Sync + 'static> {
     encoder: E,
     client: Option<P>,
+    threads: usize,
     #[consumer]
     compose_channel_rx: Option<Broadcaster<MessageSwapCompose<DB>>>,
+    #[consumer]
+    block_header_rx: Option<Broadcaster<MessageBlockHeader>>,
     #[producer]
     compose_channel_tx: Option<Broadcaster<MessageSwapCompose<DB>>>,
     _t: PhantomData<T>,
@@ -267,17 +355,31 @@ where
     DB: DatabaseRef + DatabaseLoomExt + Send + Sync + Clone + 'static,
 {
     pub fn new(encoder: E) -> Self {
-        Self { encoder, client: None, compose_channel_tx: None, compose_channel_rx: None, _t: PhantomData::<T>, _n: PhantomData::<N> }
+        Self::new_with_provider(encoder, None)
     }
 
     pub fn new_with_provider(encoder: E, client: Option<P>) -> Self {
-        Self { encoder, client, compose_channel_tx: None, compose_channel_rx: None, _t: PhantomData::<T>, _n: PhantomData::<N> }
+        Self {
+            encoder,
+            client,
+            threads: (num_cpus::get() / 4).max(1),
+            compose_channel_tx: None,
+            compose_channel_rx: None,
+            block_header_rx: None,
+            _t: PhantomData::<T>,
+            _n: PhantomData::<N>,
+        }
     }
 
-    pub fn on_bc(self, strategy: &Strategy<DB>) -> Self {
+    pub fn with_threads(self, threads: usize) -> Self {
+        Self { threads, ..self }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain, strategy: &Strategy<DB>) -> Self {
         Self {
             compose_channel_tx: Some(strategy.swap_compose_channel()),
             compose_channel_rx: Some(strategy.swap_compose_channel()),
+            block_header_rx: Some(bc.new_block_headers_channel()),
             ..self
         }
     }
@@ -292,13 +394,19 @@ where
     DB: DatabaseRef + DatabaseLoomExt + Send + Sync + Clone,
 {
     fn start(&self) -> ActorResult {
-        let task = tokio::task::spawn(estimator_worker(
+        let latest_block_number = Arc::new(AtomicU64::new(0));
+        let mut tasks = vec![tokio::task::spawn(estimator_worker(
             self.client.clone(),
             self.encoder.clone(),
+            self.threads,
+            latest_block_number.clone(),
             self.compose_channel_rx.clone().unwrap(),
             self.compose_channel_tx.clone().unwrap(),
-        ));
-        Ok(vec![task])
+        ))];
+        if let Some(block_header_rx) = self.block_header_rx.clone() {
+            tasks.push(tokio::task::spawn(latest_block_worker(block_header_rx, latest_block_number)));
+        }
+        Ok(tasks)
     }
     fn name(&self) -> &'static str {
         "EvmEstimatorActor"
@@ -0,0 +1,224 @@
+use alloy_primitives::{Address, U256};
+use eyre::{eyre, ErrReport};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error, info, trace};
+
+use crate::swap_gas_meter::SwapGasMeter;
+use loom_core_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, SharedState, WorkerResult};
+use loom_core_actors_macros::{Accessor, Consumer};
+use loom_core_blockchain::{Blockchain, BlockchainState};
+use loom_evm_utils::evm_env::env_for_block;
+use loom_evm_utils::NWETH;
+use loom_types_entities::{GasModel, Market, MarketState, PoolWrapper, Token};
+use loom_types_events::MessageBlockHeader;
+use revm::DatabaseRef;
+
+/// Swap directions measured per block
+const DEFAULT_BATCH_SIZE: usize = 20;
+/// Measurements older than this number of blocks are refreshed, about 2 hours
+const DEFAULT_REFRESH_BLOCKS: u64 = 600;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn gas_model_worker<DB: DatabaseRef<Error = ErrReport> + Send + Sync + Clone + 'static>(
+    gas_meter: SwapGasMeter,
+    batch_size: usize,
+    refresh_blocks: u64,
+    market: SharedState<Market>,
+    market_state: SharedState<MarketState<DB>>,
+    gas_model: SharedState<GasModel>,
+    block_header_rx: Broadcaster<MessageBlockHeader>,
+) -> WorkerResult {
+    subscribe!(block_header_rx);
+
+    // (pool, token_from, token_to) -> block_number of failed measurement
+    let mut failed: HashMap<(Address, Address, Address), u64> = HashMap::new();
+    let mut base_gas_block: Option<u64> = None;
+
+    loop {
+        let block_header = match block_header_rx.recv().await {
+            Ok(message_block_header) => message_block_header.inner,
+            Err(e) => match e {
+                RecvError::Closed => {
+                    error!("Block header channel closed");
+                    return Err(eyre!("BLOCK_HEADER_RX_CLOSED"));
+                }
+                RecvError::Lagged(lag) => {
+                    info!("Block header channel lagged: {}", lag);
+                    continue;
+                }
+            },
+        };
+        let block_number = block_header.header.number;
+
+        let swap_directions: Vec<(PoolWrapper, Arc<Token>, Arc<Token>)> = {
+            let market_guard = market.read().await;
+            let gas_model_guard = gas_model.read().await;
+            let mut swap_directions = Vec::new();
+            'pools: for (pool_address, pool) in market_guard.pools().iter() {
+                if market_guard.is_pool_disabled(pool_address) {
+                    continue;
+                }
+                for (token_from, token_to) in pool.get_swap_directions() {
+                    if swap_directions.len() >= batch_size {
+                        break 'pools;
+                    }
+                    if !gas_model_guard.is_stale(pool_address, &token_from, &token_to, block_number, refresh_blocks) {
+                        continue;
+                    }
+                    if failed
+                        .get(&(*pool_address, token_from, token_to))
+                        .is_some_and(|failed_block| failed_block + refresh_blocks > block_number)
+                    {
+                        continue;
+                    }
+                    swap_directions.push((
+                        pool.clone(),
+                        market_guard.get_token_or_default(&token_from),
+                        market_guard.get_token_or_default(&token_to),
+                    ));
+                }
+            }
+            swap_directions
+        };
+
+        let base_gas_stale = base_gas_block.map_or(true, |base_gas_block| base_gas_block + refresh_blocks <= block_number);
+        if swap_directions.is_empty() && !base_gas_stale {
+            continue;
+        }
+
+        let env = env_for_block(block_header.next_block_number, block_header.next_block_timestamp);
+        let state_db = market_state.read().await.state_db.clone();
+        let current_base_gas = gas_model.read().await.base_gas();
+
+        // measurements execute swaps in evm, they run on a blocking thread on a copy of the state db
+        let gas_meter_clone = gas_meter.clone();
+        let measure_result = tokio::task::spawn_blocking(move || {
+            let base_gas = if base_gas_stale { gas_meter_clone.measure_base_gas(&state_db, env.clone()).map(Some)? } else { None };
+            let step_base_gas = base_gas.unwrap_or(current_base_gas);
+
+            let mut measurements = Vec::new();
+            let mut failures = Vec::new();
+            for (pool, token_from, token_to) in swap_directions {
+                match gas_meter_clone.measure_swap_step_gas(&state_db, env.clone(), step_base_gas, &pool, token_from.clone(), token_to.clone()) {
+                    Ok(gas) => {
+                        trace!(pool = %pool.get_address(), token_from = %token_from.get_address(), gas, "Swap step gas measured");
+                        measurements.push((pool, token_from.get_address(), token_to.get_address(), gas));
+                    }
+                    Err(error) => {
+                        debug!(%error, pool = %pool.get_address(), token_from = %token_from.get_address(), "Swap step gas measurement failed");
+                        failures.push((pool.get_address(), token_from.get_address(), token_to.get_address()));
+                    }
+                }
+            }
+            Ok::<_, ErrReport>((base_gas, measurements, failures))
+        })
+        .await;
+
+        let (measured_base_gas, measurements, failures) = match measure_result {
+            Ok(Ok(result)) => result,
+            Ok(Err(error)) => {
+                error!(%error, "Base gas measurement failed");
+                continue;
+            }
+            Err(error) => {
+                error!(%error, "Gas measurement task failed");
+                continue;
+            }
+        };
+
+        for failure in failures {
+            failed.insert(failure, block_number);
+        }
+
+        let mut gas_model_guard = gas_model.write().await;
+        if let Some(measured_base_gas) = measured_base_gas {
+            base_gas_block = Some(block_number);
+            gas_model_guard.set_base_gas(measured_base_gas);
+        }
+        let base_gas = measured_base_gas.unwrap_or(current_base_gas);
+        for (pool, token_from, token_to, gas) in measurements {
+            gas_model_guard.update(&pool, token_from, token_to, gas, block_number);
+        }
+        debug!(block_number, base_gas, measured = gas_model_guard.len(), "Gas model updated");
+    }
+}
+
+/// Calibrates the gas model of swaps measuring gas of encoded swap steps of the market pools in evm.
+/// Stale measurements are refreshed in batches on every block.
+#[derive(Accessor, Consumer)]
+pub struct GasModelActor<DB> {
+    gas_meter: SwapGasMeter,
+    batch_size: usize,
+    refresh_blocks: u64,
+    #[accessor]
+    market: Option<SharedState<Market>>,
+    #[accessor]
+    market_state: Option<SharedState<MarketState<DB>>>,
+    #[accessor]
+    gas_model: Option<SharedState<GasModel>>,
+    #[consumer]
+    block_header_rx: Option<Broadcaster<MessageBlockHeader>>,
+}
+
+impl<DB> GasModelActor<DB>
+where
+    DB: DatabaseRef<Error = ErrReport> + Send + Sync + Clone + 'static,
+{
+    pub fn new(multicaller_address: Address) -> Self {
+        Self {
+            gas_meter: SwapGasMeter::new(multicaller_address, NWETH::from_float(0.1)),
+            batch_size: DEFAULT_BATCH_SIZE,
+            refresh_blocks: DEFAULT_REFRESH_BLOCKS,
+            market: None,
+            market_state: None,
+            gas_model: None,
+            block_header_rx: None,
+        }
+    }
+
+    pub fn with_probe_eth_amount(self, probe_eth_amount: U256) -> Self {
+        Self { gas_meter: SwapGasMeter::new(self.gas_meter.multicaller(), probe_eth_amount), ..self }
+    }
+
+    pub fn with_batch_size(self, batch_size: usize) -> Self {
+        Self { batch_size, ..self }
+    }
+
+    pub fn with_refresh_blocks(self, refresh_blocks: u64) -> Self {
+        Self { refresh_blocks, ..self }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain, state: &BlockchainState<DB>) -> Self {
+        Self {
+            market: Some(bc.market()),
+            market_state: Some(state.market_state()),
+            gas_model: Some(bc.gas_model()),
+            block_header_rx: Some(bc.new_block_headers_channel()),
+            ..self
+        }
+    }
+}
+
+impl<DB> Actor for GasModelActor<DB>
+where
+    DB: DatabaseRef<Error = ErrReport> + Send + Sync + Clone + 'static,
+{
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(gas_model_worker(
+            self.gas_meter.clone(),
+            self.batch_size,
+            self.refresh_blocks,
+            self.market.clone().unwrap(),
+            self.market_state.clone().unwrap(),
+            self.gas_model.clone().unwrap(),
+            self.block_header_rx.clone().unwrap(),
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "GasModelActor"
+    }
+}
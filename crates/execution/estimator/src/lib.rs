@@ -1,7 +1,11 @@
 mod evm;
+mod gas_model_actor;
 mod geth;
 mod hardhat;
+mod swap_gas_meter;
 
 pub use evm::EvmEstimatorActor;
+pub use gas_model_actor::GasModelActor;
 pub use geth::GethEstimatorActor;
 pub use hardhat::HardhatEstimatorActor;
+pub use swap_gas_meter::SwapGasMeter;
@@ -0,0 +1,113 @@
+use alloy_primitives::{Address, U256};
+use eyre::{eyre, ErrReport, Result};
+use revm::db::CacheDB;
+use revm::primitives::Env;
+use revm::DatabaseRef;
+use std::sync::Arc;
+
+use loom_evm_utils::evm::evm_call;
+use loom_evm_utils::BalanceCheater;
+use loom_execution_multicaller::{MulticallerDeployer, SwapStepEncoder};
+use loom_types_blockchain::MulticallerCalls;
+use loom_types_entities::{PoolWrapper, SwapAmountType, SwapLine, SwapPath, Token};
+
+/// Measures gas of swap steps executing calls encoded by the multicaller encoders in evm.
+/// Multicaller code and the balance of the swapped token are set on top of the state db.
+#[derive(Clone)]
+pub struct SwapGasMeter {
+    encoder: SwapStepEncoder,
+    probe_eth_amount: U256,
+}
+
+impl SwapGasMeter {
+    pub fn new(multicaller: Address, probe_eth_amount: U256) -> Self {
+        Self { encoder: SwapStepEncoder::new(multicaller), probe_eth_amount }
+    }
+
+    pub fn multicaller(&self) -> Address {
+        self.encoder.get_contract_address()
+    }
+
+    fn multicaller_db<'a, DB: DatabaseRef<Error = ErrReport>>(&self, db: &'a DB) -> CacheDB<&'a DB> {
+        MulticallerDeployer::new().multicaller_db(db, self.multicaller())
+    }
+
+    /// Gas of the multicaller transaction without calls
+    pub fn measure_base_gas<DB: DatabaseRef<Error = ErrReport>>(&self, db: &DB, env: Env) -> Result<u64> {
+        let (to, call_data) = self.encoder.to_call_data(&MulticallerCalls::new())?;
+        let (_, gas_used) = evm_call(&self.multicaller_db(db), env, to, call_data.to_vec())?;
+        Ok(gas_used)
+    }
+
+    /// Gas of the swap step of the pool in addition to `base_gas`. The swapped amount is `probe_eth_amount` valued in `token_from`.
+    pub fn measure_swap_step_gas<DB: DatabaseRef<Error = ErrReport>>(
+        &self,
+        db: &DB,
+        env: Env,
+        base_gas: u64,
+        pool: &PoolWrapper,
+        token_from: Arc<Token>,
+        token_to: Arc<Token>,
+    ) -> Result<u64> {
+        let amount_in = token_from.calc_token_value_from_eth(self.probe_eth_amount).ok_or(eyre!("TOKEN_PRICE_NOT_SET"))?;
+        if amount_in.is_zero() {
+            return Err(eyre!("ZERO_AMOUNT_IN"));
+        }
+
+        let mut swap_line = SwapLine {
+            path: SwapPath::new(vec![token_from.clone(), token_to], vec![pool.clone()]),
+            amount_in: SwapAmountType::Set(amount_in),
+            ..SwapLine::default()
+        };
+        let (amount_out, _, _) = swap_line.calculate_with_in_amount(db, env.clone(), amount_in)?;
+        swap_line.amount_out = SwapAmountType::Set(amount_out);
+
+        let multicaller = self.multicaller();
+        let opcodes = self.encoder.swap_line_encoder.encode_swap_line_in_amount(&swap_line, multicaller, multicaller)?;
+        let (to, call_data) = self.encoder.to_call_data(&opcodes)?;
+
+        let mut cache_db = self.multicaller_db(db);
+        let balance_cell = BalanceCheater::get_balance_cell_db(db, token_from.get_address(), multicaller)?;
+        cache_db.insert_account_storage(token_from.get_address(), balance_cell, amount_in)?;
+
+        let (_, gas_used) = evm_call(&cache_db, env, to, call_data.to_vec())?;
+        Ok(gas_used.saturating_sub(base_gas))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use loom_evm_db::LoomDB;
+    use loom_evm_utils::evm_env::env_for_block;
+    use loom_evm_utils::NWETH;
+    use loom_types_entities::MockPool;
+
+    #[test]
+    fn test_multicaller_db() {
+        let db = LoomDB::new();
+        let gas_meter = SwapGasMeter::new(Address::repeat_byte(0x01), NWETH::from_float(0.1));
+
+        let account = gas_meter.multicaller_db(&db).basic_ref(gas_meter.multicaller()).unwrap().unwrap();
+        assert!(account.code.is_some_and(|code| !code.is_empty()));
+        assert!(db.basic_ref(gas_meter.multicaller()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_measure_swap_step_gas_amount_in() {
+        let db = LoomDB::new();
+        let env = env_for_block(1, 0);
+        let gas_meter = SwapGasMeter::new(Address::repeat_byte(0x01), NWETH::from_float(0.1));
+
+        let token_from = Arc::new(Token::new(Address::repeat_byte(0x02)));
+        let token_to = Arc::new(Token::new(Address::repeat_byte(0x03)));
+        let pool = PoolWrapper::from(MockPool::new(token_from.get_address(), token_to.get_address(), Address::repeat_byte(0x04)));
+
+        let error = gas_meter.measure_swap_step_gas(&db, env.clone(), 0, &pool, token_from.clone(), token_to.clone()).unwrap_err();
+        assert_eq!(error.to_string(), "TOKEN_PRICE_NOT_SET");
+
+        token_from.set_eth_price(Some(U256::ZERO));
+        let error = gas_meter.measure_swap_step_gas(&db, env, 0, &pool, token_from, token_to).unwrap_err();
+        assert_eq!(error.to_string(), "ZERO_AMOUNT_IN");
+    }
+}
@@ -13,6 +13,9 @@ use alloy_transport::Transport;
 use eyre::{eyre, OptionExt, Result};
 use k256::SecretKey;
 use lazy_static::lazy_static;
+use revm::db::CacheDB;
+use revm::primitives::{AccountInfo, Bytecode};
+use revm::DatabaseRef;
 use tracing::{debug, error, info};
 
 use loom_node_debug_provider::AnvilProviderExt;
@@ -41,6 +44,14 @@ impl MulticallerDeployer {
         AccountState { balance: None, code: Some(self.code.clone()), nonce: None, storage: Default::default() }
     }
 
+    /// Cache db on top of `db` with the multicaller code set at `address`. `db` is not changed.
+    pub fn multicaller_db<'a, DB: DatabaseRef>(&self, db: &'a DB, address: Address) -> CacheDB<&'a DB> {
+        let mut cache_db = CacheDB::new(db);
+        let code = Bytecode::new_raw(self.code.clone());
+        cache_db.insert_account_info(address, AccountInfo { code_hash: code.hash_slow(), code: Some(code), ..AccountInfo::default() });
+        cache_db
+    }
+
     pub async fn deploy<P, T>(self, client: P, priv_key: SecretKey) -> Result<Self>
     where
         T: Transport + Clone,
@@ -2,7 +2,10 @@ use alloy_primitives::{Address, Bytes};
 use eyre::{eyre, OptionExt, Result};
 use tracing::error;
 
+use loom_core_actors::SharedState;
 use loom_types_blockchain::MulticallerCalls;
+use loom_types_entities::bidding::AdaptiveBidder;
+use loom_types_entities::profit_policy::ProfitPolicy;
 use loom_types_entities::Swap;
 
 use crate::SwapStepEncoder;
@@ -17,11 +20,28 @@ pub trait MulticallerEncoder {
 pub struct MulticallerSwapEncoder {
     pub multicaller_address: Address,
     pub swap_step_encoder: SwapStepEncoder,
+    pub profit_policy: SharedState<ProfitPolicy>,
+    pub bidder: Option<SharedState<AdaptiveBidder>>,
 }
 
 impl MulticallerSwapEncoder {
     pub fn new(multicaller_address: Address) -> Self {
-        Self { multicaller_address, swap_step_encoder: SwapStepEncoder::new(multicaller_address) }
+        Self {
+            multicaller_address,
+            swap_step_encoder: SwapStepEncoder::new(multicaller_address),
+            profit_policy: SharedState::new(ProfitPolicy::default()),
+            bidder: None,
+        }
+    }
+
+    /// Shared profit policy with the tips curve for swaps without tips percent and the random range of tips percent
+    pub fn with_profit_policy(self, profit_policy: SharedState<ProfitPolicy>) -> Self {
+        Self { profit_policy, ..self }
+    }
+
+    /// Bidder adjusting tips percent of all encoded swaps
+    pub fn with_bidder(self, bidder: SharedState<AdaptiveBidder>) -> Self {
+        Self { bidder: Some(bidder), ..self }
     }
 
     pub fn get_contract_address(&self) -> Address {
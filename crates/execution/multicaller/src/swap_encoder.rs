@@ -76,7 +76,12 @@ impl SwapEncoder for MulticallerSwapEncoder {
 
         let tips_vec =
             if let (Some(tips_pct), Some(sender_address), Some(sender_eth_balance)) = (tips_pct, sender_address, sender_eth_balance) {
-                let (tips_vec, _call_value) = tips_and_value_for_swap_type(&swap, Some(tips_pct), gas_cost, sender_eth_balance)?;
+                // encoding can't wait for the locks, the default curve is used while the policy is reloaded
+                // and the bidder is skipped while outcomes are being recorded
+                let tips_curve = self.profit_policy.try_read().map(|profit_policy| profit_policy.tips_curve()).unwrap_or_default();
+                let bidder = self.bidder.as_ref().and_then(|bidder| bidder.try_read().ok());
+                let (tips_vec, _call_value) =
+                    tips_and_value_for_swap_type(&swap, Some(tips_pct), &tips_curve, bidder.as_deref(), gas_cost, sender_eth_balance)?;
                 for tips in &tips_vec {
                     swap_opcodes = self.swap_step_encoder.encode_tips(
                         swap_opcodes,
@@ -83,6 +83,8 @@ pub mod node {
     pub use loom_node_grpc_exex_proto as grpc_exex_proto;
     #[cfg(feature = "node-json-rpc")]
     pub use loom_node_json_rpc as json_rpc;
+    #[cfg(feature = "node-mev-share")]
+    pub use loom_node_mev_share as mev_share;
     #[cfg(feature = "node-player")]
     pub use loom_node_player as player;
 }
@@ -1,5 +1,9 @@
 mod block_latency_actor;
 mod influxdb_actor;
+mod market_state_size_actor;
+mod mempool_source_latency_actor;
 
 pub use block_latency_actor::BlockLatencyRecorderActor;
 pub use influxdb_actor::InfluxDbWriterActor;
+pub use market_state_size_actor::MarketStateSizeRecorderActor;
+pub use mempool_source_latency_actor::MempoolSourceLatencyRecorderActor;
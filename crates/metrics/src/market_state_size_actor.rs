@@ -0,0 +1,107 @@
+use eyre::eyre;
+use influxdb::{Timestamp, WriteQuery};
+use loom_core_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
+use loom_core_actors_macros::{Accessor, Consumer, Producer};
+use loom_core_blockchain::{Blockchain, BlockchainState};
+use loom_evm_db::DatabaseLoomExt;
+use loom_types_entities::MarketState;
+use loom_types_events::MarketEvents;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{error, info};
+
+async fn market_state_size_worker<DB: DatabaseLoomExt + Send + Sync + 'static>(
+    market_state: SharedState<MarketState<DB>>,
+    market_events_rx: Broadcaster<MarketEvents>,
+    influx_channel_tx: Broadcaster<WriteQuery>,
+) -> WorkerResult {
+    subscribe!(market_events_rx);
+    loop {
+        let market_event = match market_events_rx.recv().await {
+            Ok(market_event) => market_event,
+            Err(e) => match e {
+                RecvError::Closed => {
+                    error!("Market events channel closed");
+                    return Err(eyre!("Market events channel closed".to_string()));
+                }
+                RecvError::Lagged(lag) => {
+                    info!("Market events channel lagged: {}", lag);
+                    continue;
+                }
+            },
+        };
+
+        if !matches!(market_event, MarketEvents::BlockStateUpdate { .. }) {
+            continue;
+        }
+
+        let write_query = {
+            let market_state_guard = market_state.read().await;
+            let db = &market_state_guard.state_db;
+            WriteQuery::new(Timestamp::from(chrono::Utc::now()), "market_state_size")
+                .add_field("block_number", market_state_guard.block_number)
+                .add_field("rw_accounts", db.rw_accounts_len() as u64)
+                .add_field("ro_accounts", db.ro_accounts_len() as u64)
+                .add_field("rw_storage", db.rw_storage_len() as u64)
+                .add_field("ro_storage", db.ro_storage_len() as u64)
+                .add_field("contracts", db.contracts_len() as u64)
+        };
+        if let Err(e) = influx_channel_tx.send(write_query).await {
+            error!("Failed to send market state size to influxdb: {:?}", e);
+        }
+    }
+}
+
+/// Records sizes of the read write and read only layers of the market state db after every block.
+#[derive(Accessor, Consumer, Producer)]
+pub struct MarketStateSizeRecorderActor<DB> {
+    #[accessor]
+    market_state: Option<SharedState<MarketState<DB>>>,
+    #[consumer]
+    market_events_rx: Option<Broadcaster<MarketEvents>>,
+    #[producer]
+    influxdb_write_channel_tx: Option<Broadcaster<WriteQuery>>,
+}
+
+impl<DB> MarketStateSizeRecorderActor<DB>
+where
+    DB: DatabaseLoomExt + Send + Sync + Clone + 'static,
+{
+    pub fn new() -> Self {
+        Self { market_state: None, market_events_rx: None, influxdb_write_channel_tx: None }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain, state: &BlockchainState<DB>) -> Self {
+        Self {
+            market_state: Some(state.market_state()),
+            market_events_rx: Some(bc.market_events_channel()),
+            influxdb_write_channel_tx: Some(bc.influxdb_write_channel()),
+        }
+    }
+}
+
+impl<DB> Default for MarketStateSizeRecorderActor<DB>
+where
+    DB: DatabaseLoomExt + Send + Sync + Clone + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<DB> Actor for MarketStateSizeRecorderActor<DB>
+where
+    DB: DatabaseLoomExt + Send + Sync + Clone + 'static,
+{
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(market_state_size_worker(
+            self.market_state.clone().unwrap(),
+            self.market_events_rx.clone().unwrap(),
+            self.influxdb_write_channel_tx.clone().unwrap(),
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "MarketStateSizeRecorderActor"
+    }
+}
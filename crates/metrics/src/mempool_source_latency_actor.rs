@@ -0,0 +1,126 @@
+use chrono::Utc;
+use eyre::eyre;
+use influxdb::{Timestamp, WriteQuery};
+use loom_core_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
+use loom_core_actors_macros::{Accessor, Consumer, Producer};
+use loom_core_blockchain::Blockchain;
+use loom_types_entities::MempoolSourceStats;
+use loom_types_events::{MessageBlockHeader, MessageMempoolDataUpdate};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error, info};
+
+async fn mempool_source_latency_worker(
+    mempool_source_stats: SharedState<MempoolSourceStats>,
+    mempool_update_rx: Broadcaster<MessageMempoolDataUpdate>,
+    block_header_rx: Broadcaster<MessageBlockHeader>,
+    influx_channel_tx: Broadcaster<WriteQuery>,
+) -> WorkerResult {
+    subscribe!(mempool_update_rx);
+    subscribe!(block_header_rx);
+
+    loop {
+        tokio::select! {
+            msg = mempool_update_rx.recv() => {
+                match msg {
+                    Ok(mempool_update_msg) => {
+                        if mempool_update_msg.mempool_tx.tx.is_some() {
+                            let time = mempool_update_msg.time.unwrap_or_else(Utc::now);
+                            mempool_source_stats.write().await.record(mempool_update_msg.tx_hash, &mempool_update_msg.source(), time);
+                        }
+                    }
+                    Err(RecvError::Closed) => {
+                        error!("Mempool update channel closed");
+                        return Err(eyre!("MEMPOOL_UPDATE_RX_CLOSED"));
+                    }
+                    Err(RecvError::Lagged(lag)) => {
+                        info!("Mempool update channel lagged: {}", lag);
+                    }
+                }
+            }
+
+            msg = block_header_rx.recv() => {
+                let block_number = match msg {
+                    Ok(block_header) => block_header.inner.header.number,
+                    Err(RecvError::Closed) => {
+                        error!("Block header channel closed");
+                        return Err(eyre!("BLOCK_HEADER_RX_CLOSED"));
+                    }
+                    Err(RecvError::Lagged(lag)) => {
+                        info!("Block header channel lagged: {}", lag);
+                        continue;
+                    }
+                };
+
+                let current_timestamp = Utc::now();
+                let stats = {
+                    let mut stats_guard = mempool_source_stats.write().await;
+                    let settled = stats_guard.settle(current_timestamp);
+                    debug!(block_number, settled, pending = stats_guard.pending_len(), "Mempool source stats updated");
+                    stats_guard.stats()
+                };
+
+                for source_stat in stats {
+                    let mut write_query = WriteQuery::new(Timestamp::from(current_timestamp), "mempool_source_latency")
+                        .add_tag("source", source_stat.source)
+                        .add_field("txs", source_stat.txs as u64)
+                        .add_field("wins", source_stat.wins as u64)
+                        .add_field("win_rate", source_stat.win_rate)
+                        .add_field("block_number", block_number);
+                    if let Some(median_lead_ms) = source_stat.median_lead_ms {
+                        write_query = write_query.add_field("median_lead_ms", median_lead_ms);
+                    }
+                    if let Some(p99_lead_ms) = source_stat.p99_lead_ms {
+                        write_query = write_query.add_field("p99_lead_ms", p99_lead_ms);
+                    }
+                    if let Err(e) = influx_channel_tx.send(write_query).await {
+                        error!("Failed to send mempool source latency to influxdb: {:?}", e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Measures how much earlier every mempool source delivers txs than other sources. Statistics are updated on every block.
+#[derive(Accessor, Consumer, Producer, Default)]
+pub struct MempoolSourceLatencyRecorderActor {
+    #[accessor]
+    mempool_source_stats: Option<SharedState<MempoolSourceStats>>,
+    #[consumer]
+    mempool_update_rx: Option<Broadcaster<MessageMempoolDataUpdate>>,
+    #[consumer]
+    block_header_rx: Option<Broadcaster<MessageBlockHeader>>,
+    #[producer]
+    influxdb_write_channel_tx: Option<Broadcaster<WriteQuery>>,
+}
+
+impl MempoolSourceLatencyRecorderActor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn on_bc(self, bc: &Blockchain) -> Self {
+        Self {
+            mempool_source_stats: Some(bc.mempool_source_stats()),
+            mempool_update_rx: Some(bc.new_mempool_tx_channel()),
+            block_header_rx: Some(bc.new_block_headers_channel()),
+            influxdb_write_channel_tx: Some(bc.influxdb_write_channel()),
+        }
+    }
+}
+
+impl Actor for MempoolSourceLatencyRecorderActor {
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(mempool_source_latency_worker(
+            self.mempool_source_stats.clone().unwrap(),
+            self.mempool_update_rx.clone().unwrap(),
+            self.block_header_rx.clone().unwrap(),
+            self.influxdb_write_channel_tx.clone().unwrap(),
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "MempoolSourceLatencyRecorderActor"
+    }
+}
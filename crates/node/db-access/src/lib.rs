@@ -1,3 +1,5 @@
+pub use reth_db::{open_reth_provider_factory, RethDB, RethProviderFactory};
 pub use reth_worker::RethDbAccessBlockActor;
 
+mod reth_db;
 mod reth_worker;
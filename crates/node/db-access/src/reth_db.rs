@@ -0,0 +1,176 @@
+use alloy_primitives::{Address, BlockNumber, B256, U256};
+use eyre::{eyre, ErrReport, OptionExt, Result};
+use reth_chainspec::{ChainSpec, ChainSpecBuilder};
+use reth_db::mdbx::DatabaseArguments;
+use reth_db::{open_db_read_only, ClientVersion, DatabaseEnv};
+use reth_node_ethereum::EthereumNode;
+use reth_node_types::NodeTypesWithDBAdapter;
+use reth_provider::providers::{ProviderNodeTypes, StaticFileProvider};
+use reth_provider::{ProviderFactory, StateProvider, StateProviderBox};
+use revm::primitives::{AccountInfo, Bytecode, KECCAK_EMPTY};
+use revm::{Database, DatabaseRef};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::trace;
+
+pub type RethProviderFactory = ProviderFactory<NodeTypesWithDBAdapter<EthereumNode, Arc<DatabaseEnv>>>;
+
+/// Opens the database and static files of the reth node in `db_path` read only.
+pub fn open_reth_provider_factory(db_path: &Path, chain_spec: Arc<ChainSpec>) -> Result<RethProviderFactory> {
+    let db = Arc::new(open_db_read_only(db_path.join("db").as_path(), DatabaseArguments::new(ClientVersion::default()))?);
+    let static_file_provider = StaticFileProvider::read_only(db_path.join("static_files"), true)?;
+    Ok(ProviderFactory::new(db, chain_spec, static_file_provider))
+}
+
+/// A reth MDBX-powered REVM [Database].
+///
+/// Reads the state of the local reth node without JSON-RPC. Every query opens a read only transaction,
+/// so the latest persisted state is returned. Use it as `ext_db` of `LoomDB`.
+pub struct RethDB<N: ProviderNodeTypes> {
+    factory: ProviderFactory<N>,
+    /// The block number on which the queries will be based on. Latest state if None.
+    block_number: Option<BlockNumber>,
+}
+
+impl RethDB<NodeTypesWithDBAdapter<EthereumNode, Arc<DatabaseEnv>>> {
+    /// Opens the database of the mainnet reth node in `db_path` read only.
+    pub fn open(db_path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new(open_reth_provider_factory(db_path.as_ref(), Arc::new(ChainSpecBuilder::mainnet().build()))?))
+    }
+}
+
+impl<N: ProviderNodeTypes> RethDB<N> {
+    pub fn new(factory: ProviderFactory<N>) -> Self {
+        Self { factory, block_number: None }
+    }
+
+    pub fn with_block_number(self, block_number: BlockNumber) -> Self {
+        Self { block_number: Some(block_number), ..self }
+    }
+
+    /// Set the block number on which the queries will be based on.
+    pub fn set_block_number(&mut self, block_number: Option<BlockNumber>) {
+        self.block_number = block_number;
+    }
+
+    fn state_provider(&self) -> Result<StateProviderBox> {
+        match self.block_number {
+            Some(block_number) => Ok(self.factory.history_by_block_number(block_number)?),
+            None => Ok(self.factory.latest()?),
+        }
+    }
+}
+
+impl<N: ProviderNodeTypes> DatabaseRef for RethDB<N> {
+    type Error = ErrReport;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        trace!(%address, "reth basic_ref");
+        let state_provider = self.state_provider()?;
+        let Some(account) = state_provider.basic_account(address)? else { return Ok(None) };
+
+        let (code_hash, code) = match account.bytecode_hash {
+            Some(code_hash) if code_hash != KECCAK_EMPTY => {
+                let code = state_provider.bytecode_by_hash(code_hash)?.map(|code| Bytecode::new_raw(code.original_bytes()));
+                (code_hash, code)
+            }
+            _ => (KECCAK_EMPTY, None),
+        };
+
+        Ok(Some(AccountInfo { balance: account.balance, nonce: account.nonce, code_hash, code }))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if code_hash == KECCAK_EMPTY {
+            return Ok(Bytecode::default());
+        }
+        self.state_provider()?
+            .bytecode_by_hash(code_hash)?
+            .map(|code| Bytecode::new_raw(code.original_bytes()))
+            .ok_or_else(|| eyre!("CODE_HASH_NOT_FOUND"))
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        trace!(%address, %index, "reth storage_ref");
+        Ok(self.state_provider()?.storage(address, B256::from(index))?.unwrap_or_default())
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.state_provider()?.block_hash(number)?.ok_or_eyre("BLOCK_HASH_NOT_FOUND")
+    }
+}
+
+impl<N: ProviderNodeTypes> Database for RethDB<N> {
+    type Error = ErrReport;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        <Self as DatabaseRef>::basic_ref(self, address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        <Self as DatabaseRef>::code_by_hash_ref(self, code_hash)
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        <Self as DatabaseRef>::storage_ref(self, address, index)
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        <Self as DatabaseRef>::block_hash_ref(self, number)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_primitives::Bytes;
+    use loom_evm_db::LoomDB;
+    use reth_db::tables;
+    use reth_db::transaction::DbTxMut;
+    use reth_primitives::{Account, StorageEntry};
+    use reth_provider::test_utils::create_test_provider_factory;
+
+    #[test]
+    fn test_reth_db_as_ext_db() -> Result<()> {
+        let factory = create_test_provider_factory();
+
+        let pool = Address::repeat_byte(1);
+        let eoa = Address::repeat_byte(2);
+        let code = Bytes::from(vec![0x60, 0x00, 0x60, 0x00, 0xf3]);
+        let code_hash = Bytecode::new_raw(code.clone()).hash_slow();
+
+        let provider_rw = factory.provider_rw()?;
+        provider_rw
+            .tx_ref()
+            .put::<tables::PlainAccountState>(pool, Account { nonce: 1, balance: U256::from(10), bytecode_hash: Some(code_hash) })?;
+        provider_rw.tx_ref().put::<tables::PlainAccountState>(eoa, Account { nonce: 5, balance: U256::from(20), bytecode_hash: None })?;
+        provider_rw.tx_ref().put::<tables::Bytecodes>(code_hash, reth_primitives::Bytecode::new_raw(code.clone()))?;
+        provider_rw
+            .tx_ref()
+            .put::<tables::PlainStorageState>(pool, StorageEntry { key: B256::from(U256::from(8)), value: U256::from(1000) })?;
+        provider_rw.commit()?;
+
+        let reth_db = RethDB::new(factory);
+
+        let info = reth_db.basic_ref(pool)?.unwrap();
+        assert_eq!(info.nonce, 1);
+        assert_eq!(info.balance, U256::from(10));
+        assert_eq!(info.code_hash, code_hash);
+        assert_eq!(info.code.unwrap().original_bytes(), code);
+        assert_eq!(reth_db.code_by_hash_ref(code_hash)?.original_bytes(), code);
+        assert_eq!(reth_db.basic_ref(eoa)?.unwrap().code_hash, KECCAK_EMPTY);
+        assert!(reth_db.basic_ref(Address::repeat_byte(3))?.is_none());
+        assert_eq!(reth_db.storage_ref(pool, U256::from(8))?, U256::from(1000));
+        assert_eq!(reth_db.storage_ref(pool, U256::from(9))?, U256::ZERO);
+
+        let db = LoomDB::new().with_ext_db(reth_db);
+        assert_eq!(db.basic_ref(eoa)?.unwrap().nonce, 5);
+        assert_eq!(db.storage_ref(pool, U256::from(8))?, U256::from(1000));
+
+        Ok(())
+    }
+}
@@ -8,19 +8,15 @@ use alloy_transport::Transport;
 use chrono::Utc;
 use futures::StreamExt;
 use reth_chainspec::ChainSpecBuilder;
-use reth_db::mdbx::DatabaseArguments;
-use reth_db::{open_db_read_only, ClientVersion, DatabaseEnv};
-use reth_node_ethereum::EthereumNode;
-use reth_node_types::NodeTypesWithDBAdapter;
 use reth_primitives::BlockWithSenders;
-use reth_provider::providers::StaticFileProvider;
-use reth_provider::{AccountExtReader, BlockReader, ProviderFactory, ReceiptProvider, StateProvider, StorageReader, TransactionVariant};
+use reth_provider::{AccountExtReader, BlockReader, ReceiptProvider, StateProvider, StorageReader, TransactionVariant};
 use std::collections::{BTreeMap, HashMap};
 use std::marker::PhantomData;
 use std::path::Path;
 use std::sync::Arc;
 use tracing::{debug, error, info, trace};
 
+use crate::reth_db::open_reth_provider_factory;
 use loom_core_actors::{Actor, ActorResult, Broadcaster, Producer, WorkerResult};
 use loom_core_actors_macros::Producer;
 use loom_core_blockchain::Blockchain;
@@ -51,14 +47,7 @@ where
 
     let mut block_processed: HashMap<BlockHash, chrono::DateTime<Utc>> = HashMap::new();
 
-    let db_path = Path::new(&db_path);
-    let db = Arc::new(open_db_read_only(db_path.join("db").as_path(), DatabaseArguments::new(ClientVersion::default()))?);
-    let spec = Arc::new(ChainSpecBuilder::mainnet().build());
-    let factory = ProviderFactory::<NodeTypesWithDBAdapter<EthereumNode, Arc<DatabaseEnv>>>::new(
-        db.clone(),
-        spec.clone(),
-        StaticFileProvider::read_only(db_path.join("static_files"), true)?,
-    );
+    let factory = open_reth_provider_factory(Path::new(&db_path), Arc::new(ChainSpecBuilder::mainnet().build()))?;
 
     loop {
         tokio::select! {
@@ -0,0 +1,192 @@
+use std::collections::VecDeque;
+
+use alloy_primitives::{Address, Bytes, FixedBytes, TxHash, B256, U256};
+use eyre::{eyre, Result};
+use serde::Deserialize;
+use tracing::error;
+
+/// Log shared by a MEV-Share hint. Depending on the hint settings of the user only the address and the first topic may be set.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HintLog {
+    pub address: Address,
+    #[serde(default)]
+    pub topics: Vec<B256>,
+    #[serde(default)]
+    pub data: Option<Bytes>,
+}
+
+/// Transaction shared by a MEV-Share hint.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HintTx {
+    #[serde(default)]
+    pub to: Option<Address>,
+    #[serde(default)]
+    pub function_selector: Option<FixedBytes<4>>,
+    #[serde(default)]
+    pub call_data: Option<Bytes>,
+}
+
+/// Event of the MEV-Share SSE stream.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MevShareHint {
+    pub hash: TxHash,
+    #[serde(default)]
+    pub logs: Option<Vec<HintLog>>,
+    #[serde(default)]
+    pub txs: Option<Vec<HintTx>>,
+    #[serde(default)]
+    pub mev_gas_price: Option<U256>,
+    #[serde(default)]
+    pub gas_used: Option<U256>,
+}
+
+impl MevShareHint {
+    pub fn logs(&self) -> &[HintLog] {
+        self.logs.as_deref().unwrap_or_default()
+    }
+
+    pub fn txs(&self) -> &[HintTx] {
+        self.txs.as_deref().unwrap_or_default()
+    }
+}
+
+/// Splits server-sent events stream into event payloads. Comments and fields other than `data` are ignored.
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    buffer: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl SseDecoder {
+    /// Feeds a chunk of the stream and returns payloads of the completed events.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    events.push(self.data.join("\n"));
+                    self.data.clear();
+                }
+            } else if let Some(data) = line.strip_prefix("data:") {
+                self.data.push(data.strip_prefix(' ').unwrap_or(data).to_string());
+            }
+        }
+
+        events
+    }
+}
+
+/// Connection to the MEV-Share SSE endpoint.
+pub struct MevShareHintStream {
+    response: reqwest::Response,
+    decoder: SseDecoder,
+    hints: VecDeque<MevShareHint>,
+}
+
+impl MevShareHintStream {
+    pub async fn connect(client: &reqwest::Client, url: &str) -> Result<Self> {
+        let response = client.get(url).header(reqwest::header::ACCEPT, "text/event-stream").send().await?;
+        if !response.status().is_success() {
+            return Err(eyre!("MEV_SHARE_STREAM_STATUS_{}", response.status().as_u16()));
+        }
+        Ok(Self { response, decoder: SseDecoder::default(), hints: VecDeque::new() })
+    }
+
+    /// Returns the next hint or `None` if the stream is closed. Events that can't be parsed are skipped.
+    pub async fn next_hint(&mut self) -> Result<Option<MevShareHint>> {
+        loop {
+            if let Some(hint) = self.hints.pop_front() {
+                return Ok(Some(hint));
+            }
+
+            let Some(chunk) = self.response.chunk().await? else {
+                return Ok(None);
+            };
+
+            for data in self.decoder.feed(&chunk) {
+                match serde_json::from_str::<MevShareHint>(&data) {
+                    Ok(hint) => self.hints.push_back(hint),
+                    Err(e) => error!("Cannot parse MEV-Share hint {} : {}", data, e),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const HINT: &str = r#"{"hash":"0x0101010101010101010101010101010101010101010101010101010101010101","logs":[{"address":"0x0d4a11d5eeaac28ec3f61d100daf4d40471f1852","topics":["0x1c411e9a96e071241c2f21f7726b17ae89e3cab4c78be50e062b03a9fffbbad1"],"data":"0x00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002"}],"txs":null,"mevGasPrice":"0x1","gasUsed":"0x2"}"#;
+
+    #[test]
+    fn test_parse_hint() {
+        let hint: MevShareHint = serde_json::from_str(HINT).unwrap();
+        assert_eq!(hint.hash, TxHash::repeat_byte(1));
+        assert_eq!(hint.logs().len(), 1);
+        assert_eq!(hint.logs()[0].topics.len(), 1);
+        assert_eq!(hint.logs()[0].data.as_ref().unwrap().len(), 64);
+        assert!(hint.txs().is_empty());
+        assert_eq!(hint.gas_used, Some(U256::from(2)));
+
+        let hint: MevShareHint = serde_json::from_str(
+            r#"{"hash":"0x0101010101010101010101010101010101010101010101010101010101010101","txs":[{"to":"0x0d4a11d5eeaac28ec3f61d100daf4d40471f1852","functionSelector":"0x022c0d9f"}]}"#,
+        )
+        .unwrap();
+        assert!(hint.logs().is_empty());
+        assert_eq!(hint.txs()[0].function_selector, Some(FixedBytes::new([0x02, 0x2c, 0x0d, 0x9f])));
+        assert_eq!(hint.txs()[0].call_data, None);
+    }
+
+    #[test]
+    fn test_sse_decoder() {
+        let mut decoder = SseDecoder::default();
+
+        assert!(decoder.feed(b":ping\n\nda").is_empty());
+        assert!(decoder.feed(b"ta: first\r\n").is_empty());
+        assert_eq!(decoder.feed(b"\r\nevent: x\ndata:second\ndata: line\n\n"), vec!["first".to_string(), "second\nline".to_string()]);
+        assert!(decoder.feed(b"data: incomplete").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_hint_stream() -> Result<()> {
+        let server = MockServer::start().await;
+        let body = format!(":keepalive\n\ndata: {HINT}\n\ndata: not a hint\n\ndata: {HINT}\n\n");
+
+        Mock::given(method("GET"))
+            .and(header("accept", "text/event-stream"))
+            .respond_with(ResponseTemplate::new(200).insert_header("content-type", "text/event-stream").set_body_string(body))
+            .mount(&server)
+            .await;
+
+        let client = reqwest::Client::new();
+        let mut stream = MevShareHintStream::connect(&client, server.uri().as_str()).await?;
+
+        assert_eq!(stream.next_hint().await?.unwrap().hash, TxHash::repeat_byte(1));
+        assert_eq!(stream.next_hint().await?.unwrap().logs().len(), 1);
+        assert!(stream.next_hint().await?.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hint_stream_status_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).respond_with(ResponseTemplate::new(503)).mount(&server).await;
+
+        let client = reqwest::Client::new();
+        assert!(MevShareHintStream::connect(&client, server.uri().as_str()).await.is_err());
+    }
+}
@@ -0,0 +1,338 @@
+use std::collections::{BTreeMap, HashSet};
+
+use alloy_primitives::{Address, Bytes, Log as EVMLog, B256, U256};
+use alloy_sol_types::{SolCall, SolEvent, SolEventInterface};
+use lazy_static::lazy_static;
+use revm::DatabaseRef;
+use tracing::debug;
+
+use crate::hint::{HintLog, HintTx, MevShareHint};
+use loom_defi_abi::uniswap2::IUniswapV2Pair;
+use loom_defi_abi::uniswap2::IUniswapV2Pair::IUniswapV2PairEvents;
+use loom_types_blockchain::GethStateUpdate;
+use loom_types_entities::{Market, PoolClass, PoolWrapper};
+
+lazy_static! {
+    static ref UNISWAP_V2_RESERVES_CELL: U256 = U256::from(8);
+    static ref UNISWAP_V2_TIMESTAMP_MASK: U256 = U256::MAX << 224;
+    static ref UNISWAP_V2_RESERVE_MASK: U256 = (U256::from(1) << 112) - U256::from(1);
+    static ref FEE_DENOMINATOR: U256 = U256::from(10000);
+}
+
+/// Pools of the hint with the state derived from the hint and pools only known to be touched by the hint.
+#[derive(Default)]
+struct HintPools {
+    /// Packed reserves cell of UniswapV2 pools after the hint transaction
+    reserves: BTreeMap<Address, U256>,
+    /// Logs of these pools have Sync events, their swap events are already reflected in the reserves
+    synced: HashSet<Address>,
+    touched: BTreeMap<Address, PoolWrapper>,
+    updated: BTreeMap<Address, PoolWrapper>,
+}
+
+fn unpack_reserves(value: U256) -> (U256, U256) {
+    (value & *UNISWAP_V2_RESERVE_MASK, (value >> 112) & *UNISWAP_V2_RESERVE_MASK)
+}
+
+/// Reserves are packed with blockTimestampLast, the timestamp is kept from the current value
+fn pack_reserves(cur_value: U256, reserve0: U256, reserve1: U256) -> Option<U256> {
+    if reserve0 > *UNISWAP_V2_RESERVE_MASK || reserve1 > *UNISWAP_V2_RESERVE_MASK {
+        return None;
+    }
+    Some((cur_value & *UNISWAP_V2_TIMESTAMP_MASK) | reserve0 | (reserve1 << 112))
+}
+
+/// Reserves after the Sync or Swap event of the log. Swap amounts are applied to the reserves before the log.
+fn reserves_from_log(log: &HintLog, reserves: (U256, U256), synced: bool) -> Option<(U256, U256)> {
+    let log = EVMLog::new(log.address, log.topics.clone(), log.data.clone()?)?;
+
+    match IUniswapV2PairEvents::decode_log(&log, false).ok()?.data {
+        IUniswapV2PairEvents::Sync(sync) => Some((U256::from(sync.reserve0), U256::from(sync.reserve1))),
+        IUniswapV2PairEvents::Swap(swap) if !synced => Some((
+            reserves.0.checked_add(swap.amount0In)?.checked_sub(swap.amount0Out)?,
+            reserves.1.checked_add(swap.amount1In)?.checked_sub(swap.amount1Out)?,
+        )),
+        _ => None,
+    }
+}
+
+/// Reserves after the swap call of the hint calldata. Only amounts out are shared, the amount in is the minimum amount
+/// accepted by the pool with the pool fee.
+fn reserves_from_call_data(call_data: &Bytes, fee: U256, reserves: (U256, U256)) -> Option<(U256, U256)> {
+    let call = IUniswapV2Pair::swapCall::abi_decode(call_data, false).ok()?;
+    if fee.is_zero() || fee > *FEE_DENOMINATOR {
+        return None;
+    }
+
+    let amount_in = |reserve_in: U256, reserve_out: U256, amount_out: U256| -> Option<U256> {
+        let reserve_out_after = reserve_out.checked_sub(amount_out).filter(|reserve| !reserve.is_zero())?;
+        Some(reserve_in * amount_out * *FEE_DENOMINATOR / (reserve_out_after * fee) + U256::from(1))
+    };
+
+    match (call.amount0Out.is_zero(), call.amount1Out.is_zero()) {
+        (true, false) => {
+            let amount0_in = amount_in(reserves.0, reserves.1, call.amount1Out)?;
+            Some((reserves.0 + amount0_in, reserves.1 - call.amount1Out))
+        }
+        (false, true) => {
+            let amount1_in = amount_in(reserves.1, reserves.0, call.amount0Out)?;
+            Some((reserves.0 - call.amount0Out, reserves.1 + amount1_in))
+        }
+        _ => None,
+    }
+}
+
+impl HintPools {
+    fn cur_value<DB: DatabaseRef>(&self, pool_address: &Address, db: &DB) -> U256 {
+        match self.reserves.get(pool_address) {
+            Some(value) => *value,
+            None => db.storage_ref(*pool_address, *UNISWAP_V2_RESERVES_CELL).unwrap_or_default(),
+        }
+    }
+
+    fn update(&mut self, pool: &PoolWrapper, cur_value: U256, reserves: Option<(U256, U256)>) {
+        match reserves.and_then(|(reserve0, reserve1)| pack_reserves(cur_value, reserve0, reserve1)) {
+            Some(value) => {
+                self.reserves.insert(pool.get_address(), value);
+                self.touched.remove(&pool.get_address());
+                self.updated.insert(pool.get_address(), pool.clone());
+            }
+            None => {
+                if !self.updated.contains_key(&pool.get_address()) {
+                    self.touched.insert(pool.get_address(), pool.clone());
+                }
+            }
+        }
+    }
+
+    fn add_log<DB: DatabaseRef>(&mut self, pool: &PoolWrapper, log: &HintLog, db: &DB) {
+        let pool_address = pool.get_address();
+        if pool.get_class() != PoolClass::UniswapV2 {
+            self.update(pool, U256::ZERO, None);
+            return;
+        }
+
+        let synced = self.synced.contains(&pool_address);
+        let cur_value = self.cur_value(&pool_address, db);
+        match reserves_from_log(log, unpack_reserves(cur_value), synced) {
+            Some(reserves) => {
+                if log.topics.first() == Some(&IUniswapV2Pair::Sync::SIGNATURE_HASH) {
+                    self.synced.insert(pool_address);
+                }
+                self.update(pool, cur_value, Some(reserves));
+            }
+            // swap events of synced pools are reflected in the reserves
+            None if synced => {}
+            None => self.update(pool, cur_value, None),
+        }
+    }
+
+    fn add_tx<DB: DatabaseRef>(&mut self, pool: &PoolWrapper, tx: &HintTx, db: &DB) {
+        let pool_address = pool.get_address();
+        if self.updated.contains_key(&pool_address) {
+            // state derived from logs includes the call
+            return;
+        }
+
+        let cur_value = self.cur_value(&pool_address, db);
+        let reserves = match (pool.get_class(), tx.call_data.as_ref()) {
+            (PoolClass::UniswapV2, Some(call_data)) => reserves_from_call_data(call_data, pool.get_fee(), unpack_reserves(cur_value)),
+            _ => None,
+        };
+        self.update(pool, cur_value, reserves);
+    }
+}
+
+/// Matches hint logs and transactions with market pools. Returns state update for pools with the state derived from logs or
+/// swap calldata and swap directions of all pools touched by the hint. Pools with unknown state are searched on the current
+/// state with the hint transaction as the first bundle item.
+pub fn hint_state_update<DB: DatabaseRef>(
+    hint: &MevShareHint,
+    market: &Market,
+    db: &DB,
+) -> (GethStateUpdate, BTreeMap<PoolWrapper, Vec<(Address, Address)>>) {
+    let mut hint_pools = HintPools::default();
+
+    for log in hint.logs() {
+        if let Some(pool) = market.get_pool(&log.address).filter(|_| !market.is_pool_disabled(&log.address)) {
+            hint_pools.add_log(pool, log, db);
+        }
+    }
+
+    for tx in hint.txs() {
+        if let Some(pool) = tx.to.and_then(|to| market.get_pool(&to)).filter(|pool| !market.is_pool_disabled(&pool.get_address())) {
+            hint_pools.add_tx(pool, tx, db);
+        }
+    }
+
+    let mut state_update: GethStateUpdate = GethStateUpdate::new();
+    for (pool_address, value) in hint_pools.reserves.iter() {
+        state_update.entry(*pool_address).or_default().storage.insert(B256::from(*UNISWAP_V2_RESERVES_CELL), B256::from(*value));
+    }
+
+    if !hint_pools.touched.is_empty() {
+        debug!(hash = %hint.hash, updated_pools = hint_pools.updated.len(), touched_pools = hint_pools.touched.len(), "MEV-Share hint touches pools without state");
+    }
+
+    let directions = hint_pools
+        .updated
+        .into_values()
+        .chain(hint_pools.touched.into_values())
+        .map(|pool| {
+            let pool_directions = pool.get_swap_directions();
+            (pool, pool_directions)
+        })
+        .collect();
+
+    (state_update, directions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::TxHash;
+    use loom_defi_abi::uniswap2::IUniswapV2Pair::{Swap, Sync};
+    use loom_defi_pools::UniswapV2Pool;
+    use loom_evm_db::LoomDB;
+    use loom_types_entities::MockPool;
+
+    fn sync_log(address: Address, reserve0: u64, reserve1: u64) -> HintLog {
+        let event = Sync { reserve0: U256::from(reserve0).to(), reserve1: U256::from(reserve1).to() };
+        HintLog { address, topics: vec![Sync::SIGNATURE_HASH], data: Some(Bytes::from(event.encode_data())) }
+    }
+
+    fn swap_log(address: Address, amount0_in: u64, amount1_out: u64) -> HintLog {
+        let event = Swap {
+            sender: Address::ZERO,
+            amount0In: U256::from(amount0_in),
+            amount1In: U256::ZERO,
+            amount0Out: U256::ZERO,
+            amount1Out: U256::from(amount1_out),
+            to: Address::ZERO,
+        };
+        HintLog { address, topics: vec![Swap::SIGNATURE_HASH, B256::ZERO, B256::ZERO], data: Some(Bytes::from(event.encode_data())) }
+    }
+
+    fn reserves_value(state_update: &GethStateUpdate, pool_address: &Address) -> U256 {
+        let value = state_update.get(pool_address).unwrap().storage.get(&B256::from(*UNISWAP_V2_RESERVES_CELL)).unwrap();
+        U256::from_be_bytes(value.0)
+    }
+
+    fn market_with_pool(pool_address: Address) -> Market {
+        let mut market = Market::default();
+        let pool = UniswapV2Pool::new_with_data(
+            pool_address,
+            Address::repeat_byte(2),
+            Address::repeat_byte(3),
+            Address::ZERO,
+            U256::ZERO,
+            U256::ZERO,
+        );
+        market.add_pool(pool).unwrap();
+        market
+    }
+
+    fn db_with_reserves(pool_address: Address, reserve0: u64, reserve1: u64) -> LoomDB {
+        let mut db = LoomDB::new();
+        db.insert_account_storage(pool_address, *UNISWAP_V2_RESERVES_CELL, U256::from(reserve0) | (U256::from(reserve1) << 112)).unwrap();
+        db
+    }
+
+    #[test]
+    fn test_hint_state_update() {
+        let pool_address = Address::repeat_byte(1);
+        let token0 = Address::repeat_byte(2);
+        let token1 = Address::repeat_byte(3);
+
+        let mut market = Market::default();
+        market.add_pool(MockPool::new(token0, token1, pool_address)).unwrap();
+
+        let timestamp = U256::from(0x1234) << 224;
+        let mut db = LoomDB::new();
+        db.insert_account_storage(pool_address, *UNISWAP_V2_RESERVES_CELL, timestamp | U256::from(10)).unwrap();
+
+        let hint = MevShareHint {
+            hash: TxHash::repeat_byte(4),
+            logs: Some(vec![
+                sync_log(pool_address, 100, 200),
+                HintLog { address: Address::repeat_byte(5), topics: vec![Sync::SIGNATURE_HASH], data: None },
+            ]),
+            ..MevShareHint::default()
+        };
+
+        let (state_update, directions) = hint_state_update(&hint, &market, &db);
+
+        assert_eq!(directions.len(), 1);
+        assert_eq!(directions.values().next().unwrap(), &vec![(token0, token1), (token1, token0)]);
+
+        let value = state_update.get(&pool_address).unwrap().storage.get(&B256::from(*UNISWAP_V2_RESERVES_CELL)).unwrap();
+        assert_eq!(U256::from_be_bytes(value.0), timestamp | U256::from(100) | (U256::from(200) << 112));
+    }
+
+    #[test]
+    fn test_hint_swap_log() {
+        let pool_address = Address::repeat_byte(1);
+        let market = market_with_pool(pool_address);
+        let db = db_with_reserves(pool_address, 1000, 2000);
+
+        let hint =
+            MevShareHint { hash: TxHash::repeat_byte(4), logs: Some(vec![swap_log(pool_address, 100, 150)]), ..MevShareHint::default() };
+        let (state_update, directions) = hint_state_update(&hint, &market, &db);
+        assert_eq!(directions.len(), 1);
+        assert_eq!(reserves_value(&state_update, &pool_address), U256::from(1100) | (U256::from(1850) << 112));
+
+        // swap is already reflected in the reserves of the sync event
+        let hint = MevShareHint {
+            hash: TxHash::repeat_byte(4),
+            logs: Some(vec![sync_log(pool_address, 1100, 1850), swap_log(pool_address, 100, 150)]),
+            ..MevShareHint::default()
+        };
+        let (state_update, _) = hint_state_update(&hint, &market, &db);
+        assert_eq!(reserves_value(&state_update, &pool_address), U256::from(1100) | (U256::from(1850) << 112));
+    }
+
+    #[test]
+    fn test_hint_swap_call_data() {
+        let pool_address = Address::repeat_byte(1);
+        let market = market_with_pool(pool_address);
+        let db = db_with_reserves(pool_address, 1_000_000, 2_000_000);
+
+        let call = IUniswapV2Pair::swapCall { amount0Out: U256::ZERO, amount1Out: U256::from(1000), to: Address::ZERO, data: Bytes::new() };
+        let hint = MevShareHint {
+            hash: TxHash::repeat_byte(4),
+            txs: Some(vec![HintTx { to: Some(pool_address), call_data: Some(Bytes::from(call.abi_encode())), ..HintTx::default() }]),
+            ..MevShareHint::default()
+        };
+
+        let (state_update, directions) = hint_state_update(&hint, &market, &db);
+        assert_eq!(directions.len(), 1);
+        // minimum amount in with 0.3% fee
+        assert_eq!(reserves_value(&state_update, &pool_address), U256::from(1_000_502) | (U256::from(1_999_000) << 112));
+    }
+
+    #[test]
+    fn test_hint_touched_pools() {
+        let pool_address = Address::repeat_byte(1);
+        let other_pool_address = Address::repeat_byte(5);
+
+        let mut market = Market::default();
+        market.add_pool(MockPool::new(Address::repeat_byte(2), Address::repeat_byte(3), pool_address)).unwrap();
+        market.add_pool(MockPool::new(Address::repeat_byte(2), Address::repeat_byte(3), other_pool_address)).unwrap();
+
+        let hint = MevShareHint {
+            hash: TxHash::repeat_byte(4),
+            logs: Some(vec![HintLog { address: pool_address, topics: vec![Sync::SIGNATURE_HASH], data: None }]),
+            txs: Some(vec![HintTx {
+                to: Some(other_pool_address),
+                function_selector: Some(IUniswapV2Pair::swapCall::SELECTOR.into()),
+                ..HintTx::default()
+            }]),
+            ..MevShareHint::default()
+        };
+
+        // pools without state are searched on the current state
+        let (state_update, directions) = hint_state_update(&hint, &market, &LoomDB::new());
+        assert!(state_update.is_empty());
+        assert_eq!(directions.len(), 2);
+    }
+}
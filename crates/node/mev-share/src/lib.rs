@@ -0,0 +1,7 @@
+pub use hint::{HintLog, HintTx, MevShareHint, MevShareHintStream, SseDecoder};
+pub use hint_state::hint_state_update;
+pub use mev_share_hint_actor::{MevShareHintActor, MEV_SHARE_STREAM_URL};
+
+mod hint;
+mod hint_state;
+mod mev_share_hint_actor;
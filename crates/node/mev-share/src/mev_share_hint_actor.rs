@@ -0,0 +1,270 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use eyre::Result;
+use revm::DatabaseRef;
+use tokio::sync::Semaphore;
+use tracing::{debug, error, info, warn};
+
+use crate::hint::{MevShareHint, MevShareHintStream};
+use crate::hint_state::hint_state_update;
+use loom_core_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
+use loom_core_actors_macros::{Accessor, Consumer, Producer};
+use loom_core_blockchain::{Blockchain, BlockchainState, Strategy};
+use loom_types_entities::{Market, MarketState};
+use loom_types_events::{MarketEvents, StateUpdateEvent, MEV_SHARE_ORIGIN};
+
+/// Flashbots MEV-Share hint stream
+pub const MEV_SHARE_STREAM_URL: &str = "https://mev-share.flashbots.net";
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Hints processed at the same time, hints above the limit are skipped
+const MAX_CONCURRENT_HINTS: usize = 64;
+
+#[derive(Clone, Copy, Debug)]
+struct NextBlock {
+    number: u64,
+    timestamp: u64,
+    base_fee: u64,
+}
+
+async fn process_hint<DB>(
+    hint: MevShareHint,
+    next_block: NextBlock,
+    market: SharedState<Market>,
+    market_state: SharedState<MarketState<DB>>,
+    state_updates_tx: Broadcaster<StateUpdateEvent<DB>>,
+) -> Result<()>
+where
+    DB: DatabaseRef + Send + Sync + Clone + 'static,
+{
+    let market_state_guard = market_state.read().await;
+    let (state_update, directions) = hint_state_update(&hint, &*market.read().await, &market_state_guard.state_db);
+
+    if directions.is_empty() {
+        return Ok(());
+    }
+
+    debug!(hash = %hint.hash, pools = directions.len(), "MEV-Share hint affected pools");
+
+    let request = StateUpdateEvent::new(
+        next_block.number,
+        next_block.timestamp,
+        next_block.base_fee,
+        market_state_guard.state_db.clone(),
+        vec![state_update],
+        None,
+        directions,
+        vec![hint.hash],
+        vec![],
+        MEV_SHARE_ORIGIN.to_string(),
+        9000,
+    );
+    drop(market_state_guard);
+
+    if let Err(e) = state_updates_tx.send(request).await {
+        error!("state_updates_tx.send error : {}", e)
+    }
+    Ok(())
+}
+
+pub async fn mev_share_hint_worker<DB>(
+    url: String,
+    market: SharedState<Market>,
+    market_state: SharedState<MarketState<DB>>,
+    market_events_rx: Broadcaster<MarketEvents>,
+    state_updates_tx: Broadcaster<StateUpdateEvent<DB>>,
+) -> WorkerResult
+where
+    DB: DatabaseRef + Send + Sync + Clone + 'static,
+{
+    subscribe!(market_events_rx);
+
+    let client = reqwest::Client::new();
+    let mut next_block: Option<NextBlock> = None;
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_HINTS));
+
+    loop {
+        let mut stream = match MevShareHintStream::connect(&client, url.as_str()).await {
+            Ok(stream) => {
+                info!("Connected to MEV-Share stream {}", url);
+                stream
+            }
+            Err(e) => {
+                error!("Cannot connect to MEV-Share stream {} : {}", url, e);
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                continue;
+            }
+        };
+
+        loop {
+            tokio::select! {
+                msg = market_events_rx.recv() => {
+                    if let Ok(MarketEvents::BlockHeaderUpdate{ block_number, timestamp, next_base_fee, .. }) = msg {
+                        next_block = Some(NextBlock { number: block_number + 1, timestamp: timestamp + 12, base_fee: next_base_fee });
+                    }
+                }
+                hint = stream.next_hint() => {
+                    match hint {
+                        Ok(Some(hint)) => {
+                            let Some(next_block) = next_block else {
+                                warn!("Did not received block header update yet!");
+                                continue;
+                            };
+                            let Ok(permit) = semaphore.clone().try_acquire_owned() else {
+                                warn!(hash = %hint.hash, "Too many MEV-Share hints in progress, skipping");
+                                continue;
+                            };
+                            let market = market.clone();
+                            let market_state = market_state.clone();
+                            let state_updates_tx = state_updates_tx.clone();
+                            tokio::task::spawn(async move {
+                                if let Err(e) = process_hint(hint, next_block, market, market_state, state_updates_tx).await {
+                                    error!("process_hint error : {}", e)
+                                }
+                                drop(permit);
+                            });
+                        }
+                        Ok(None) => {
+                            warn!("MEV-Share stream closed {}", url);
+                            break;
+                        }
+                        Err(e) => {
+                            error!("MEV-Share stream error {} : {}", url, e);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+/// Subscribes to MEV-Share hints and sends [`StateUpdateEvent`] for pools with the state known from the hint logs.
+#[derive(Accessor, Consumer, Producer)]
+pub struct MevShareHintActor<DB: Clone + Send + Sync + 'static> {
+    url: String,
+    #[accessor]
+    market: Option<SharedState<Market>>,
+    #[accessor]
+    market_state: Option<SharedState<MarketState<DB>>>,
+    #[consumer]
+    market_events_rx: Option<Broadcaster<MarketEvents>>,
+    #[producer]
+    state_updates_tx: Option<Broadcaster<StateUpdateEvent<DB>>>,
+}
+
+impl<DB> MevShareHintActor<DB>
+where
+    DB: DatabaseRef + Send + Sync + Clone + 'static,
+{
+    pub fn new(url: String) -> MevShareHintActor<DB> {
+        MevShareHintActor { url, market: None, market_state: None, market_events_rx: None, state_updates_tx: None }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain, state: &BlockchainState<DB>, strategy: &Strategy<DB>) -> Self {
+        Self {
+            market: Some(bc.market()),
+            market_state: Some(state.market_state()),
+            market_events_rx: Some(bc.market_events_channel()),
+            state_updates_tx: Some(strategy.state_update_channel()),
+            ..self
+        }
+    }
+}
+
+impl<DB> Actor for MevShareHintActor<DB>
+where
+    DB: DatabaseRef + Send + Sync + Clone + 'static,
+{
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(mev_share_hint_worker(
+            self.url.clone(),
+            self.market.clone().unwrap(),
+            self.market_state.clone().unwrap(),
+            self.market_events_rx.clone().unwrap(),
+            self.state_updates_tx.clone().unwrap(),
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "MevShareHintActor"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, TxHash, B256, U256};
+    use loom_defi_pools::UniswapV2Pool;
+    use loom_evm_db::LoomDB;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    // Sync(1, 2) of the pool 0x0d4a11d5eeaac28ec3f61d100daf4d40471f1852
+    const HINT: &str = r#"{"hash":"0x0101010101010101010101010101010101010101010101010101010101010101","logs":[{"address":"0x0d4a11d5eeaac28ec3f61d100daf4d40471f1852","topics":["0x1c411e9a96e071241c2f21f7726b17ae89e3cab4c78be50e062b03a9fffbbad1"],"data":"0x00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002"}],"txs":null}"#;
+
+    #[tokio::test]
+    async fn test_hint_actor_state_update() -> Result<()> {
+        let server = MockServer::start().await;
+        // delay the body to receive the block header before the hint
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(format!("data: {HINT}\n\n"))
+                    .set_delay(Duration::from_millis(500)),
+            )
+            .mount(&server)
+            .await;
+
+        let pool_address: Address = "0x0d4a11d5eeaac28ec3f61d100daf4d40471f1852".parse()?;
+        let token0 = Address::repeat_byte(2);
+        let token1 = Address::repeat_byte(3);
+        let mut market = Market::default();
+        market.add_pool(UniswapV2Pool::new_with_data(pool_address, token0, token1, Address::ZERO, U256::ZERO, U256::ZERO))?;
+
+        let market_events: Broadcaster<MarketEvents> = Broadcaster::new(10);
+        let state_updates: Broadcaster<StateUpdateEvent<LoomDB>> = Broadcaster::new(10);
+        let mut state_updates_rx = state_updates.subscribe().await;
+
+        let mut actor = MevShareHintActor::new(server.uri());
+        actor
+            .access(SharedState::new(market))
+            .access(SharedState::new(MarketState::new(LoomDB::new())))
+            .consume(market_events.clone())
+            .produce(state_updates.clone());
+        actor.start()?;
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        market_events
+            .send(MarketEvents::BlockHeaderUpdate {
+                block_number: 100,
+                block_hash: B256::ZERO,
+                timestamp: 1000,
+                base_fee: 10,
+                next_base_fee: 11,
+            })
+            .await?;
+
+        let event = tokio::time::timeout(Duration::from_secs(5), state_updates_rx.recv()).await??;
+
+        assert_eq!(event.next_block_number, 101);
+        assert_eq!(event.next_block_timestamp, 1012);
+        assert_eq!(event.next_base_fee, 11);
+        assert_eq!(event.stuffing_txs_hashes, vec![TxHash::repeat_byte(1)]);
+        assert_eq!(event.origin, MEV_SHARE_ORIGIN);
+        assert_eq!(event.directions().len(), 1);
+        assert_eq!(event.directions().values().next().unwrap(), &vec![(token0, token1), (token1, token0)]);
+
+        // reserves slot of the pair
+        let reserves = event.state_update()[0].get(&pool_address).unwrap().storage.get(&B256::from(U256::from(8))).unwrap();
+        assert_eq!(U256::from_be_bytes(reserves.0), U256::from(1) | (U256::from(2) << 112));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MempoolSourcesResponse {
+    pub sources: Vec<MempoolSource>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MempoolSource {
+    pub source: String,
+    pub txs: usize,
+    pub wins: usize,
+    pub win_rate: f64,
+    pub median_lead_ms: Option<f64>,
+    pub p99_lead_ms: Option<f64>,
+}
+
+impl From<loom_types_entities::MempoolSourceStat> for MempoolSource {
+    fn from(stat: loom_types_entities::MempoolSourceStat) -> Self {
+        MempoolSource {
+            source: stat.source,
+            txs: stat.txs,
+            wins: stat.wins,
+            win_rate: stat.win_rate,
+            median_lead_ms: stat.median_lead_ms,
+            p99_lead_ms: stat.p99_lead_ms,
+        }
+    }
+}
@@ -1,5 +1,6 @@
 pub mod block;
 pub mod flashbots;
+pub mod mempool;
 pub mod pagination;
 pub mod pool;
 pub mod quote;
@@ -152,3 +152,91 @@ impl From<&PoolProtocol> for loom_types_entities::PoolProtocol {
 pub struct MarketStats {
     pub total_pools: usize,
 }
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct QuarantinedPoolResponse {
+    pub pools: Vec<QuarantinedPool>,
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct QuarantinedPool {
+    #[schema(schema_with = String::schema)]
+    pub address: Address,
+    pub status: PoolQuarantineStatus,
+    pub until_block: Option<u64>,
+    pub score: f64,
+    pub score_block: u64,
+    pub quarantine_count: u32,
+    pub reasons: Vec<PoolErrorReasonCount>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PoolErrorReasonCount {
+    pub reason: PoolErrorReason,
+    pub count: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PoolQuarantineStatus {
+    Disabled,
+    Quarantined,
+    Active,
+}
+impl From<loom_types_entities::PoolQuarantineStatus> for PoolQuarantineStatus {
+    fn from(status: loom_types_entities::PoolQuarantineStatus) -> Self {
+        match status {
+            loom_types_entities::PoolQuarantineStatus::Active => PoolQuarantineStatus::Active,
+            loom_types_entities::PoolQuarantineStatus::Quarantined { .. } => PoolQuarantineStatus::Quarantined,
+            loom_types_entities::PoolQuarantineStatus::Disabled => PoolQuarantineStatus::Disabled,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PoolErrorReason {
+    ZeroAmount,
+    Liquidity,
+    Overflow,
+    State,
+    NotSupported,
+    Reverted,
+    Other,
+}
+impl From<loom_types_entities::PoolErrorReason> for PoolErrorReason {
+    fn from(reason: loom_types_entities::PoolErrorReason) -> Self {
+        match reason {
+            loom_types_entities::PoolErrorReason::ZeroAmount => PoolErrorReason::ZeroAmount,
+            loom_types_entities::PoolErrorReason::Liquidity => PoolErrorReason::Liquidity,
+            loom_types_entities::PoolErrorReason::Overflow => PoolErrorReason::Overflow,
+            loom_types_entities::PoolErrorReason::State => PoolErrorReason::State,
+            loom_types_entities::PoolErrorReason::NotSupported => PoolErrorReason::NotSupported,
+            loom_types_entities::PoolErrorReason::Reverted => PoolErrorReason::Reverted,
+            loom_types_entities::PoolErrorReason::Other => PoolErrorReason::Other,
+        }
+    }
+}
+
+impl From<(Address, &loom_types_entities::PoolHealthRecord)> for QuarantinedPool {
+    fn from((address, record): (Address, &loom_types_entities::PoolHealthRecord)) -> Self {
+        let until_block = match record.status {
+            loom_types_entities::PoolQuarantineStatus::Quarantined { until_block } => Some(until_block),
+            _ => None,
+        };
+        let mut reasons: Vec<(loom_types_entities::PoolErrorReason, u64)> = record.reasons.iter().map(|(r, c)| (*r, *c)).collect();
+        reasons.sort();
+        QuarantinedPool {
+            address,
+            status: PoolQuarantineStatus::from(record.status),
+            until_block,
+            score: record.score,
+            score_block: record.score_block,
+            quarantine_count: record.quarantine_count,
+            reasons: reasons.into_iter().map(|(reason, count)| PoolErrorReasonCount { reason: reason.into(), count }).collect(),
+            last_error: record.last_error.clone(),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use crate::dto::mempool::{MempoolSource, MempoolSourcesResponse};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use loom_rpc_state::AppState;
+use revm::{DatabaseCommit, DatabaseRef};
+
+/// Get mempool sources
+///
+/// Get win rate and lead time of every mempool source over its latest txs
+#[utoipa::path(
+    get,
+    path = "/sources",
+    tag = "mempool",
+    tags = [],
+    responses(
+    (status = 200, description = "Latency statistics of mempool sources", body = MempoolSourcesResponse),
+    )
+)]
+pub async fn mempool_sources<DB: DatabaseRef + DatabaseCommit + Send + Sync + Clone + 'static>(
+    State(app_state): State<AppState<DB>>,
+) -> Result<Json<MempoolSourcesResponse>, (StatusCode, String)> {
+    let stats = app_state.bc.mempool_source_stats().read().await.stats();
+    Ok(Json(MempoolSourcesResponse { sources: stats.into_iter().map(MempoolSource::from).collect() }))
+}
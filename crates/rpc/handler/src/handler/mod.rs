@@ -1,4 +1,5 @@
 pub mod blocks;
 pub mod flashbots;
+pub mod mempool;
 pub mod pools;
 pub mod ws;
@@ -1,5 +1,7 @@
 use crate::dto::pagination::Pagination;
-use crate::dto::pool::{MarketStats, Pool, PoolClass, PoolDetailsResponse, PoolProtocol, PoolResponse};
+use crate::dto::pool::{
+    MarketStats, Pool, PoolClass, PoolDetailsResponse, PoolProtocol, PoolResponse, QuarantinedPool, QuarantinedPoolResponse,
+};
 use crate::dto::quote::{Filter, QuoteRequest, QuoteResponse};
 use alloy_primitives::Address;
 use axum::extract::{Path, Query, State};
@@ -108,6 +110,37 @@ pub async fn pool<DB: DatabaseRef + DatabaseCommit + Send + Sync + Clone + 'stat
     }
 }
 
+/// Get quarantined pools
+///
+/// Get pools with swap errors, their quarantine status and error reasons
+#[utoipa::path(
+    get,
+    path = "/quarantine",
+    tag = "market",
+    tags = [],
+    params(
+        Pagination
+    ),
+    responses(
+    (status = 200, description = "Pools with swap errors", body = QuarantinedPoolResponse),
+    )
+)]
+pub async fn quarantined_pools<DB: DatabaseRef + DatabaseCommit + Send + Sync + Clone + 'static>(
+    State(app_state): State<AppState<DB>>,
+    pagination: Query<Pagination>,
+) -> Result<Json<QuarantinedPoolResponse>, (StatusCode, String)> {
+    let pool_quarantine = app_state.bc.pool_quarantine();
+    let pool_quarantine_guard = pool_quarantine.read().await;
+
+    let mut pools: Vec<QuarantinedPool> =
+        pool_quarantine_guard.records().iter().map(|(address, record)| QuarantinedPool::from((*address, record))).collect();
+    pools.sort_by(|a, b| a.status.cmp(&b.status).then(a.address.cmp(&b.address)));
+    let total = pools.len();
+    let pools = pools.into_iter().skip(pagination.start()).take(pagination.limit).collect();
+
+    Ok(Json(QuarantinedPoolResponse { pools, total }))
+}
+
 /// Market statistics
 ///
 /// Get the latest market statistics
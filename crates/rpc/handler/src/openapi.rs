@@ -1,17 +1,26 @@
 use crate::dto::block::BlockHeader;
+use crate::dto::mempool::MempoolSource;
+use crate::dto::mempool::MempoolSourcesResponse;
 use crate::dto::pool::MarketStats;
 use crate::dto::pool::Pool;
 use crate::dto::pool::PoolClass;
 use crate::dto::pool::PoolDetailsResponse;
+use crate::dto::pool::PoolErrorReason;
+use crate::dto::pool::PoolErrorReasonCount;
 use crate::dto::pool::PoolProtocol;
+use crate::dto::pool::PoolQuarantineStatus;
 use crate::dto::pool::PoolResponse;
+use crate::dto::pool::QuarantinedPool;
+use crate::dto::pool::QuarantinedPoolResponse;
 use crate::dto::quote::QuoteRequest;
 use crate::dto::quote::QuoteResponse;
 use crate::handler::blocks::__path_latest_block;
+use crate::handler::mempool::__path_mempool_sources;
 use crate::handler::pools::__path_market_stats;
 use crate::handler::pools::__path_pool;
 use crate::handler::pools::__path_pool_quote;
 use crate::handler::pools::__path_pools;
+use crate::handler::pools::__path_quarantined_pools;
 use utoipa::OpenApi;
 
 #[derive(OpenApi)]
@@ -26,19 +35,44 @@ pub struct BlockApi;
 
 #[derive(OpenApi)]
 #[openapi(
-    paths(pool, pools, pool_quote, market_stats),
+    paths(pool, pools, pool_quote, quarantined_pools, market_stats),
     tags(
         (name = "market", description = "Market")
     ),
-    components(schemas(PoolResponse, PoolDetailsResponse, Pool, PoolClass, PoolProtocol, MarketStats, QuoteRequest, QuoteResponse))
+    components(schemas(
+        PoolResponse,
+        PoolDetailsResponse,
+        Pool,
+        PoolClass,
+        PoolProtocol,
+        MarketStats,
+        QuoteRequest,
+        QuoteResponse,
+        QuarantinedPoolResponse,
+        QuarantinedPool,
+        PoolQuarantineStatus,
+        PoolErrorReason,
+        PoolErrorReasonCount
+    ))
 )]
 pub struct MarketApi;
 
+#[derive(OpenApi)]
+#[openapi(
+    paths(mempool_sources),
+    tags(
+        (name = "mempool", description = "Mempool")
+    ),
+    components(schemas(MempoolSourcesResponse, MempoolSource))
+)]
+pub struct MempoolApi;
+
 #[derive(OpenApi)]
 #[openapi(
     nest(
         (path = "/api/v1/block/", api = BlockApi),
-        (path = "/api/v1/markets", api = MarketApi)
+        (path = "/api/v1/markets", api = MarketApi),
+        (path = "/api/v1/mempool", api = MempoolApi)
     )
 )]
 pub struct ApiDoc;
@@ -1,6 +1,7 @@
 use crate::handler::blocks::latest_block;
 use crate::handler::flashbots::flashbots;
-use crate::handler::pools::{market_stats, pool, pool_quote, pools};
+use crate::handler::mempool::mempool_sources;
+use crate::handler::pools::{market_stats, pool, pool_quote, pools, quarantined_pools};
 use crate::handler::ws::ws_handler;
 use crate::openapi::ApiDoc;
 use axum::routing::{get, post};
@@ -18,6 +19,7 @@ pub fn router<DB: DatabaseRef<Error = ErrReport> + DatabaseCommit + Sync + Send
             Router::new()
                 .nest("/block", router_block()) // rename to node
                 .nest("/markets", router_market())
+                .nest("/mempool", router_mempool())
                 .nest("/flashbots", Router::new().route("/", post(flashbots))),
         )
         .route("/ws", get(ws_handler))
@@ -29,10 +31,15 @@ pub fn router_block<DB: DatabaseRef + DatabaseCommit + Sync + Send + Clone + 'st
     Router::new().route("/latest_block", get(latest_block))
 }
 
+pub fn router_mempool<DB: DatabaseRef + DatabaseCommit + Sync + Send + Clone + 'static>() -> Router<AppState<DB>> {
+    Router::new().route("/sources", get(mempool_sources))
+}
+
 pub fn router_market<DB: DatabaseRef<Error = ErrReport> + DatabaseCommit + Sync + Send + Clone + 'static>() -> Router<AppState<DB>> {
     Router::new()
         .route("/pools/:address", get(pool))
         .route("/pools/:address/quote", post(pool_quote))
         .route("/pools", get(pools))
+        .route("/quarantine", get(quarantined_pools))
         .route("/", get(market_stats))
 }
@@ -0,0 +1,8 @@
+CREATE TABLE token_metadata
+(
+    address    bytea PRIMARY KEY,
+    symbol     text      NULL,
+    name       text      NULL,
+    decimals   smallint  NULL,
+    updated_at timestamp NOT NULL DEFAULT now()
+);
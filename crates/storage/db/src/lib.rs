@@ -1,3 +1,6 @@
 pub use pool::{init_db_pool, DbPool};
+pub use token_metadata::{read_token_metadata, write_token_metadata, TokenMetadata, TokenMetadataError};
 
 mod pool;
+mod schema;
+mod token_metadata;
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use alloy_primitives::Address;
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use diesel_async::pooled_connection::PoolError;
+use diesel_async::RunQueryDsl;
+use thiserror::Error;
+
+use crate::schema::token_metadata;
+use crate::DbPool;
+
+#[derive(Debug, Error)]
+pub enum TokenMetadataError {
+    #[error("Failed to get connection: {0}")]
+    ConnectionError(#[from] bb8::RunError<PoolError>),
+    #[error("Failed to query token metadata: {0}")]
+    QueryError(#[from] diesel::result::Error),
+}
+
+/// ERC20 token metadata. Fields are None if the token call reverted or returned unexpected data.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TokenMetadata {
+    pub symbol: Option<String>,
+    pub name: Option<String>,
+    pub decimals: Option<u8>,
+}
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = token_metadata)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+struct TokenMetadataRow {
+    address: Vec<u8>,
+    symbol: Option<String>,
+    name: Option<String>,
+    decimals: Option<i16>,
+}
+
+/// Reads stored metadata of the given tokens. Tokens without a row are absent in the result.
+pub async fn read_token_metadata(db_pool: &DbPool, addresses: &[Address]) -> Result<HashMap<Address, TokenMetadata>, TokenMetadataError> {
+    let mut conn = db_pool.get().await?;
+    let keys: Vec<Vec<u8>> = addresses.iter().map(|address| address.to_vec()).collect();
+
+    let rows: Vec<TokenMetadataRow> =
+        token_metadata::table.filter(token_metadata::address.eq_any(keys)).select(TokenMetadataRow::as_select()).load(&mut conn).await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            let address = Address::try_from(row.address.as_slice()).ok()?;
+            let metadata = TokenMetadata { symbol: row.symbol, name: row.name, decimals: row.decimals.and_then(|d| u8::try_from(d).ok()) };
+            Some((address, metadata))
+        })
+        .collect())
+}
+
+/// Inserts or replaces stored metadata of the given tokens
+pub async fn write_token_metadata(db_pool: &DbPool, entries: &[(Address, TokenMetadata)]) -> Result<(), TokenMetadataError> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let mut conn = db_pool.get().await?;
+    let rows: Vec<TokenMetadataRow> = entries
+        .iter()
+        .map(|(address, metadata)| TokenMetadataRow {
+            address: address.to_vec(),
+            symbol: metadata.symbol.clone(),
+            name: metadata.name.clone(),
+            decimals: metadata.decimals.map(i16::from),
+        })
+        .collect();
+
+    diesel::insert_into(token_metadata::table)
+        .values(&rows)
+        .on_conflict(token_metadata::address)
+        .do_update()
+        .set((
+            token_metadata::symbol.eq(excluded(token_metadata::symbol)),
+            token_metadata::name.eq(excluded(token_metadata::name)),
+            token_metadata::decimals.eq(excluded(token_metadata::decimals)),
+            token_metadata::updated_at.eq(diesel::dsl::now),
+        ))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
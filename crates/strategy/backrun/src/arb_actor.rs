@@ -10,18 +10,20 @@ use tracing::info;
 
 use loom_core_actors::{Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
 use loom_core_actors_macros::{Accessor, Consumer, Producer};
+use loom_evm_db::DatabaseLoomExt;
 use loom_node_debug_provider::DebugProviderExt;
 use loom_types_blockchain::Mempool;
-use loom_types_entities::{BlockHistory, LatestBlock, Market, MarketState};
+use loom_types_entities::{BlockHistory, GasModel, LatestBlock, Market, MarketState};
 use loom_types_events::{MarketEvents, MempoolEvents, MessageHealthEvent, MessageSwapCompose};
 
 use super::{PendingTxStateChangeProcessorActor, StateChangeArbSearcherActor};
 use crate::block_state_change_processor::BlockStateChangeProcessorActor;
-use crate::BackrunConfig;
+use crate::{BackrunConfig, ProfitPolicy};
 
 #[derive(Accessor, Consumer, Producer)]
 pub struct StateChangeArbActor<P, T, N, DB: Clone + Send + Sync + 'static> {
     backrun_config: BackrunConfig,
+    profit_policy: Option<SharedState<ProfitPolicy>>,
     client: P,
     use_blocks: bool,
     use_mempool: bool,
@@ -35,6 +37,8 @@ pub struct StateChangeArbActor<P, T, N, DB: Clone + Send + Sync + 'static> {
     market_state: Option<SharedState<MarketState<DB>>>,
     #[accessor]
     block_history: Option<SharedState<BlockHistory<DB>>>,
+    #[accessor]
+    gas_model: Option<SharedState<GasModel>>,
     #[consumer]
     mempool_events_tx: Option<Broadcaster<MempoolEvents>>,
     #[consumer]
@@ -57,6 +61,7 @@ where
     pub fn new(client: P, use_blocks: bool, use_mempool: bool, backrun_config: BackrunConfig) -> StateChangeArbActor<P, T, N, DB> {
         StateChangeArbActor {
             backrun_config,
+            profit_policy: None,
             client,
             use_blocks,
             use_mempool,
@@ -65,6 +70,7 @@ where
             latest_block: None,
             block_history: None,
             market_state: None,
+            gas_model: None,
             mempool_events_tx: None,
             market_events_tx: None,
             compose_channel_tx: None,
@@ -73,6 +79,11 @@ where
             _n: PhantomData,
         }
     }
+
+    /// Shares the profit policy with the searcher, a reloaded policy is applied to the next state update
+    pub fn with_profit_policy(self, profit_policy: SharedState<ProfitPolicy>) -> Self {
+        Self { profit_policy: Some(profit_policy), ..self }
+    }
 }
 
 impl<P, T, N, DB> Actor for StateChangeArbActor<P, T, N, DB>
@@ -80,13 +91,27 @@ where
     T: Transport + Clone,
     N: Network,
     P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
-    DB: DatabaseRef<Error = ErrReport> + Database<Error = ErrReport> + DatabaseCommit + Send + Sync + Clone + Default + 'static,
+    DB: DatabaseRef<Error = ErrReport>
+        + Database<Error = ErrReport>
+        + DatabaseCommit
+        + DatabaseLoomExt
+        + Send
+        + Sync
+        + Clone
+        + Default
+        + 'static,
 {
     fn start(&self) -> ActorResult {
         let searcher_pool_update_channel = Broadcaster::new(100);
         let mut tasks: Vec<JoinHandle<WorkerResult>> = Vec::new();
 
         let mut state_update_searcher = StateChangeArbSearcherActor::new(self.backrun_config.clone());
+        if let Some(profit_policy) = self.profit_policy.clone() {
+            state_update_searcher = state_update_searcher.with_profit_policy(profit_policy);
+        }
+        if let Some(gas_model) = self.gas_model.clone() {
+            state_update_searcher.access(gas_model);
+        }
         match state_update_searcher
             .access(self.market.clone().unwrap())
             .consume(searcher_pool_update_channel.clone())
@@ -104,7 +129,8 @@ where
         }
 
         if self.mempool_events_tx.is_some() && self.use_mempool {
-            let mut pending_tx_state_processor = PendingTxStateChangeProcessorActor::new(self.client.clone());
+            let mut pending_tx_state_processor =
+                PendingTxStateChangeProcessorActor::new(self.client.clone()).with_simulation(self.backrun_config.pending_tx_simulation());
             match pending_tx_state_processor
                 .access(self.mempool.clone().unwrap())
                 .access(self.latest_block.clone().unwrap())
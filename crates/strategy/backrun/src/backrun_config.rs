@@ -1,7 +1,10 @@
 use alloy_primitives::Address;
+use loom_types_entities::bidding::BiddingConfig;
 use loom_types_entities::config::StrategyConfig;
 use serde::Deserialize;
 
+use crate::{PendingTxSimulation, ProfitPolicy};
+
 #[derive(Clone, Deserialize, Debug)]
 pub struct BackrunConfigSection {
     pub backrun_strategy: BackrunConfig,
@@ -11,6 +14,11 @@ pub struct BackrunConfigSection {
 pub struct BackrunConfig {
     eoa: Option<Address>,
     smart: bool,
+    #[serde(default)]
+    pending_tx_simulation: PendingTxSimulation,
+    #[serde(default)]
+    profit_policy: ProfitPolicy,
+    bidding: Option<BiddingConfig>,
 }
 
 impl StrategyConfig for BackrunConfig {
@@ -24,13 +32,38 @@ impl BackrunConfig {
         self.smart
     }
 
+    pub fn pending_tx_simulation(&self) -> PendingTxSimulation {
+        self.pending_tx_simulation
+    }
+
+    pub fn profit_policy(&self) -> ProfitPolicy {
+        self.profit_policy.clone()
+    }
+
+    /// Adaptive bidding is disabled if not set
+    pub fn bidding(&self) -> Option<BiddingConfig> {
+        self.bidding.clone()
+    }
+
     pub fn new_dumb() -> Self {
-        Self { eoa: None, smart: false }
+        Self {
+            eoa: None,
+            smart: false,
+            pending_tx_simulation: PendingTxSimulation::default(),
+            profit_policy: ProfitPolicy::default(),
+            bidding: None,
+        }
     }
 }
 
 impl Default for BackrunConfig {
     fn default() -> Self {
-        Self { eoa: None, smart: true }
+        Self {
+            eoa: None,
+            smart: true,
+            pending_tx_simulation: PendingTxSimulation::default(),
+            profit_policy: ProfitPolicy::default(),
+            bidding: None,
+        }
     }
 }
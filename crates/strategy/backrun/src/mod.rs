@@ -1,11 +1,15 @@
 pub use arb_actor::StateChangeArbActor;
 pub use backrun_config::{BackrunConfig, BackrunConfigSection};
 pub use block_state_change_processor::BlockStateChangeProcessorActor;
+pub use loom_types_entities::profit_policy::ProfitPolicy;
+pub use pending_tx_simulation::{simulate_pending_tx, PendingTxSimulation};
 pub use pending_tx_state_change_processor::PendingTxStateChangeProcessorActor;
+pub use profit_policy_reload_actor::{reload_profit_policy, ProfitPolicyReloadActor};
 pub use state_change_arb_searcher::StateChangeArbSearcherActor;
 pub use swap_calculator::SwapCalculator;
 
 mod block_state_change_processor;
+mod pending_tx_simulation;
 mod pending_tx_state_change_processor;
 mod state_change_arb_searcher;
 
@@ -13,4 +17,5 @@ mod affected_pools;
 mod affected_pools_code;
 mod arb_actor;
 mod backrun_config;
+mod profit_policy_reload_actor;
 mod swap_calculator;
@@ -0,0 +1,221 @@
+use alloy_primitives::{Address, B256, U256};
+use alloy_rpc_types::Transaction;
+use alloy_rpc_types_trace::geth::AccountState;
+use eyre::{eyre, ErrReport, Result};
+use loom_evm_db::DatabaseLoomExt;
+use loom_evm_utils::evm::revert_bytes_to_string;
+use loom_evm_utils::evm_env::env_for_block;
+use loom_evm_utils::evm_tx_env::tx_to_evm_tx;
+use loom_types_blockchain::GethStateUpdate;
+use loom_types_entities::Market;
+use revm::primitives::{AccountInfo, Bytecode, EVMError, ExecutionResult, CANCUN};
+use revm::{DatabaseRef, Evm};
+use serde::Deserialize;
+use tracing::trace;
+
+/// How state changes of pending txs are obtained.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PendingTxSimulation {
+    /// `debug_traceCall` of the node
+    #[default]
+    Node,
+    /// revm on the market state db, `debug_traceCall` of the node if the tx reads records that are not in the db
+    Local,
+}
+
+const CACHE_MISS: &str = "CACHE_MISS";
+
+/// Read only view of the market state db that fails on records that are not loaded instead of fetching them.
+struct CachedStateDB<'a, DB> {
+    db: &'a DB,
+    caller: Address,
+    coinbase: Address,
+}
+
+impl<DB> DatabaseRef for CachedStateDB<'_, DB>
+where
+    DB: DatabaseRef<Error = ErrReport> + DatabaseLoomExt,
+{
+    type Error = ErrReport;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        if self.db.is_account(&address) {
+            self.db.basic_ref(address)
+        } else if address == self.caller || address == self.coinbase {
+            // balance and nonce checks are disabled
+            Ok(Some(AccountInfo::default()))
+        } else {
+            trace!(%address, "Pending tx simulation account cache miss");
+            Err(eyre!(CACHE_MISS))
+        }
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.db.code_by_hash_ref(code_hash)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if self.db.is_slot(&address, &index) {
+            self.db.storage_ref(address, index)
+        } else {
+            trace!(%address, %index, "Pending tx simulation slot cache miss");
+            Err(eyre!(CACHE_MISS))
+        }
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.db.block_hash_ref(number)
+    }
+}
+
+/// Executes the pending tx on top of `state_db` and returns pre and post state of changed pools, tokens and created
+/// contracts in the format of `debug_traceCall` with the prestate tracer in diff mode.
+/// Returns None if the tx reads an account or a slot that is not loaded to `state_db`.
+pub fn simulate_pending_tx<DB>(
+    state_db: &DB,
+    market: &Market,
+    tx: &Transaction,
+    block_number: u64,
+    block_timestamp: u64,
+    base_fee: u64,
+) -> Result<Option<(GethStateUpdate, GethStateUpdate)>>
+where
+    DB: DatabaseRef<Error = ErrReport> + DatabaseLoomExt,
+{
+    let mut env = env_for_block(block_number, block_timestamp);
+    env.block.basefee = U256::from(base_fee);
+    env.cfg.disable_balance_check = true;
+    env.tx = tx_to_evm_tx(tx);
+    env.tx.nonce = None;
+    env.tx.chain_id = None;
+    env.tx.gas_price = env.tx.gas_price.max(env.block.basefee);
+
+    let db = CachedStateDB { db: state_db, caller: tx.from, coinbase: env.block.coinbase };
+
+    let mut evm = Evm::builder().with_spec_id(CANCUN).with_ref_db(&db).with_env(Box::new(env)).build();
+    let result_and_state = match evm.transact() {
+        Ok(result_and_state) => result_and_state,
+        Err(EVMError::Database(e)) if e.to_string() == CACHE_MISS => return Ok(None),
+        Err(e) => return Err(eyre!("TRANSACT_ERROR: {}", e)),
+    };
+    drop(evm);
+
+    match result_and_state.result {
+        ExecutionResult::Success { .. } => {}
+        ExecutionResult::Revert { output, gas_used } => {
+            return Err(eyre!("REVERTED: {} gas_used={}", revert_bytes_to_string(&output), gas_used));
+        }
+        ExecutionResult::Halt { reason, gas_used } => return Err(eyre!("HALTED: {:?} gas_used={}", reason, gas_used)),
+    }
+
+    let mut pre = GethStateUpdate::new();
+    let mut post = GethStateUpdate::new();
+
+    for (address, account) in result_and_state.state.into_iter() {
+        if !account.is_touched() {
+            continue;
+        }
+        let is_created = account.is_created();
+        if !is_created && !market.is_pool(&address) && market.get_token(&address).is_none() {
+            continue;
+        }
+
+        let original_info = if is_created { None } else { db.basic_ref(address)? };
+        let mut pre_state = AccountState::default();
+        let mut post_state = AccountState::default();
+
+        match &original_info {
+            Some(original_info) => {
+                if original_info.balance != account.info.balance {
+                    pre_state.balance = Some(original_info.balance);
+                    post_state.balance = Some(account.info.balance);
+                }
+                if original_info.nonce != account.info.nonce {
+                    pre_state.nonce = Some(original_info.nonce);
+                    post_state.nonce = Some(account.info.nonce);
+                }
+            }
+            None => {
+                post_state.balance = Some(account.info.balance);
+                post_state.nonce = Some(account.info.nonce);
+                post_state.code = account.info.code.as_ref().map(|code| code.original_bytes());
+            }
+        }
+
+        for (slot, value) in account.changed_storage_slots() {
+            pre_state.storage.insert((*slot).into(), value.original_value().into());
+            post_state.storage.insert((*slot).into(), value.present_value().into());
+        }
+
+        if post_state == AccountState::default() {
+            continue;
+        }
+        if original_info.is_some() {
+            pre.insert(address, pre_state);
+        }
+        post.insert(address, post_state);
+    }
+
+    Ok(Some((pre, post)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_consensus::TxLegacy;
+    use alloy_primitives::{Bytes, TxKind};
+    use loom_evm_db::LoomDB;
+    use loom_types_blockchain::mock_tx;
+    use loom_types_entities::Token;
+
+    // stores calldata word to slot 0: PUSH1 0 CALLDATALOAD PUSH1 0 SSTORE STOP
+    const STORE_CODE: [u8; 7] = [0x60, 0x00, 0x35, 0x60, 0x00, 0x55, 0x00];
+
+    fn state_db(token: Address, with_slot: bool) -> LoomDB {
+        let mut db = LoomDB::new();
+        let code = Bytecode::new_raw(Bytes::from(STORE_CODE.to_vec()));
+        db.insert_account_info(token, AccountInfo { code_hash: code.hash_slow(), code: Some(code), ..Default::default() });
+        if with_slot {
+            db.insert_account_storage(token, U256::ZERO, U256::from(1)).unwrap();
+        }
+        db
+    }
+
+    fn store_tx(to: Address, value: U256) -> Transaction {
+        let tx = TxLegacy {
+            to: TxKind::Call(to),
+            gas_limit: 100_000,
+            gas_price: 1,
+            input: value.to_be_bytes_vec().into(),
+            ..Default::default()
+        };
+        mock_tx(Address::repeat_byte(0xee), tx, B256::ZERO)
+    }
+
+    #[test]
+    fn test_simulate_pending_tx() -> Result<()> {
+        let token = Address::repeat_byte(1);
+        let mut market = Market::default();
+        market.add_token(Token::new(token))?;
+
+        let (pre, post) = simulate_pending_tx(&state_db(token, true), &market, &store_tx(token, U256::from(7)), 1, 12, 10)?.unwrap();
+
+        assert_eq!(pre[&token].storage[&B256::ZERO], B256::from(U256::from(1)));
+        assert_eq!(post[&token].storage[&B256::ZERO], B256::from(U256::from(7)));
+        assert!(!post.contains_key(&Address::repeat_byte(0xee)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_simulate_pending_tx_cache_miss() -> Result<()> {
+        let token = Address::repeat_byte(1);
+        let market = Market::default();
+
+        assert!(simulate_pending_tx(&state_db(token, false), &market, &store_tx(token, U256::from(7)), 1, 12, 10)?.is_none());
+        assert!(
+            simulate_pending_tx(&state_db(token, true), &market, &store_tx(Address::repeat_byte(2), U256::from(7)), 1, 12, 10)?.is_none()
+        );
+        Ok(())
+    }
+}
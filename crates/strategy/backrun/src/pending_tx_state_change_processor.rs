@@ -7,7 +7,7 @@ use alloy_rpc_types::state::StateOverride;
 use alloy_rpc_types::{BlockOverrides, TransactionRequest};
 use alloy_rpc_types_trace::geth::GethDebugTracingCallOptions;
 use alloy_transport::Transport;
-use eyre::{eyre, Result};
+use eyre::{eyre, ErrReport, Result};
 use lazy_static::lazy_static;
 use revm::primitives::bitvec::macros::internal::funty::Fundamental;
 use revm::{Database, DatabaseCommit, DatabaseRef};
@@ -16,11 +16,12 @@ use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{debug, error, warn};
+use tracing::{debug, error, trace, warn};
 
 use loom_core_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
 use loom_core_actors_macros::{Accessor, Consumer, Producer};
 use loom_core_blockchain::{Blockchain, BlockchainState, Strategy};
+use loom_evm_db::DatabaseLoomExt;
 use loom_node_debug_provider::DebugProviderExt;
 use loom_types_blockchain::{debug_trace_call_diff, GethStateUpdateVec, Mempool, TRACING_CALL_OPTS};
 use loom_types_entities::required_state::{accounts_vec_len, storage_vec_len};
@@ -29,6 +30,7 @@ use loom_types_events::{MarketEvents, MempoolEvents, StateUpdateEvent};
 
 use super::affected_pools::get_affected_pools;
 use super::affected_pools_code::{get_affected_pools_from_code, is_pool_code};
+use super::pending_tx_simulation::{simulate_pending_tx, PendingTxSimulation};
 
 lazy_static! {
     static ref COINBASE: Address = "0x1f9090aaE28b8a3dCeaDf281B0F12828e676c326".parse().unwrap();
@@ -48,13 +50,14 @@ pub async fn pending_tx_state_change_task<P, T, N, DB>(
     cur_block_time: u64,
     cur_next_base_fee: u64,
     cur_state_override: StateOverride,
+    simulation: PendingTxSimulation,
     state_updates_broadcaster: Broadcaster<StateUpdateEvent<DB>>,
 ) -> Result<()>
 where
     T: Transport + Clone,
     N: Network,
     P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
-    DB: DatabaseRef + Database + DatabaseCommit + Clone + Send + Sync + 'static,
+    DB: DatabaseRef<Error = ErrReport> + Database + DatabaseCommit + DatabaseLoomExt + Clone + Send + Sync + 'static,
 {
     let mut state_update_vec: GethStateUpdateVec = Vec::new();
     let mut state_required_vec: GethStateUpdateVec = Vec::new();
@@ -137,8 +140,25 @@ where
         return Err(eyre!("NON_AFFECTING_TX"));
     }
 
-    let diff_trace_result =
-        debug_trace_call_diff(client.clone(), transaction_request, BlockNumberOrTag::Latest.into(), Some(call_opts)).await;
+    let local_simulation_result = match simulation {
+        PendingTxSimulation::Local => {
+            let cur_state_db = market_state.read().await.state_db.clone();
+            let market_guard = market.read().await;
+            simulate_pending_tx(&cur_state_db, &market_guard, &tx, cur_block_number, cur_block_time, cur_next_base_fee)
+        }
+        PendingTxSimulation::Node => Ok(None),
+    };
+
+    let diff_trace_result = match local_simulation_result {
+        Ok(Some(diff)) => Ok(diff),
+        Ok(None) => {
+            if simulation == PendingTxSimulation::Local {
+                trace!(%tx_hash, "Local simulation cache miss, tracing with node");
+            }
+            debug_trace_call_diff(client.clone(), transaction_request, BlockNumberOrTag::Latest.into(), Some(call_opts)).await
+        }
+        Err(error) => Err(error),
+    };
     match diff_trace_result {
         Ok((pre, post)) => {
             state_required_vec.push(pre.clone());
@@ -261,12 +281,13 @@ pub async fn pending_tx_state_change_worker<P, T, N, DB>(
     mempool_events_rx: Broadcaster<MempoolEvents>,
     market_events_rx: Broadcaster<MarketEvents>,
     state_updates_broadcaster: Broadcaster<StateUpdateEvent<DB>>,
+    simulation: PendingTxSimulation,
 ) -> WorkerResult
 where
     T: Transport + Clone,
     N: Network,
     P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
-    DB: DatabaseRef + Database + DatabaseCommit + Clone + Send + Sync + 'static,
+    DB: DatabaseRef<Error = ErrReport> + Database + DatabaseCommit + DatabaseLoomExt + Clone + Send + Sync + 'static,
 {
     subscribe!(mempool_events_rx);
     subscribe!(market_events_rx);
@@ -322,9 +343,14 @@ where
                                 cur_block_time.unwrap_or_default(),
                                 cur_next_base_fee,
                                 cur_state_override.clone(),
+                                simulation,
                                 state_updates_broadcaster.clone(),
                             )
                         );
+                    } else if let MempoolEvents::MempoolTxReplaced{ tx_hash, replaced_by } = mempool_event_msg {
+                        // the replaced tx can't be mined, its updates are not processed anymore
+                        debug!("Pending tx {} replaced by {}", tx_hash, replaced_by);
+                        affecting_tx.write().await.insert(tx_hash, false);
                     }
                 }
             }
@@ -335,6 +361,7 @@ where
 #[derive(Accessor, Consumer, Producer)]
 pub struct PendingTxStateChangeProcessorActor<P, T, N, DB: Clone + Send + Sync + 'static> {
     client: P,
+    simulation: PendingTxSimulation,
     #[accessor]
     market: Option<SharedState<Market>>,
     #[accessor]
@@ -363,6 +390,7 @@ where
     pub fn new(client: P) -> PendingTxStateChangeProcessorActor<P, T, N, DB> {
         PendingTxStateChangeProcessorActor {
             client,
+            simulation: PendingTxSimulation::default(),
             market: None,
             mempool: None,
             market_state: None,
@@ -375,6 +403,10 @@ where
         }
     }
 
+    pub fn with_simulation(self, simulation: PendingTxSimulation) -> Self {
+        Self { simulation, ..self }
+    }
+
     pub fn on_bc(self, bc: &Blockchain, state: &BlockchainState<DB>, strategy: &Strategy<DB>) -> Self {
         Self {
             market: Some(bc.market()),
@@ -394,7 +426,7 @@ where
     T: Transport + Clone,
     N: Network,
     P: Provider<T, N> + DebugProviderExt<T, N> + Send + Sync + Clone + 'static,
-    DB: DatabaseRef + Database + DatabaseCommit + Send + Sync + Clone + 'static,
+    DB: DatabaseRef<Error = ErrReport> + Database + DatabaseCommit + DatabaseLoomExt + Send + Sync + Clone + 'static,
 {
     fn start(&self) -> ActorResult {
         let task = tokio::task::spawn(pending_tx_state_change_worker(
@@ -406,6 +438,7 @@ where
             self.mempool_events_rx.clone().unwrap(),
             self.market_events_rx.clone().unwrap(),
             self.state_updates_tx.clone().unwrap(),
+            self.simulation,
         ));
         Ok(vec![task])
     }
@@ -0,0 +1,102 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use eyre::Result;
+use loom_core_actors::{Accessor, Actor, ActorResult, SharedState, WorkerResult};
+use loom_core_actors_macros::Accessor;
+use loom_types_entities::config::load_from_file;
+use tracing::{error, info};
+
+use crate::{BackrunConfigSection, ProfitPolicy};
+
+async fn config_modified(config_path: &Path) -> Option<SystemTime> {
+    tokio::fs::metadata(config_path).await.and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Replaces the profit policy with the `backrun_strategy.profit_policy` section of the config file
+pub async fn reload_profit_policy(config_path: &Path, profit_policy: &SharedState<ProfitPolicy>) -> Result<()> {
+    let config = load_from_file::<BackrunConfigSection>(config_path.to_path_buf()).await?;
+    *profit_policy.write().await = config.backrun_strategy.profit_policy();
+    Ok(())
+}
+
+pub async fn profit_policy_reload_worker(
+    config_path: PathBuf,
+    interval: Duration,
+    profit_policy: SharedState<ProfitPolicy>,
+) -> WorkerResult {
+    let mut modified = config_modified(&config_path).await;
+    let mut interval = tokio::time::interval(interval);
+
+    loop {
+        interval.tick().await;
+        let cur_modified = config_modified(&config_path).await;
+        if cur_modified == modified {
+            continue;
+        }
+        modified = cur_modified;
+
+        match reload_profit_policy(&config_path, &profit_policy).await {
+            Ok(_) => info!(path = %config_path.display(), "Profit policy reloaded"),
+            Err(error) => error!(%error, path = %config_path.display(), "Profit policy reload failed, the current policy is kept"),
+        }
+    }
+}
+
+/// Reloads the shared profit policy of the backrun searchers when the config file is modified
+#[derive(Accessor)]
+pub struct ProfitPolicyReloadActor {
+    config_path: PathBuf,
+    interval: Duration,
+    #[accessor]
+    profit_policy: Option<SharedState<ProfitPolicy>>,
+}
+
+impl ProfitPolicyReloadActor {
+    pub fn new(config_path: impl Into<PathBuf>) -> Self {
+        Self { config_path: config_path.into(), interval: Duration::from_secs(10), profit_policy: None }
+    }
+
+    /// Interval of config file modification checks
+    pub fn with_interval(self, interval: Duration) -> Self {
+        Self { interval, ..self }
+    }
+
+    pub fn with_profit_policy(self, profit_policy: SharedState<ProfitPolicy>) -> Self {
+        Self { profit_policy: Some(profit_policy), ..self }
+    }
+}
+
+impl Actor for ProfitPolicyReloadActor {
+    fn start(&self) -> ActorResult {
+        let task =
+            tokio::task::spawn(profit_policy_reload_worker(self.config_path.clone(), self.interval, self.profit_policy.clone().unwrap()));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "ProfitPolicyReloadActor"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use loom_types_entities::temp_file_path;
+
+    #[tokio::test]
+    async fn test_reload_profit_policy() -> Result<()> {
+        let (_dir, path) = temp_file_path("config.toml")?;
+        let profit_policy = SharedState::new(ProfitPolicy::default());
+
+        tokio::fs::write(&path, "[backrun_strategy]\nsmart = true\n[backrun_strategy.profit_policy]\nmin_net_profit_eth = 0.1\n").await?;
+        reload_profit_policy(&path, &profit_policy).await?;
+        assert_eq!(profit_policy.read().await.min_net_profit_eth, 0.1);
+
+        // invalid config keeps the current policy
+        tokio::fs::write(&path, "[backrun_strategy.profit_policy]\nmin_net_profit_eth = \"a\"\n").await?;
+        assert!(reload_profit_policy(&path, &profit_policy).await.is_err());
+        assert_eq!(profit_policy.read().await.min_net_profit_eth, 0.1);
+        Ok(())
+    }
+}
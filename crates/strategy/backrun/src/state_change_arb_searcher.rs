@@ -14,6 +14,7 @@ use tracing::warn;
 use tracing::{debug, error, info, trace};
 
 use crate::BackrunConfig;
+use crate::ProfitPolicy;
 use crate::SwapCalculator;
 use loom_core_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
 use loom_core_actors_macros::{Accessor, Consumer, Producer};
@@ -21,22 +22,30 @@ use loom_core_blockchain::{Blockchain, Strategy};
 use loom_evm_db::DatabaseHelpers;
 use loom_types_blockchain::SwapError;
 use loom_types_entities::config::StrategyConfig;
-use loom_types_entities::{Market, PoolWrapper, Swap, SwapLine, SwapPath};
+use loom_types_entities::{GasModel, Market, PoolWrapper, Swap, SwapLine, SwapPath};
 use loom_types_events::{
     BestTxSwapCompose, HealthEvent, Message, MessageHealthEvent, MessageSwapCompose, StateUpdateEvent, SwapComposeData, SwapComposeMessage,
     TxComposeData,
 };
 
+/// Max number of calculated swap lines ranked together before the prepare requests are sent
+const RANK_BATCH_SIZE: usize = 32;
+
 async fn state_change_arb_searcher_task<DB: DatabaseRef<Error = ErrReport> + DatabaseCommit + Send + Sync + Clone + Default + 'static>(
     thread_pool: Arc<ThreadPool>,
     backrun_config: BackrunConfig,
+    profit_policy: SharedState<ProfitPolicy>,
     state_update_event: StateUpdateEvent<DB>,
     market: SharedState<Market>,
+    gas_model: SharedState<GasModel>,
     swap_request_tx: Broadcaster<MessageSwapCompose<DB>>,
     pool_health_monitor_tx: Broadcaster<MessageHealthEvent>,
 ) -> Result<()> {
     debug!("Message received {} stuffing : {:?}", state_update_event.origin, state_update_event.stuffing_tx_hash());
 
+    // policy is fixed for the task, updates are applied to the next state update
+    let profit_policy = profit_policy.read().await.clone();
+
     let mut db = state_update_event.market_state().clone();
     DatabaseHelpers::apply_geth_state_update_vec(&mut db, state_update_event.state_update().clone());
 
@@ -51,11 +60,18 @@ async fn state_change_arb_searcher_task<DB: DatabaseRef<Error = ErrReport> + Dat
             Some(paths) => paths
                 .into_iter()
                 .filter(|swap_path| !swap_path.pools.iter().any(|pool| !market_guard_read.is_pool_disabled(&pool.get_address())))
+                .filter(|swap_path| swap_path.is_transfer_safe())
+                .filter(|swap_path| profit_policy.is_path_allowed(swap_path))
                 .collect(),
             None => {
                 let mut pool_direction: BTreeMap<PoolWrapper, Vec<(Address, Address)>> = BTreeMap::new();
                 pool_direction.insert(pool.clone(), v.clone());
-                market_guard_read.build_swap_path_vec(&pool_direction).unwrap_or_default()
+                market_guard_read
+                    .build_swap_path_vec(&pool_direction)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|swap_path| profit_policy.is_path_allowed(swap_path))
+                    .collect()
             }
         };
 
@@ -104,12 +120,12 @@ async fn state_change_arb_searcher_task<DB: DatabaseRef<Error = ErrReport> + Dat
                         trace!("Calc result received: {}", mut_item);
 
                         if let Ok(profit) = mut_item.profit() {
-                            if profit.is_positive() && mut_item.abs_profit_eth() > U256::from(state_update_event.next_base_fee * 100_000) {
+                            if profit.is_positive() {
                                 if let Err(error) = swap_path_tx.try_send(Ok(mut_item)) {
                                     error!(%error, "swap_path_tx.try_send")
                                 }
                             } else {
-                                trace!("profit is not enough")
+                                trace!("profit is not positive")
                             }
                         }
                     }
@@ -143,44 +159,72 @@ async fn state_change_arb_searcher_task<DB: DatabaseRef<Error = ErrReport> + Dat
 
     let mut failed_pools: HashSet<SwapError> = HashSet::new();
 
-    while let Some(swap_line_result) = swap_line_rx.recv().await {
-        match swap_line_result {
-            Ok(swap_line) => {
-                let prepare_request = SwapComposeMessage::Prepare(SwapComposeData {
-                    tx_compose: TxComposeData {
-                        eoa: backrun_config.eoa(),
-                        next_block_number: state_update_event.next_block_number,
-                        next_block_timestamp: state_update_event.next_block_timestamp,
-                        next_block_base_fee: state_update_event.next_base_fee,
-                        gas: swap_line.gas_used.unwrap_or(300000),
-                        stuffing_txs: state_update_event.stuffing_txs.clone(),
-                        stuffing_txs_hashes: state_update_event.stuffing_txs_hashes.clone(),
-                        ..TxComposeData::default()
-                    },
-                    swap: Swap::BackrunSwapLine(swap_line),
-                    origin: Some(state_update_event.origin.clone()),
-                    tips_pct: Some(state_update_event.tips_pct),
-                    poststate: Some(db.clone()),
-                    poststate_update: Some(state_update_event.state_update().clone()),
-                    ..SwapComposeData::default()
-                });
-
-                if !backrun_config.smart() || best_answers.check(&prepare_request) {
-                    if let Err(e) = swap_request_tx_clone.send(Message::new(prepare_request)).await {
-                        error!("swap_request_tx_clone.send {}", e)
+    // results are ranked and sent in batches as they arrive, so the first profitable lines are not held back by the slowest paths
+    let mut batch: Vec<Result<SwapLine, SwapError>> = Vec::with_capacity(RANK_BATCH_SIZE);
+
+    while swap_line_rx.recv_many(&mut batch, RANK_BATCH_SIZE).await > 0 {
+        let mut swap_lines: Vec<SwapLine> = Vec::with_capacity(batch.len());
+        for swap_line_result in batch.drain(..) {
+            match swap_line_result {
+                Ok(swap_line) => swap_lines.push(swap_line),
+                Err(swap_error) => {
+                    if swap_error.error.is_pool_error() && failed_pools.insert(swap_error.clone()) {
+                        if let Err(e) = pool_health_monitor_tx_clone.send(Message::new(HealthEvent::PoolSwapError(swap_error))).await {
+                            error!("try_send to pool_health_monitor error : {:?}", e)
+                        }
                     }
                 }
             }
-            Err(swap_error) => {
-                if failed_pools.insert(swap_error.clone()) {
-                    if let Err(e) = pool_health_monitor_tx_clone.send(Message::new(HealthEvent::PoolSwapError(swap_error))).await {
-                        error!("try_send to pool_health_monitor error : {:?}", e)
-                    }
-                }
+
+            answers += 1;
+        }
+
+        // filter profit against gas cost of the calibrated gas model with the profit policy and rank by net profit
+        let gas_model_guard = gas_model.read().await;
+        let mut ranked_swap_lines: Vec<(U256, u64, SwapLine)> = Vec::new();
+        for mut swap_line in swap_lines {
+            if let Some(swap_gas) = gas_model_guard.swap_line_gas(&swap_line) {
+                swap_line.gas_used = Some(swap_gas);
+            }
+            let gas = gas_model_guard.swap_line_tx_gas(&swap_line);
+            let gas_cost = U256::from(gas) * U256::from(state_update_event.next_base_fee);
+            match profit_policy.net_profit_eth(&swap_line, gas_cost) {
+                Ok(net_profit_eth) => ranked_swap_lines.push((net_profit_eth, gas, swap_line)),
+                Err(error) => trace!(profit_eth = %swap_line.abs_profit_eth(), %gas_cost, %error, "profit is not enough"),
             }
         }
+        drop(gas_model_guard);
+        ranked_swap_lines.sort_by(|a, b| b.0.cmp(&a.0));
+
+        for (_, gas, swap_line) in ranked_swap_lines {
+            let profit_eth = swap_line.abs_profit_eth();
+            let tips_pct = profit_policy.tips_pct(&profit_eth, state_update_event.tips_pct);
+            let prepare_request = SwapComposeMessage::Prepare(SwapComposeData {
+                tx_compose: TxComposeData {
+                    eoa: backrun_config.eoa(),
+                    next_block_number: state_update_event.next_block_number,
+                    next_block_timestamp: state_update_event.next_block_timestamp,
+                    next_block_base_fee: state_update_event.next_base_fee,
+                    gas,
+                    stuffing_txs: state_update_event.stuffing_txs.clone(),
+                    stuffing_txs_hashes: state_update_event.stuffing_txs_hashes.clone(),
+                    origin: Some(state_update_event.origin.clone()),
+                    ..TxComposeData::default()
+                },
+                swap: Swap::BackrunSwapLine(swap_line),
+                origin: Some(state_update_event.origin.clone()),
+                tips_pct: Some(tips_pct),
+                poststate: Some(db.clone()),
+                poststate_update: Some(state_update_event.state_update().clone()),
+                ..SwapComposeData::default()
+            });
 
-        answers += 1;
+            if !backrun_config.smart() || best_answers.check(&prepare_request) {
+                if let Err(e) = swap_request_tx_clone.send(Message::new(prepare_request)).await {
+                    error!("swap_request_tx_clone.send {}", e)
+                }
+            }
+        }
     }
     info!(
         origin = %state_update_event.origin,
@@ -198,7 +242,9 @@ pub async fn state_change_arb_searcher_worker<
     DB: DatabaseRef<Error = ErrReport> + DatabaseCommit + Send + Sync + Clone + Default + 'static,
 >(
     backrun_config: BackrunConfig,
+    profit_policy: SharedState<ProfitPolicy>,
     market: SharedState<Market>,
+    gas_model: SharedState<GasModel>,
     search_request_rx: Broadcaster<StateUpdateEvent<DB>>,
     swap_request_tx: Broadcaster<MessageSwapCompose<DB>>,
     pool_health_monitor_tx: Broadcaster<MessageHealthEvent>,
@@ -219,8 +265,10 @@ pub async fn state_change_arb_searcher_worker<
                         state_change_arb_searcher_task(
                             thread_pool.clone(),
                             backrun_config.clone(),
+                            profit_policy.clone(),
                             msg,
                             market.clone(),
+                            gas_model.clone(),
                             swap_request_tx.clone(),
                             pool_health_monitor_tx.clone()
                         )
@@ -234,8 +282,11 @@ pub async fn state_change_arb_searcher_worker<
 #[derive(Accessor, Consumer, Producer)]
 pub struct StateChangeArbSearcherActor<DB: Clone + Send + Sync + 'static> {
     backrun_config: BackrunConfig,
+    profit_policy: SharedState<ProfitPolicy>,
     #[accessor]
     market: Option<SharedState<Market>>,
+    #[accessor]
+    gas_model: Option<SharedState<GasModel>>,
     #[consumer]
     state_update_rx: Option<Broadcaster<StateUpdateEvent<DB>>>,
     #[producer]
@@ -246,18 +297,37 @@ pub struct StateChangeArbSearcherActor<DB: Clone + Send + Sync + 'static> {
 
 impl<DB: DatabaseRef<Error = ErrReport> + Send + Sync + Clone + 'static> StateChangeArbSearcherActor<DB> {
     pub fn new(backrun_config: BackrunConfig) -> StateChangeArbSearcherActor<DB> {
-        StateChangeArbSearcherActor { backrun_config, market: None, state_update_rx: None, compose_tx: None, pool_health_monitor_tx: None }
+        StateChangeArbSearcherActor {
+            profit_policy: SharedState::new(backrun_config.profit_policy()),
+            backrun_config,
+            market: None,
+            gas_model: None,
+            state_update_rx: None,
+            compose_tx: None,
+            pool_health_monitor_tx: None,
+        }
     }
 
     pub fn on_bc(self, bc: &Blockchain, strategy: &Strategy<DB>) -> Self {
         Self {
             market: Some(bc.market()),
+            gas_model: Some(bc.gas_model()),
             pool_health_monitor_tx: Some(bc.pool_health_monitor_channel()),
             compose_tx: Some(strategy.swap_compose_channel()),
             state_update_rx: Some(strategy.state_update_channel()),
             ..self
         }
     }
+
+    /// Uses a shared profit policy, a new policy written to it is applied to the next state update
+    pub fn with_profit_policy(self, profit_policy: SharedState<ProfitPolicy>) -> Self {
+        Self { profit_policy, ..self }
+    }
+
+    /// Shared profit policy of the searcher that can be replaced while the searcher is running
+    pub fn profit_policy(&self) -> SharedState<ProfitPolicy> {
+        self.profit_policy.clone()
+    }
 }
 
 impl<DB: DatabaseRef<Error = ErrReport> + DatabaseCommit + Send + Sync + Clone + Default + 'static> Actor
@@ -266,7 +336,9 @@ impl<DB: DatabaseRef<Error = ErrReport> + DatabaseCommit + Send + Sync + Clone +
     fn start(&self) -> ActorResult {
         let task = tokio::task::spawn(state_change_arb_searcher_worker(
             self.backrun_config.clone(),
+            self.profit_policy.clone(),
             self.market.clone().unwrap(),
+            self.gas_model.clone().unwrap_or_else(|| SharedState::new(GasModel::new())),
             self.state_update_rx.clone().unwrap(),
             self.compose_tx.clone().unwrap(),
             self.pool_health_monitor_tx.clone().unwrap(),
@@ -3,7 +3,7 @@ use alloy_primitives::U256;
 use eyre::ErrReport;
 use lazy_static::lazy_static;
 use loom_types_blockchain::LoomDataTypes;
-use loom_types_blockchain::SwapError;
+use loom_types_blockchain::{PoolError, SwapError};
 use loom_types_entities::SwapLine;
 use revm::primitives::Env;
 use revm::DatabaseRef;
@@ -26,7 +26,7 @@ impl SwapCalculator {
             //trace!("calculate : {} amount in : {}",first_token.get_symbol(), first_token.to_float(amount_in) );
             path.optimize_with_in_amount(state, env, amount_in)
         } else {
-            Err(path.to_error("PRICE_NOT_SET".to_string()))
+            Err(path.to_error(PoolError::PriceNotSet))
         }
     }
 }
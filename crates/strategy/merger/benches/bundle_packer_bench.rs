@@ -0,0 +1,42 @@
+use std::env;
+use std::fs;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use loom_strategy_merger::{pack_candidates, PackingProblem};
+
+/// Candidate sets recorded by `PackingMergerActor::with_record_dir`, read from the directory in `PACKING_CANDIDATES_DIR`
+fn load_problems() -> Vec<(String, PackingProblem)> {
+    let Ok(dir) = env::var("PACKING_CANDIDATES_DIR") else {
+        eprintln!("PACKING_CANDIDATES_DIR is not set, bundle packer benchmarks are skipped");
+        return Vec::new();
+    };
+    let mut problems: Vec<(String, PackingProblem)> = fs::read_dir(dir)
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .map(|entry| {
+            let problem: PackingProblem = serde_json::from_slice(&fs::read(entry.path()).unwrap()).unwrap();
+            (entry.file_name().to_string_lossy().to_string(), problem)
+        })
+        .collect();
+    problems.sort_by(|a, b| b.1.candidates.len().cmp(&a.1.candidates.len()));
+    problems
+}
+
+pub fn bench_bundle_packer(c: &mut Criterion) {
+    let problems = load_problems();
+    if problems.is_empty() {
+        return;
+    }
+    let mut group = c.benchmark_group("bundle_packer");
+
+    for (name, problem) in problems.into_iter().take(10) {
+        group.bench_function(format!("{}_{}", name, problem.candidates.len()), |b| {
+            b.iter(|| pack_candidates(black_box(&problem.candidates), black_box(problem.limits)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_bundle_packer);
+criterion_main!(benches);
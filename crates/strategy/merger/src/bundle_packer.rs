@@ -0,0 +1,183 @@
+use std::collections::HashSet;
+
+use alloy_primitives::{Address, TxHash, U256};
+use loom_types_events::SwapComposeData;
+use serde::{Deserialize, Serialize};
+
+/// Candidates with the lowest profit are ignored above this count, the search is exponential in the number of candidates
+pub const MAX_PACK_CANDIDATES: usize = 24;
+
+/// Swap of a bundle for the target block reduced to what constrains packing with other swaps
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackCandidate {
+    /// Profit in ETH after the gas cost
+    pub profit_eth: U256,
+    pub gas: u64,
+    pub gas_cost: U256,
+    pub pools: Vec<Address>,
+    pub stuffing_txs: Vec<TxHash>,
+    /// Addresses with state changed by the stuffing txs
+    pub stuffing_changes: Vec<Address>,
+}
+
+impl PackCandidate {
+    pub fn from_request<DB: Clone + 'static>(request: &SwapComposeData<DB>) -> Self {
+        let gas_cost = U256::from(request.gas_cost());
+        let stuffing_changes = if request.tx_compose.stuffing_txs_hashes.is_empty() {
+            Vec::new()
+        } else {
+            let changes: HashSet<Address> =
+                request.poststate_update.iter().flatten().flat_map(|state_update| state_update.keys().cloned()).collect();
+            changes.into_iter().collect()
+        };
+
+        PackCandidate {
+            profit_eth: request.swap.abs_profit_eth().saturating_sub(gas_cost),
+            gas: request.tx_compose.gas,
+            gas_cost,
+            pools: request.swap.get_pool_address_vec(),
+            stuffing_txs: request.tx_compose.stuffing_txs_hashes.clone(),
+            stuffing_changes,
+        }
+    }
+
+    fn changed_by_foreign_stuffing(&self, other: &PackCandidate) -> bool {
+        other.stuffing_txs.iter().any(|tx_hash| !self.stuffing_txs.contains(tx_hash))
+            && self.pools.iter().any(|pool| other.stuffing_changes.contains(pool))
+    }
+
+    /// Candidates conflict if they swap on the same pool or stuffing txs of one change pools of the other.
+    /// Shared stuffing txs are included once and don't conflict.
+    pub fn conflicts(&self, other: &PackCandidate) -> bool {
+        self.pools.iter().any(|pool| other.pools.contains(pool))
+            || self.changed_by_foreign_stuffing(other)
+            || other.changed_by_foreign_stuffing(self)
+    }
+}
+
+/// Limits of the merged bundle. Gas cost of all swaps is paid by the signer of the bundle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackingLimits {
+    pub max_gas: u64,
+    pub balance: U256,
+}
+
+impl PackingLimits {
+    /// Limits left after the candidate is packed
+    pub fn without(&self, candidate: &PackCandidate) -> Option<PackingLimits> {
+        Some(PackingLimits { max_gas: self.max_gas.checked_sub(candidate.gas)?, balance: self.balance.checked_sub(candidate.gas_cost)? })
+    }
+}
+
+/// Candidate set of the block recorded for benchmarks
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PackingProblem {
+    pub limits: PackingLimits,
+    pub candidates: Vec<PackCandidate>,
+}
+
+struct PackingSearch<'a> {
+    candidates: &'a [PackCandidate],
+    limits: PackingLimits,
+    order: Vec<usize>,
+    conflicts: Vec<Vec<bool>>,
+    // profit of candidates from the position in the order to the end
+    remaining_profit: Vec<U256>,
+    best: Vec<usize>,
+    best_profit: U256,
+}
+
+impl PackingSearch<'_> {
+    fn search(&mut self, pos: usize, chosen: &mut Vec<usize>, profit: U256, gas: u64, gas_cost: U256) {
+        if profit > self.best_profit {
+            self.best_profit = profit;
+            self.best = chosen.iter().map(|idx| self.order[*idx]).collect();
+        }
+        if pos >= self.order.len() || profit + self.remaining_profit[pos] <= self.best_profit {
+            return;
+        }
+
+        let candidate = &self.candidates[self.order[pos]];
+        let fits = gas + candidate.gas <= self.limits.max_gas && gas_cost + candidate.gas_cost <= self.limits.balance;
+        if fits && chosen.iter().all(|idx| !self.conflicts[pos][*idx]) {
+            chosen.push(pos);
+            self.search(pos + 1, chosen, profit + candidate.profit_eth, gas + candidate.gas, gas_cost + candidate.gas_cost);
+            chosen.pop();
+        }
+        self.search(pos + 1, chosen, profit, gas, gas_cost);
+    }
+}
+
+/// Returns indexes of the compatible candidates with the largest total profit that fit into the limits, ordered by profit.
+/// Branch and bound over the [`MAX_PACK_CANDIDATES`] most profitable candidates.
+pub fn pack_candidates(candidates: &[PackCandidate], limits: PackingLimits) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..candidates.len())
+        .filter(|idx| {
+            let candidate = &candidates[*idx];
+            !candidate.profit_eth.is_zero() && candidate.gas <= limits.max_gas && candidate.gas_cost <= limits.balance
+        })
+        .collect();
+    order.sort_by(|a, b| candidates[*b].profit_eth.cmp(&candidates[*a].profit_eth));
+    order.truncate(MAX_PACK_CANDIDATES);
+
+    let conflicts: Vec<Vec<bool>> =
+        order.iter().map(|a| order.iter().map(|b| a != b && candidates[*a].conflicts(&candidates[*b])).collect()).collect();
+
+    let mut remaining_profit = vec![U256::ZERO; order.len() + 1];
+    for pos in (0..order.len()).rev() {
+        remaining_profit[pos] = remaining_profit[pos + 1] + candidates[order[pos]].profit_eth;
+    }
+
+    let mut search = PackingSearch { candidates, limits, order, conflicts, remaining_profit, best: Vec::new(), best_profit: U256::ZERO };
+    search.search(0, &mut Vec::new(), U256::ZERO, 0, U256::ZERO);
+    search.best
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn candidate(profit: u64, gas: u64, pools: &[u8], stuffing_txs: &[u8], stuffing_changes: &[u8]) -> PackCandidate {
+        PackCandidate {
+            profit_eth: U256::from(profit),
+            gas,
+            gas_cost: U256::from(gas),
+            pools: pools.iter().map(|pool| Address::repeat_byte(*pool)).collect(),
+            stuffing_txs: stuffing_txs.iter().map(|tx| TxHash::repeat_byte(*tx)).collect(),
+            stuffing_changes: stuffing_changes.iter().map(|pool| Address::repeat_byte(*pool)).collect(),
+        }
+    }
+
+    const NO_LIMITS: PackingLimits = PackingLimits { max_gas: u64::MAX, balance: U256::MAX };
+
+    #[test]
+    fn test_conflicts() {
+        assert!(candidate(1, 1, &[1, 2], &[], &[]).conflicts(&candidate(1, 1, &[2, 3], &[], &[])));
+        assert!(!candidate(1, 1, &[1], &[], &[]).conflicts(&candidate(1, 1, &[2], &[], &[])));
+        // stuffing tx of the second candidate changes the pool of the first
+        assert!(candidate(1, 1, &[1], &[1], &[]).conflicts(&candidate(1, 1, &[2], &[2], &[1, 2])));
+        // shared stuffing tx
+        assert!(!candidate(1, 1, &[1], &[1], &[1, 2]).conflicts(&candidate(1, 1, &[2], &[1], &[1, 2])));
+    }
+
+    #[test]
+    fn test_pack_beats_greedy() {
+        // greedy takes the first candidate and can't add others
+        let candidates = vec![candidate(10, 1, &[1, 2], &[], &[]), candidate(7, 1, &[1], &[], &[]), candidate(6, 1, &[2], &[], &[])];
+        assert_eq!(pack_candidates(&candidates, NO_LIMITS), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_pack_limits() {
+        let candidates = vec![candidate(10, 100, &[1], &[], &[]), candidate(7, 60, &[2], &[], &[]), candidate(6, 50, &[3], &[], &[])];
+        assert_eq!(pack_candidates(&candidates, NO_LIMITS), vec![0, 1, 2]);
+        assert_eq!(pack_candidates(&candidates, PackingLimits { max_gas: 110, balance: U256::MAX }), vec![1, 2]);
+        assert_eq!(pack_candidates(&candidates, PackingLimits { max_gas: u64::MAX, balance: U256::from(150) }), vec![0, 2]);
+        assert!(pack_candidates(&candidates, PackingLimits { max_gas: 10, balance: U256::MAX }).is_empty());
+        assert_eq!(
+            PackingLimits { max_gas: 110, balance: U256::from(100) }.without(&candidates[0]),
+            Some(PackingLimits { max_gas: 10, balance: U256::ZERO })
+        );
+        assert_eq!(PackingLimits { max_gas: 90, balance: U256::MAX }.without(&candidates[0]), None);
+    }
+}
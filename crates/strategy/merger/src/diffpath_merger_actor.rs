@@ -53,6 +53,10 @@ where
             msg = market_events_rx.recv() => {
                 if let Ok(msg) = msg {
                     let market_event_msg : MarketEvents = msg;
+                    if let MarketEvents::Reorg{depth, ..} = market_event_msg {
+                        debug!("Cleaning swap paths after reorg depth {}", depth);
+                        swap_paths = Vec::new();
+                    }
                     if let MarketEvents::BlockHeaderUpdate{block_number, block_hash, timestamp, base_fee, next_base_fee} =  market_event_msg {
                         debug!("Block header update {} {} ts {} base_fee {} next {} ", block_number, block_hash, timestamp, base_fee, next_base_fee);
                         //cur_block_number = Some( block_number + 1);
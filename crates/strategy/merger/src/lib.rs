@@ -1,7 +1,13 @@
+mod bundle_packer;
 mod diffpath_merger_actor;
+mod merger_config;
+mod packing_merger_actor;
 mod samepath_merger_actor;
 mod swappath_merger_actor;
 
+pub use bundle_packer::{pack_candidates, PackCandidate, PackingLimits, PackingProblem, MAX_PACK_CANDIDATES};
 pub use diffpath_merger_actor::DiffPathMergerActor;
+pub use merger_config::{MergerConfig, MergerConfigSection};
+pub use packing_merger_actor::PackingMergerActor;
 pub use samepath_merger_actor::SamePathMergerActor;
 pub use swappath_merger_actor::ArbSwapPathMergerActor;
@@ -0,0 +1,26 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct MergerConfigSection {
+    #[serde(default)]
+    pub merger: MergerConfig,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct MergerConfig {
+    /// Swaps of the block are merged by [`crate::PackingMergerActor`] instead of [`crate::DiffPathMergerActor`]
+    pub packing: bool,
+    /// Gas limit of the packed bundle
+    pub packing_max_gas: u64,
+    /// Candidate sets of every block are written to the directory for the bundle packer benchmark
+    pub packing_record_dir: Option<PathBuf>,
+}
+
+impl Default for MergerConfig {
+    fn default() -> Self {
+        Self { packing: false, packing_max_gas: 3_000_000, packing_record_dir: None }
+    }
+}
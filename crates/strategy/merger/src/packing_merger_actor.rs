@@ -0,0 +1,331 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use alloy_network::TransactionResponse;
+use alloy_primitives::{TxHash, U256};
+use alloy_rpc_types::Transaction;
+use eyre::{eyre, ErrReport, Result};
+use revm::primitives::{BlockEnv, Env, CANCUN};
+use revm::{Database, DatabaseCommit, DatabaseRef, Evm};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error, info, trace};
+
+use crate::bundle_packer::{pack_candidates, PackCandidate, PackingLimits, PackingProblem};
+use crate::MergerConfig;
+use loom_core_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Consumer, Producer, SharedState, WorkerResult};
+use loom_core_actors_macros::{Accessor, Consumer, Producer};
+use loom_core_blockchain::{Blockchain, BlockchainState, Strategy};
+use loom_evm_utils::evm::evm_transact;
+use loom_evm_utils::evm_tx_env::tx_to_evm_tx;
+use loom_evm_utils::NWETH;
+use loom_types_entities::profit_policy::ProfitPolicy;
+use loom_types_entities::{MarketState, Swap, SwapAmountType, SwapLine};
+use loom_types_events::{MarketEvents, MessageSwapCompose, SwapComposeData, SwapComposeMessage, TxComposeData};
+
+const PACKING_MERGER_ORIGIN: &str = "packing_merger";
+
+/// Executes stuffing txs of the packed requests on the market state and recalculates their swap lines on the resulting state.
+/// Requests with failed stuffing txs or without profit after the gas cost are dropped.
+fn resimulate_packed<DB>(db: DB, env: Env, requests: &[SwapComposeData<DB>]) -> Result<(DB, Vec<SwapLine>, Vec<Transaction>, u64)>
+where
+    DB: DatabaseRef<Error = ErrReport> + Database<Error = ErrReport> + DatabaseCommit + Send + Sync + Clone + 'static,
+{
+    let mut stuffing_txs: Vec<Transaction> = Vec::new();
+    for request in requests.iter() {
+        for tx in request.tx_compose.stuffing_txs.iter() {
+            if !stuffing_txs.iter().any(|stuffing_tx| stuffing_tx.tx_hash() == tx.tx_hash()) {
+                stuffing_txs.push(tx.clone());
+            }
+        }
+    }
+
+    let mut evm = Evm::builder().with_spec_id(CANCUN).with_db(db).with_env(Box::new(env.clone())).build();
+    let mut failed_txs: HashSet<TxHash> = HashSet::new();
+    for tx in stuffing_txs.iter() {
+        evm.context.evm.env.tx = tx_to_evm_tx(tx);
+        if let Err(error) = evm_transact(&mut evm) {
+            debug!(tx_hash = %tx.tx_hash(), %error, "Stuffing tx failed");
+            failed_txs.insert(tx.tx_hash());
+        }
+    }
+    let (db, _) = evm.into_db_and_env_with_handler_cfg();
+
+    let mut swap_lines: Vec<SwapLine> = Vec::new();
+    let mut gas = 0;
+    for request in requests.iter() {
+        if request.tx_compose.stuffing_txs_hashes.iter().any(|tx_hash| failed_txs.contains(tx_hash)) {
+            continue;
+        }
+        let Swap::BackrunSwapLine(swap_line) = &request.swap else {
+            continue;
+        };
+        let mut swap_line = swap_line.clone();
+        let amount_in = swap_line.amount_in.unwrap_or_zero();
+        match swap_line.calculate_with_in_amount(&db, env.clone(), amount_in) {
+            Ok((amount_out, gas_used, calculation_results)) => {
+                swap_line.amount_out = SwapAmountType::Set(amount_out);
+                swap_line.gas_used = Some(gas_used);
+                swap_line.calculation_results = calculation_results;
+            }
+            Err(error) => {
+                debug!(?error, "Packed swap line calculation failed");
+                continue;
+            }
+        }
+        if swap_line.abs_profit_eth() > U256::from(request.gas_cost()) {
+            gas += request.tx_compose.gas;
+            swap_lines.push(swap_line);
+        } else {
+            trace!(%swap_line, "Packed swap line is not profitable after resimulation");
+        }
+    }
+
+    if swap_lines.len() < 2 {
+        return Err(eyre!("NOT_MERGED"));
+    }
+
+    let used_txs: HashSet<TxHash> = requests
+        .iter()
+        .filter(|request| request.tx_compose.stuffing_txs_hashes.iter().all(|tx_hash| !failed_txs.contains(tx_hash)))
+        .flat_map(|request| request.tx_compose.stuffing_txs_hashes.iter().cloned())
+        .collect();
+    stuffing_txs.retain(|tx| used_txs.contains(&tx.tx_hash()));
+
+    Ok((db, swap_lines, stuffing_txs, gas))
+}
+
+async fn packing_merger_task<DB>(
+    market_state: SharedState<MarketState<DB>>,
+    requests: Vec<SwapComposeData<DB>>,
+    profit_policy: SharedState<ProfitPolicy>,
+    compose_channel_tx: Broadcaster<MessageSwapCompose<DB>>,
+) -> Result<()>
+where
+    DB: DatabaseRef<Error = ErrReport> + Database<Error = ErrReport> + DatabaseCommit + Send + Sync + Clone + 'static,
+{
+    let request = requests.first().ok_or(eyre!("NO_REQUESTS"))?.clone();
+    let env = Env {
+        block: BlockEnv {
+            number: U256::from(request.tx_compose.next_block_number),
+            timestamp: U256::from(request.tx_compose.next_block_timestamp),
+            basefee: U256::from(request.tx_compose.next_block_base_fee),
+            ..BlockEnv::default()
+        },
+        ..Env::default()
+    };
+
+    let db = market_state.read().await.state_db.clone();
+    let swaps = requests.len();
+    let (db, swap_lines, stuffing_txs, gas) = tokio::task::spawn_blocking(move || resimulate_packed(db, env, &requests)).await??;
+
+    let swap = Swap::Multiple(swap_lines.into_iter().map(Swap::BackrunSwapLine).collect());
+    let encode_request = MessageSwapCompose::prepare(SwapComposeData {
+        tx_compose: TxComposeData {
+            stuffing_txs_hashes: stuffing_txs.iter().map(|tx| tx.tx_hash()).collect(),
+            stuffing_txs,
+            gas,
+            ..request.tx_compose
+        },
+        tips_pct: Some(profit_policy.read().await.tips_curve().tips_pct(&swap.abs_profit_eth())),
+        swap,
+        origin: Some(PACKING_MERGER_ORIGIN.to_string()),
+        poststate: Some(db),
+        poststate_update: None,
+        ..request
+    });
+    info!(swaps, profit_eth = NWETH::to_float(encode_request.inner.swap.abs_profit_eth()), "Packed bundle resimulated");
+
+    if let Err(e) = compose_channel_tx.send(encode_request).await {
+        error!("{}", e)
+    }
+    Ok(())
+}
+
+fn record_candidates<DB: Clone + 'static>(dir: &Path, block_number: u64, max_gas: u64, requests: &[SwapComposeData<DB>]) -> Result<()> {
+    let problem = PackingProblem {
+        limits: PackingLimits { max_gas, balance: requests.iter().map(|request| request.tx_compose.eth_balance).max().unwrap_or_default() },
+        candidates: requests.iter().map(PackCandidate::from_request).collect(),
+    };
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(dir.join(format!("candidates_{block_number}.json")), serde_json::to_vec(&problem)?)?;
+    Ok(())
+}
+
+async fn packing_merger_worker<DB>(
+    max_gas: u64,
+    record_dir: Option<PathBuf>,
+    profit_policy: SharedState<ProfitPolicy>,
+    market_state: SharedState<MarketState<DB>>,
+    market_events_rx: Broadcaster<MarketEvents>,
+    compose_channel_rx: Broadcaster<MessageSwapCompose<DB>>,
+    compose_channel_tx: Broadcaster<MessageSwapCompose<DB>>,
+) -> WorkerResult
+where
+    DB: DatabaseRef<Error = ErrReport> + Database<Error = ErrReport> + DatabaseCommit + Send + Sync + Clone + 'static,
+{
+    subscribe!(market_events_rx);
+    subscribe!(compose_channel_rx);
+
+    let mut requests: Vec<SwapComposeData<DB>> = Vec::new();
+    let mut cur_block_number: u64 = 0;
+
+    loop {
+        tokio::select! {
+            msg = market_events_rx.recv() => {
+                if let Ok(MarketEvents::BlockHeaderUpdate{ block_number, .. }) = msg {
+                    if let Some(record_dir) = record_dir.as_ref() {
+                        if !requests.is_empty() {
+                            if let Err(error) = record_candidates(record_dir, cur_block_number, max_gas, &requests) {
+                                error!(%error, "Recording packing candidates failed");
+                            }
+                        }
+                    }
+                    cur_block_number = block_number + 1;
+                    requests = Vec::new();
+                } else if let Ok(MarketEvents::Reorg{ depth, .. }) = msg {
+                    debug!("Cleaning packing requests after reorg depth {}", depth);
+                    requests = Vec::new();
+                }
+            }
+
+            msg = compose_channel_rx.recv() => {
+                let msg : Result<MessageSwapCompose<DB>, RecvError> = msg;
+                match msg {
+                    Ok(compose_request) => {
+                        let SwapComposeMessage::Ready(request) = compose_request.inner() else {
+                            continue;
+                        };
+                        if !matches!(request.swap, Swap::BackrunSwapLine(_)) || request.tx_compose.next_block_number < cur_block_number {
+                            continue;
+                        }
+
+                        // the new request is always packed, bundles without it were sent before
+                        let candidate = PackCandidate::from_request(request);
+                        let limits = PackingLimits { max_gas, balance: request.tx_compose.eth_balance };
+                        if let Some(limits) = limits.without(&candidate) {
+                            let (compatible, compatible_candidates): (Vec<&SwapComposeData<DB>>, Vec<PackCandidate>) = requests
+                                .iter()
+                                .filter(|other| other.tx_compose.next_block_number == request.tx_compose.next_block_number)
+                                .map(|other| (other, PackCandidate::from_request(other)))
+                                .filter(|(_, other_candidate)| !candidate.conflicts(other_candidate))
+                                .unzip();
+                            let packed = pack_candidates(&compatible_candidates, limits);
+
+                            if !packed.is_empty() {
+                                let mut packed_requests = vec![request.clone()];
+                                packed_requests.extend(packed.iter().map(|idx| compatible[*idx].clone()));
+                                debug!(packed = packed_requests.len(), candidates = requests.len() + 1, "Swaps packed");
+                                tokio::task::spawn(packing_merger_task(
+                                    market_state.clone(),
+                                    packed_requests,
+                                    profit_policy.clone(),
+                                    compose_channel_tx.clone(),
+                                ));
+                            }
+                        }
+
+                        requests.push(request.clone());
+                    }
+                    Err(e) => {
+                        error!("{e}")
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Merges backrun swaps for the same target block into the most profitable compatible set. Swaps are compatible if they don't
+/// share pools and their stuffing txs don't change pools of each other, the set is limited by the gas limit and the signer balance.
+/// The merged bundle is resimulated on the market state with all stuffing txs.
+#[derive(Consumer, Producer, Accessor)]
+pub struct PackingMergerActor<DB: Send + Sync + Clone + 'static> {
+    max_gas: u64,
+    record_dir: Option<PathBuf>,
+    profit_policy: SharedState<ProfitPolicy>,
+    #[accessor]
+    market_state: Option<SharedState<MarketState<DB>>>,
+    #[consumer]
+    market_events: Option<Broadcaster<MarketEvents>>,
+    #[consumer]
+    compose_channel_rx: Option<Broadcaster<MessageSwapCompose<DB>>>,
+    #[producer]
+    compose_channel_tx: Option<Broadcaster<MessageSwapCompose<DB>>>,
+}
+
+impl<DB> Default for PackingMergerActor<DB>
+where
+    DB: DatabaseRef + DatabaseCommit + Send + Sync + Clone + 'static,
+{
+    fn default() -> Self {
+        Self {
+            max_gas: 3_000_000,
+            record_dir: None,
+            profit_policy: SharedState::new(ProfitPolicy::default()),
+            market_state: None,
+            market_events: None,
+            compose_channel_rx: None,
+            compose_channel_tx: None,
+        }
+    }
+}
+
+impl<DB> PackingMergerActor<DB>
+where
+    DB: DatabaseRef + DatabaseCommit + Send + Sync + Clone + 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gas limit of the merged bundle
+    pub fn with_max_gas(self, max_gas: u64) -> Self {
+        Self { max_gas, ..self }
+    }
+
+    /// Writes candidate sets of every block to the directory for packing benchmarks
+    pub fn with_record_dir(self, record_dir: PathBuf) -> Self {
+        Self { record_dir: Some(record_dir), ..self }
+    }
+
+    /// Shared profit policy with the tips curve of the merged swaps, a reloaded policy is applied to the next merged swap
+    pub fn with_profit_policy(self, profit_policy: SharedState<ProfitPolicy>) -> Self {
+        Self { profit_policy, ..self }
+    }
+
+    pub fn with_config(self, config: &MergerConfig) -> Self {
+        Self { max_gas: config.packing_max_gas, record_dir: config.packing_record_dir.clone(), ..self }
+    }
+
+    pub fn on_bc(self, bc: &Blockchain, state: &BlockchainState<DB>, strategy: &Strategy<DB>) -> Self {
+        Self {
+            market_state: Some(state.market_state_commit()),
+            market_events: Some(bc.market_events_channel()),
+            compose_channel_tx: Some(strategy.swap_compose_channel()),
+            compose_channel_rx: Some(strategy.swap_compose_channel()),
+            ..self
+        }
+    }
+}
+
+impl<DB> Actor for PackingMergerActor<DB>
+where
+    DB: DatabaseRef<Error = ErrReport> + Database<Error = ErrReport> + DatabaseCommit + Send + Sync + Clone + 'static,
+{
+    fn start(&self) -> ActorResult {
+        let task = tokio::task::spawn(packing_merger_worker(
+            self.max_gas,
+            self.record_dir.clone(),
+            self.profit_policy.clone(),
+            self.market_state.clone().unwrap(),
+            self.market_events.clone().unwrap(),
+            self.compose_channel_rx.clone().unwrap(),
+            self.compose_channel_tx.clone().unwrap(),
+        ));
+        Ok(vec![task])
+    }
+
+    fn name(&self) -> &'static str {
+        "PackingMergerActor"
+    }
+}
@@ -27,6 +27,7 @@ use loom_evm_utils::evm::evm_transact;
 use loom_evm_utils::evm_tx_env::tx_to_evm_tx;
 use loom_node_debug_provider::DebugProviderExt;
 use loom_types_blockchain::{debug_trace_call_pre_state, GethStateUpdate, GethStateUpdateVec, TRACING_CALL_OPTS};
+use loom_types_entities::profit_policy::ProfitPolicy;
 use loom_types_entities::{DataFetcher, FetchState, LatestBlock, MarketState, Swap};
 use loom_types_events::{MarketEvents, MessageSwapCompose, SwapComposeData, SwapComposeMessage, TxComposeData};
 
@@ -69,6 +70,7 @@ async fn same_path_merger_task<P, T, N, DB>(
     pre_states: Arc<RwLock<DataFetcher<TxHash, GethStateUpdate>>>,
     market_state: SharedState<MarketState<DB>>,
     call_opts: GethDebugTracingCallOptions,
+    profit_policy: SharedState<ProfitPolicy>,
     request: SwapComposeData<DB>,
     swap_request_tx: Broadcaster<MessageSwapCompose<DB>>,
 ) -> Result<()>
@@ -213,7 +215,7 @@ where
                         },
                         swap: Swap::BackrunSwapLine(swap_line.clone()),
                         origin: Some("samepath_merger".to_string()),
-                        tips_pct: None,
+                        tips_pct: Some(profit_policy.read().await.tips_curve().tips_pct(&swap_line.abs_profit_eth())),
                         poststate: Some(db),
                         poststate_update: None,
                         ..request
@@ -243,6 +245,7 @@ async fn same_path_merger_worker<
     DB: DatabaseRef<Error = ErrReport> + Database<Error = ErrReport> + DatabaseCommit + Send + Sync + Clone + 'static,
 >(
     client: P,
+    profit_policy: SharedState<ProfitPolicy>,
     latest_block: SharedState<LatestBlock>,
     market_state: SharedState<MarketState<DB>>,
     market_events_rx: Broadcaster<MarketEvents>,
@@ -268,6 +271,13 @@ async fn same_path_merger_worker<
             msg = market_events_rx.recv() => {
                 if let Ok(msg) = msg {
                     let market_event_msg : MarketEvents = msg;
+                    if let MarketEvents::Reorg{depth, new_head, ..} = market_event_msg {
+                        // merges and prestates were built on the orphaned branch
+                        debug!("Cleaning swap paths after reorg depth {} new head {}", depth, new_head);
+                        *prestate.write().await = DataFetcher::<TxHash, GethStateUpdate>::new();
+                        swap_paths = HashMap::new();
+                        cur_state_override = latest_block.read().await.node_state_override();
+                    }
                     if let MarketEvents::BlockHeaderUpdate{block_number, block_hash,  base_fee, next_base_fee, timestamp} =  market_event_msg {
                         debug!("Block header update {} {} base_fee {} ", block_number, block_hash, base_fee);
                         cur_block_number = Some( block_number + 1);
@@ -330,6 +340,7 @@ async fn same_path_merger_worker<
                                                 prestate_clone,
                                                 market_state.clone(),
                                                 call_opts,
+                                                profit_policy.clone(),
                                                 sign_request.clone(),
                                                 compose_channel_tx.clone()
                                             )
@@ -356,6 +367,7 @@ async fn same_path_merger_worker<
 pub struct SamePathMergerActor<P, T, N, DB: Send + Sync + Clone + 'static> {
     client: P,
     //encoder: SwapStepEncoder,
+    profit_policy: SharedState<ProfitPolicy>,
     #[accessor]
     market_state: Option<SharedState<MarketState<DB>>>,
     #[accessor]
@@ -380,6 +392,7 @@ where
     pub fn new(client: P) -> Self {
         Self {
             client,
+            profit_policy: SharedState::new(ProfitPolicy::default()),
             market_state: None,
             latest_block: None,
             market_events: None,
@@ -390,6 +403,11 @@ where
         }
     }
 
+    /// Shared profit policy with the tips curve of the merged swaps, a reloaded policy is applied to the next merged swap
+    pub fn with_profit_policy(self, profit_policy: SharedState<ProfitPolicy>) -> Self {
+        Self { profit_policy, ..self }
+    }
+
     pub fn on_bc(self, bc: &Blockchain, state: &BlockchainState<DB>, strategy: &Strategy<DB>) -> Self {
         Self {
             market_state: Some(state.market_state_commit()),
@@ -412,6 +430,7 @@ where
     fn start(&self) -> ActorResult {
         let task = tokio::task::spawn(same_path_merger_worker(
             self.client.clone(),
+            self.profit_policy.clone(),
             self.latest_block.clone().unwrap(),
             self.market_state.clone().unwrap(),
             self.market_events.clone().unwrap(),
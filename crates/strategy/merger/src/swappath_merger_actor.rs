@@ -9,6 +9,7 @@ use loom_core_actors::{subscribe, Accessor, Actor, ActorResult, Broadcaster, Con
 use loom_core_actors_macros::{Accessor, Consumer, Producer};
 use loom_core_blockchain::{Blockchain, Strategy};
 use loom_execution_multicaller::SwapStepEncoder;
+use loom_types_entities::profit_policy::ProfitPolicy;
 use loom_types_entities::{LatestBlock, Swap, SwapStep};
 use loom_types_events::{MarketEvents, MessageSwapCompose, SwapComposeData, SwapComposeMessage};
 
@@ -16,6 +17,7 @@ async fn arb_swap_steps_optimizer_task<DB: DatabaseRef + Send + Sync + Clone>(
     compose_channel_tx: Broadcaster<MessageSwapCompose<DB>>,
     state_db: &(dyn DatabaseRef<Error = ErrReport> + Send + Sync + 'static),
     evm_env: Env,
+    profit_policy: SharedState<ProfitPolicy>,
     request: SwapComposeData<DB>,
 ) -> Result<()> {
     debug!("Step Simulation started");
@@ -24,10 +26,11 @@ async fn arb_swap_steps_optimizer_task<DB: DatabaseRef + Send + Sync + Clone>(
         let start_time = chrono::Local::now();
         match SwapStep::optimize_swap_steps(&state_db, evm_env, &sp0, &sp1, None) {
             Ok((s0, s1)) => {
+                let swap = Swap::BackrunSwapSteps((s0, s1));
                 let encode_request = MessageSwapCompose::prepare(SwapComposeData {
                     origin: Some("merger_searcher".to_string()),
-                    tips_pct: None,
-                    swap: Swap::BackrunSwapSteps((s0, s1)),
+                    tips_pct: Some(profit_policy.read().await.tips_curve().tips_pct(&swap.abs_profit_eth())),
+                    swap,
                     ..request
                 });
                 compose_channel_tx.send(encode_request).await.map_err(|_| eyre!("CANNOT_SEND"))?;
@@ -48,6 +51,7 @@ async fn arb_swap_steps_optimizer_task<DB: DatabaseRef + Send + Sync + Clone>(
 
 async fn arb_swap_path_merger_worker<DB: DatabaseRef<Error = ErrReport> + Send + Sync + Clone + 'static>(
     encoder: SwapStepEncoder,
+    profit_policy: SharedState<ProfitPolicy>,
     latest_block: SharedState<LatestBlock>,
     market_events_rx: Broadcaster<MarketEvents>,
     compose_channel_rx: Broadcaster<MessageSwapCompose<DB>>,
@@ -69,6 +73,10 @@ async fn arb_swap_path_merger_worker<DB: DatabaseRef<Error = ErrReport> + Send +
                                 debug!("Cleaning ready requests");
                                 ready_requests = Vec::new();
                             }
+                            MarketEvents::Reorg{depth, ..} =>{
+                                debug!("Cleaning ready requests after reorg depth {}", depth);
+                                ready_requests = Vec::new();
+                            }
                             MarketEvents::BlockStateUpdate{..}=>{
                                 debug!("State updated");
                                 //state_db = market_state.read().await.state_db.clone();
@@ -130,11 +138,13 @@ async fn arb_swap_path_merger_worker<DB: DatabaseRef<Error = ErrReport> + Send +
                                     if let Some(db) = compose_data.poststate.clone() {
                                         let db_clone = db.clone();
                                         let compose_channel_clone = compose_channel_tx.clone();
+                                        let profit_policy = profit_policy.clone();
                                         tokio::task::spawn( async move {
                                                 arb_swap_steps_optimizer_task(
                                                 compose_channel_clone,
                                                 &db_clone,
                                                 evm_env,
+                                                profit_policy,
                                                 request
                                             ).await
                                         });
@@ -160,6 +170,7 @@ async fn arb_swap_path_merger_worker<DB: DatabaseRef<Error = ErrReport> + Send +
 #[derive(Consumer, Producer, Accessor)]
 pub struct ArbSwapPathMergerActor<DB: Send + Sync + Clone + 'static> {
     encoder: SwapStepEncoder,
+    profit_policy: SharedState<ProfitPolicy>,
     #[accessor]
     latest_block: Option<SharedState<LatestBlock>>,
     #[consumer]
@@ -177,12 +188,19 @@ where
     pub fn new(multicaller: Address) -> ArbSwapPathMergerActor<DB> {
         ArbSwapPathMergerActor {
             encoder: SwapStepEncoder::new(multicaller),
+            profit_policy: SharedState::new(ProfitPolicy::default()),
             latest_block: None,
             market_events: None,
             compose_channel_rx: None,
             compose_channel_tx: None,
         }
     }
+
+    /// Shared profit policy with the tips curve of the merged swaps, a reloaded policy is applied to the next merged swap
+    pub fn with_profit_policy(self, profit_policy: SharedState<ProfitPolicy>) -> Self {
+        Self { profit_policy, ..self }
+    }
+
     pub fn on_bc(self, bc: &Blockchain, strategy: &Strategy<DB>) -> Self {
         Self {
             latest_block: Some(bc.latest_block()),
@@ -201,6 +219,7 @@ where
     fn start(&self) -> ActorResult {
         let task = tokio::task::spawn(arb_swap_path_merger_worker(
             self.encoder.clone(),
+            self.profit_policy.clone(),
             self.latest_block.clone().unwrap(),
             self.market_events.clone().unwrap(),
             self.compose_channel_rx.clone().unwrap(),
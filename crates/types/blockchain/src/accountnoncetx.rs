@@ -1,21 +1,75 @@
-use alloy_primitives::TxHash;
+use std::collections::BTreeMap;
 
-#[derive(Debug, Clone, Default)]
-pub struct AccountNonceAndTransactions {
+use crate::{LoomDataTypes, LoomDataTypesEthereum};
+
+#[derive(Debug, Clone)]
+pub struct AccountNonceAndTransactions<LDT: LoomDataTypes = LoomDataTypesEthereum> {
+    /// Nonce of the last mined tx
     pub nonce: Option<u64>,
-    pub txs: Vec<TxHash>,
+    /// Pending tx hashes by nonce. The last hash of the nonce is the current tx, the others are replaced by it.
+    pub txs: BTreeMap<u64, Vec<LDT::TxHash>>,
 }
 
-impl AccountNonceAndTransactions {
+impl<LDT: LoomDataTypes> Default for AccountNonceAndTransactions<LDT> {
+    fn default() -> Self {
+        Self { nonce: None, txs: BTreeMap::new() }
+    }
+}
+
+impl<LDT: LoomDataTypes> AccountNonceAndTransactions<LDT> {
     pub fn new() -> Self {
         Self::default()
     }
 
-    pub fn add_tx_hash(&mut self, tx_hash: TxHash) -> &mut Self {
-        self.txs.push(tx_hash);
+    /// Returns the current tx of the nonce
+    pub fn get_tx_hash(&self, nonce: u64) -> Option<LDT::TxHash> {
+        self.txs.get(&nonce).and_then(|tx_hashes| tx_hashes.last().cloned())
+    }
+
+    /// Adds the tx as the current tx of the nonce
+    pub fn add_tx_hash(&mut self, nonce: u64, tx_hash: LDT::TxHash) -> &mut Self {
+        self.txs.entry(nonce).or_default().push(tx_hash);
         self
     }
 
+    /// Adds the tx as replaced by the current tx of the nonce
+    pub fn add_replaced_tx_hash(&mut self, nonce: u64, tx_hash: LDT::TxHash) -> &mut Self {
+        let tx_hashes = self.txs.entry(nonce).or_default();
+        tx_hashes.insert(tx_hashes.len().saturating_sub(1), tx_hash);
+        self
+    }
+
+    pub fn remove_tx_hash(&mut self, nonce: u64, tx_hash: &LDT::TxHash) -> &mut Self {
+        if let Some(tx_hashes) = self.txs.get_mut(&nonce) {
+            tx_hashes.retain(|hash| hash != tx_hash);
+            if tx_hashes.is_empty() {
+                self.txs.remove(&nonce);
+            }
+        }
+        self
+    }
+
+    /// Removes txs with nonces up to the last mined nonce and returns their hashes
+    pub fn remove_mined_nonces(&mut self) -> Vec<LDT::TxHash> {
+        let Some(nonce) = self.nonce else {
+            return Vec::new();
+        };
+        let pending = self.txs.split_off(&(nonce + 1));
+        std::mem::replace(&mut self.txs, pending).into_values().flatten().collect()
+    }
+
+    /// Returns current txs of the nonces following the last mined nonce without gaps. `state_nonce` is the nonce of the account
+    /// in the latest state, it is used if no tx of the account is seen mined yet.
+    pub fn get_executable_tx_hashes(&self, state_nonce: u64) -> Vec<LDT::TxHash> {
+        let mut next_nonce = self.nonce.map_or(state_nonce, |nonce| (nonce + 1).max(state_nonce));
+        let mut tx_hashes = Vec::new();
+        while let Some(tx_hash) = self.get_tx_hash(next_nonce) {
+            tx_hashes.push(tx_hash);
+            next_nonce += 1;
+        }
+        tx_hashes
+    }
+
     pub fn set_nonce(&mut self, nonce: Option<u64>) -> &mut Self {
         if let Some(cur_nonce) = self.nonce {
             if let Some(some_nonce) = nonce {
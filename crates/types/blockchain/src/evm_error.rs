@@ -0,0 +1,14 @@
+use revm::primitives::HaltReason;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum EvmError {
+    #[error("Evm transact error")]
+    TransactError,
+    #[error("Evm transact commit error with err={0}")]
+    TransactCommitError(String),
+    #[error("Reverted with reason={0}, gas_used={1}")]
+    Reverted(String, u64),
+    #[error("Halted with halt_reason={0:?}, gas_used={1}")]
+    Halted(HaltReason, u64),
+}
@@ -1,23 +1,27 @@
 pub use accountnoncetx::AccountNonceAndTransactions;
 pub use chain_parameters::ChainParameters;
+pub use evm_error::EvmError;
 pub use fetchstate::FetchState;
 pub use loom_data_types::{LoomBlock, LoomDataTypes, LoomHeader, LoomTx};
 pub use loom_data_types_ethereum::LoomDataTypesEthereum;
 pub use mempool::Mempool;
 pub use mempool_tx::MempoolTx;
+pub use mock_tx::mock_tx;
 pub use opcodes::*;
 pub use state_update::{
     debug_log_geth_state_update, debug_trace_block, debug_trace_call_diff, debug_trace_call_post_state, debug_trace_call_pre_state,
     debug_trace_transaction, GethStateUpdate, GethStateUpdateVec, TRACING_CALL_OPTS, TRACING_OPTS,
 };
-pub use swap::SwapError;
+pub use swap::{PoolError, SwapError};
 mod accountnoncetx;
 mod chain_parameters;
+mod evm_error;
 mod fetchstate;
 mod loom_data_types;
 mod loom_data_types_ethereum;
 mod mempool;
 mod mempool_tx;
+mod mock_tx;
 mod new_block;
 mod opcodes;
 mod state_update;
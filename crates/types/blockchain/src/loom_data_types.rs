@@ -27,6 +27,10 @@ pub trait LoomBlock<LDT: LoomDataTypes> {
     fn transactions(&self) -> Vec<LDT::Transaction>;
 
     fn number(&self) -> u64;
+
+    fn hash(&self) -> LDT::BlockHash;
+
+    fn parent_hash(&self) -> LDT::BlockHash;
 }
 
 pub trait LoomDataTypes: Debug + Clone + Send + Sync {
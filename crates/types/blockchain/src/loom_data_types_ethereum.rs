@@ -83,4 +83,12 @@ impl LoomBlock<LoomDataTypesEthereum> for EthBlock {
     fn number(&self) -> u64 {
         self.header.number
     }
+
+    fn hash(&self) -> <LoomDataTypesEthereum as LoomDataTypes>::BlockHash {
+        self.header.hash
+    }
+
+    fn parent_hash(&self) -> <LoomDataTypesEthereum as LoomDataTypes>::BlockHash {
+        self.header.parent_hash
+    }
 }
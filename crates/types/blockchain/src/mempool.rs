@@ -10,7 +10,7 @@ use std::collections::hash_map::Entry;
 #[derive(Clone, Debug, Default)]
 pub struct Mempool<LDT: LoomDataTypes = LoomDataTypesEthereum> {
     pub txs: HashMap<LDT::TxHash, MempoolTx<LDT>>,
-    accounts: HashMap<LDT::Address, AccountNonceAndTransactions>,
+    accounts: HashMap<LDT::Address, AccountNonceAndTransactions<LDT>>,
 }
 
 impl<LDT: LoomDataTypes> Mempool<LDT> {
@@ -32,11 +32,48 @@ impl<LDT: LoomDataTypes> Mempool<LDT> {
 
     pub fn add_tx(&mut self, tx: LDT::Transaction) -> &mut Self {
         let tx_hash: LDT::TxHash = tx.tx_hash();
-        let entry = self.txs.entry(tx_hash).or_default();
-        entry.tx = Some(tx);
+        let entry = self.txs.entry(tx_hash).or_insert(MempoolTx { tx_hash, ..MempoolTx::default() });
+        entry.tx = Some(tx.clone());
+        self.update_nonce_queue(&tx);
         self
     }
 
+    /// Adds the tx to the nonce queue of its sender. If the nonce already has a tx, the tx with the higher gas price
+    /// stays current and hashes of the replaced tx and of the tx replacing it are returned.
+    /// Txs with nonces that are already mined are not queued.
+    pub fn update_nonce_queue(&mut self, tx: &LDT::Transaction) -> Option<(LDT::TxHash, LDT::TxHash)> {
+        let (tx_hash, nonce) = (tx.tx_hash(), tx.nonce());
+        let account = self.accounts.get(&tx.from());
+        if account.is_some_and(|account| {
+            account.nonce.is_some_and(|mined_nonce| nonce <= mined_nonce)
+                || account.txs.get(&nonce).is_some_and(|tx_hashes| tx_hashes.contains(&tx_hash))
+        }) {
+            return None;
+        }
+
+        let current_tx_hash = account.and_then(|account| account.get_tx_hash(nonce));
+        let current_gas_price = current_tx_hash
+            .and_then(|current_tx_hash| self.txs.get(&current_tx_hash))
+            .and_then(|mempool_tx| mempool_tx.tx.as_ref())
+            .map(|current_tx| current_tx.gas_price());
+
+        let account = self.accounts.entry(tx.from()).or_default();
+        match (current_tx_hash, current_gas_price) {
+            (Some(current_tx_hash), Some(current_gas_price)) if current_gas_price >= tx.gas_price() => {
+                account.add_replaced_tx_hash(nonce, tx_hash);
+                Some((tx_hash, current_tx_hash))
+            }
+            (Some(current_tx_hash), _) => {
+                account.add_tx_hash(nonce, tx_hash);
+                Some((current_tx_hash, tx_hash))
+            }
+            (None, _) => {
+                account.add_tx_hash(nonce, tx_hash);
+                None
+            }
+        }
+    }
+
     pub fn add_tx_logs(&mut self, tx_hash: LDT::TxHash, logs: Vec<LDT::Log>) -> &mut Self {
         let entry = self.txs.entry(tx_hash).or_default();
         entry.logs = Some(logs);
@@ -97,6 +134,14 @@ impl<LDT: LoomDataTypes> Mempool<LDT> {
             .into_iter()
             .filter(|(_, v)| v.mined.unwrap_or(max_block_number + 1) > max_block_number && v.time > max_time)
             .collect();
+
+        let txs = &self.txs;
+        for account in self.accounts.values_mut() {
+            account.txs.retain(|_, tx_hashes| {
+                tx_hashes.retain(|tx_hash| txs.contains_key(tx_hash));
+                !tx_hashes.is_empty()
+            });
+        }
     }
 
     pub fn set_mined(&mut self, tx_hash: LDT::TxHash, block_number: BlockNumber) -> &mut Self {
@@ -105,6 +150,23 @@ impl<LDT: LoomDataTypes> Mempool<LDT> {
         self
     }
 
+    /// Returns the tx mined in an orphaned block to the pending txs. The mined nonce of the sender is rolled back below the tx nonce.
+    pub fn set_unmined(&mut self, tx_hash: LDT::TxHash) -> &mut Self {
+        let Some(mempool_tx) = self.txs.get_mut(&tx_hash) else {
+            return self;
+        };
+        mempool_tx.mined = None;
+        if let Some(tx) = mempool_tx.tx.clone() {
+            if let Some(account) = self.accounts.get_mut(&tx.from()) {
+                if account.nonce.is_some_and(|nonce| nonce >= tx.nonce()) {
+                    account.nonce = tx.nonce().checked_sub(1);
+                }
+            }
+            self.update_nonce_queue(&tx);
+        }
+        self
+    }
+
     pub fn set_failed(&mut self, tx_hash: LDT::TxHash) {
         if let Entry::Occupied(mut e) = self.txs.entry(tx_hash) {
             let value = e.get_mut();
@@ -118,8 +180,38 @@ impl<LDT: LoomDataTypes> Mempool<LDT> {
         self
     }
 
+    /// Removes pending txs of the account with nonces up to the last mined nonce, mined txs are kept.
+    /// Returns hashes of the removed txs.
+    pub fn evict_mined_nonces(&mut self, account: &LDT::Address) -> Vec<LDT::TxHash> {
+        let Some(account) = self.accounts.get_mut(account) else {
+            return Vec::new();
+        };
+        let evicted: Vec<LDT::TxHash> = account.remove_mined_nonces().into_iter().filter(|tx_hash| !self.is_mined(tx_hash)).collect();
+        for tx_hash in evicted.iter() {
+            self.txs.remove(tx_hash);
+        }
+        evicted
+    }
+
+    /// Returns pending txs of the account that can be executed in the next block in nonce order.
+    /// `state_nonce` is the nonce of the account in the latest state.
+    pub fn get_executable_txs(&self, account: &LDT::Address, state_nonce: u64) -> Vec<&MempoolTx<LDT>> {
+        self.accounts
+            .get(account)
+            .map(|account| account.get_executable_tx_hashes(state_nonce).into_iter().map_while(|tx_hash| self.txs.get(&tx_hash)).collect())
+            .unwrap_or_default()
+    }
+
     pub fn is_valid_tx(&self, tx: &LDT::Transaction) -> bool {
         self.accounts.get(&tx.from()).map_or_else(|| true, |acc| acc.nonce.map_or_else(|| true, |nonce| tx.nonce() == nonce + 1))
+            && self.get_replacement(tx).is_none()
+    }
+
+    /// Returns the hash of the current tx with the same sender and nonce if it has a higher or equal gas price
+    pub fn get_replacement(&self, tx: &LDT::Transaction) -> Option<LDT::TxHash> {
+        let current_tx_hash = self.accounts.get(&tx.from())?.get_tx_hash(tx.nonce())?;
+        let current_tx = self.txs.get(&current_tx_hash)?.tx.as_ref()?;
+        (current_tx_hash != tx.tx_hash() && current_tx.gas_price() >= tx.gas_price()).then_some(current_tx_hash)
     }
 
     pub fn get_tx_by_hash(&self, tx_hash: &LDT::TxHash) -> Option<&MempoolTx<LDT>> {
@@ -131,6 +223,99 @@ impl<LDT: LoomDataTypes> Mempool<LDT> {
     }
 
     pub fn remove_tx(&mut self, tx_hash: &LDT::TxHash) -> Option<MempoolTx<LDT>> {
-        self.txs.remove(tx_hash)
+        let mempool_tx = self.txs.remove(tx_hash)?;
+        if let Some(tx) = mempool_tx.tx.as_ref() {
+            if let Some(account) = self.accounts.get_mut(&tx.from()) {
+                account.remove_tx_hash(tx.nonce(), tx_hash);
+            }
+        }
+        Some(mempool_tx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock_tx;
+    use alloy_consensus::TxLegacy;
+    use alloy_primitives::{Address, TxHash};
+    use alloy_rpc_types::Transaction;
+
+    const SENDER: Address = Address::repeat_byte(0xee);
+
+    fn tx(nonce: u64, gas_price: u128, hash: u8) -> Transaction {
+        mock_tx(SENDER, TxLegacy { nonce, gas_price, gas_limit: 100_000, ..Default::default() }, TxHash::repeat_byte(hash))
+    }
+
+    #[test]
+    fn test_replacement() {
+        let mut mempool: Mempool = Mempool::default();
+        assert_eq!(mempool.update_nonce_queue(&tx(1, 10, 1)), None);
+        mempool.add_tx(tx(1, 10, 1));
+
+        // higher gas price replaces the current tx, lower gas price is replaced by it
+        assert_eq!(mempool.update_nonce_queue(&tx(1, 20, 2)), Some((TxHash::repeat_byte(1), TxHash::repeat_byte(2))));
+        mempool.add_tx(tx(1, 20, 2));
+        assert_eq!(mempool.update_nonce_queue(&tx(1, 15, 3)), Some((TxHash::repeat_byte(3), TxHash::repeat_byte(2))));
+        mempool.add_tx(tx(1, 15, 3));
+
+        assert_eq!(mempool.get_replacement(&tx(1, 10, 1)), Some(TxHash::repeat_byte(2)));
+        assert_eq!(mempool.get_replacement(&tx(1, 15, 3)), Some(TxHash::repeat_byte(2)));
+        assert_eq!(mempool.get_replacement(&tx(1, 20, 2)), None);
+        assert!(!mempool.is_valid_tx(&tx(1, 10, 1)));
+        assert!(mempool.is_valid_tx(&tx(1, 20, 2)));
+
+        mempool.remove_tx(&TxHash::repeat_byte(2));
+        assert_eq!(mempool.get_replacement(&tx(1, 15, 3)), None);
+    }
+
+    #[test]
+    fn test_executable_txs_and_eviction() {
+        let mut mempool: Mempool = Mempool::default();
+        for (nonce, hash) in [(1, 1), (2, 2), (3, 3), (5, 5)] {
+            mempool.add_tx(tx(nonce, 10, hash));
+        }
+        mempool.add_tx(tx(2, 20, 12));
+
+        let executable: Vec<TxHash> = mempool.get_executable_txs(&SENDER, 1).iter().map(|mempool_tx| mempool_tx.tx_hash).collect();
+        assert_eq!(executable, vec![TxHash::repeat_byte(1), TxHash::repeat_byte(12), TxHash::repeat_byte(3)]);
+
+        // no tx of the sender is seen mined yet, the state nonce is used
+        assert!(mempool.get_executable_txs(&SENDER, 0).is_empty());
+        let executable: Vec<TxHash> = mempool.get_executable_txs(&SENDER, 2).iter().map(|mempool_tx| mempool_tx.tx_hash).collect();
+        assert_eq!(executable, vec![TxHash::repeat_byte(12), TxHash::repeat_byte(3)]);
+
+        mempool.set_mined(TxHash::repeat_byte(12), 100).set_nonce(SENDER, 2);
+        let evicted = mempool.evict_mined_nonces(&SENDER);
+        assert_eq!(evicted.len(), 2);
+        assert!(evicted.contains(&TxHash::repeat_byte(1)) && evicted.contains(&TxHash::repeat_byte(2)));
+        assert!(mempool.is_mined(&TxHash::repeat_byte(12)));
+        assert!(!mempool.is_tx(&TxHash::repeat_byte(2)));
+
+        let executable: Vec<TxHash> = mempool.get_executable_txs(&SENDER, 3).iter().map(|mempool_tx| mempool_tx.tx_hash).collect();
+        assert_eq!(executable, vec![TxHash::repeat_byte(3)]);
+
+        // replacement of the mined nonce is not queued
+        assert_eq!(mempool.update_nonce_queue(&tx(2, 30, 22)), None);
+    }
+
+    #[test]
+    fn test_unmined_after_reorg() {
+        let mut mempool: Mempool = Mempool::default();
+        for (nonce, hash) in [(1, 1), (2, 2), (3, 3)] {
+            mempool.add_tx(tx(nonce, 10, hash));
+        }
+        mempool.set_mined(TxHash::repeat_byte(1), 100).set_nonce(SENDER, 1);
+        mempool.set_mined(TxHash::repeat_byte(2), 101).set_nonce(SENDER, 2);
+        mempool.evict_mined_nonces(&SENDER);
+
+        // block 101 is orphaned, tx 2 is pending again and the mined nonce is rolled back
+        mempool.set_unmined(TxHash::repeat_byte(2));
+        assert!(!mempool.is_mined(&TxHash::repeat_byte(2)));
+        assert!(mempool.is_mined(&TxHash::repeat_byte(1)));
+        assert!(mempool.is_valid_tx(&tx(2, 10, 2)));
+
+        let executable: Vec<TxHash> = mempool.get_executable_txs(&SENDER, 2).iter().map(|mempool_tx| mempool_tx.tx_hash).collect();
+        assert_eq!(executable, vec![TxHash::repeat_byte(2), TxHash::repeat_byte(3)]);
     }
 }
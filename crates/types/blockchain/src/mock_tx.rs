@@ -0,0 +1,16 @@
+use alloy_consensus::{Signed, TxEnvelope, TxLegacy};
+use alloy_primitives::{Address, PrimitiveSignature, TxHash, U256};
+use alloy_rpc_types::Transaction;
+
+/// Legacy transaction from `from` with a fake signature and the given hash. Used in tests, the transaction is not recoverable.
+pub fn mock_tx(from: Address, tx: TxLegacy, hash: TxHash) -> Transaction {
+    let signed_tx = Signed::new_unchecked(tx, PrimitiveSignature::new(U256::from(1), U256::from(1), false), hash);
+    Transaction {
+        inner: TxEnvelope::Legacy(signed_tx),
+        block_hash: None,
+        block_number: None,
+        transaction_index: None,
+        effective_gas_price: None,
+        from,
+    }
+}
@@ -1,11 +1,73 @@
-use crate::{LoomDataTypes, LoomDataTypesEthereum};
+use crate::{EvmError, LoomDataTypes, LoomDataTypesEthereum};
 use alloy_primitives::U256;
-use eyre::{eyre, Report};
+use eyre::Report;
 use std::hash::{Hash, Hasher};
+use thiserror::Error;
+
+/// Errors of pool calculations and swaps
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Error)]
+pub enum PoolError {
+    /// Amount in or out of the swap is zero, usually the swapped amount is too small
+    #[error("ZERO_AMOUNT: {0}")]
+    ZeroAmount(&'static str),
+    /// Reserves or liquidity of the pool are not enough for the swapped amount
+    #[error("INSUFFICIENT_LIQUIDITY: {0}")]
+    InsufficientLiquidity(&'static str),
+    /// Arithmetic overflow or underflow in the pool math
+    #[error("MATH_OVERFLOW: {0}")]
+    MathOverflow(&'static str),
+    /// Pool state can't be read from the state db
+    #[error("STATE_MISSING: {0}")]
+    StateMissing(&'static str),
+    /// Swap direction or calculation is not supported by the pool
+    #[error("UNSUPPORTED_DIRECTION: {0}")]
+    UnsupportedDirection(&'static str),
+    /// Evm call of the pool reverted or halted
+    #[error("EVM_REVERT: {reason}")]
+    EvmRevert { reason: String, gas_used: u64 },
+    /// Return data of the pool call can't be decoded
+    #[error("DECODE_ERROR: {0}")]
+    DecodeError(String),
+    /// Price of the first token of the swap line is not set
+    #[error("PRICE_NOT_SET")]
+    PriceNotSet,
+    #[error("{0}")]
+    Other(String),
+}
+
+impl PoolError {
+    /// Returns true if the error is caused by the pool and not by the swap line
+    pub fn is_pool_error(&self) -> bool {
+        !matches!(self, PoolError::PriceNotSet)
+    }
+}
+
+impl From<EvmError> for PoolError {
+    fn from(evm_error: EvmError) -> Self {
+        match evm_error {
+            EvmError::Reverted(reason, gas_used) => PoolError::EvmRevert { reason, gas_used },
+            EvmError::Halted(halt_reason, gas_used) => PoolError::EvmRevert { reason: format!("{:?}", halt_reason), gas_used },
+            EvmError::TransactError | EvmError::TransactCommitError(_) => PoolError::StateMissing("EVM_TRANSACT_ERROR"),
+        }
+    }
+}
+
+impl From<Report> for PoolError {
+    fn from(report: Report) -> Self {
+        let report = match report.downcast::<PoolError>() {
+            Ok(pool_error) => return pool_error,
+            Err(report) => report,
+        };
+        match report.downcast::<EvmError>() {
+            Ok(evm_error) => evm_error.into(),
+            Err(report) => PoolError::Other(report.to_string()),
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct SwapError<LDT: LoomDataTypes = LoomDataTypesEthereum> {
-    pub msg: String,
+    pub error: PoolError,
     pub pool: LDT::Address,
     pub token_from: LDT::Address,
     pub token_to: LDT::Address,
@@ -15,7 +77,7 @@ pub struct SwapError<LDT: LoomDataTypes = LoomDataTypesEthereum> {
 
 impl<LDT: LoomDataTypes> From<SwapError<LDT>> for Report {
     fn from(value: SwapError<LDT>) -> Self {
-        eyre!(value.msg)
+        Report::new(value.error)
     }
 }
 
@@ -34,3 +96,21 @@ impl<LDT: LoomDataTypes> PartialEq<Self> for SwapError<LDT> {
 }
 
 impl<LDT: LoomDataTypes> Eq for SwapError<LDT> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use eyre::eyre;
+
+    #[test]
+    fn test_pool_error_from_report() {
+        let report: Report = PoolError::InsufficientLiquidity("RESERVE_EXCEEDED").into();
+        assert_eq!(PoolError::from(report), PoolError::InsufficientLiquidity("RESERVE_EXCEEDED"));
+
+        let report = eyre!(EvmError::Reverted("STF".to_string(), 21000));
+        assert_eq!(PoolError::from(report), PoolError::EvmRevert { reason: "STF".to_string(), gas_used: 21000 });
+
+        let report = eyre!("SOMETHING");
+        assert_eq!(PoolError::from(report), PoolError::Other("SOMETHING".to_string()));
+    }
+}
@@ -0,0 +1,345 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+
+use alloy_primitives::U256;
+use eyre::Result;
+use loom_evm_utils::NWETH;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tracing::info;
+
+use crate::Swap;
+
+const MAX_PCT: u32 = 10000;
+
+/// Kind of the opportunity the tips are bid for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OpportunityType {
+    Backrun,
+    Merged,
+    Exchange,
+}
+
+impl OpportunityType {
+    pub fn from_swap(swap: &Swap) -> Option<Self> {
+        match swap {
+            Swap::BackrunSwapLine(_) | Swap::BackrunSwapSteps(_) => Some(OpportunityType::Backrun),
+            Swap::Multiple(_) => Some(OpportunityType::Merged),
+            Swap::ExchangeSwapLine(_) => Some(OpportunityType::Exchange),
+            Swap::None => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BidResult {
+    Landed,
+    /// Competitor tips are the coinbase payment of the transaction that took the opportunity, in basis points of our profit
+    Missed {
+        competitor_tips_pct: Option<u32>,
+    },
+}
+
+/// Outcome of a bid recorded after the block with the opportunity is mined
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BidOutcome {
+    pub block_number: u64,
+    pub opportunity: OpportunityType,
+    pub profit_eth: U256,
+    pub tips_pct: u32,
+    /// Relays the bundle was accepted by
+    pub relays: Vec<String>,
+    pub result: BidResult,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RelayBidStats {
+    pub sent: u64,
+    pub landed: u64,
+}
+
+impl RelayBidStats {
+    pub fn landing_rate(&self) -> f64 {
+        if self.sent == 0 {
+            0.0
+        } else {
+            self.landed as f64 / self.sent as f64
+        }
+    }
+}
+
+/// All percents are in basis points of the profit
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct BiddingConfig {
+    /// Tips percent is never lowered below the floor
+    pub floor_pct: u32,
+    /// Tips percent is never raised above the ceiling
+    pub ceiling_pct: u32,
+    /// Tips percent is lowered by the step after a landed bid and raised by the step after a miss without competitor tips
+    pub step_pct: u32,
+    /// Margin over the competitor tips after a miss
+    pub outbid_pct: u32,
+    /// Bounds of profit buckets in ETH, sorted
+    pub profit_buckets_eth: Vec<f64>,
+    /// Number of outcomes kept for replay
+    pub history_len: usize,
+    /// Outcomes are appended to the file as JSON lines and recorded again to restore the bidder on start
+    pub history_path: Option<PathBuf>,
+}
+
+impl Default for BiddingConfig {
+    fn default() -> Self {
+        Self {
+            floor_pct: 5000,
+            ceiling_pct: 9900,
+            step_pct: 100,
+            outbid_pct: 50,
+            profit_buckets_eth: vec![0.01, 0.1, 1.0, 10.0],
+            history_len: 10000,
+            history_path: None,
+        }
+    }
+}
+
+impl BiddingConfig {
+    pub fn profit_bucket(&self, profit_eth: &U256) -> usize {
+        self.profit_buckets_eth.iter().filter(|bound| NWETH::from_float(**bound) <= *profit_eth).count()
+    }
+
+    /// Limits the tips percent to the floor and the ceiling
+    pub fn clamp(&self, tips_pct: u32) -> u32 {
+        tips_pct.clamp(self.floor_pct, self.ceiling_pct.max(self.floor_pct))
+    }
+}
+
+/// Result of the replay of recorded outcomes
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BidEvaluation {
+    pub bids: usize,
+    /// Bids landed in the recorded history
+    pub recorded_landed: usize,
+    /// Bids that would land with the tips of the bidder
+    pub landed: usize,
+    /// Profit kept after tips of the landed bids
+    pub profit_eth: U256,
+    pub tips_eth: U256,
+}
+
+/// Adjusts tips percent per opportunity type and profit bucket from the outcomes of previous bids. A landed bid lowers the tips
+/// of the bucket by a step, a miss raises them over the competitor tips, or by a step if the competitor tips are unknown.
+/// Buckets without outcomes use the tips percent of the request.
+#[derive(Debug)]
+pub struct AdaptiveBidder {
+    config: BiddingConfig,
+    tips_pct: HashMap<(OpportunityType, usize), u32>,
+    relays: HashMap<String, RelayBidStats>,
+    history: VecDeque<BidOutcome>,
+}
+
+impl AdaptiveBidder {
+    pub fn new(config: BiddingConfig) -> Self {
+        Self { config, tips_pct: HashMap::new(), relays: HashMap::new(), history: VecDeque::new() }
+    }
+
+    /// Creates the bidder and records the outcomes of `history_path` if the file exists
+    pub async fn restore(config: BiddingConfig) -> Result<Self> {
+        let history = match config.history_path.as_ref() {
+            Some(path) if tokio::fs::try_exists(path).await? => read_bid_history(path).await?,
+            _ => Vec::new(),
+        };
+        let mut bidder = Self::new(config);
+        info!(outcomes = history.len(), "Adaptive bidder restored");
+        for outcome in history {
+            bidder.record(outcome);
+        }
+        Ok(bidder)
+    }
+
+    pub fn config(&self) -> &BiddingConfig {
+        &self.config
+    }
+
+    /// Tips percent for the opportunity, `default_pct` is used for buckets without outcomes
+    pub fn tips_pct(&self, opportunity: OpportunityType, profit_eth: &U256, default_pct: u32) -> u32 {
+        let key = (opportunity, self.config.profit_bucket(profit_eth));
+        let tips_pct = self.tips_pct.get(&key).cloned().unwrap_or(default_pct);
+        self.config.clamp(tips_pct)
+    }
+
+    pub fn record(&mut self, outcome: BidOutcome) {
+        let key = (outcome.opportunity, self.config.profit_bucket(&outcome.profit_eth));
+        let cur_pct = self.tips_pct.get(&key).cloned().unwrap_or(outcome.tips_pct);
+        let tips_pct = match outcome.result {
+            BidResult::Landed => cur_pct.min(outcome.tips_pct).saturating_sub(self.config.step_pct),
+            BidResult::Missed { competitor_tips_pct: Some(competitor_tips_pct) } => {
+                cur_pct.max(competitor_tips_pct.saturating_add(self.config.outbid_pct).min(MAX_PCT))
+            }
+            BidResult::Missed { competitor_tips_pct: None } => cur_pct.saturating_add(self.config.step_pct),
+        };
+        self.tips_pct.insert(key, self.config.clamp(tips_pct));
+
+        for relay in outcome.relays.iter() {
+            let stats = self.relays.entry(relay.clone()).or_default();
+            stats.sent += 1;
+            if outcome.result == BidResult::Landed {
+                stats.landed += 1;
+            }
+        }
+
+        if self.history.len() >= self.config.history_len {
+            self.history.pop_front();
+        }
+        self.history.push_back(outcome);
+    }
+
+    pub fn relay_stats(&self) -> HashMap<String, RelayBidStats> {
+        self.relays.clone()
+    }
+
+    /// Recorded outcomes, oldest first
+    pub fn history(&self) -> Vec<BidOutcome> {
+        self.history.iter().cloned().collect()
+    }
+
+    /// Replays recorded outcomes with a new bidder. A bid is counted as landed if it pays at least the recorded tips of a landed bid
+    /// or more than the competitor of a missed one, misses with unknown competitor tips are never landed.
+    pub fn replay(config: BiddingConfig, history: &[BidOutcome]) -> BidEvaluation {
+        let mut bidder = AdaptiveBidder::new(config);
+        let mut evaluation = BidEvaluation::default();
+
+        for outcome in history {
+            let tips_pct = bidder.tips_pct(outcome.opportunity, &outcome.profit_eth, outcome.tips_pct);
+            let landed = match outcome.result {
+                BidResult::Landed => tips_pct >= outcome.tips_pct,
+                BidResult::Missed { competitor_tips_pct: Some(competitor_tips_pct) } => tips_pct > competitor_tips_pct,
+                BidResult::Missed { competitor_tips_pct: None } => false,
+            };
+
+            evaluation.bids += 1;
+            if outcome.result == BidResult::Landed {
+                evaluation.recorded_landed += 1;
+            }
+            let result = if landed {
+                let tips = outcome.profit_eth * U256::from(tips_pct) / U256::from(MAX_PCT);
+                evaluation.landed += 1;
+                evaluation.tips_eth += tips;
+                evaluation.profit_eth += outcome.profit_eth - tips;
+                BidResult::Landed
+            } else {
+                match outcome.result {
+                    BidResult::Landed => BidResult::Missed { competitor_tips_pct: None },
+                    missed => missed,
+                }
+            };
+            bidder.record(BidOutcome { tips_pct, result, ..outcome.clone() });
+        }
+        evaluation
+    }
+}
+
+/// Reads outcomes written by [append_bid_outcome], oldest first
+pub async fn read_bid_history(path: &Path) -> Result<Vec<BidOutcome>> {
+    let data = tokio::fs::read_to_string(path).await?;
+    let mut history = Vec::new();
+    for line in data.lines().filter(|line| !line.trim().is_empty()) {
+        history.push(serde_json::from_str(line)?);
+    }
+    Ok(history)
+}
+
+/// Appends the outcome to the history file as a JSON line
+pub async fn append_bid_outcome(path: &Path, outcome: &BidOutcome) -> Result<()> {
+    let mut line = serde_json::to_string(outcome)?;
+    line.push('\n');
+    let mut file = tokio::fs::OpenOptions::new().create(true).append(true).open(path).await?;
+    file.write_all(line.as_bytes()).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::temp_file_path;
+
+    fn outcome(tips_pct: u32, result: BidResult) -> BidOutcome {
+        BidOutcome {
+            block_number: 1,
+            opportunity: OpportunityType::Backrun,
+            profit_eth: NWETH::from_float(0.4),
+            tips_pct,
+            relays: vec!["flashbots".to_string()],
+            result,
+        }
+    }
+
+    #[test]
+    fn test_profit_bucket() {
+        let config = BiddingConfig::default();
+        assert_eq!(config.profit_bucket(&U256::ZERO), 0);
+        assert_eq!(config.profit_bucket(&NWETH::from_float(0.01)), 1);
+        assert_eq!(config.profit_bucket(&NWETH::from_float(0.4)), 2);
+        assert_eq!(config.profit_bucket(&NWETH::from_float(100.0)), 4);
+    }
+
+    #[test]
+    fn test_record() {
+        let mut bidder = AdaptiveBidder::new(BiddingConfig::default());
+        let profit_eth = NWETH::from_float(0.4);
+        assert_eq!(bidder.tips_pct(OpportunityType::Backrun, &profit_eth, 9000), 9000);
+        assert_eq!(bidder.tips_pct(OpportunityType::Backrun, &profit_eth, 1000), 5000);
+
+        bidder.record(outcome(9000, BidResult::Landed));
+        assert_eq!(bidder.tips_pct(OpportunityType::Backrun, &profit_eth, 9000), 8900);
+        // other buckets and opportunity types are not changed
+        assert_eq!(bidder.tips_pct(OpportunityType::Merged, &profit_eth, 9000), 9000);
+        assert_eq!(bidder.tips_pct(OpportunityType::Backrun, &NWETH::from_float(5.0), 9000), 9000);
+
+        bidder.record(outcome(8900, BidResult::Missed { competitor_tips_pct: Some(9300) }));
+        assert_eq!(bidder.tips_pct(OpportunityType::Backrun, &profit_eth, 9000), 9350);
+        bidder.record(outcome(9350, BidResult::Missed { competitor_tips_pct: Some(9950) }));
+        assert_eq!(bidder.tips_pct(OpportunityType::Backrun, &profit_eth, 9000), 9900);
+        bidder.record(outcome(9900, BidResult::Missed { competitor_tips_pct: None }));
+        assert_eq!(bidder.tips_pct(OpportunityType::Backrun, &profit_eth, 9000), 9900);
+
+        assert_eq!(bidder.relay_stats()["flashbots"], RelayBidStats { sent: 4, landed: 1 });
+        assert_eq!(bidder.history().len(), 4);
+    }
+
+    #[test]
+    fn test_replay() {
+        let history = vec![
+            outcome(9000, BidResult::Landed),
+            outcome(9000, BidResult::Missed { competitor_tips_pct: Some(9100) }),
+            outcome(9000, BidResult::Missed { competitor_tips_pct: Some(9100) }),
+            outcome(9000, BidResult::Missed { competitor_tips_pct: None }),
+        ];
+        let evaluation = AdaptiveBidder::replay(BiddingConfig::default(), &history);
+        assert_eq!(evaluation.bids, 4);
+        assert_eq!(evaluation.recorded_landed, 1);
+        // first bid pays recorded tips, second misses with lowered tips, third outbids the competitor
+        assert_eq!(evaluation.landed, 2);
+        let profit_eth = NWETH::from_float(0.4);
+        assert_eq!(
+            evaluation.tips_eth,
+            profit_eth * U256::from(9000) / U256::from(MAX_PCT) + profit_eth * U256::from(9150) / U256::from(MAX_PCT)
+        );
+        assert_eq!(evaluation.profit_eth + evaluation.tips_eth, profit_eth * U256::from(2));
+    }
+
+    #[tokio::test]
+    async fn test_bid_history_file() -> Result<()> {
+        let (_dir, path) = temp_file_path("bid_history.jsonl")?;
+        let history = vec![outcome(9000, BidResult::Landed), outcome(8900, BidResult::Missed { competitor_tips_pct: Some(9300) })];
+        for outcome in history.iter() {
+            append_bid_outcome(&path, outcome).await?;
+        }
+        assert_eq!(read_bid_history(&path).await?, history);
+
+        let config = BiddingConfig { history_path: Some(path.clone()), ..BiddingConfig::default() };
+        let bidder = AdaptiveBidder::restore(config).await?;
+        assert_eq!(bidder.history(), history);
+        assert_eq!(bidder.tips_pct(OpportunityType::Backrun, &NWETH::from_float(0.4), 9000), 9350);
+        Ok(())
+    }
+}
@@ -53,6 +53,22 @@ impl BlockHistoryEntry {
     }
 }
 
+/// Blocks between two chain heads and their common ancestor.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReorgPath {
+    pub ancestor: BlockHash,
+    /// Blocks of the old branch, newest first.
+    pub reverted: Vec<BlockHash>,
+    /// Blocks of the new branch, oldest first.
+    pub applied: Vec<BlockHash>,
+}
+
+impl ReorgPath {
+    pub fn depth(&self) -> usize {
+        self.reverted.len()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct BlockHistory<S> {
     depth: usize,
@@ -245,6 +261,27 @@ impl<S> BlockHistory<S> {
     pub fn contains_block(&self, block_hash: &BlockHash) -> bool {
         self.block_entries.contains_key(block_hash)
     }
+
+    /// Walks parent links of both heads back to their common ancestor. Returns None if a block on the way is not in the history.
+    pub fn find_common_ancestor(&self, old_head: BlockHash, new_head: BlockHash) -> Option<ReorgPath> {
+        let mut old_entry = self.get_block_history_entry(&old_head)?;
+        let mut new_entry = self.get_block_history_entry(&new_head)?;
+        let mut reverted: Vec<BlockHash> = Vec::new();
+        let mut applied: Vec<BlockHash> = Vec::new();
+
+        while old_entry.hash() != new_entry.hash() {
+            if old_entry.number() >= new_entry.number() {
+                reverted.push(old_entry.hash());
+                old_entry = self.get_block_history_entry(&old_entry.parent_hash())?;
+            } else {
+                applied.push(new_entry.hash());
+                new_entry = self.get_block_history_entry(&new_entry.parent_hash())?;
+            }
+        }
+        applied.reverse();
+
+        Some(ReorgPath { ancestor: old_entry.hash(), reverted, applied })
+    }
 }
 
 pub struct BlockHistoryManager<P, T, D> {
@@ -459,6 +496,41 @@ where
 
         Ok(db)
     }
+
+    /// Moves `state` of block `state_block_hash` to block `block_hash` on another branch. Updates of orphaned blocks are
+    /// reverted to the values of the common ancestor, then updates of the new branch are applied, so records loaded after
+    /// the ancestor block are kept.
+    pub async fn apply_reorg_on_state(
+        &self,
+        block_history: &mut BlockHistory<S>,
+        market_state_config: &MarketStateConfig,
+        state: S,
+        state_block_hash: BlockHash,
+        block_hash: BlockHash,
+    ) -> Result<(S, ReorgPath)> {
+        let reorg_path = block_history.find_common_ancestor(state_block_hash, block_hash).ok_or_eyre("COMMON_ANCESTOR_NOT_FOUND")?;
+        let mut db = state;
+
+        if !reorg_path.reverted.is_empty() {
+            let ancestor_db = block_history.block_states.get(&reorg_path.ancestor).cloned().ok_or_eyre("ANCESTOR_STATE_NOT_FOUND")?;
+            for reverted_block_hash in reorg_path.reverted.iter() {
+                let reverted_entry = block_history.get_block_history_entry(reverted_block_hash).ok_or_eyre("ENTRY_NOT_FOUND")?;
+                db = db.revert_update(reverted_entry, &ancestor_db);
+            }
+            debug!(depth = reorg_path.depth(), ancestor = %reorg_path.ancestor, "State reverted to common ancestor");
+        }
+
+        for applied_block_hash in reorg_path.applied.iter() {
+            let applied_entry = block_history.get_entry_mut(applied_block_hash).ok_or_eyre("ENTRY_NOT_FOUND")?;
+            if !applied_entry.is_fetched() {
+                self.fetch_entry_data(applied_entry).await?;
+            }
+            db = db.apply_update(applied_entry, market_state_config);
+            block_history.block_states.insert(*applied_block_hash, db.clone());
+        }
+
+        Ok((db, reorg_path))
+    }
 }
 
 #[cfg(test)]
@@ -554,6 +626,58 @@ mod test {
         assert_eq!(block_history.block_numbers[&4], header_4_1.hash);
     }
 
+    #[test]
+    fn test_find_common_ancestor_depth_2() {
+        let mut block_history = BlockHistory::<LoomDBType>::new(10);
+
+        let header_1_0 = create_header(1, U256::from(1).into());
+        let header_2_0 = create_next_header(&header_1_0, 0);
+        let header_3_0 = create_next_header(&header_2_0, 0);
+        let header_2_1 = create_next_header(&header_1_0, 1);
+        let header_3_1 = create_next_header(&header_2_1, 0);
+
+        for header in [&header_1_0, &header_2_0, &header_3_0, &header_2_1, &header_3_1] {
+            block_history.add_block_header(header.clone()).unwrap();
+        }
+
+        let reorg_path = block_history.find_common_ancestor(header_3_0.hash, header_3_1.hash).unwrap();
+        assert_eq!(reorg_path.depth(), 2);
+        assert_eq!(reorg_path.ancestor, header_1_0.hash);
+        assert_eq!(reorg_path.reverted, vec![header_3_0.hash, header_2_0.hash]);
+        assert_eq!(reorg_path.applied, vec![header_2_1.hash, header_3_1.hash]);
+    }
+
+    #[test]
+    fn test_find_common_ancestor_depth_3() {
+        let mut block_history = BlockHistory::<LoomDBType>::new(10);
+
+        let header_1_0 = create_header(1, U256::from(1).into());
+        let header_2_0 = create_next_header(&header_1_0, 0);
+        let header_3_0 = create_next_header(&header_2_0, 0);
+        let header_4_0 = create_next_header(&header_3_0, 0);
+        let header_2_1 = create_next_header(&header_1_0, 1);
+        let header_3_1 = create_next_header(&header_2_1, 0);
+        let header_4_1 = create_next_header(&header_3_1, 0);
+        let header_5_1 = create_next_header(&header_4_1, 0);
+
+        for header in [&header_1_0, &header_2_0, &header_3_0, &header_4_0, &header_2_1, &header_3_1, &header_4_1, &header_5_1] {
+            block_history.add_block_header(header.clone()).unwrap();
+        }
+
+        let reorg_path = block_history.find_common_ancestor(header_4_0.hash, header_5_1.hash).unwrap();
+        assert_eq!(reorg_path.depth(), 3);
+        assert_eq!(reorg_path.ancestor, header_1_0.hash);
+        assert_eq!(reorg_path.reverted, vec![header_4_0.hash, header_3_0.hash, header_2_0.hash]);
+        assert_eq!(reorg_path.applied, vec![header_2_1.hash, header_3_1.hash, header_4_1.hash, header_5_1.hash]);
+
+        // same branch, no reorg
+        let reorg_path = block_history.find_common_ancestor(header_3_1.hash, header_5_1.hash).unwrap();
+        assert_eq!(reorg_path.depth(), 0);
+        assert_eq!(reorg_path.applied, vec![header_4_1.hash, header_5_1.hash]);
+
+        assert!(block_history.find_common_ancestor(header_4_0.hash, BlockHash::repeat_byte(0xff)).is_none());
+    }
+
     #[tokio::test]
     async fn test_with_anvil() -> Result<()> {
         let anvil = Anvil::new().try_spawn()?;
@@ -1,10 +1,16 @@
 use crate::market_state::MarketStateConfig;
 use crate::BlockHistoryEntry;
+use alloy_primitives::{Address, U256};
 use loom_evm_db::{DatabaseLoomExt, LoomDB};
+use revm::DatabaseRef;
 use tracing::{error, trace};
 
 pub trait BlockHistoryState {
     fn apply_update(self, block_history_entry: &BlockHistoryEntry, market_state_config: &MarketStateConfig) -> Self;
+
+    /// Restores records changed by the orphaned block to their values in the state of the common ancestor.
+    /// Changed slots that are not in the ancestor state are removed to be fetched again.
+    fn revert_update(self, block_history_entry: &BlockHistoryEntry, ancestor_state: &Self) -> Self;
 }
 
 impl BlockHistoryState for LoomDB {
@@ -60,4 +66,56 @@ impl BlockHistoryState for LoomDB {
 
         db
     }
+
+    fn revert_update(self, block_history_entry: &BlockHistoryEntry, ancestor_state: &Self) -> Self {
+        let mut db = self;
+        // slots loaded after the ancestor state may hold values of the orphaned branch
+        let mut refetch_slots: Vec<(Address, U256)> = Vec::new();
+        if let Some(state_update) = &block_history_entry.state_update {
+            for state_diff in state_update.iter().rev() {
+                for (address, account_state) in state_diff.iter() {
+                    if (account_state.balance.is_some() || account_state.nonce.is_some()) && db.is_rw_ro_account(address) {
+                        match ancestor_state.basic_ref(*address) {
+                            Ok(Some(ancestor_info)) if ancestor_state.is_rw_ro_account(address) => {
+                                if let Ok(x) = db.load_ro_rw_account(*address) {
+                                    x.info.balance = ancestor_info.balance;
+                                    x.info.nonce = ancestor_info.nonce;
+                                    trace!("Account reverted {:#20x} {} {}", address, ancestor_info.balance, ancestor_info.nonce);
+                                }
+                            }
+                            _ => {
+                                trace!("Account revert for {:#20x} not found in ancestor state", address);
+                            }
+                        }
+                    }
+
+                    for slot in account_state.storage.keys() {
+                        let slot: U256 = (*slot).into();
+                        if !db.is_rw_ro_slot(address, &slot) {
+                            continue;
+                        }
+                        if !ancestor_state.is_rw_ro_slot(address, &slot) {
+                            trace!("Slot revert for {:#20x} {} not found in ancestor state, removed for refetch", address, slot);
+                            refetch_slots.push((*address, slot));
+                            continue;
+                        }
+                        match ancestor_state.storage_ref(*address, slot) {
+                            Ok(value) => {
+                                trace!("Slot reverted {:#20x} {} {}", address, slot, value);
+                                if let Err(e) = db.insert_account_storage(*address, slot, value) {
+                                    error!("{}", e)
+                                }
+                            }
+                            Err(e) => {
+                                error!("{}", e)
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        db.remove_account_storage(&refetch_slots);
+
+        db
+    }
 }
@@ -1,4 +1,4 @@
-pub use block_history_impl::{BlockHistory, BlockHistoryEntry, BlockHistoryManager};
+pub use block_history_impl::{BlockHistory, BlockHistoryEntry, BlockHistoryManager, ReorgPath};
 pub use block_history_state::BlockHistoryState;
 
 mod block_history_impl;
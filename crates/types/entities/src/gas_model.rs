@@ -0,0 +1,155 @@
+use alloy_primitives::map::HashMap;
+
+use crate::{PoolClass, PoolWrapper, SwapLine};
+use loom_types_blockchain::{LoomDataTypes, LoomDataTypesEthereum};
+
+/// Gas of a transaction calling the multicaller without swaps, used until it is measured.
+pub const DEFAULT_BASE_GAS: u64 = 50_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GasMeasurement {
+    pub pool_class: PoolClass,
+    pub gas: u64,
+    pub block_number: u64,
+}
+
+/// Gas model of swaps calibrated with gas measured by execution of encoded swap steps in evm.
+/// Swap steps are measured for every pool and swap direction, pools that are not measured yet are
+/// estimated with the average of their pool class.
+#[derive(Clone, Default)]
+pub struct GasModel<LDT: LoomDataTypes = LoomDataTypesEthereum> {
+    // (pool_address, token_from, token_to) -> measurement
+    measurements: HashMap<(LDT::Address, LDT::Address, LDT::Address), GasMeasurement>,
+    // pool_class -> (gas_sum, measurements_count)
+    class_gas: HashMap<PoolClass, (u64, u64)>,
+    base_gas: Option<u64>,
+}
+
+impl<LDT: LoomDataTypes> GasModel<LDT> {
+    pub fn new() -> Self {
+        Self { measurements: HashMap::default(), class_gas: HashMap::default(), base_gas: None }
+    }
+
+    pub fn len(&self) -> usize {
+        self.measurements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.measurements.is_empty()
+    }
+
+    /// Gas of the transaction without swaps
+    pub fn base_gas(&self) -> u64 {
+        self.base_gas.unwrap_or(DEFAULT_BASE_GAS)
+    }
+
+    pub fn set_base_gas(&mut self, gas: u64) {
+        self.base_gas = Some(gas);
+    }
+
+    pub fn update(&mut self, pool: &PoolWrapper<LDT>, token_from: LDT::Address, token_to: LDT::Address, gas: u64, block_number: u64) {
+        let pool_class = pool.get_class();
+        if let Some(prev) =
+            self.measurements.insert((pool.get_address(), token_from, token_to), GasMeasurement { pool_class, gas, block_number })
+        {
+            if let Some((sum, count)) = self.class_gas.get_mut(&prev.pool_class) {
+                *sum -= prev.gas;
+                *count -= 1;
+            }
+        }
+        let (sum, count) = self.class_gas.entry(pool_class).or_default();
+        *sum += gas;
+        *count += 1;
+    }
+
+    pub fn get(&self, pool_address: &LDT::Address, token_from: &LDT::Address, token_to: &LDT::Address) -> Option<&GasMeasurement> {
+        self.measurements.get(&(*pool_address, *token_from, *token_to))
+    }
+
+    /// Returns true if the swap direction is not measured or the measurement is older than `max_age` blocks
+    pub fn is_stale(
+        &self,
+        pool_address: &LDT::Address,
+        token_from: &LDT::Address,
+        token_to: &LDT::Address,
+        block_number: u64,
+        max_age: u64,
+    ) -> bool {
+        match self.get(pool_address, token_from, token_to) {
+            Some(measurement) => measurement.block_number + max_age <= block_number,
+            None => true,
+        }
+    }
+
+    pub fn class_gas(&self, pool_class: &PoolClass) -> Option<u64> {
+        self.class_gas.get(pool_class).filter(|(_, count)| *count > 0).map(|(sum, count)| sum / count)
+    }
+
+    /// Measured gas of the swap direction or the average gas of the pool class
+    pub fn pool_gas(&self, pool: &PoolWrapper<LDT>, token_from: &LDT::Address, token_to: &LDT::Address) -> Option<u64> {
+        match self.get(&pool.get_address(), token_from, token_to) {
+            Some(measurement) => Some(measurement.gas),
+            None => self.class_gas(&pool.get_class()),
+        }
+    }
+
+    /// Gas of all swap steps of the swap line, None if any of its pool classes is not measured
+    pub fn swap_line_gas(&self, swap_line: &SwapLine<LDT>) -> Option<u64> {
+        let tokens = swap_line.tokens();
+        swap_line
+            .pools()
+            .iter()
+            .enumerate()
+            .map(|(i, pool)| self.pool_gas(pool, &tokens[i].get_address(), &tokens[i + 1].get_address()))
+            .sum()
+    }
+
+    /// Gas of the transaction executing the swap line. Falls back to the gas returned by pools calculation.
+    pub fn swap_line_tx_gas(&self, swap_line: &SwapLine<LDT>) -> u64 {
+        self.base_gas() + self.swap_line_gas(swap_line).or(swap_line.gas_used).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MockPool, SwapPath, Token};
+    use alloy_primitives::Address;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_gas_model() {
+        let token0 = Address::repeat_byte(1);
+        let token1 = Address::repeat_byte(2);
+        let pool0 = PoolWrapper::from(MockPool::new(token0, token1, Address::repeat_byte(0x10)));
+        let pool1 = PoolWrapper::from(MockPool::new(token0, token1, Address::repeat_byte(0x11)));
+
+        let swap_line: SwapLine = SwapLine {
+            path: SwapPath::new(
+                vec![Arc::new(Token::new(token0)), Arc::new(Token::new(token1)), Arc::new(Token::new(token0))],
+                vec![pool0.clone(), pool1.clone()],
+            ),
+            gas_used: Some(200_000),
+            ..SwapLine::default()
+        };
+
+        let mut gas_model: GasModel = GasModel::new();
+        assert_eq!(gas_model.swap_line_gas(&swap_line), None);
+        assert_eq!(gas_model.swap_line_tx_gas(&swap_line), DEFAULT_BASE_GAS + 200_000);
+
+        gas_model.update(&pool0, token0, token1, 60_000, 100);
+        assert_eq!(gas_model.swap_line_gas(&swap_line), Some(120_000));
+
+        gas_model.update(&pool1, token1, token0, 80_000, 100);
+        gas_model.update(&pool0, token0, token1, 70_000, 110);
+        assert_eq!(gas_model.class_gas(&PoolClass::UniswapV2), Some(75_000));
+        assert_eq!(gas_model.swap_line_gas(&swap_line), Some(150_000));
+
+        gas_model.set_base_gas(30_000);
+        assert_eq!(gas_model.swap_line_tx_gas(&swap_line), 180_000);
+
+        assert!(!gas_model.is_stale(&pool0.get_address(), &token0, &token1, 150, 100));
+        assert!(gas_model.is_stale(&pool0.get_address(), &token0, &token1, 210, 100));
+        assert!(gas_model.is_stale(&pool0.get_address(), &token1, &token0, 150, 100));
+    }
+}
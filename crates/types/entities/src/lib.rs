@@ -3,16 +3,19 @@
 extern crate core;
 
 pub use account_nonce_balance::{AccountNonceAndBalanceState, AccountNonceAndBalances};
-pub use block_history::{BlockHistory, BlockHistoryEntry, BlockHistoryManager, BlockHistoryState};
+pub use block_history::{BlockHistory, BlockHistoryEntry, BlockHistoryManager, BlockHistoryState, ReorgPath};
 pub use calculation_result::CalculationResult;
 pub use call_sequence::{CallSequence, FlashLoanParams};
 pub use datafetcher::{DataFetcher, FetchState};
+pub use gas_model::{GasMeasurement, GasModel, DEFAULT_BASE_GAS};
 pub use keystore::KeyStore;
 pub use latest_block::LatestBlock;
 pub use market::Market;
 pub use market_state::MarketState;
+pub use mempool_source_stats::{MempoolSourceStat, MempoolSourceStats, MempoolSourceStatsConfig};
 pub use mock_pool::MockPool;
 pub use pool::{get_protocol_by_factory, AbiSwapEncoder, Pool, PoolClass, PoolProtocol, PoolWrapper, PreswapRequirement};
+pub use pool_quarantine::{PoolErrorReason, PoolHealthRecord, PoolQuarantine, PoolQuarantineConfig, PoolQuarantineStatus};
 pub use signers::{LoomTxSigner, TxSignerEth, TxSigners};
 pub use swap::Swap;
 pub use swap_encoder::SwapEncoder;
@@ -20,18 +23,22 @@ pub use swapline::{SwapAmountType, SwapLine};
 pub use swappath::{SwapPath, SwapPaths};
 pub use swappath_builder::build_swap_path_vec;
 pub use swapstep::SwapStep;
-pub use token::{Token, TokenWrapper};
+pub use temp_file::temp_file_path;
+pub use token::{Token, TokenTransferClass, TokenWrapper};
 
 mod block_history;
 mod latest_block;
 mod market;
 mod market_state;
+mod mempool_source_stats;
 mod pool;
+mod pool_quarantine;
 mod swapline;
 mod swappath;
 mod token;
 
 pub mod account_nonce_balance;
+pub mod bidding;
 pub mod required_state;
 mod swappath_builder;
 mod swapstep;
@@ -41,14 +48,17 @@ mod signers;
 mod keystore;
 
 pub mod private;
+pub mod profit_policy;
 
 mod calculation_result;
 pub mod config;
 mod datafetcher;
+mod gas_model;
 mod mock_pool;
 
 pub mod call_sequence;
 mod mock_pool_generic;
 mod swap;
 mod swap_encoder;
+mod temp_file;
 pub mod tips;
@@ -153,6 +153,12 @@ impl<LDT: LoomDataTypes> Market<LDT> {
         self.tokens.get(address).cloned()
     }
 
+    /// Get all tokens of the market.
+    #[inline]
+    pub fn tokens(&self) -> &HashMap<LDT::Address, Arc<Token<LDT>>> {
+        &self.tokens
+    }
+
     /// Get all pool addresses that allow to swap from `token_from_address` to `token_to_address`.
 
     #[inline]
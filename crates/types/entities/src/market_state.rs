@@ -9,6 +9,8 @@ use revm::{Database, DatabaseCommit, DatabaseRef};
 pub struct MarketStateConfig {
     pub force_insert_accounts: HashSet<Address>,
     pub read_only_cells: HashMap<Address, HashSet<U256>>,
+    /// Storage slots of other contracts the pools depend on, e.g. token balances of pools. They are never evicted.
+    pub protected_slots: HashMap<Address, HashSet<U256>>,
 }
 
 impl MarketStateConfig {
@@ -30,6 +32,17 @@ impl MarketStateConfig {
         }
     }
 
+    pub fn protect_slots(&mut self, address: Address, slots: impl IntoIterator<Item = U256>) {
+        self.protected_slots.entry(address).or_default().extend(slots)
+    }
+
+    /// Protects storage slots of accounts other than the pool that are read to calculate the pool
+    pub fn protect_pool_state(&mut self, pool_address: Address, state: &GethStateUpdate) {
+        for (address, account_state) in state.iter().filter(|(address, _)| **address != pool_address) {
+            self.protect_slots(*address, account_state.storage.keys().map(|slot| U256::from_be_bytes(slot.0)));
+        }
+    }
+
     pub fn is_read_only_cell(&self, address: &Address, cell: &U256) -> bool {
         match self.read_only_cells.get(address) {
             Some(hashset) => hashset.contains(cell),
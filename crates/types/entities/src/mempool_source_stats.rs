@@ -0,0 +1,187 @@
+use std::collections::VecDeque;
+
+use alloy_primitives::map::HashMap;
+use chrono::{DateTime, Duration, Utc};
+
+use loom_types_blockchain::{LoomDataTypes, LoomDataTypesEthereum};
+
+#[derive(Clone, Debug)]
+pub struct MempoolSourceStatsConfig {
+    /// Time to wait for other sources after a tx is first seen
+    pub settle_ms: i64,
+    /// Number of latest txs of every source in the statistics
+    pub window: usize,
+}
+
+impl Default for MempoolSourceStatsConfig {
+    fn default() -> Self {
+        Self { settle_ms: 12_000, window: 10_000 }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct SourceSample {
+    first: bool,
+    /// Time from this source to the fastest other source in ms, negative if another source was faster
+    lead_ms: Option<f64>,
+}
+
+/// Rolling statistics of a mempool source over its latest txs
+#[derive(Clone, Debug, PartialEq)]
+pub struct MempoolSourceStat {
+    pub source: String,
+    pub txs: usize,
+    /// Txs this source delivered first
+    pub wins: usize,
+    pub win_rate: f64,
+    /// Lead time over the fastest other source for txs seen by several sources
+    pub median_lead_ms: Option<f64>,
+    pub p99_lead_ms: Option<f64>,
+}
+
+fn percentile(sorted_values: &[f64], pct: f64) -> Option<f64> {
+    if sorted_values.is_empty() {
+        return None;
+    }
+    let idx = ((sorted_values.len() - 1) as f64 * pct / 100.0).round() as usize;
+    Some(sorted_values[idx])
+}
+
+/// Records when every mempool source first delivered a tx and measures how much earlier sources are than each other
+#[derive(Clone, Debug, Default)]
+pub struct MempoolSourceStats<LDT: LoomDataTypes = LoomDataTypesEthereum> {
+    config: MempoolSourceStatsConfig,
+    /// First-seen time by source of txs waiting for other sources
+    pending: HashMap<LDT::TxHash, Vec<(String, DateTime<Utc>)>>,
+    samples: HashMap<String, VecDeque<SourceSample>>,
+}
+
+impl<LDT: LoomDataTypes> MempoolSourceStats<LDT> {
+    pub fn new(config: MempoolSourceStatsConfig) -> Self {
+        Self { config, pending: HashMap::default(), samples: HashMap::default() }
+    }
+
+    pub fn config(&self) -> &MempoolSourceStatsConfig {
+        &self.config
+    }
+
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Records the first time the source delivered the tx, later deliveries of the source are ignored
+    pub fn record(&mut self, tx_hash: LDT::TxHash, source: &str, time: DateTime<Utc>) {
+        let seen = self.pending.entry(tx_hash).or_default();
+        if !seen.iter().any(|(seen_source, _)| seen_source == source) {
+            seen.push((source.to_string(), time));
+        }
+    }
+
+    /// Moves txs first seen before the settle time into the statistics. Returns the number of settled txs.
+    pub fn settle(&mut self, now: DateTime<Utc>) -> usize {
+        let settle_time = now - Duration::milliseconds(self.config.settle_ms);
+        let settled: Vec<LDT::TxHash> = self
+            .pending
+            .iter()
+            .filter(|(_, seen)| seen.iter().map(|(_, time)| *time).min().is_some_and(|first_time| first_time <= settle_time))
+            .map(|(tx_hash, _)| *tx_hash)
+            .collect();
+
+        for tx_hash in settled.iter() {
+            if let Some(seen) = self.pending.remove(tx_hash) {
+                self.add_samples(&seen);
+            }
+        }
+        settled.len()
+    }
+
+    fn add_samples(&mut self, seen: &[(String, DateTime<Utc>)]) {
+        let Some(first_idx) = seen.iter().enumerate().min_by_key(|(_, (_, time))| *time).map(|(idx, _)| idx) else {
+            return;
+        };
+
+        for (idx, (source, time)) in seen.iter().enumerate() {
+            let others_time = seen.iter().enumerate().filter(|(other_idx, _)| *other_idx != idx).map(|(_, (_, time))| *time).min();
+            let sample = SourceSample {
+                first: idx == first_idx,
+                lead_ms: others_time.map(|others_time| (others_time - *time).num_microseconds().unwrap_or_default() as f64 / 1000.0),
+            };
+
+            let samples = self.samples.entry(source.clone()).or_default();
+            samples.push_back(sample);
+            while samples.len() > self.config.window {
+                samples.pop_front();
+            }
+        }
+    }
+
+    /// Statistics of all sources ordered by the source name
+    pub fn stats(&self) -> Vec<MempoolSourceStat> {
+        let mut stats: Vec<MempoolSourceStat> = self
+            .samples
+            .iter()
+            .map(|(source, samples)| {
+                let wins = samples.iter().filter(|sample| sample.first).count();
+                let mut leads: Vec<f64> = samples.iter().filter_map(|sample| sample.lead_ms).collect();
+                leads.sort_by(|a, b| a.total_cmp(b));
+
+                MempoolSourceStat {
+                    source: source.clone(),
+                    txs: samples.len(),
+                    wins,
+                    win_rate: if samples.is_empty() { 0.0 } else { wins as f64 / samples.len() as f64 },
+                    median_lead_ms: percentile(&leads, 50.0),
+                    p99_lead_ms: percentile(&leads, 99.0),
+                }
+            })
+            .collect();
+        stats.sort_by(|a, b| a.source.cmp(&b.source));
+        stats
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_primitives::TxHash;
+
+    #[test]
+    fn test_mempool_source_stats() {
+        let mut stats: MempoolSourceStats = MempoolSourceStats::new(MempoolSourceStatsConfig { settle_ms: 1000, window: 3 });
+        let start = Utc::now();
+        let ms = Duration::milliseconds;
+
+        // local is 10ms faster than remote for the first tx and 30ms slower for the second, the third is seen only by local
+        stats.record(TxHash::repeat_byte(1), "local", start);
+        stats.record(TxHash::repeat_byte(1), "remote", start + ms(10));
+        stats.record(TxHash::repeat_byte(1), "local", start + ms(20));
+        stats.record(TxHash::repeat_byte(2), "remote", start + ms(100));
+        stats.record(TxHash::repeat_byte(2), "local", start + ms(130));
+        stats.record(TxHash::repeat_byte(3), "local", start + ms(2000));
+
+        assert_eq!(stats.settle(start + ms(500)), 0);
+        assert_eq!(stats.settle(start + ms(1200)), 2);
+        assert_eq!(stats.pending_len(), 1);
+
+        let result = stats.stats();
+        assert_eq!(result.len(), 2);
+        assert_eq!((result[0].source.as_str(), result[0].txs, result[0].wins), ("local", 2, 1));
+        assert_eq!(result[0].median_lead_ms, Some(10.0));
+        assert_eq!(result[0].p99_lead_ms, Some(10.0));
+        assert_eq!((result[1].source.as_str(), result[1].txs, result[1].wins), ("remote", 2, 1));
+        assert_eq!(result[1].median_lead_ms, Some(30.0));
+
+        assert_eq!(stats.settle(start + ms(3000)), 1);
+        let result = stats.stats();
+        assert_eq!((result[0].txs, result[0].wins), (3, 2));
+        assert!((result[0].win_rate - 2.0 / 3.0).abs() < 1e-9);
+
+        // only the latest txs of the window are kept
+        stats.record(TxHash::repeat_byte(4), "local", start + ms(3000));
+        stats.record(TxHash::repeat_byte(4), "remote", start + ms(3050));
+        stats.settle(start + ms(5000));
+        let result = stats.stats();
+        assert_eq!((result[0].txs, result[0].wins), (3, 2));
+        assert_eq!(result[0].median_lead_ms, Some(50.0));
+    }
+}
@@ -3,6 +3,7 @@ use crate::{AbiSwapEncoder, Pool, PoolClass, PoolProtocol};
 use alloy_primitives::{Address, U256};
 use eyre::ErrReport;
 use eyre::Result;
+use loom_types_blockchain::PoolError;
 use revm::primitives::Env;
 use revm::DatabaseRef;
 
@@ -47,7 +48,7 @@ impl Pool for MockPool {
         token_address_from: &Address,
         token_address_to: &Address,
         in_amount: U256,
-    ) -> Result<(U256, u64), ErrReport> {
+    ) -> Result<(U256, u64), PoolError> {
         panic!("Not implemented")
     }
 
@@ -58,7 +59,7 @@ impl Pool for MockPool {
         token_address_from: &Address,
         token_address_to: &Address,
         out_amount: U256,
-    ) -> Result<(U256, u64), ErrReport> {
+    ) -> Result<(U256, u64), PoolError> {
         panic!("Not implemented")
     }
 
@@ -8,6 +8,7 @@ use alloy_transport::Transport;
 use eyre::Result;
 use eyre::{eyre, ErrReport};
 use loom_evm_db::{AlloyDB, LoomDBType};
+use loom_types_blockchain::PoolError;
 use revm::primitives::Env;
 use revm::DatabaseRef;
 use std::marker::PhantomData;
@@ -53,7 +54,7 @@ where
         token_address_from: &Address,
         token_address_to: &Address,
         in_amount: U256,
-    ) -> Result<(U256, u64), ErrReport> {
+    ) -> Result<(U256, u64), PoolError> {
         let alloy_db = AlloyDB::new(self.client.clone(), BlockNumberOrTag::Latest.into()).ok_or(eyre!("ALLOY_DB_NOT_CREATED"))?;
         let state = LoomDBType::new().with_ext_db(alloy_db);
 
@@ -67,7 +68,7 @@ where
         token_address_from: &Address,
         token_address_to: &Address,
         out_amount: U256,
-    ) -> Result<(U256, u64), ErrReport> {
+    ) -> Result<(U256, u64), PoolError> {
         panic!("Not implemented")
     }
 
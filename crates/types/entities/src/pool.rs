@@ -8,7 +8,7 @@ use crate::required_state::RequiredState;
 use alloy_primitives::{Address, Bytes, U256};
 use eyre::{eyre, ErrReport, Result};
 use loom_defi_address_book::FactoryAddress;
-use loom_types_blockchain::{LoomDataTypes, LoomDataTypesEthereum};
+use loom_types_blockchain::{LoomDataTypes, LoomDataTypesEthereum, PoolError};
 use revm::primitives::Env;
 use revm::DatabaseRef;
 use serde::{Deserialize, Serialize};
@@ -224,7 +224,7 @@ pub trait Pool<LDT: LoomDataTypes = LoomDataTypesEthereum>: Sync + Send {
         token_address_from: &LDT::Address,
         token_address_to: &LDT::Address,
         in_amount: U256,
-    ) -> Result<(U256, u64), ErrReport>;
+    ) -> Result<(U256, u64), PoolError>;
 
     // returns (in_amount, gas_used)
     fn calculate_in_amount(
@@ -234,7 +234,7 @@ pub trait Pool<LDT: LoomDataTypes = LoomDataTypesEthereum>: Sync + Send {
         token_address_from: &LDT::Address,
         token_address_to: &LDT::Address,
         out_amount: U256,
-    ) -> Result<(U256, u64), ErrReport>;
+    ) -> Result<(U256, u64), PoolError>;
 
     fn can_flash_swap(&self) -> bool;
 
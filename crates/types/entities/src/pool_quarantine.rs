@@ -0,0 +1,247 @@
+use alloy_primitives::map::HashMap;
+
+use loom_types_blockchain::{LoomDataTypes, LoomDataTypesEthereum, PoolError, SwapError};
+
+/// Category of a pool swap error
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PoolErrorReason {
+    /// Amount in or out is zero, usually the swapped amount is too small
+    ZeroAmount,
+    /// Not enough reserves or liquidity for the swapped amount
+    Liquidity,
+    /// Arithmetic overflow or underflow in the calculation
+    Overflow,
+    /// State is missing or the evm call of the pool failed
+    State,
+    /// Swap direction or pool feature is not supported
+    NotSupported,
+    /// Evm call of the pool reverted
+    Reverted,
+    Other,
+}
+
+impl PoolErrorReason {
+    /// Error score added by an error of this category. Errors that don't depend on the swapped amount weigh more.
+    pub fn weight(&self) -> f64 {
+        match self {
+            PoolErrorReason::ZeroAmount | PoolErrorReason::Liquidity | PoolErrorReason::Overflow => 1.0,
+            PoolErrorReason::State | PoolErrorReason::Reverted | PoolErrorReason::Other => 2.0,
+            PoolErrorReason::NotSupported => 5.0,
+        }
+    }
+}
+
+impl From<&PoolError> for PoolErrorReason {
+    fn from(pool_error: &PoolError) -> Self {
+        match pool_error {
+            PoolError::ZeroAmount(_) => PoolErrorReason::ZeroAmount,
+            PoolError::InsufficientLiquidity(_) => PoolErrorReason::Liquidity,
+            PoolError::MathOverflow(_) => PoolErrorReason::Overflow,
+            PoolError::StateMissing(_) | PoolError::DecodeError(_) => PoolErrorReason::State,
+            PoolError::UnsupportedDirection(_) => PoolErrorReason::NotSupported,
+            PoolError::EvmRevert { .. } => PoolErrorReason::Reverted,
+            PoolError::PriceNotSet | PoolError::Other(_) => PoolErrorReason::Other,
+        }
+    }
+}
+
+impl<LDT: LoomDataTypes> From<&SwapError<LDT>> for PoolErrorReason {
+    fn from(swap_error: &SwapError<LDT>) -> Self {
+        PoolErrorReason::from(&swap_error.error)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PoolQuarantineStatus {
+    #[default]
+    Active,
+    /// Disabled until the block, re-verified before enabling
+    Quarantined { until_block: u64 },
+    /// Disabled after too many quarantines
+    Disabled,
+}
+
+#[derive(Clone, Debug)]
+pub struct PoolQuarantineConfig {
+    /// Error score halves every `decay_blocks` blocks
+    pub decay_blocks: u64,
+    /// Pool is quarantined when the error score reaches the threshold
+    pub score_threshold: f64,
+    /// Duration of the first quarantine, doubled for every next one
+    pub quarantine_blocks: u64,
+    /// Pool is disabled permanently when quarantined more times than this
+    pub max_quarantines: u32,
+    /// Quarantine count is reset if the pool had no quarantine for this number of blocks
+    pub reset_blocks: u64,
+}
+
+impl Default for PoolQuarantineConfig {
+    fn default() -> Self {
+        Self { decay_blocks: 300, score_threshold: 10.0, quarantine_blocks: 50, max_quarantines: 3, reset_blocks: 7200 }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PoolHealthRecord {
+    pub status: PoolQuarantineStatus,
+    pub score: f64,
+    pub score_block: u64,
+    pub reasons: HashMap<PoolErrorReason, u64>,
+    pub last_error: Option<String>,
+    pub quarantine_count: u32,
+    pub quarantine_block: Option<u64>,
+}
+
+impl PoolHealthRecord {
+    fn decayed_score(&self, block_number: u64, decay_blocks: u64) -> f64 {
+        if decay_blocks == 0 {
+            return 0.0;
+        }
+        let age = block_number.saturating_sub(self.score_block) as f64;
+        self.score * 0.5f64.powf(age / decay_blocks as f64)
+    }
+}
+
+/// Pool errors reported by searchers and pools quarantined because of them.
+/// Error scores decay over blocks so transient errors don't disable pools. Quarantined pools are re-verified
+/// when the quarantine expires and disabled permanently after `max_quarantines` quarantines.
+#[derive(Clone, Default)]
+pub struct PoolQuarantine<LDT: LoomDataTypes = LoomDataTypesEthereum> {
+    config: PoolQuarantineConfig,
+    records: HashMap<LDT::Address, PoolHealthRecord>,
+}
+
+impl<LDT: LoomDataTypes> PoolQuarantine<LDT> {
+    pub fn new(config: PoolQuarantineConfig) -> Self {
+        Self { config, records: HashMap::default() }
+    }
+
+    pub fn config(&self) -> &PoolQuarantineConfig {
+        &self.config
+    }
+
+    pub fn records(&self) -> &HashMap<LDT::Address, PoolHealthRecord> {
+        &self.records
+    }
+
+    pub fn get(&self, pool_address: &LDT::Address) -> Option<&PoolHealthRecord> {
+        self.records.get(pool_address)
+    }
+
+    pub fn status(&self, pool_address: &LDT::Address) -> PoolQuarantineStatus {
+        self.records.get(pool_address).map(|record| record.status).unwrap_or_default()
+    }
+
+    pub fn score(&self, pool_address: &LDT::Address, block_number: u64) -> f64 {
+        self.records.get(pool_address).map_or(0.0, |record| record.decayed_score(block_number, self.config.decay_blocks))
+    }
+
+    /// Records the error of the active pool, returns true if the pool reached the score threshold and should be quarantined
+    pub fn record_error(&mut self, pool_address: LDT::Address, reason: PoolErrorReason, msg: String, block_number: u64) -> bool {
+        let decay_blocks = self.config.decay_blocks;
+        let record = self.records.entry(pool_address).or_default();
+        *record.reasons.entry(reason).or_default() += 1;
+        record.last_error = Some(msg);
+
+        if record.status != PoolQuarantineStatus::Active {
+            return false;
+        }
+        record.score = record.decayed_score(block_number, decay_blocks) + reason.weight();
+        record.score_block = block_number;
+        record.score >= self.config.score_threshold
+    }
+
+    /// Quarantines the pool for a period doubled with every quarantine, or disables it when quarantined too often
+    pub fn quarantine(&mut self, pool_address: LDT::Address, block_number: u64) -> PoolQuarantineStatus {
+        let config = &self.config;
+        let record = self.records.entry(pool_address).or_default();
+
+        if record.quarantine_block.is_some_and(|quarantine_block| quarantine_block + config.reset_blocks <= block_number) {
+            record.quarantine_count = 0;
+        }
+        record.quarantine_count += 1;
+        record.quarantine_block = Some(block_number);
+        record.score = 0.0;
+        record.score_block = block_number;
+
+        record.status = if record.quarantine_count > config.max_quarantines {
+            PoolQuarantineStatus::Disabled
+        } else {
+            let until_block = block_number + (config.quarantine_blocks << (record.quarantine_count - 1).min(16));
+            PoolQuarantineStatus::Quarantined { until_block }
+        };
+        record.status
+    }
+
+    /// Pools with expired quarantine that should be re-verified
+    pub fn expired(&self, block_number: u64) -> Vec<LDT::Address> {
+        self.records
+            .iter()
+            .filter_map(|(pool_address, record)| match record.status {
+                PoolQuarantineStatus::Quarantined { until_block } if until_block <= block_number => Some(*pool_address),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Re-enables the verified pool, quarantine count is kept for escalation
+    pub fn release(&mut self, pool_address: &LDT::Address, block_number: u64) {
+        if let Some(record) = self.records.get_mut(pool_address) {
+            record.status = PoolQuarantineStatus::Active;
+            record.score = 0.0;
+            record.score_block = block_number;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloy_primitives::Address;
+
+    #[test]
+    fn test_error_reason() {
+        assert_eq!(PoolErrorReason::from(&PoolError::ZeroAmount("ZERO_OUT_AMOUNT")), PoolErrorReason::ZeroAmount);
+        assert_eq!(PoolErrorReason::from(&PoolError::InsufficientLiquidity("NOT_ENOUGH_LIQUIDITY")), PoolErrorReason::Liquidity);
+        assert_eq!(PoolErrorReason::from(&PoolError::MathOverflow("NUMERATOR_OVERFLOW")), PoolErrorReason::Overflow);
+        assert_eq!(PoolErrorReason::from(&PoolError::StateMissing("ERROR_READING_STATE_DB")), PoolErrorReason::State);
+        assert_eq!(PoolErrorReason::from(&PoolError::DecodeError("BUFFER_OVERRUN".to_string())), PoolErrorReason::State);
+        assert_eq!(
+            PoolErrorReason::from(&PoolError::UnsupportedDirection("GET_DY_UNDERLYING_NOT_SUPPORTED")),
+            PoolErrorReason::NotSupported
+        );
+        assert_eq!(PoolErrorReason::from(&PoolError::EvmRevert { reason: "STF".to_string(), gas_used: 0 }), PoolErrorReason::Reverted);
+        assert_eq!(PoolErrorReason::from(&PoolError::Other("SOMETHING".to_string())), PoolErrorReason::Other);
+    }
+
+    #[test]
+    fn test_pool_quarantine() {
+        let pool = Address::repeat_byte(1);
+        let mut quarantine: PoolQuarantine = PoolQuarantine::new(PoolQuarantineConfig::default());
+
+        for _ in 0..9 {
+            assert!(!quarantine.record_error(pool, PoolErrorReason::ZeroAmount, "ZERO_OUT_AMOUNT".to_string(), 100));
+        }
+        // score decays to 4.5 after 300 blocks
+        assert!(!quarantine.record_error(pool, PoolErrorReason::ZeroAmount, "ZERO_OUT_AMOUNT".to_string(), 400));
+        assert!((quarantine.score(&pool, 400) - 5.5).abs() < 1e-9);
+
+        assert!(quarantine.record_error(pool, PoolErrorReason::NotSupported, "NOT_SUPPORTED".to_string(), 400));
+        assert_eq!(quarantine.quarantine(pool, 400), PoolQuarantineStatus::Quarantined { until_block: 450 });
+        assert!(!quarantine.record_error(pool, PoolErrorReason::NotSupported, "NOT_SUPPORTED".to_string(), 410));
+        assert!(quarantine.expired(449).is_empty());
+        assert_eq!(quarantine.expired(450), vec![pool]);
+
+        quarantine.release(&pool, 450);
+        assert_eq!(quarantine.status(&pool), PoolQuarantineStatus::Active);
+        assert_eq!(quarantine.quarantine(pool, 460), PoolQuarantineStatus::Quarantined { until_block: 560 });
+        assert_eq!(quarantine.quarantine(pool, 560), PoolQuarantineStatus::Quarantined { until_block: 760 });
+        assert_eq!(quarantine.quarantine(pool, 760), PoolQuarantineStatus::Disabled);
+        assert_eq!(quarantine.get(&pool).unwrap().reasons.get(&PoolErrorReason::NotSupported), Some(&2));
+
+        let pool = Address::repeat_byte(2);
+        assert_eq!(quarantine.quarantine(pool, 1000), PoolQuarantineStatus::Quarantined { until_block: 1050 });
+        quarantine.release(&pool, 1050);
+        assert_eq!(quarantine.quarantine(pool, 9000), PoolQuarantineStatus::Quarantined { until_block: 9050 });
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::{HashMap, HashSet};
+
+use alloy_primitives::{Address, U256};
+use eyre::{eyre, Result};
+use loom_evm_utils::NWETH;
+use serde::Deserialize;
+
+use crate::tips::TipsCurve;
+use crate::{SwapLine, SwapPath};
+
+/// Rules for swap paths searched by the backrun searcher and for the profit of found swaps.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ProfitPolicy {
+    /// Minimum profit in ETH after the gas cost of the gas model
+    pub min_net_profit_eth: f64,
+    /// Minimum profit in units of the first token of the swap, e.g. 10.0 for 10 USDC
+    pub token_min_profit: HashMap<Address, f64>,
+    /// Paths with these tokens are skipped
+    pub deny_tokens: HashSet<Address>,
+    /// Only paths with all tokens in the list are searched if the list is set
+    pub allow_tokens: Option<HashSet<Address>>,
+    /// Paths with these pools are skipped
+    pub deny_pools: HashSet<Address>,
+    /// Only paths with all pools in the list are searched if the list is set
+    pub allow_pools: Option<HashSet<Address>>,
+    /// Tips percent of the searched and merged swaps and the random range of the encoder. Searchers use tips percent of the
+    /// state update and mergers the default curve if not set.
+    pub tips: Option<TipsCurve>,
+}
+
+impl ProfitPolicy {
+    pub fn is_token_allowed(&self, token_address: &Address) -> bool {
+        !self.deny_tokens.contains(token_address)
+            && self.allow_tokens.as_ref().map_or(true, |allow_tokens| allow_tokens.contains(token_address))
+    }
+
+    pub fn is_pool_allowed(&self, pool_address: &Address) -> bool {
+        !self.deny_pools.contains(pool_address) && self.allow_pools.as_ref().map_or(true, |allow_pools| allow_pools.contains(pool_address))
+    }
+
+    pub fn is_path_allowed(&self, swap_path: &SwapPath) -> bool {
+        swap_path.tokens.iter().all(|token| self.is_token_allowed(&token.get_address()))
+            && swap_path.pools.iter().all(|pool| self.is_pool_allowed(&pool.get_address()))
+    }
+
+    /// Returns the profit in ETH after the gas cost if the swap line satisfies the policy
+    pub fn net_profit_eth(&self, swap_line: &SwapLine, gas_cost: U256) -> Result<U256> {
+        let net_profit_eth = swap_line.abs_profit_eth().checked_sub(gas_cost).ok_or(eyre!("PROFIT_BELOW_GAS_COST"))?;
+        if net_profit_eth.is_zero() || net_profit_eth < NWETH::from_float(self.min_net_profit_eth) {
+            return Err(eyre!("NET_PROFIT_BELOW_MIN"));
+        }
+
+        if let Some(token) = swap_line.get_first_token() {
+            if let Some(min_profit) = self.token_min_profit.get(&token.get_address()) {
+                if token.to_float(swap_line.abs_profit()) < *min_profit {
+                    return Err(eyre!("TOKEN_PROFIT_BELOW_MIN"));
+                }
+            }
+        }
+        Ok(net_profit_eth)
+    }
+
+    /// Tips curve or the default curve if not set
+    pub fn tips_curve(&self) -> TipsCurve {
+        self.tips.clone().unwrap_or_default()
+    }
+
+    /// Tips percent of the tips curve or `default_pct` if the curve is not set. The encoder randomizes the percent.
+    pub fn tips_pct(&self, profit_eth: &U256, default_pct: u32) -> u32 {
+        self.tips.as_ref().map_or(default_pct, |tips| tips.tips_pct(profit_eth))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{SwapAmountType, Token};
+    use std::sync::Arc;
+
+    fn swap_line(token: Address, amount_in: f64, amount_out: f64) -> SwapLine {
+        let token = Arc::new(Token::new_with_data(token, None, None, Some(18), false, false));
+        token.set_eth_price(Some(NWETH::from_float(1.0)));
+        SwapLine {
+            path: SwapPath { tokens: vec![token.clone(), token], ..SwapPath::default() },
+            amount_in: SwapAmountType::Set(NWETH::from_float(amount_in)),
+            amount_out: SwapAmountType::Set(NWETH::from_float(amount_out)),
+            ..SwapLine::default()
+        }
+    }
+
+    #[test]
+    fn test_token_and_pool_lists() {
+        let (token0, token1, pool) = (Address::repeat_byte(1), Address::repeat_byte(2), Address::repeat_byte(3));
+
+        let policy = ProfitPolicy { deny_tokens: HashSet::from([token0]), deny_pools: HashSet::from([pool]), ..ProfitPolicy::default() };
+        assert!(!policy.is_token_allowed(&token0));
+        assert!(policy.is_token_allowed(&token1));
+        assert!(!policy.is_pool_allowed(&pool));
+
+        let policy = ProfitPolicy { allow_tokens: Some(HashSet::from([token1])), ..ProfitPolicy::default() };
+        assert!(!policy.is_token_allowed(&token0));
+        assert!(policy.is_token_allowed(&token1));
+        assert!(policy.is_pool_allowed(&pool));
+    }
+
+    #[test]
+    fn test_net_profit() {
+        let token = Address::repeat_byte(1);
+        let gas_cost = NWETH::from_float(0.01);
+
+        let policy = ProfitPolicy::default();
+        assert_eq!(policy.net_profit_eth(&swap_line(token, 1.0, 1.1), gas_cost).unwrap(), NWETH::from_float(0.09));
+        assert!(policy.net_profit_eth(&swap_line(token, 1.0, 1.005), gas_cost).is_err());
+
+        let policy = ProfitPolicy { min_net_profit_eth: 0.1, ..ProfitPolicy::default() };
+        assert!(policy.net_profit_eth(&swap_line(token, 1.0, 1.1), gas_cost).is_err());
+
+        let policy = ProfitPolicy { token_min_profit: HashMap::from([(token, 0.2)]), ..ProfitPolicy::default() };
+        assert!(policy.net_profit_eth(&swap_line(token, 1.0, 1.1), gas_cost).is_err());
+        assert!(policy.net_profit_eth(&swap_line(token, 1.0, 1.3), gas_cost).is_ok());
+    }
+
+    #[test]
+    fn test_tips_pct() {
+        let policy = ProfitPolicy::default();
+        assert_eq!(policy.tips_pct(&U256::ZERO, 9000), 9000);
+        assert_eq!(policy.tips_pct(&NWETH::from_float(100.0), 9000), 9000);
+
+        let policy = ProfitPolicy { tips: Some(TipsCurve::default()), ..ProfitPolicy::default() };
+        assert_eq!(policy.tips_pct(&U256::ZERO, 9000), 9900);
+        assert_eq!(policy.tips_pct(&NWETH::from_float(10.0), 9000), 7000);
+    }
+}
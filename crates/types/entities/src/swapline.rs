@@ -4,8 +4,8 @@ use std::sync::Arc;
 
 use alloy_primitives::{I256, U256};
 use eyre::{eyre, ErrReport, Result};
-use loom_types_blockchain::SwapError;
 use loom_types_blockchain::{LoomDataTypes, LoomDataTypesEthereum};
+use loom_types_blockchain::{PoolError, SwapError};
 use revm::primitives::Env;
 use revm::DatabaseRef;
 use tracing::debug;
@@ -135,9 +135,9 @@ impl<LDT: LoomDataTypes> From<SwapPath<LDT>> for SwapLine<LDT> {
 }
 
 impl<LDT: LoomDataTypes> SwapLine<LDT> {
-    pub fn to_error(&self, msg: String) -> SwapError<LDT> {
+    pub fn to_error(&self, error: PoolError) -> SwapError<LDT> {
         SwapError {
-            msg,
+            error,
             pool: self.get_first_pool().map_or(LDT::Address::default(), |x| x.get_address()),
             token_from: self.get_first_token().map_or(LDT::Address::default(), |x| x.get_address()),
             token_to: self.get_last_token().map_or(LDT::Address::default(), |x| x.get_address()),
@@ -320,7 +320,7 @@ impl<LDT: LoomDataTypes> SwapLine<LDT> {
                 Ok((out_amount_result, gas_result)) => {
                     if out_amount_result.is_zero() {
                         return Err(SwapError::<LDT> {
-                            msg: "ZERO_AMOUNT".to_string(),
+                            error: PoolError::ZeroAmount("ZERO_AMOUNT"),
                             pool: pool.get_address(),
                             token_from: token_from.get_address(),
                             token_to: token_to.get_address(),
@@ -336,7 +336,7 @@ impl<LDT: LoomDataTypes> SwapLine<LDT> {
                 Err(e) => {
                     //error!("calculate_with_in_amount calculate_out_amount error {} amount {} : {}", self, in_amount, e);
                     return Err(SwapError {
-                        msg: e.to_string(),
+                        error: e,
                         pool: pool.get_address(),
                         token_from: token_from.get_address(),
                         token_to: token_to.get_address(),
@@ -374,7 +374,7 @@ impl<LDT: LoomDataTypes> SwapLine<LDT> {
                 Ok((in_amount_result, gas_result)) => {
                     if in_amount_result == U256::MAX || in_amount_result == U256::ZERO {
                         return Err(SwapError::<LDT> {
-                            msg: "ZERO_AMOUNT".to_string(),
+                            error: PoolError::ZeroAmount("ZERO_AMOUNT"),
                             pool: pool.get_address(),
                             token_from: token_from.get_address(),
                             token_to: token_to.get_address(),
@@ -391,7 +391,7 @@ impl<LDT: LoomDataTypes> SwapLine<LDT> {
                     //error!("calculate_with_out_amount calculate_in_amount error {} amount {} : {}", self, in_amount, e);
 
                     return Err(SwapError {
-                        msg: e.to_string(),
+                        error: e,
                         pool: pool.get_address(),
                         token_from: token_from.get_address(),
                         token_to: token_to.get_address(),
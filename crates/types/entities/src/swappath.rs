@@ -110,6 +110,12 @@ impl<LDT: LoomDataTypes> SwapPath<LDT> {
         }
         false
     }
+
+    /// Returns false if any token of the path is known to take fees on transfers or to block them
+    #[inline]
+    pub fn is_transfer_safe(&self) -> bool {
+        self.tokens.iter().all(|token| token.is_transfer_safe())
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -190,7 +196,8 @@ mod test {
     use crate::required_state::RequiredState;
     use crate::{AbiSwapEncoder, Pool};
     use alloy_primitives::{Address, U256};
-    use eyre::{eyre, ErrReport};
+    use eyre::ErrReport;
+    use loom_types_blockchain::PoolError;
     use revm::primitives::Env;
     use revm::DatabaseRef;
     use tokio::task::JoinHandle;
@@ -219,8 +226,8 @@ mod test {
             _token_address_from: &Address,
             _token_address_to: &Address,
             _in_amount: U256,
-        ) -> Result<(U256, u64), ErrReport> {
-            Err(eyre!("NOT_IMPLEMENTED"))
+        ) -> Result<(U256, u64), PoolError> {
+            Err(PoolError::UnsupportedDirection("NOT_IMPLEMENTED"))
         }
 
         fn calculate_in_amount(
@@ -230,8 +237,8 @@ mod test {
             _token_address_from: &Address,
             _token_address_to: &Address,
             _out_amount: U256,
-        ) -> eyre::Result<(U256, u64), ErrReport> {
-            Err(eyre!("NOT_IMPLEMENTED"))
+        ) -> Result<(U256, u64), PoolError> {
+            Err(PoolError::UnsupportedDirection("NOT_IMPLEMENTED"))
         }
 
         fn can_flash_swap(&self) -> bool {
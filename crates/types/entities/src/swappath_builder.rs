@@ -24,6 +24,11 @@ impl<LDT: LoomDataTypes> SwapPathSet<LDT> {
         self.set.into_iter().collect()
     }
 
+    // amounts of paths through fee-on-transfer or untradeable tokens can't be calculated
+    pub fn retain_transfer_safe(&mut self) {
+        self.set.retain(|path| path.is_transfer_safe())
+    }
+
     pub fn arc_vec(self) -> Vec<Arc<SwapPath<LDT>>> {
         self.set.into_iter().map(Arc::new).collect()
     }
@@ -468,5 +473,7 @@ pub fn build_swap_path_vec<LDT: LoomDataTypes>(
         }
     }
 
+    ret_map.retain_transfer_safe();
+
     Ok(ret_map.vec())
 }
@@ -0,0 +1,10 @@
+use std::path::PathBuf;
+
+use tempfile::TempDir;
+
+/// Path of `file_name` in a new temporary directory. The directory and the file are removed when the returned [`TempDir`] is dropped.
+pub fn temp_file_path(file_name: &str) -> std::io::Result<(TempDir, PathBuf)> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join(file_name);
+    Ok((dir, path))
+}
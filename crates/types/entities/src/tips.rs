@@ -2,13 +2,14 @@ use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use std::sync::Arc;
 
+use crate::bidding::{AdaptiveBidder, OpportunityType};
 use crate::{Swap, Token};
 use alloy_primitives::utils::format_units;
 use alloy_primitives::{Address, U256};
 use eyre::{eyre, OptionExt, Result};
-use lazy_static::lazy_static;
 use loom_evm_utils::NWETH;
 use rand::random;
+use serde::Deserialize;
 use tracing::{error, info};
 
 #[derive(Clone, Debug)]
@@ -34,42 +35,71 @@ impl Display for Tips {
     }
 }
 
-lazy_static! {
-    static ref START_PCT : U256 = U256::from(9900);
+/// Tips percent in basis points of the profit, linearly interpolated between the points of the curve.
+/// The swap encoder lowers the percent by a random value below `random_pct` so competitors can't predict the tips.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct TipsCurve {
+    /// Tips percent for zero profit
+    pub start_pct: u32,
+    /// Points of the curve as (profit in ETH, tips percent), sorted by profit. Tips percent of the last point is used for larger profits.
+    pub points: Vec<(f64, u32)>,
+    pub random_pct: u32,
+}
 
-    static ref SLOPES : Vec<(U256,U256)> = vec![
-//x        (U256::from(0), U256::from(10000)),
-        (U256::from(10).pow(U256::from(19)), U256::from(7000)),
-        (U256::from(10).pow(U256::from(19))*U256::from(5), U256::from(5000))
-    ];
+impl Default for TipsCurve {
+    fn default() -> Self {
+        Self { start_pct: 9900, points: vec![(10.0, 7000), (50.0, 5000)], random_pct: 50 }
+    }
 }
-pub fn tips_pct_advanced(profit: &U256) -> u32 {
-    let mut start_point = U256::ZERO;
-    let mut start_pct = *START_PCT;
-    for (x, y) in SLOPES.iter() {
-        if x > profit {
-            return (start_pct - ((start_pct - y) * (profit - start_point) / (x - start_point))).to::<u32>();
+
+impl TipsCurve {
+    pub fn tips_pct(&self, profit_eth: &U256) -> u32 {
+        let mut start_point = U256::ZERO;
+        let mut start_pct = U256::from(self.start_pct);
+        for (x, y) in self.points.iter() {
+            let (x, y) = (NWETH::from_float(*x), U256::from(*y));
+            if x > *profit_eth {
+                if start_pct >= y {
+                    return (start_pct - ((start_pct - y) * (profit_eth - start_point) / (x - start_point))).to::<u32>();
+                } else {
+                    return (start_pct + ((y - start_pct) * (profit_eth - start_point) / (x - start_point))).to::<u32>();
+                }
+            }
+            start_point = x;
+            start_pct = y;
         }
-        start_point = *x;
-        start_pct = *y;
+        start_pct.to()
     }
-    start_pct.to()
-}
 
-pub fn randomize_tips_pct(tips_pct: u32) -> u32 {
-    let rnd: u32 = random::<u32>() % 50;
-    tips_pct - rnd
+    pub fn randomize(&self, tips_pct: u32) -> u32 {
+        if self.random_pct == 0 {
+            return tips_pct;
+        }
+        tips_pct.saturating_sub(random::<u32>() % self.random_pct)
+    }
 }
 
+/// Returns tips for the swap and the value of the call. Tips percent of the curve is used if `tips_pct` is not set, the percent is
+/// randomized with the random range of the curve. If the [`AdaptiveBidder`] is set, it adjusts the tips percent for the opportunity
+/// type and profit, and its floor and ceiling are applied to the randomized percent.
 pub fn tips_and_value_for_swap_type(
     swap: &Swap,
     tips_pct: Option<u32>,
+    tips_curve: &TipsCurve,
+    bidder: Option<&AdaptiveBidder>,
     gas_cost: Option<U256>,
     eth_balance: U256,
 ) -> Result<(Vec<Tips>, U256)> {
     let total_profit_eth = swap.abs_profit_eth();
     info!("Total profit eth : {}", format_units(total_profit_eth, "ether").unwrap_or_default());
-    let tips_pct = randomize_tips_pct(tips_pct.unwrap_or(tips_pct_advanced(&total_profit_eth)));
+    let tips_pct = tips_pct.unwrap_or_else(|| tips_curve.tips_pct(&total_profit_eth));
+    let tips_pct = match (bidder, OpportunityType::from_swap(swap)) {
+        (Some(bidder), Some(opportunity)) => {
+            bidder.config().clamp(tips_curve.randomize(bidder.tips_pct(opportunity, &total_profit_eth, tips_pct)))
+        }
+        _ => tips_curve.randomize(tips_pct),
+    };
 
     if let Some(gas_cost) = gas_cost {
         if total_profit_eth < gas_cost {
@@ -202,3 +232,76 @@ pub fn tips_and_value_for_swap_type(
         _ => Err(eyre!("NOT_IMPLEMENTED")),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bidding::BiddingConfig;
+    use crate::{SwapAmountType, SwapLine, SwapPath};
+    use loom_defi_address_book::TokenAddressEth;
+
+    #[test]
+    fn test_tips_curve() {
+        let curve = TipsCurve { random_pct: 0, ..TipsCurve::default() };
+        assert_eq!(curve.tips_pct(&U256::ZERO), 9900);
+        assert_eq!(curve.tips_pct(&NWETH::from_float(5.0)), 8450);
+        assert_eq!(curve.tips_pct(&NWETH::from_float(10.0)), 7000);
+        assert_eq!(curve.tips_pct(&NWETH::from_float(30.0)), 6000);
+        assert_eq!(curve.tips_pct(&NWETH::from_float(100.0)), 5000);
+        assert_eq!(curve.randomize(curve.tips_pct(&NWETH::from_float(100.0))), 5000);
+
+        let curve = TipsCurve::default();
+        let tips_pct = curve.randomize(curve.tips_pct(&U256::ZERO));
+        assert!(tips_pct <= 9900 && tips_pct > 9850);
+    }
+
+    #[test]
+    fn test_tips_pct_randomized() {
+        let weth = Arc::new(Token::new(TokenAddressEth::WETH));
+        let swap = Swap::BackrunSwapLine(SwapLine {
+            path: SwapPath { tokens: vec![weth.clone(), weth], ..SwapPath::default() },
+            amount_in: SwapAmountType::Set(NWETH::from_float(1.0)),
+            amount_out: SwapAmountType::Set(NWETH::from_float(2.0)),
+            ..SwapLine::default()
+        });
+
+        let tips_for_pct = |tips_pct: u32| NWETH::from_float(1.0) * U256::from(tips_pct) / U256::from(10000);
+
+        let curve = TipsCurve { random_pct: 0, ..TipsCurve::default() };
+        let (tips, _) = tips_and_value_for_swap_type(&swap, Some(9000), &curve, None, None, U256::ZERO).unwrap();
+        assert_eq!(tips[0].tips, tips_for_pct(9000));
+        let (tips, _) = tips_and_value_for_swap_type(&swap, None, &curve, None, None, U256::ZERO).unwrap();
+        assert_eq!(tips[0].tips, tips_for_pct(9610));
+
+        // explicit tips percent is randomized too
+        let curve = TipsCurve { random_pct: 1000, ..TipsCurve::default() };
+        let (tips, _) = tips_and_value_for_swap_type(&swap, Some(9000), &curve, None, None, U256::ZERO).unwrap();
+        assert!(tips[0].tips <= tips_for_pct(9000) && tips[0].tips > tips_for_pct(8000));
+    }
+
+    #[test]
+    fn test_tips_pct_bidder_floor_after_randomize() {
+        let weth = Arc::new(Token::new(TokenAddressEth::WETH));
+        let swap_line = SwapLine {
+            path: SwapPath { tokens: vec![weth.clone(), weth], ..SwapPath::default() },
+            amount_in: SwapAmountType::Set(NWETH::from_float(1.0)),
+            amount_out: SwapAmountType::Set(NWETH::from_float(2.0)),
+            ..SwapLine::default()
+        };
+        let tips_for_pct = |tips_pct: u32| NWETH::from_float(1.0) * U256::from(tips_pct) / U256::from(10000);
+
+        let bidder = AdaptiveBidder::new(BiddingConfig { floor_pct: 9000, ceiling_pct: 9500, ..BiddingConfig::default() });
+        let curve = TipsCurve { random_pct: 5000, ..TipsCurve::default() };
+
+        // randomized percent is raised to the floor of the bidder
+        let swap = Swap::BackrunSwapLine(swap_line.clone());
+        let (tips, _) = tips_and_value_for_swap_type(&swap, Some(9000), &curve, Some(&bidder), None, U256::ZERO).unwrap();
+        assert_eq!(tips[0].tips, tips_for_pct(9000));
+
+        // ceiling of the bidder is applied to merged swaps too
+        let curve = TipsCurve { random_pct: 0, ..TipsCurve::default() };
+        let swap = Swap::Multiple(vec![Swap::BackrunSwapLine(swap_line)]);
+        let (tips, _) = tips_and_value_for_swap_type(&swap, Some(9900), &curve, Some(&bidder), None, U256::ZERO).unwrap();
+        assert_eq!(tips[0].tips, tips_for_pct(9500));
+    }
+}
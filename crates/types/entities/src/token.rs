@@ -11,6 +11,19 @@ use loom_types_blockchain::{LoomDataTypes, LoomDataTypesEthereum};
 
 const ONE_ETHER: U256 = Unit::ETHER.wei_const();
 
+/// Transfer behaviour of the token detected by simulation of transfers and swaps
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TokenTransferClass {
+    /// Not screened yet
+    #[default]
+    Unknown,
+    Normal,
+    /// Part of the transferred amount is taken, fee in basis points
+    FeeOnTransfer(u32),
+    /// Transfers or sells revert
+    Untradeable,
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct Token<LDT: LoomDataTypes = LoomDataTypesEthereum> {
     address: LDT::Address,
@@ -20,6 +33,7 @@ pub struct Token<LDT: LoomDataTypes = LoomDataTypesEthereum> {
     name: Option<String>,
     symbol: Option<String>,
     eth_price: Arc<RwLock<Option<U256>>>,
+    transfer_class: Arc<RwLock<TokenTransferClass>>,
 }
 
 pub type TokenWrapper<LDT> = Arc<Token<LDT>>;
@@ -72,7 +86,16 @@ impl<LDT: LoomDataTypes> Token<LDT> {
         basic: bool,
         middle: bool,
     ) -> Token<LDT> {
-        Token { address, symbol, name, decimals: decimals.unwrap_or(18), basic, middle, eth_price: Arc::new(RwLock::new(None)) }
+        Token {
+            address,
+            symbol,
+            name,
+            decimals: decimals.unwrap_or(18),
+            basic,
+            middle,
+            eth_price: Arc::new(RwLock::new(None)),
+            transfer_class: Arc::new(RwLock::new(TokenTransferClass::Unknown)),
+        }
     }
 
     #[inline]
@@ -180,6 +203,24 @@ impl<LDT: LoomDataTypes> Token<LDT> {
         }
     }
 
+    pub fn get_transfer_class(&self) -> TokenTransferClass {
+        match self.transfer_class.read() {
+            Ok(x) => *x,
+            _ => TokenTransferClass::Unknown,
+        }
+    }
+
+    pub fn set_transfer_class(&self, transfer_class: TokenTransferClass) {
+        if let Ok(mut x) = self.transfer_class.write() {
+            *x = transfer_class;
+        }
+    }
+
+    /// Returns false if the token is known to take fees on transfers or to block them, swap amounts of such tokens can't be calculated
+    pub fn is_transfer_safe(&self) -> bool {
+        matches!(self.get_transfer_class(), TokenTransferClass::Unknown | TokenTransferClass::Normal)
+    }
+
     pub fn calc_eth_value(&self, value: U256) -> Option<U256> {
         self.get_eth_price().map(|x| value.mul(ONE_ETHER).div(x))
     }
@@ -212,4 +253,19 @@ mod test {
 
         println!("{}", weth_token.to_float(one_ether));
     }
+
+    #[test]
+    fn test_transfer_class() {
+        let token = Token::<LoomDataTypesEthereum>::new(TokenAddressEth::USDT);
+        let token_clone = token.clone();
+        assert_eq!(token.get_transfer_class(), TokenTransferClass::Unknown);
+        assert!(token.is_transfer_safe());
+
+        token.set_transfer_class(TokenTransferClass::FeeOnTransfer(200));
+        assert_eq!(token_clone.get_transfer_class(), TokenTransferClass::FeeOnTransfer(200));
+        assert!(!token_clone.is_transfer_safe());
+
+        token.set_transfer_class(TokenTransferClass::Normal);
+        assert!(token_clone.is_transfer_safe());
+    }
 }
@@ -7,6 +7,7 @@ pub enum MarketEvents<LDT: LoomDataTypes = LoomDataTypesEthereum> {
     BlockTxUpdate { block_number: BlockNumber, block_hash: LDT::BlockHash },
     BlockLogsUpdate { block_number: BlockNumber, block_hash: LDT::BlockHash },
     BlockStateUpdate { block_hash: LDT::BlockHash },
+    Reorg { depth: u64, old_head: LDT::BlockHash, new_head: LDT::BlockHash },
 }
 
 #[derive(Clone, Debug)]
@@ -25,4 +26,9 @@ pub enum MempoolEvents<LDT: LoomDataTypes = LoomDataTypesEthereum> {
     MempoolLogUpdate {
         tx_hash: LDT::TxHash,
     },
+    /// Another transaction with the same sender and nonce and a higher gas price is in the mempool.
+    MempoolTxReplaced {
+        tx_hash: LDT::TxHash,
+        replaced_by: LDT::TxHash,
+    },
 }
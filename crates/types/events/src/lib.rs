@@ -3,6 +3,7 @@ pub use defi_events::*;
 pub use health_event::*;
 pub use message::Message;
 pub use node::*;
+pub use relay_events::*;
 pub use state_update_event::*;
 pub use swap_compose::*;
 pub use tasks::Task;
@@ -13,6 +14,7 @@ mod defi_events;
 mod health_event;
 mod message;
 mod node;
+mod relay_events;
 mod swap_compose;
 
 mod state_update_event;
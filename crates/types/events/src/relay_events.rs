@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+use alloy_primitives::{BlockNumber, B256};
+
+use crate::Message;
+
+/// Outcome of a bundle submission to a single relay.
+#[derive(Clone, Debug)]
+pub struct RelayBundleResult {
+    pub relay_id: Option<u16>,
+    pub relay_name: String,
+    pub target_block: BlockNumber,
+    pub bundle_hash: Option<B256>,
+    pub error: Option<String>,
+    pub http_status: Option<u16>,
+    pub latency: Duration,
+    pub degraded: bool,
+}
+
+/// Bundle stats polled from a relay after submission.
+#[derive(Clone, Debug)]
+pub struct RelayBundleStats {
+    pub relay_name: String,
+    pub target_block: BlockNumber,
+    pub bundle_hash: B256,
+    pub is_simulated: Option<bool>,
+    pub is_high_priority: Option<bool>,
+    pub considered_by_builders: usize,
+    pub sealed_by_builders: usize,
+    pub status: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub enum RelayEvents {
+    BundleResult(RelayBundleResult),
+    BundleStats(RelayBundleStats),
+}
+
+pub type MessageRelayEvent = Message<RelayEvents>;
@@ -4,6 +4,9 @@ use loom_types_blockchain::{LoomDataTypes, LoomDataTypesEthereum};
 use loom_types_entities::{LoomTxSigner, Swap};
 use std::sync::Arc;
 
+/// Origin of bundles backrunning MEV-Share hints. Hint transactions are known by hash only and can't be sent with `eth_sendBundle`.
+pub const MEV_SHARE_ORIGIN: &str = "mevshare_searcher";
+
 #[derive(Debug, Clone)]
 pub enum RlpState {
     Stuffing(Bytes),